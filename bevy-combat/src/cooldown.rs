@@ -0,0 +1,127 @@
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+use crate::damage::Ability;
+use crate::state::CombatManager;
+
+/// Turns every ability use locks out all of an entity's abilities
+pub const DEFAULT_GLOBAL_COOLDOWN: f32 = 1.0;
+
+/// Shared lockout started by any ability use
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub struct GlobalCooldown {
+    pub remaining: f32,
+    pub duration: f32,
+}
+
+impl Default for GlobalCooldown {
+    fn default() -> Self {
+        Self::new(DEFAULT_GLOBAL_COOLDOWN)
+    }
+}
+
+impl GlobalCooldown {
+    pub fn new(duration: f32) -> Self {
+        Self {
+            remaining: 0.0,
+            duration,
+        }
+    }
+}
+
+/// Turns left before each ability, by name, can be used again
+#[derive(Component, Debug, Clone, Default, Reflect)]
+#[reflect(Component)]
+pub struct AbilityCooldowns(pub HashMap<String, f32>);
+
+impl AbilityCooldowns {
+    pub fn remaining(&self, ability: &str) -> f32 {
+        self.0.get(ability).copied().unwrap_or(0.0)
+    }
+}
+
+/// Why an ability couldn't be used
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CastBlocked {
+    GlobalCooldown { remaining: f32 },
+    AbilityCooldown { remaining: f32 },
+}
+
+/// Start `ability`'s cooldown and the global cooldown, or report why the
+/// ability can't be used yet
+pub fn use_ability(
+    ability: &Ability,
+    cooldowns: &mut AbilityCooldowns,
+    global: &mut GlobalCooldown,
+) -> Result<(), CastBlocked> {
+    if global.remaining > 0.0 {
+        return Err(CastBlocked::GlobalCooldown {
+            remaining: global.remaining,
+        });
+    }
+    let remaining = cooldowns.remaining(&ability.name);
+    if remaining > 0.0 {
+        return Err(CastBlocked::AbilityCooldown { remaining });
+    }
+
+    global.remaining = global.duration;
+    if ability.cooldown > 0.0 {
+        cooldowns.0.insert(ability.name.clone(), ability.cooldown);
+    }
+    Ok(())
+}
+
+/// Advance every cooldown by `turns`
+pub fn tick_cooldowns(cooldowns: &mut AbilityCooldowns, global: &mut GlobalCooldown, turns: f32) {
+    global.remaining = (global.remaining - turns).max(0.0);
+    cooldowns.0.retain(|_, remaining| {
+        *remaining -= turns;
+        *remaining > 0.0
+    });
+}
+
+/// System that counts cooldowns down by one turn when a turn starts. Turns
+/// resumed after an interrupt don't count.
+pub fn tick_cooldowns_on_turn_start(
+    manager: Res<CombatManager>,
+    mut query: Query<(Option<&mut AbilityCooldowns>, &mut GlobalCooldown)>,
+) {
+    if manager.resumed_turn {
+        return;
+    }
+
+    for (cooldowns, mut global) in query.iter_mut() {
+        match cooldowns {
+            Some(mut cooldowns) => tick_cooldowns(&mut cooldowns, &mut global, 1.0),
+            None => global.remaining = (global.remaining - 1.0).max(0.0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::damage::DamageType;
+
+    #[test]
+    fn test_global_cooldown_blocks_other_abilities() {
+        let fireball = Ability::new("Fireball", DamageType::Fire, 1.5).with_cooldown(3.0);
+        let slash = Ability::new("Slash", DamageType::Physical, 1.0);
+        let mut cooldowns = AbilityCooldowns::default();
+        let mut global = GlobalCooldown::new(1.0);
+
+        assert_eq!(use_ability(&fireball, &mut cooldowns, &mut global), Ok(()));
+        assert_eq!(
+            use_ability(&slash, &mut cooldowns, &mut global),
+            Err(CastBlocked::GlobalCooldown { remaining: 1.0 })
+        );
+
+        tick_cooldowns(&mut cooldowns, &mut global, 1.0);
+        assert_eq!(
+            use_ability(&fireball, &mut cooldowns, &mut global),
+            Err(CastBlocked::AbilityCooldown { remaining: 2.0 })
+        );
+        assert_eq!(use_ability(&slash, &mut cooldowns, &mut global), Ok(()));
+    }
+}
@@ -1,11 +1,19 @@
 use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::defend::Defending;
+use crate::state::CombatLogEvent;
 
 /// Type of damage dealt in combat
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Reflect)]
 pub enum DamageType {
     Physical,
     Magical,
+    Fire,
+    Ice,
     Eldritch,
     Corrupted,
     True,
@@ -36,6 +44,109 @@ impl Default for CombatStats {
     }
 }
 
+/// Hit points of a combatant
+#[derive(Component, Debug, Clone, Serialize, Deserialize, Reflect)]
+#[reflect(Component)]
+pub struct Health {
+    pub current: f32,
+    pub max: f32,
+}
+
+impl Health {
+    pub fn new(max: f32) -> Self {
+        Self { current: max, max }
+    }
+
+    /// Subtract `amount`, stopping at zero, and report how the hit landed
+    pub fn take_damage(&mut self, amount: f32) -> DamageResult {
+        let before = self.current;
+        self.current = (before - amount).max(0.0);
+        let was_lethal = before > 0.0 && self.current == 0.0;
+
+        DamageResult {
+            dealt: before - self.current,
+            overkill: if was_lethal { amount - before } else { 0.0 },
+            was_lethal,
+        }
+    }
+}
+
+/// How much of a hit landed on a target's health
+#[derive(Debug, Clone, Copy, PartialEq, Default, Reflect)]
+pub struct DamageResult {
+    /// Health actually removed
+    pub dealt: f32,
+    /// Damage beyond what it took to reach zero, on the killing blow
+    pub overkill: f32,
+    /// Whether this hit took the target from alive to zero health
+    pub was_lethal: bool,
+}
+
+/// Per-type damage resistance. `0.5` halves damage of that type, while a
+/// negative value is a weakness that amplifies it.
+#[derive(Component, Debug, Clone, Default, Reflect)]
+#[reflect(Component)]
+pub struct Resistances(pub HashMap<DamageType, f32>);
+
+impl Resistances {
+    pub fn with(mut self, damage_type: DamageType, resistance: f32) -> Self {
+        self.0.insert(damage_type, resistance);
+        self
+    }
+
+    /// Factor applied to incoming damage of `damage_type`
+    pub fn multiplier(&self, damage_type: DamageType) -> f32 {
+        let resistance = self.0.get(&damage_type).copied().unwrap_or(0.0);
+        (1.0 - resistance).max(0.0)
+    }
+}
+
+/// An attack or spell and the kind of damage it deals
+#[derive(Debug, Clone, Serialize, Deserialize, Reflect)]
+pub struct Ability {
+    pub name: String,
+    pub damage_type: DamageType,
+    /// Scales the attacker's base damage
+    pub power: f32,
+    /// Turns before the ability can be used again
+    #[serde(default)]
+    pub cooldown: f32,
+}
+
+impl Ability {
+    pub fn new(name: impl Into<String>, damage_type: DamageType, power: f32) -> Self {
+        Self {
+            name: name.into(),
+            damage_type,
+            power,
+            cooldown: 0.0,
+        }
+    }
+
+    pub fn with_cooldown(mut self, turns: f32) -> Self {
+        self.cooldown = turns;
+        self
+    }
+
+    /// Roll damage for this ability and build the event to apply it
+    pub fn hit(
+        &self,
+        attacker: (Entity, &CombatStats),
+        target: (Entity, &CombatStats),
+        config: &DamageConfig,
+    ) -> DamageEvent {
+        let (amount, is_critical) =
+            calculate_damage(attacker.1, target.1, self.damage_type, config);
+        DamageEvent {
+            attacker: attacker.0,
+            target: target.0,
+            damage_type: self.damage_type,
+            raw_amount: amount * self.power,
+            is_critical,
+        }
+    }
+}
+
 /// Event fired when damage is dealt
 #[derive(Message, Debug, Clone, Reflect)]
 pub struct DamageEvent {
@@ -63,19 +174,72 @@ impl Default for DamageConfig {
     }
 }
 
+/// Seedable source of combat rolls, so battles can be replayed and simulated
+#[derive(Resource)]
+pub struct CombatRng(pub StdRng);
+
+impl Default for CombatRng {
+    fn default() -> Self {
+        Self(StdRng::from_os_rng())
+    }
+}
+
+impl CombatRng {
+    pub fn seeded(seed: u64) -> Self {
+        Self(StdRng::seed_from_u64(seed))
+    }
+}
+
 /// System for calculating and applying damage
 pub fn calculate_damage(
     attacker_stats: &CombatStats,
     target_stats: &CombatStats,
     damage_type: DamageType,
     config: &DamageConfig,
+) -> (f32, bool) {
+    calculate_damage_with(
+        &mut rand::rng(),
+        attacker_stats,
+        target_stats,
+        damage_type,
+        config,
+    )
+}
+
+/// `calculate_damage` drawing crits and variance from `rng`
+pub fn calculate_damage_with(
+    rng: &mut impl Rng,
+    attacker_stats: &CombatStats,
+    target_stats: &CombatStats,
+    damage_type: DamageType,
+    config: &DamageConfig,
 ) -> (f32, bool) {
     // Check for critical hit
-    let is_critical = rand::random::<f32>() < attacker_stats.crit_chance;
+    let is_critical = rng.random::<f32>() < attacker_stats.crit_chance;
+
+    let mut final_damage = base_damage(attacker_stats, target_stats, damage_type);
 
-    let base_damage = match damage_type {
+    // Apply critical multiplier
+    if is_critical {
+        final_damage *= attacker_stats.crit_multiplier;
+    }
+
+    // Apply variance
+    let variance_factor = 1.0 + (rng.random::<f32>() * 2.0 - 1.0) * config.variance;
+    final_damage *= variance_factor;
+
+    (final_damage.max(config.min_damage), is_critical)
+}
+
+/// Damage before crits, variance and resistances
+pub fn base_damage(
+    attacker_stats: &CombatStats,
+    target_stats: &CombatStats,
+    damage_type: DamageType,
+) -> f32 {
+    match damage_type {
         DamageType::Physical => (attacker_stats.attack * 2.0 - target_stats.defense).max(0.0),
-        DamageType::Magical => {
+        DamageType::Magical | DamageType::Fire | DamageType::Ice => {
             (attacker_stats.magic_attack * 2.0 - target_stats.magic_defense).max(0.0)
         }
         DamageType::Eldritch => {
@@ -89,18 +253,179 @@ pub fn calculate_damage(
             (attacker_stats.attack * 1.5 - target_stats.defense * 0.5).max(0.0)
         }
         DamageType::True => attacker_stats.attack,
-    };
+    }
+}
 
-    let mut final_damage = base_damage;
+/// A resolved hit, after resistances
+#[derive(Debug, Clone, Reflect)]
+pub struct CombatLogEntry {
+    pub attacker: Entity,
+    pub target: Entity,
+    pub damage_type: DamageType,
+    pub multiplier: f32,
+    pub amount: f32,
+    pub is_critical: bool,
+    pub result: DamageResult,
+}
 
-    // Apply critical multiplier
-    if is_critical {
-        final_damage *= attacker_stats.crit_multiplier;
+/// Record of damage applied during combat
+#[derive(Resource, Debug, Clone, Default, Reflect)]
+#[reflect(Resource)]
+pub struct CombatLog {
+    pub entries: Vec<CombatLogEntry>,
+}
+
+/// System that applies damage events to health, consulting resistances and
+/// whether the target is `Defending`. Each hit's `DamageResult` is logged, and killing blows also emit
+/// `CombatLogEvent::Death`.
+pub fn apply_damage(
+    mut events: MessageReader<DamageEvent>,
+    mut targets: Query<(&mut Health, Option<&Resistances>, Option<&Defending>)>,
+    mut log: ResMut<CombatLog>,
+    mut log_events: MessageWriter<CombatLogEvent>,
+) {
+    for event in events.read() {
+        let Ok((mut health, resistances, defending)) = targets.get_mut(event.target) else {
+            continue;
+        };
+
+        let multiplier = resistances.map_or(1.0, |r| r.multiplier(event.damage_type))
+            * defending.map_or(1.0, Defending::multiplier);
+        let amount = event.raw_amount * multiplier;
+        let result = health.take_damage(amount);
+
+        info!(
+            "{:?} hit {:?} for {:.1} {:?} damage (x{:.2})",
+            event.attacker, event.target, amount, event.damage_type, multiplier
+        );
+        log.entries.push(CombatLogEntry {
+            attacker: event.attacker,
+            target: event.target,
+            damage_type: event.damage_type,
+            multiplier,
+            amount,
+            is_critical: event.is_critical,
+            result,
+        });
+
+        if result.was_lethal {
+            log_events.write(CombatLogEvent::Death {
+                entity: event.target,
+                killer: event.attacker,
+            });
+        }
     }
+}
 
-    // Apply variance
-    let variance_factor = 1.0 + (rand::random::<f32>() * 2.0 - 1.0) * config.variance;
-    final_damage *= variance_factor;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    (final_damage.max(config.min_damage), is_critical)
+    #[test]
+    fn test_fire_resistance_and_weakness() {
+        let mut app = App::new();
+        app.add_message::<DamageEvent>()
+            .add_message::<CombatLogEvent>()
+            .init_resource::<CombatLog>()
+            .add_systems(Update, apply_damage);
+
+        let attacker = app.world_mut().spawn_empty().id();
+        let resistant = app
+            .world_mut()
+            .spawn((
+                Health::new(100.0),
+                Resistances::default().with(DamageType::Fire, 0.5),
+            ))
+            .id();
+        let weak = app
+            .world_mut()
+            .spawn((
+                Health::new(100.0),
+                Resistances::default().with(DamageType::Fire, -0.5),
+            ))
+            .id();
+
+        for target in [resistant, weak] {
+            app.world_mut().write_message(DamageEvent {
+                attacker,
+                target,
+                damage_type: DamageType::Fire,
+                raw_amount: 20.0,
+                is_critical: false,
+            });
+        }
+        app.update();
+
+        let world = app.world();
+        assert_eq!(world.get::<Health>(resistant).unwrap().current, 90.0);
+        assert_eq!(world.get::<Health>(weak).unwrap().current, 70.0);
+
+        let log = world.resource::<CombatLog>();
+        assert_eq!(log.entries.len(), 2);
+        assert_eq!(log.entries[0].damage_type, DamageType::Fire);
+        assert_eq!(log.entries[0].multiplier, 0.5);
+        assert_eq!(log.entries[1].multiplier, 1.5);
+    }
+
+    #[test]
+    fn test_lethal_hit_reports_overkill() {
+        let mut app = App::new();
+        app.add_message::<DamageEvent>()
+            .add_message::<CombatLogEvent>()
+            .init_resource::<CombatLog>()
+            .add_systems(Update, apply_damage);
+
+        let attacker = app.world_mut().spawn_empty().id();
+        let mut wounded = Health::new(100.0);
+        wounded.current = 15.0;
+        let target = app.world_mut().spawn(wounded).id();
+
+        for raw_amount in [10.0, 25.0] {
+            app.world_mut().write_message(DamageEvent {
+                attacker,
+                target,
+                damage_type: DamageType::Physical,
+                raw_amount,
+                is_critical: false,
+            });
+        }
+        app.update();
+
+        let log = app.world().resource::<CombatLog>();
+        assert_eq!(
+            log.entries[0].result,
+            DamageResult {
+                dealt: 10.0,
+                overkill: 0.0,
+                was_lethal: false,
+            }
+        );
+        assert_eq!(
+            log.entries[1].result,
+            DamageResult {
+                dealt: 5.0,
+                overkill: 20.0,
+                was_lethal: true,
+            }
+        );
+        assert_eq!(app.world().get::<Health>(target).unwrap().current, 0.0);
+
+        let deaths: Vec<CombatLogEvent> = app
+            .world_mut()
+            .resource_mut::<Messages<CombatLogEvent>>()
+            .drain()
+            .collect();
+        assert_eq!(
+            deaths,
+            vec![CombatLogEvent::Death {
+                entity: target,
+                killer: attacker,
+            }]
+        );
+
+        // Hitting the body again is neither lethal nor overkill
+        let mut dead = Health::new(10.0);
+        dead.current = 0.0;
+        assert_eq!(dead.take_damage(5.0), DamageResult::default());
+    }
 }
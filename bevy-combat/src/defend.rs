@@ -0,0 +1,129 @@
+use bevy::prelude::*;
+
+use crate::state::{advance_turn, CombatLogEvent, CombatManager, CombatState};
+
+/// Share of incoming damage a defending entity ignores
+pub const DEFEND_REDUCTION: f32 = 0.5;
+
+/// Reduces damage taken until the defender's next turn starts
+#[derive(Component, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct Defending {
+    pub reduction: f32,
+    /// Turn starts left before it wears off: the other side's turn, then
+    /// the defender's own
+    pub turns_remaining: u32,
+}
+
+impl Default for Defending {
+    fn default() -> Self {
+        Self {
+            reduction: DEFEND_REDUCTION,
+            turns_remaining: 2,
+        }
+    }
+}
+
+impl Defending {
+    /// Factor applied to incoming damage
+    pub fn multiplier(&self) -> f32 {
+        (1.0 - self.reduction).max(0.0)
+    }
+}
+
+/// Have `entity` spend its turn defending, then pass the turn
+pub fn defend(world: &mut World, entity: Entity) {
+    let Ok(mut defender) = world.get_entity_mut(entity) else {
+        return;
+    };
+    defender.insert(Defending::default());
+    world.write_message(CombatLogEvent::Defend { entity });
+
+    let Some(current) = world.get_resource::<State<CombatState>>().map(|s| *s.get()) else {
+        return;
+    };
+    world.resource_scope(|world, mut next_state: Mut<NextState<CombatState>>| {
+        let mut manager = world.resource_mut::<CombatManager>();
+        advance_turn(&current, &mut next_state, &mut manager);
+    });
+}
+
+/// System that wears defending down as turns start. Turns resumed after an
+/// interrupt don't count.
+pub fn expire_defending(
+    mut commands: Commands,
+    manager: Res<CombatManager>,
+    mut query: Query<(Entity, &mut Defending)>,
+) {
+    if manager.resumed_turn {
+        return;
+    }
+
+    for (entity, mut defending) in query.iter_mut() {
+        defending.turns_remaining = defending.turns_remaining.saturating_sub(1);
+        if defending.turns_remaining == 0 {
+            commands.entity(entity).remove::<Defending>();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::damage::{apply_damage, CombatLog, DamageEvent, DamageType, Health};
+    use bevy::state::app::StatesPlugin;
+
+    fn hit(app: &mut App, attacker: Entity, target: Entity) {
+        app.world_mut().write_message(DamageEvent {
+            attacker,
+            target,
+            damage_type: DamageType::Physical,
+            raw_amount: 20.0,
+            is_critical: false,
+        });
+    }
+
+    #[test]
+    fn test_defending_halves_damage_for_one_turn() {
+        let mut app = App::new();
+        app.add_plugins(StatesPlugin)
+            .insert_state(CombatState::PlayerTurn)
+            .init_resource::<CombatManager>()
+            .init_resource::<CombatLog>()
+            .add_message::<DamageEvent>()
+            .add_message::<CombatLogEvent>()
+            .add_systems(Update, apply_damage)
+            .add_systems(OnEnter(CombatState::PlayerTurn), expire_defending)
+            .add_systems(OnEnter(CombatState::EnemyTurn), expire_defending);
+
+        let hero = app.world_mut().spawn(Health::new(100.0)).id();
+        let goblin = app.world_mut().spawn_empty().id();
+        app.update();
+
+        defend(app.world_mut(), hero);
+        hit(&mut app, goblin, hero);
+        app.update();
+
+        // Defending passed the turn and blocked half the hit
+        assert_eq!(
+            *app.world().resource::<State<CombatState>>().get(),
+            CombatState::EnemyTurn
+        );
+        assert_eq!(app.world().get::<Health>(hero).unwrap().current, 90.0);
+        let log: Vec<CombatLogEvent> = app
+            .world_mut()
+            .resource_mut::<Messages<CombatLogEvent>>()
+            .drain()
+            .collect();
+        assert_eq!(log, vec![CombatLogEvent::Defend { entity: hero }]);
+
+        // The hero's next turn ends it
+        app.world_mut()
+            .resource_mut::<NextState<CombatState>>()
+            .set(CombatState::PlayerTurn);
+        hit(&mut app, goblin, hero);
+        app.update();
+        assert!(app.world().get::<Defending>(hero).is_none());
+        assert_eq!(app.world().get::<Health>(hero).unwrap().current, 70.0);
+    }
+}
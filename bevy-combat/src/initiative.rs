@@ -0,0 +1,137 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::damage::CombatRng;
+
+/// How soon a combatant acts in a round; higher goes first
+#[derive(Component, Debug, Clone, Copy, Default, Reflect)]
+#[reflect(Component)]
+pub struct Speed(pub f32);
+
+/// How combatants with equal speed are ordered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+pub enum TieBreak {
+    /// Lower entity ids first, so the same battle always plays out the same
+    #[default]
+    ByEntityId,
+    /// Shuffled with `CombatRng`, reproducible when it is seeded
+    Random,
+    /// Player-controlled combatants first, then by entity id
+    PlayersFirst,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct InitiativeEntry {
+    entity: Entity,
+    speed: f32,
+    is_player: bool,
+}
+
+/// Collects combatants and sorts them into turn order
+#[derive(Debug, Clone, Default)]
+pub struct InitiativeBuilder {
+    entries: Vec<InitiativeEntry>,
+    tie_break: TieBreak,
+}
+
+impl InitiativeBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, entity: Entity, speed: f32) -> Self {
+        self.entries.push(InitiativeEntry {
+            entity,
+            speed,
+            is_player: false,
+        });
+        self
+    }
+
+    pub fn with_player(mut self, entity: Entity, speed: f32) -> Self {
+        self.entries.push(InitiativeEntry {
+            entity,
+            speed,
+            is_player: true,
+        });
+        self
+    }
+
+    pub fn with_tie_break(mut self, tie_break: TieBreak) -> Self {
+        self.tie_break = tie_break;
+        self
+    }
+
+    /// Turn order, fastest first. `rng` is only drawn from for `TieBreak::Random`.
+    pub fn build(self, rng: &mut CombatRng) -> Vec<Entity> {
+        let mut keyed: Vec<(InitiativeEntry, u64)> = self
+            .entries
+            .into_iter()
+            .map(|entry| {
+                let key = match self.tie_break {
+                    TieBreak::Random => rng.0.random(),
+                    TieBreak::ByEntityId | TieBreak::PlayersFirst => 0,
+                };
+                (entry, key)
+            })
+            .collect();
+
+        keyed.sort_by(|(a, a_key), (b, b_key)| {
+            b.speed
+                .total_cmp(&a.speed)
+                .then_with(|| match self.tie_break {
+                    TieBreak::Random => a_key.cmp(b_key),
+                    TieBreak::PlayersFirst => b.is_player.cmp(&a.is_player),
+                    TieBreak::ByEntityId => std::cmp::Ordering::Equal,
+                })
+                // `Entity`'s own `Ord` puts higher indices first
+                .then_with(|| a.entity.index().cmp(&b.entity.index()))
+                .then_with(|| a.entity.generation().cmp_approx(&b.entity.generation()))
+        });
+
+        keyed.into_iter().map(|(entry, _)| entry.entity).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_equal_speed_ties_resolve_predictably() {
+        let mut world = World::new();
+        let first = world.spawn_empty().id();
+        let second = world.spawn_empty().id();
+        let fast = world.spawn_empty().id();
+
+        // Insertion order doesn't matter under the default policy
+        for builder in [
+            InitiativeBuilder::new().with(second, 5.0).with(first, 5.0),
+            InitiativeBuilder::new().with(first, 5.0).with(second, 5.0),
+        ] {
+            let order = builder.with(fast, 9.0).build(&mut CombatRng::seeded(0));
+            assert_eq!(order, vec![fast, first, second]);
+        }
+
+        let players_first = InitiativeBuilder::new()
+            .with(first, 5.0)
+            .with_player(second, 5.0)
+            .with_tie_break(TieBreak::PlayersFirst)
+            .build(&mut CombatRng::seeded(0));
+        assert_eq!(players_first, vec![second, first]);
+
+        let random = |seed| {
+            InitiativeBuilder::new()
+                .with(first, 5.0)
+                .with(second, 5.0)
+                .with_tie_break(TieBreak::Random)
+                .build(&mut CombatRng::seeded(seed))
+        };
+        let orders: Vec<Vec<Entity>> = (0..32).map(random).collect();
+        for (seed, order) in orders.iter().enumerate() {
+            assert_eq!(*order, random(seed as u64));
+        }
+        assert!(orders.contains(&vec![first, second]));
+        assert!(orders.contains(&vec![second, first]));
+    }
+}
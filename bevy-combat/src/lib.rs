@@ -1,6 +1,10 @@
+pub mod cooldown;
 pub mod damage;
+pub mod defend;
 pub mod effects;
+pub mod initiative;
 pub mod progression;
+pub mod simulation;
 pub mod state;
 
 use bevy::prelude::*;
@@ -11,37 +15,90 @@ impl Plugin for CombatPlugin {
     fn build(&self, app: &mut App) {
         app
             // Register types for reflection
+            .register_type::<cooldown::AbilityCooldowns>()
+            .register_type::<cooldown::GlobalCooldown>()
             .register_type::<damage::CombatStats>()
             .register_type::<damage::DamageConfig>()
+            .register_type::<damage::Health>()
+            .register_type::<damage::Resistances>()
+            .register_type::<damage::CombatLog>()
+            .register_type::<defend::Defending>()
             .register_type::<effects::EffectRegistry>()
+            .register_type::<initiative::Speed>()
             .register_type::<progression::Progression>()
+            .register_type::<progression::CombatRewards>()
             .register_type::<state::CombatState>()
             .register_type::<state::CombatManager>()
+            .register_type::<state::TurnTimer>()
+            .register_type::<state::CombatInterrupt>()
             // Add states
             .init_state::<state::CombatState>()
             // Add resources
             .init_resource::<damage::DamageConfig>()
+            .init_resource::<damage::CombatLog>()
+            .init_resource::<damage::CombatRng>()
             .init_resource::<state::CombatManager>()
+            .init_resource::<progression::CombatRewards>()
             // Add events
             .add_message::<damage::DamageEvent>()
             .add_message::<progression::LevelUpEvent>()
+            .add_message::<state::CombatLogEvent>()
+            .add_message::<state::CombatInterrupt>()
             // Add systems
             .add_systems(
                 Update,
                 (
+                    damage::apply_damage,
                     effects::update_effects,
                     effects::handle_madness,
-                    state::manage_combat_state,
+                    (
+                        state::queue_interrupts,
+                        state::manage_combat_state,
+                        state::resolve_interrupts.run_if(in_state(state::CombatState::Processing)),
+                    )
+                        .chain(),
+                    state::tick_turn_timer.run_if(
+                        in_state(state::CombatState::PlayerTurn)
+                            .and(resource_exists::<state::TurnTimer>),
+                    ),
                 ),
+            )
+            .add_systems(
+                OnEnter(state::CombatState::PlayerTurn),
+                (
+                    state::reset_turn_timer,
+                    defend::expire_defending.before(cooldown::tick_cooldowns_on_turn_start),
+                    cooldown::tick_cooldowns_on_turn_start,
+                ),
+            )
+            .add_systems(
+                OnEnter(state::CombatState::EnemyTurn),
+                (
+                    defend::expire_defending.before(cooldown::tick_cooldowns_on_turn_start),
+                    cooldown::tick_cooldowns_on_turn_start,
+                ),
+            )
+            .add_systems(
+                OnEnter(state::CombatState::Victory),
+                progression::apply_victory_rewards,
             );
     }
 }
 
 /// Prelude for easy access to combat types
 pub mod prelude {
-    pub use crate::damage::{CombatStats, DamageConfig, DamageEvent, DamageType};
+    pub use crate::cooldown::{use_ability, AbilityCooldowns, CastBlocked, GlobalCooldown};
+    pub use crate::damage::{
+        Ability, CombatLog, CombatLogEntry, CombatRng, CombatStats, DamageConfig, DamageEvent,
+        DamageResult, DamageType, Health, Resistances,
+    };
+    pub use crate::defend::{defend, Defending};
     pub use crate::effects::{EffectRegistry, EffectType, StatusEffect};
-    pub use crate::progression::{LevelUpEvent, Progression};
-    pub use crate::state::{CombatManager, CombatState};
+    pub use crate::initiative::{InitiativeBuilder, Speed, TieBreak};
+    pub use crate::progression::{award_experience, CombatRewards, LevelUpEvent, Progression};
+    pub use crate::simulation::{simulate_encounter, Combatant, EncounterSpec, SimReport};
+    pub use crate::state::{
+        CombatInterrupt, CombatLogEvent, CombatManager, CombatState, TurnTimer,
+    };
     pub use crate::CombatPlugin;
 }
@@ -1,6 +1,9 @@
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
 
+use crate::damage::{CombatStats, Health};
+use crate::state::CombatLogEvent;
+
 /// Component tracking experience and levels
 #[derive(Component, Debug, Clone, Serialize, Deserialize, Reflect)]
 #[reflect(Component)]
@@ -38,6 +41,71 @@ impl Progression {
     }
 }
 
+/// Max health gained on reaching `level`
+pub fn health_gain(level: u32) -> f32 {
+    10.0 + level as f32 * 2.0
+}
+
+/// Attack gained on reaching `level`
+pub fn attack_gain(level: u32) -> f32 {
+    2.0 + (level / 5) as f32
+}
+
+/// XP earned during the current battle, handed out on victory
+#[derive(Resource, Debug, Clone, Default, Reflect)]
+#[reflect(Resource)]
+pub struct CombatRewards {
+    pub pending: Vec<(Entity, u32)>,
+}
+
+impl CombatRewards {
+    pub fn add(&mut self, entity: Entity, amount: u32) {
+        self.pending.push((entity, amount));
+    }
+}
+
+/// Give `amount` XP to `entity`, applying stat growth for every level gained.
+/// Returns the number of levels gained.
+pub fn award_experience(world: &mut World, entity: Entity, amount: u32) -> u32 {
+    let Some(mut progression) = world.get_mut::<Progression>(entity) else {
+        return 0;
+    };
+    let old_level = progression.level;
+    let levels_gained = progression.add_xp(amount);
+    let new_level = progression.level;
+
+    for level in old_level + 1..=new_level {
+        if let Some(mut health) = world.get_mut::<Health>(entity) {
+            health.max += health_gain(level);
+            health.current += health_gain(level);
+        }
+        if let Some(mut stats) = world.get_mut::<CombatStats>(entity) {
+            stats.attack += attack_gain(level);
+        }
+    }
+
+    if levels_gained > 0 {
+        info!(
+            "Entity {:?} leveled up: {} -> {}",
+            entity, old_level, new_level
+        );
+        world.write_message(LevelUpEvent { entity, new_level });
+        world.write_message(CombatLogEvent::LevelUp {
+            entity,
+            level: new_level,
+        });
+    }
+    levels_gained
+}
+
+/// System that hands out pending XP when a battle is won
+pub fn apply_victory_rewards(world: &mut World) {
+    let pending = std::mem::take(&mut world.resource_mut::<CombatRewards>().pending);
+    for (entity, amount) in pending {
+        award_experience(world, entity, amount);
+    }
+}
+
 /// Event fired when an entity levels up
 #[derive(Message, Debug, Clone, Reflect)]
 pub struct LevelUpEvent {
@@ -70,3 +138,50 @@ pub fn handle_xp_gain(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level_up_boosts_stats_once() {
+        let mut world = World::new();
+        world.init_resource::<Messages<LevelUpEvent>>();
+        world.init_resource::<Messages<CombatLogEvent>>();
+        let hero = world
+            .spawn((
+                Progression::default(),
+                Health::new(50.0),
+                CombatStats::default(),
+            ))
+            .id();
+
+        assert_eq!(award_experience(&mut world, hero, 60), 0);
+        assert_eq!(world.get::<Progression>(hero).unwrap().level, 1);
+
+        assert_eq!(award_experience(&mut world, hero, 60), 1);
+        assert_eq!(award_experience(&mut world, hero, 5), 0);
+
+        assert_eq!(world.get::<Progression>(hero).unwrap().level, 2);
+        assert_eq!(
+            world.get::<Health>(hero).unwrap().max,
+            50.0 + health_gain(2)
+        );
+        assert_eq!(
+            world.get::<CombatStats>(hero).unwrap().attack,
+            CombatStats::default().attack + attack_gain(2)
+        );
+
+        let log: Vec<CombatLogEvent> = world
+            .resource_mut::<Messages<CombatLogEvent>>()
+            .drain()
+            .collect();
+        assert_eq!(
+            log,
+            vec![CombatLogEvent::LevelUp {
+                entity: hero,
+                level: 2
+            }]
+        );
+    }
+}
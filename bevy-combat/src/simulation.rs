@@ -0,0 +1,288 @@
+use crate::damage::{
+    base_damage, calculate_damage_with, Ability, CombatRng, CombatStats, DamageConfig, DamageType,
+};
+
+/// Rounds after which an unfinished battle counts as a loss
+pub const MAX_SIMULATED_ROUNDS: u32 = 100;
+
+/// One side's fighter in a simulated encounter
+#[derive(Debug, Clone)]
+pub struct Combatant {
+    pub name: String,
+    pub stats: CombatStats,
+    pub max_health: f32,
+    /// Falls back to a basic physical attack when empty
+    pub abilities: Vec<Ability>,
+}
+
+impl Combatant {
+    pub fn new(name: impl Into<String>, stats: CombatStats, max_health: f32) -> Self {
+        Self {
+            name: name.into(),
+            stats,
+            max_health,
+            abilities: Vec::new(),
+        }
+    }
+
+    pub fn with_ability(mut self, ability: Ability) -> Self {
+        self.abilities.push(ability);
+        self
+    }
+}
+
+/// Two sides to fight each other
+#[derive(Debug, Clone, Default)]
+pub struct EncounterSpec {
+    pub players: Vec<Combatant>,
+    pub enemies: Vec<Combatant>,
+    pub config: DamageConfig,
+}
+
+/// Outcome of many simulated battles, from the players' side
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SimReport {
+    pub trials: u32,
+    pub wins: u32,
+    pub win_rate: f32,
+    pub average_rounds: f32,
+    /// Total damage the player side took per battle
+    pub average_damage_taken: f32,
+}
+
+/// A fighter mid-battle
+struct Fighter<'a> {
+    combatant: &'a Combatant,
+    health: f32,
+}
+
+impl Fighter<'_> {
+    fn is_alive(&self) -> bool {
+        self.health > 0.0
+    }
+}
+
+/// How much a hit of `damage` is worth against a target on `health`: 1.0 if
+/// it finishes the target, otherwise up to 0.5 by the share of health it
+/// takes, so wounding never counts as much as a kill
+pub fn kill_potential(damage: f32, health: f32) -> f32 {
+    if health <= 0.0 {
+        0.0
+    } else if damage >= health {
+        1.0
+    } else {
+        0.5 * damage.max(0.0) / health
+    }
+}
+
+/// The enemy AI's turn: hit the living opponent with the best
+/// `kill_potential`, ties going to the weakest, with the ability expected to
+/// deal the most damage to it. Returns `(ability, target)` indices, or `None`
+/// if no opponent is alive.
+pub fn ai_turn(actor: &Combatant, opponents: &[(&CombatStats, f32)]) -> Option<(usize, usize)> {
+    let strongest = |target_stats: &CombatStats| {
+        actor
+            .abilities
+            .iter()
+            .map(|ability| {
+                base_damage(&actor.stats, target_stats, ability.damage_type) * ability.power
+            })
+            .enumerate()
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .unwrap_or_else(|| {
+                (
+                    0,
+                    base_damage(&actor.stats, target_stats, DamageType::Physical),
+                )
+            })
+    };
+
+    opponents
+        .iter()
+        .enumerate()
+        .filter(|(_, (_, health))| *health > 0.0)
+        .map(|(target, (stats, health))| {
+            let (ability, damage) = strongest(stats);
+            (ability, target, kill_potential(damage, *health), *health)
+        })
+        .max_by(|a, b| a.2.total_cmp(&b.2).then(b.3.total_cmp(&a.3)))
+        .map(|(ability, target, ..)| (ability, target))
+}
+
+/// Play out `trials` battles between the two sides, each with its own seed
+/// derived from `seed`, and summarize the results
+pub fn simulate_encounter(spec: &EncounterSpec, trials: u32, seed: u64) -> SimReport {
+    let mut report = SimReport {
+        trials,
+        ..Default::default()
+    };
+    if trials == 0 {
+        return report;
+    }
+
+    let mut total_rounds = 0;
+    let mut total_damage_taken = 0.0;
+    for trial in 0..trials {
+        let mut rng = CombatRng::seeded(seed.wrapping_add(trial as u64));
+        let (won, rounds, damage_taken) = simulate_battle(spec, &mut rng);
+        report.wins += won as u32;
+        total_rounds += rounds;
+        total_damage_taken += damage_taken;
+    }
+
+    report.win_rate = report.wins as f32 / trials as f32;
+    report.average_rounds = total_rounds as f32 / trials as f32;
+    report.average_damage_taken = total_damage_taken / trials as f32;
+    report
+}
+
+/// Returns whether the players won, the rounds fought and the damage the
+/// players took
+fn simulate_battle(spec: &EncounterSpec, rng: &mut CombatRng) -> (bool, u32, f32) {
+    let mut players = fighters(&spec.players);
+    let mut enemies = fighters(&spec.enemies);
+    let mut damage_taken = 0.0;
+
+    for round in 1..=MAX_SIMULATED_ROUNDS {
+        take_turns(&players, &mut enemies, &spec.config, rng);
+        if !enemies.iter().any(Fighter::is_alive) {
+            return (true, round, damage_taken);
+        }
+
+        damage_taken += take_turns(&enemies, &mut players, &spec.config, rng);
+        if !players.iter().any(Fighter::is_alive) {
+            return (false, round, damage_taken);
+        }
+    }
+
+    (false, MAX_SIMULATED_ROUNDS, damage_taken)
+}
+
+fn fighters(side: &[Combatant]) -> Vec<Fighter<'_>> {
+    side.iter()
+        .map(|combatant| Fighter {
+            combatant,
+            health: combatant.max_health,
+        })
+        .collect()
+}
+
+/// Every living attacker acts once; returns the damage dealt
+fn take_turns(
+    attackers: &[Fighter],
+    defenders: &mut [Fighter],
+    config: &DamageConfig,
+    rng: &mut CombatRng,
+) -> f32 {
+    let basic_attack = Ability::new("Attack", DamageType::Physical, 1.0);
+    let mut dealt = 0.0;
+
+    for attacker in attackers.iter().filter(|fighter| fighter.is_alive()) {
+        let opponents: Vec<(&CombatStats, f32)> = defenders
+            .iter()
+            .map(|fighter| (&fighter.combatant.stats, fighter.health))
+            .collect();
+        let Some((ability, target)) = ai_turn(attacker.combatant, &opponents) else {
+            break;
+        };
+        let ability = attacker
+            .combatant
+            .abilities
+            .get(ability)
+            .unwrap_or(&basic_attack);
+
+        let defender = &mut defenders[target];
+        let (amount, _) = calculate_damage_with(
+            &mut rng.0,
+            &attacker.combatant.stats,
+            &defender.combatant.stats,
+            ability.damage_type,
+            config,
+        );
+        let amount = (amount * ability.power).min(defender.health);
+        defender.health -= amount;
+        dealt += amount;
+    }
+
+    dealt
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hero(attack: f32, max_health: f32) -> Combatant {
+        Combatant::new(
+            "Hero",
+            CombatStats {
+                attack,
+                ..Default::default()
+            },
+            max_health,
+        )
+    }
+
+    fn goblins(count: usize) -> Vec<Combatant> {
+        (0..count)
+            .map(|_| Combatant::new("Goblin", CombatStats::default(), 30.0))
+            .collect()
+    }
+
+    #[test]
+    fn test_overpowered_players_nearly_always_win() {
+        let spec = EncounterSpec {
+            players: vec![hero(500.0, 10_000.0)],
+            enemies: goblins(3),
+            ..Default::default()
+        };
+
+        let report = simulate_encounter(&spec, 200, 42);
+        assert_eq!(report.trials, 200);
+        assert!(report.win_rate > 0.99);
+        // One goblin falls per round
+        assert!(report.average_rounds <= 3.0);
+        assert!(report.average_damage_taken > 0.0);
+    }
+
+    #[test]
+    fn test_simulation_is_reproducible_for_a_seed() {
+        let spec = EncounterSpec {
+            players: vec![hero(12.0, 60.0)],
+            enemies: goblins(2),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            simulate_encounter(&spec, 50, 7),
+            simulate_encounter(&spec, 50, 7)
+        );
+    }
+
+    #[test]
+    fn test_ai_targets_weakest_with_strongest_ability() {
+        let caster = hero(10.0, 50.0)
+            .with_ability(Ability::new("Jab", DamageType::Physical, 0.5))
+            .with_ability(Ability::new("Smite", DamageType::Physical, 2.0));
+        let stats = CombatStats::default();
+
+        let choice = ai_turn(&caster, &[(&stats, 40.0), (&stats, 5.0), (&stats, 0.0)]);
+        assert_eq!(choice, Some((1, 1)));
+        assert_eq!(ai_turn(&caster, &[(&stats, 0.0)]), None);
+    }
+
+    #[test]
+    fn test_ai_finishes_a_target_it_can_kill() {
+        let caster =
+            hero(10.0, 50.0).with_ability(Ability::new("Smite", DamageType::Physical, 2.0));
+        let armored = CombatStats {
+            defense: 25.0,
+            ..Default::default()
+        };
+        let exposed = CombatStats::default();
+
+        // The armored target is weaker but shrugs the hit off; the exposed
+        // one goes down to it
+        let choice = ai_turn(&caster, &[(&armored, 10.0), (&exposed, 25.0)]);
+        assert_eq!(choice, Some((0, 1)));
+    }
+}
@@ -1,5 +1,11 @@
 use bevy::prelude::*;
 
+use crate::damage::{Ability, CombatStats, DamageConfig, DamageEvent};
+
+/// Most interrupts resolved in one turn. Further interrupts are dropped so
+/// effects that trigger each other can't stall combat.
+pub const MAX_INTERRUPTS_PER_TURN: u32 = 8;
+
 /// States for the combat system
 #[derive(States, Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Reflect)]
 pub enum CombatState {
@@ -19,6 +25,115 @@ pub enum CombatState {
 pub struct CombatManager {
     pub round: u32,
     pub current_turn_entity: Option<Entity>,
+    /// Interrupts waiting to be resolved in `CombatState::Processing`
+    pub pending_interrupts: Vec<CombatInterrupt>,
+    /// The turn to return to once pending interrupts are resolved
+    pub interrupted_turn: Option<InterruptedTurn>,
+    pub interrupts_this_turn: u32,
+    /// Set when the current turn was re-entered after an interrupt rather
+    /// than newly started
+    pub resumed_turn: bool,
+}
+
+/// An action taken out of turn order, e.g. a trap or an opportunity attack
+#[derive(Message, Debug, Clone, Reflect)]
+pub struct CombatInterrupt {
+    pub actor: Entity,
+    pub target: Entity,
+    pub action: Ability,
+}
+
+/// Where combat resumes after an interrupt
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+pub struct InterruptedTurn {
+    pub state: CombatState,
+    pub entity: Option<Entity>,
+}
+
+/// Notable combat happenings for UI and logs
+#[derive(Message, Debug, Clone, PartialEq, Reflect)]
+pub enum CombatLogEvent {
+    /// The acting entity ran out of time and its turn was passed
+    TurnTimedOut { entity: Option<Entity> },
+    /// An entity reached a new level
+    LevelUp { entity: Entity, level: u32 },
+    /// An interrupt was ignored because the turn hit its interrupt limit
+    InterruptDropped { actor: Entity },
+    /// A hit brought an entity to zero health
+    Death { entity: Entity, killer: Entity },
+    /// An entity spent its turn defending
+    Defend { entity: Entity },
+}
+
+/// Optional time limit for the player's turn. Insert it to enable auto-pass;
+/// without it turns wait indefinitely.
+#[derive(Resource, Debug, Clone, Reflect)]
+#[reflect(Resource)]
+pub struct TurnTimer {
+    pub remaining: f32,
+    pub limit: f32,
+}
+
+impl TurnTimer {
+    pub fn new(limit: f32) -> Self {
+        Self {
+            remaining: limit,
+            limit,
+        }
+    }
+}
+
+/// Move from the current turn to the other side's turn
+pub fn advance_turn(
+    current: &CombatState,
+    next_state: &mut NextState<CombatState>,
+    manager: &mut CombatManager,
+) {
+    match current {
+        CombatState::PlayerTurn => next_state.set(CombatState::EnemyTurn),
+        CombatState::EnemyTurn => {
+            manager.round += 1;
+            next_state.set(CombatState::PlayerTurn);
+        }
+        _ => return,
+    }
+    manager.current_turn_entity = None;
+    manager.interrupts_this_turn = 0;
+    manager.resumed_turn = false;
+}
+
+/// System that restarts the turn timer when the player's turn begins. A turn
+/// resumed after an interrupt keeps the time it had left.
+pub fn reset_turn_timer(manager: Res<CombatManager>, timer: Option<ResMut<TurnTimer>>) {
+    if manager.resumed_turn {
+        return;
+    }
+
+    if let Some(mut timer) = timer {
+        timer.remaining = timer.limit;
+    }
+}
+
+/// System that counts down the player's turn and auto-passes at zero
+pub fn tick_turn_timer(
+    time: Res<Time>,
+    state: Res<State<CombatState>>,
+    mut timer: ResMut<TurnTimer>,
+    mut next_state: ResMut<NextState<CombatState>>,
+    mut manager: ResMut<CombatManager>,
+    mut log: MessageWriter<CombatLogEvent>,
+) {
+    if timer.remaining <= 0.0 {
+        return;
+    }
+
+    timer.remaining -= time.delta_secs();
+    if timer.remaining <= 0.0 {
+        log.write(CombatLogEvent::TurnTimedOut {
+            entity: manager.current_turn_entity,
+        });
+        advance_turn(state.get(), &mut next_state, &mut manager);
+    }
 }
 
 /// System for transitioning between combat states
@@ -33,6 +148,8 @@ pub fn manage_combat_state(
             manager.round = 1;
             next_state.set(CombatState::PlayerTurn);
         }
+        // Interrupted turns are resumed by `resolve_interrupts`
+        CombatState::Processing if manager.interrupted_turn.is_some() => {}
         CombatState::Processing => {
             // After processing actions, usually go to next turn or end battle
             // For now, just a placeholder transition
@@ -41,3 +158,233 @@ pub fn manage_combat_state(
         _ => {}
     }
 }
+
+/// System that queues incoming interrupts and suspends the active turn
+pub fn queue_interrupts(
+    mut interrupts: MessageReader<CombatInterrupt>,
+    state: Res<State<CombatState>>,
+    mut next_state: ResMut<NextState<CombatState>>,
+    mut manager: ResMut<CombatManager>,
+    mut log: MessageWriter<CombatLogEvent>,
+) {
+    for interrupt in interrupts.read() {
+        if manager.interrupts_this_turn >= MAX_INTERRUPTS_PER_TURN {
+            log.write(CombatLogEvent::InterruptDropped {
+                actor: interrupt.actor,
+            });
+            continue;
+        }
+        manager.interrupts_this_turn += 1;
+        manager.pending_interrupts.push(interrupt.clone());
+    }
+
+    let current = *state.get();
+    if manager.pending_interrupts.is_empty()
+        || manager.interrupted_turn.is_some()
+        || !matches!(current, CombatState::PlayerTurn | CombatState::EnemyTurn)
+    {
+        return;
+    }
+
+    manager.interrupted_turn = Some(InterruptedTurn {
+        state: current,
+        entity: manager.current_turn_entity,
+    });
+    next_state.set(CombatState::Processing);
+}
+
+/// System that fires queued interrupts, then hands control back to the
+/// interrupted turn
+pub fn resolve_interrupts(
+    mut next_state: ResMut<NextState<CombatState>>,
+    mut manager: ResMut<CombatManager>,
+    stats: Query<&CombatStats>,
+    config: Res<DamageConfig>,
+    mut damage: MessageWriter<DamageEvent>,
+) {
+    let Some(resume) = manager.interrupted_turn else {
+        return;
+    };
+
+    for interrupt in std::mem::take(&mut manager.pending_interrupts) {
+        let (Ok(actor_stats), Ok(target_stats)) =
+            (stats.get(interrupt.actor), stats.get(interrupt.target))
+        else {
+            continue;
+        };
+        manager.current_turn_entity = Some(interrupt.actor);
+        damage.write(interrupt.action.hit(
+            (interrupt.actor, actor_stats),
+            (interrupt.target, target_stats),
+            &config,
+        ));
+    }
+
+    manager.interrupted_turn = None;
+    manager.resumed_turn = true;
+    manager.current_turn_entity = resume.entity;
+    next_state.set(resume.state);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::damage::DamageType;
+    use bevy::state::app::StatesPlugin;
+    use std::time::Duration;
+
+    #[test]
+    fn test_turn_timer_auto_passes() {
+        let mut app = App::new();
+        app.add_plugins(StatesPlugin)
+            .insert_state(CombatState::PlayerTurn)
+            .init_resource::<Time>()
+            .init_resource::<CombatManager>()
+            .insert_resource(TurnTimer::new(5.0))
+            .add_message::<CombatLogEvent>()
+            .add_systems(
+                Update,
+                tick_turn_timer.run_if(in_state(CombatState::PlayerTurn)),
+            );
+
+        app.world_mut()
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs(3));
+        app.update();
+        assert_eq!(
+            *app.world().resource::<State<CombatState>>().get(),
+            CombatState::PlayerTurn
+        );
+
+        app.world_mut()
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs(3));
+        app.update();
+        app.update();
+        assert_eq!(
+            *app.world().resource::<State<CombatState>>().get(),
+            CombatState::EnemyTurn
+        );
+
+        let events: Vec<CombatLogEvent> = app
+            .world_mut()
+            .resource_mut::<Messages<CombatLogEvent>>()
+            .drain()
+            .collect();
+        assert_eq!(events, vec![CombatLogEvent::TurnTimedOut { entity: None }]);
+    }
+
+    fn interrupt_app(hero: impl FnOnce(&mut World) -> Entity) -> (App, Entity) {
+        let mut app = App::new();
+        app.add_plugins(StatesPlugin)
+            .insert_state(CombatState::PlayerTurn)
+            .init_resource::<CombatManager>()
+            .init_resource::<DamageConfig>()
+            .add_message::<CombatInterrupt>()
+            .add_message::<CombatLogEvent>()
+            .add_message::<DamageEvent>()
+            .add_systems(
+                Update,
+                (
+                    queue_interrupts,
+                    manage_combat_state,
+                    resolve_interrupts.run_if(in_state(CombatState::Processing)),
+                )
+                    .chain(),
+            );
+
+        let hero = hero(app.world_mut());
+        app.world_mut()
+            .resource_mut::<CombatManager>()
+            .current_turn_entity = Some(hero);
+        (app, hero)
+    }
+
+    fn drain<M: Message>(app: &mut App) -> Vec<M> {
+        app.world_mut()
+            .resource_mut::<Messages<M>>()
+            .drain()
+            .collect()
+    }
+
+    #[test]
+    fn test_interrupt_fires_and_returns_control() {
+        let (mut app, hero) = interrupt_app(|world| world.spawn(CombatStats::default()).id());
+        let trap = app.world_mut().spawn(CombatStats::default()).id();
+
+        app.world_mut().write_message(CombatInterrupt {
+            actor: trap,
+            target: hero,
+            action: Ability::new("Spike Trap", DamageType::Physical, 1.0),
+        });
+        app.update();
+        assert_eq!(
+            app.world().resource::<CombatManager>().interrupted_turn,
+            Some(InterruptedTurn {
+                state: CombatState::PlayerTurn,
+                entity: Some(hero),
+            })
+        );
+
+        app.update();
+        app.update();
+        assert_eq!(
+            *app.world().resource::<State<CombatState>>().get(),
+            CombatState::PlayerTurn
+        );
+        let manager = app.world().resource::<CombatManager>();
+        assert_eq!(manager.current_turn_entity, Some(hero));
+        assert!(manager.interrupted_turn.is_none());
+
+        let hits = drain::<DamageEvent>(&mut app);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].attacker, trap);
+        assert_eq!(hits[0].target, hero);
+    }
+
+    #[test]
+    fn test_interrupt_keeps_remaining_turn_time() {
+        let (mut app, hero) = interrupt_app(|world| world.spawn(CombatStats::default()).id());
+        app.insert_resource(TurnTimer::new(5.0))
+            .add_systems(OnEnter(CombatState::PlayerTurn), reset_turn_timer);
+        // Enter the turn, then spend some of it before the interrupt
+        app.update();
+        app.world_mut().resource_mut::<TurnTimer>().remaining = 2.0;
+
+        app.world_mut().write_message(CombatInterrupt {
+            actor: hero,
+            target: hero,
+            action: Ability::new("Riposte", DamageType::Physical, 1.0),
+        });
+        app.update();
+        app.update();
+        app.update();
+        assert_eq!(
+            *app.world().resource::<State<CombatState>>().get(),
+            CombatState::PlayerTurn
+        );
+        assert_eq!(app.world().resource::<TurnTimer>().remaining, 2.0);
+    }
+
+    #[test]
+    fn test_interrupts_are_capped_per_turn() {
+        let (mut app, hero) = interrupt_app(|world| world.spawn(CombatStats::default()).id());
+
+        for _ in 0..MAX_INTERRUPTS_PER_TURN + 3 {
+            app.world_mut().write_message(CombatInterrupt {
+                actor: hero,
+                target: hero,
+                action: Ability::new("Riposte", DamageType::Physical, 1.0),
+            });
+        }
+        app.update();
+        assert_eq!(drain::<CombatLogEvent>(&mut app).len(), 3);
+
+        app.update();
+        app.update();
+        assert_eq!(
+            drain::<DamageEvent>(&mut app).len(),
+            MAX_INTERRUPTS_PER_TURN as usize
+        );
+    }
+}
@@ -5,11 +5,13 @@
 //! - Sound effect generation with retro constraints
 //! - MIDI pattern generation for game loops
 //! - Audio style consistency across tracks
+//! - Speech-to-text transcription of recorded voice input
 
 use anyhow::{Context, Result};
 use async_openai::{
     Client,
     config::OpenAIConfig,
+    types::audio::{AudioInput, CreateTranscriptionRequestArgs},
     types::chat::{
         ChatCompletionRequestSystemMessageArgs, ChatCompletionRequestUserMessageArgs,
         CreateChatCompletionRequestArgs,
@@ -18,6 +20,7 @@ use async_openai::{
 use minijinja::Environment;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::Mutex;
@@ -25,6 +28,7 @@ use tokio::sync::Mutex;
 use super::{
     AiGenerator,
     cache::{AiCache, CachedData},
+    telemetry::{CallRecord, Telemetry},
     tokens::TokenCounter,
 };
 
@@ -35,6 +39,9 @@ pub struct AudioGenerator {
     cache: Arc<Mutex<AiCache>>,
     token_counter: Arc<Mutex<TokenCounter>>,
     template_env: Arc<Mutex<Environment<'static>>>,
+    telemetry: Telemetry,
+    /// Bypass the cache, see `with_preview`
+    preview: bool,
 }
 
 /// Configuration for audio generation
@@ -156,15 +163,32 @@ impl AudioGenerator {
             cache,
             token_counter,
             template_env: Arc::new(Mutex::new(env)),
+            telemetry: Telemetry::default(),
+            preview: false,
         }
     }
 
+    /// Report calls to a shared telemetry handle
+    pub fn with_telemetry(mut self, telemetry: Telemetry) -> Self {
+        self.telemetry = telemetry;
+        self
+    }
+
+    /// Throwaway previews for prompt iteration: results are neither read
+    /// from nor written to the cache. Usage and cost are still recorded.
+    pub fn with_preview(mut self, preview: bool) -> Self {
+        self.preview = preview;
+        self
+    }
+
     /// Generate music track description (to be converted to MIDI/audio externally)
     pub async fn generate_music_description(
         &self,
         music_type: &str,
         config: AudioConfig,
     ) -> Result<MusicDescription> {
+        let started = std::time::Instant::now();
+
         // Determine which template to use
         let template_name = match music_type {
             "battle" => "battle_music",
@@ -205,10 +229,12 @@ impl AudioGenerator {
             .generate_key("audio_music", template_name, &params);
 
         // Check cache
-        if let Some(cached) = self.cache.lock().await.get(&cache_key).await
+        if !self.preview
+            && let Some(cached) = self.cache.lock().await.get(&cache_key).await
             && let CachedData::Text(data) = &cached.data
             && let Ok(description) = serde_json::from_str::<MusicDescription>(data)
         {
+            self.record_call(CallRecord::cache_hit(started.elapsed()));
             return Ok(description);
         }
 
@@ -257,15 +283,20 @@ impl AudioGenerator {
         for (k, v) in params {
             cache_params.insert(k, serde_json::Value::String(v));
         }
-        self.cache
-            .lock()
-            .await
-            .put(cache_key, CachedData::Text(cache_data), cache_params)
-            .await?;
+        if !self.preview {
+            self.cache
+                .lock()
+                .await
+                .put(cache_key, CachedData::Text(cache_data), cache_params)
+                .await?;
+        }
 
         // Track usage
+        let mut record = CallRecord::api(0, 0.0, started.elapsed());
         if let Some(usage) = response.usage {
-            self.token_counter
+            record.tokens = usage.total_tokens as usize;
+            record.cost = self
+                .token_counter
                 .lock()
                 .await
                 .record_usage(
@@ -275,6 +306,7 @@ impl AudioGenerator {
                 )
                 .await?;
         }
+        self.record_call(record);
 
         Ok(description)
     }
@@ -343,6 +375,8 @@ impl AudioGenerator {
         effect_type: &str,
         duration: f32,
     ) -> Result<SoundEffectDescription> {
+        let started = std::time::Instant::now();
+
         // Prepare context for template
         let context = json!({
             "effect_type": effect_type,
@@ -364,10 +398,12 @@ impl AudioGenerator {
             .generate_key("audio_sfx", effect_type, &params);
 
         // Check cache
-        if let Some(cached) = self.cache.lock().await.get(&cache_key).await
+        if !self.preview
+            && let Some(cached) = self.cache.lock().await.get(&cache_key).await
             && let CachedData::Text(data) = &cached.data
             && let Ok(sfx) = serde_json::from_str::<SoundEffectDescription>(data)
         {
+            self.record_call(CallRecord::cache_hit(started.elapsed()));
             return Ok(sfx);
         }
 
@@ -416,15 +452,20 @@ impl AudioGenerator {
         for (k, v) in params {
             cache_params.insert(k, serde_json::Value::String(v));
         }
-        self.cache
-            .lock()
-            .await
-            .put(cache_key, CachedData::Text(cache_data), cache_params)
-            .await?;
+        if !self.preview {
+            self.cache
+                .lock()
+                .await
+                .put(cache_key, CachedData::Text(cache_data), cache_params)
+                .await?;
+        }
 
         // Track usage
+        let mut record = CallRecord::api(0, 0.0, started.elapsed());
         if let Some(usage) = response.usage {
-            self.token_counter
+            record.tokens = usage.total_tokens as usize;
+            record.cost = self
+                .token_counter
                 .lock()
                 .await
                 .record_usage(
@@ -434,6 +475,7 @@ impl AudioGenerator {
                 )
                 .await?;
         }
+        self.record_call(record);
 
         Ok(sfx)
     }
@@ -577,8 +619,63 @@ impl AudioGenerator {
 
         Ok(results)
     }
+
+    /// Transcribe recorded speech (WAV bytes) into text using Whisper
+    pub async fn transcribe_audio(&self, audio: Vec<u8>) -> Result<String> {
+        // Cache by audio content so re-sending the same clip is free
+        let audio_hash = format!("{:x}", Sha256::digest(&audio));
+        let mut params = HashMap::new();
+        params.insert("model".to_string(), TRANSCRIPTION_MODEL.to_string());
+
+        let cache_key =
+            self.cache
+                .lock()
+                .await
+                .generate_key("audio_transcription", &audio_hash, &params);
+
+        if !self.preview
+            && let Some(cached) = self.cache.lock().await.get(&cache_key).await
+            && let CachedData::Text(text) = &cached.data
+        {
+            return Ok(text.clone());
+        }
+
+        let request = CreateTranscriptionRequestArgs::default()
+            .file(AudioInput::from_vec_u8(
+                "voice_input.wav".to_string(),
+                audio,
+            ))
+            .model(TRANSCRIPTION_MODEL)
+            .build()?;
+
+        let response = self
+            .client
+            .audio()
+            .transcription()
+            .create(request)
+            .await
+            .context("Failed to transcribe audio")?;
+        let text = response.text.trim().to_string();
+
+        let mut cache_params = HashMap::new();
+        for (k, v) in params {
+            cache_params.insert(k, serde_json::Value::String(v));
+        }
+        if !self.preview {
+            self.cache
+                .lock()
+                .await
+                .put(cache_key, CachedData::Text(text.clone()), cache_params)
+                .await?;
+        }
+
+        Ok(text)
+    }
 }
 
+/// Speech-to-text model used for voice input
+const TRANSCRIPTION_MODEL: &str = "whisper-1";
+
 #[async_trait::async_trait]
 impl AiGenerator for AudioGenerator {
     async fn estimate_tokens(&self, request: &str) -> Result<usize> {
@@ -598,6 +695,21 @@ impl AiGenerator for AudioGenerator {
     async fn clear_cache(&self, key: &str) -> Result<()> {
         self.cache.lock().await.clear(key).await
     }
+
+    fn name(&self) -> &'static str {
+        "audio"
+    }
+
+    fn telemetry(&self) -> &Telemetry {
+        &self.telemetry
+    }
+
+    async fn generate_asset(&self, prompt: &str) -> Result<Vec<u8>> {
+        let description = self
+            .generate_music_description(prompt, AudioConfig::default())
+            .await?;
+        Ok(serde_json::to_vec(&description)?)
+    }
 }
 
 /// Music track description
@@ -701,3 +813,38 @@ fn capitalize_first(s: &str) -> String {
         Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::CacheConfig;
+    use crate::test_support::mock_openai;
+
+    #[tokio::test]
+    async fn test_transcribe_audio_parses_whisper_response() {
+        let api_base = mock_openai(
+            r#"{"text": " A dungeon crawler with cats. ", "usage": {"type": "duration", "seconds": 2}}"#,
+        ).await;
+        let client = Arc::new(Client::with_config(
+            OpenAIConfig::new()
+                .with_api_key("test")
+                .with_api_base(api_base),
+        ));
+        let cache = AiCache::with_config(CacheConfig {
+            cache_dir: std::env::temp_dir().join("vintage_ai_client_transcription_tests"),
+            ..CacheConfig::default()
+        })
+        .unwrap();
+        // Unique audio per run so a cached result from a previous run can't mask the mock
+        let audio = uuid::Uuid::new_v4().as_bytes().to_vec();
+
+        let generator = AudioGenerator::new(
+            client,
+            Arc::new(Mutex::new(cache)),
+            Arc::new(Mutex::new(TokenCounter::new())),
+        );
+        let text = generator.transcribe_audio(audio).await.unwrap();
+
+        assert_eq!(text, "A dungeon crawler with cats.");
+    }
+}
@@ -14,6 +14,7 @@ use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
 use tokio::sync::RwLock;
 
 /// Main cache manager for all AI operations
@@ -27,6 +28,29 @@ pub struct AiCache {
     config: CacheConfig,
     /// Cache statistics
     stats: Arc<RwLock<CacheStats>>,
+    /// Hit, miss, insertion and eviction counts, shared between clones
+    counters: Arc<CacheCounters>,
+}
+
+/// Lock-free event counts behind `AiCache::stats`
+#[derive(Debug, Default)]
+struct CacheCounters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    insertions: AtomicU64,
+    evictions: AtomicU64,
+}
+
+impl CacheCounters {
+    fn record(counter: &AtomicU64, count: u64) {
+        counter.fetch_add(count, Ordering::Relaxed);
+    }
+
+    fn reset(&self) {
+        for counter in [&self.hits, &self.misses, &self.insertions, &self.evictions] {
+            counter.store(0, Ordering::Relaxed);
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -81,8 +105,8 @@ pub enum CachedData {
     Json(serde_json::Value),
     /// Binary data
     Binary(Vec<u8>),
-    /// Embedding vector
-    Embedding(Vec<f32>),
+    /// Embedding vector with the space it was produced in
+    Embedding(crate::embeddings::Embedding),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -109,6 +133,10 @@ pub struct CacheStats {
     pub hits: u64,
     /// Total cache misses
     pub misses: u64,
+    /// Items written with `put`
+    pub insertions: u64,
+    /// Expired items removed from memory or disk
+    pub evictions: u64,
     /// Total size in bytes
     pub total_size_bytes: u64,
     /// Number of items in cache
@@ -123,6 +151,60 @@ pub struct CacheStats {
     pub disk_usage_bytes: u64,
 }
 
+/// Turns a request into a cache key, deciding which parameters matter
+pub trait KeyStrategy: Send + Sync {
+    fn key(&self, prefix: &str, content: &str, params: &HashMap<String, String>) -> String;
+}
+
+/// Hashes the prefix, content and every parameter
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultKeyStrategy;
+
+impl KeyStrategy for DefaultKeyStrategy {
+    fn key(&self, prefix: &str, content: &str, params: &HashMap<String, String>) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(prefix);
+        hasher.update(content);
+
+        // Sort parameters for consistent hashing
+        let mut sorted_params: Vec<_> = params.iter().collect();
+        sorted_params.sort_by_key(|(k, _)| *k);
+
+        for (key, value) in sorted_params {
+            hasher.update(key);
+            hasher.update(value);
+        }
+
+        format!("{:x}", hasher.finalize())
+    }
+}
+
+/// `DefaultKeyStrategy` minus parameters that don't change the output,
+/// e.g. playback rate or pitch for voice lines
+#[derive(Debug, Clone, Default)]
+pub struct IgnoreParams {
+    pub ignored: Vec<String>,
+}
+
+impl IgnoreParams {
+    pub fn new(ignored: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            ignored: ignored.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl KeyStrategy for IgnoreParams {
+    fn key(&self, prefix: &str, content: &str, params: &HashMap<String, String>) -> String {
+        let kept = params
+            .iter()
+            .filter(|(key, _)| !self.ignored.contains(key))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        DefaultKeyStrategy.key(prefix, content, &kept)
+    }
+}
+
 impl AiCache {
     /// Create a new cache instance
     pub fn new() -> Result<Self> {
@@ -140,6 +222,7 @@ impl AiCache {
             cache_dir: config.cache_dir.clone(),
             config,
             stats: Arc::new(RwLock::new(CacheStats::default())),
+            counters: Arc::new(CacheCounters::default()),
         })
     }
 
@@ -150,20 +233,18 @@ impl AiCache {
         content: &str,
         params: &HashMap<String, String>,
     ) -> String {
-        let mut hasher = Sha256::new();
-        hasher.update(prefix);
-        hasher.update(content);
-
-        // Sort parameters for consistent hashing
-        let mut sorted_params: Vec<_> = params.iter().collect();
-        sorted_params.sort_by_key(|(k, _)| *k);
-
-        for (key, value) in sorted_params {
-            hasher.update(key);
-            hasher.update(value);
-        }
+        self.generate_key_with(&DefaultKeyStrategy, prefix, content, params)
+    }
 
-        format!("{:x}", hasher.finalize())
+    /// Generate cache key using a caller-chosen strategy
+    pub fn generate_key_with(
+        &self,
+        strategy: &dyn KeyStrategy,
+        prefix: &str,
+        content: &str,
+        params: &HashMap<String, String>,
+    ) -> String {
+        strategy.key(prefix, content, params)
     }
 
     /// Get item from cache
@@ -180,13 +261,13 @@ impl AiCache {
                     item.metadata.last_accessed = now;
 
                     // Update global stats
-                    let mut stats = self.stats.write().await;
-                    stats.hits += 1;
+                    CacheCounters::record(&self.counters.hits, 1);
 
                     return Some(item.clone());
                 } else {
                     // Remove expired item
                     cache.remove(key);
+                    CacheCounters::record(&self.counters.evictions, 1);
                 }
             }
         }
@@ -200,15 +281,13 @@ impl AiCache {
             }
 
             // Update stats
-            let mut stats = self.stats.write().await;
-            stats.hits += 1;
+            CacheCounters::record(&self.counters.hits, 1);
 
             return Some(item);
         }
 
         // Cache miss
-        let mut stats = self.stats.write().await;
-        stats.misses += 1;
+        CacheCounters::record(&self.counters.misses, 1);
 
         None
     }
@@ -227,7 +306,7 @@ impl AiCache {
             CachedData::Text(s) => s.len(),
             CachedData::Image(v) | CachedData::Audio(v) | CachedData::Binary(v) => v.len(),
             CachedData::Json(j) => serde_json::to_vec(j)?.len(),
-            CachedData::Embedding(v) => v.len() * std::mem::size_of::<f32>(),
+            CachedData::Embedding(e) => e.vector.len() * std::mem::size_of::<f32>(),
         };
 
         let metadata = CacheMetadata {
@@ -260,6 +339,7 @@ impl AiCache {
         }
 
         // Update stats
+        CacheCounters::record(&self.counters.insertions, 1);
         let mut stats = self.stats.write().await;
         stats.memory_usage_bytes = self.calculate_memory_usage().await;
 
@@ -320,21 +400,39 @@ impl AiCache {
             }
         }
 
+        CacheCounters::record(&self.counters.evictions, cleared as u64);
         Ok(cleared)
     }
 
+    /// Hit, miss, insertion and eviction counts so far. Size and savings
+    /// figures are left at zero; `get_stats` fills those in too.
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.counters.hits.load(Ordering::Relaxed),
+            misses: self.counters.misses.load(Ordering::Relaxed),
+            insertions: self.counters.insertions.load(Ordering::Relaxed),
+            evictions: self.counters.evictions.load(Ordering::Relaxed),
+            ..CacheStats::default()
+        }
+    }
+
+    /// Zero the counts reported by `stats`
+    pub fn reset_stats(&self) {
+        self.counters.reset();
+    }
+
     /// Get cache statistics
     pub async fn get_stats(&self) -> CacheStats {
+        let counts = self.stats();
         let stats_guard = self.stats.read().await;
         CacheStats {
-            hits: stats_guard.hits,
-            misses: stats_guard.misses,
             total_size_bytes: stats_guard.total_size_bytes,
             items_count: stats_guard.items_count,
             bytes_saved: stats_guard.bytes_saved,
             cost_saved: stats_guard.cost_saved,
             memory_usage_bytes: stats_guard.memory_usage_bytes,
             disk_usage_bytes: stats_guard.disk_usage_bytes,
+            ..counts
         }
     }
 
@@ -508,3 +606,73 @@ pub enum ImageFormat {
     WebP,
     Original,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_custom_strategy_ignores_field() {
+        let cache = AiCache::with_config(CacheConfig {
+            cache_dir: std::env::temp_dir().join("vintage_ai_client_cache_tests"),
+            ..CacheConfig::default()
+        })
+        .unwrap();
+        let params = |rate: &str| {
+            HashMap::from([
+                ("voice_id".to_string(), "hero".to_string()),
+                ("rate".to_string(), rate.to_string()),
+            ])
+        };
+        let strategy = IgnoreParams::new(["rate"]);
+
+        assert_eq!(
+            cache.generate_key_with(&strategy, "voice", "Hello", &params("1.0")),
+            cache.generate_key_with(&strategy, "voice", "Hello", &params("1.5"))
+        );
+        assert_ne!(
+            cache.generate_key("voice", "Hello", &params("1.0")),
+            cache.generate_key("voice", "Hello", &params("1.5"))
+        );
+
+        // Parameters that aren't ignored still count
+        let mut other_voice = params("1.0");
+        other_voice.insert("voice_id".to_string(), "villain".to_string());
+        assert_ne!(
+            cache.generate_key_with(&strategy, "voice", "Hello", &params("1.0")),
+            cache.generate_key_with(&strategy, "voice", "Hello", &other_voice)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stats_count_miss_then_hit() {
+        let cache = AiCache::with_config(CacheConfig {
+            cache_dir: std::env::temp_dir().join("vintage_ai_client_cache_stats_tests"),
+            ..CacheConfig::default()
+        })
+        .unwrap();
+        let key = "stats_miss_then_hit";
+        cache.clear(key).await.unwrap();
+
+        assert!(cache.get(key).await.is_none());
+        cache
+            .put(
+                key.to_string(),
+                CachedData::Text("cached".to_string()),
+                HashMap::new(),
+            )
+            .await
+            .unwrap();
+        assert!(cache.get(key).await.is_some());
+
+        let stats = cache.stats();
+        assert_eq!(
+            (stats.hits, stats.misses, stats.insertions, stats.evictions),
+            (1, 1, 1, 0)
+        );
+
+        cache.reset_stats();
+        let stats = cache.get_stats().await;
+        assert_eq!((stats.hits, stats.misses, stats.insertions), (0, 0, 0));
+    }
+}
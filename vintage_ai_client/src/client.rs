@@ -413,6 +413,11 @@ impl AiClient {
         self.service.conversation()
     }
 
+    /// Transcribe recorded speech into text
+    pub async fn transcribe_audio(&self, audio: Vec<u8>) -> Result<String> {
+        self.service.audio().transcribe_audio(audio).await
+    }
+
     /// Start a streaming chat response
     pub async fn chat_stream(
         &self,
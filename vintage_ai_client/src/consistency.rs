@@ -818,6 +818,56 @@ pub mod sprite_sheets {
         Ok(DynamicImage::ImageRgba8(sheet))
     }
 
+    /// Pack named, encoded frames into a PNG atlas with a rect per frame.
+    ///
+    /// Frames of differing sizes are padded to the largest frame so every
+    /// grid cell, and so every rect, has the same size.
+    pub fn pack_sprite_sheet(
+        frames: Vec<(String, Vec<u8>)>,
+    ) -> Result<(Vec<u8>, SpriteSheetMetadata)> {
+        let mut names = Vec::with_capacity(frames.len());
+        let mut sprites = Vec::with_capacity(frames.len());
+        for (name, bytes) in frames {
+            let sprite = image::load_from_memory(&bytes)
+                .with_context(|| format!("Failed to decode frame {name}"))?;
+            names.push(name);
+            sprites.push(sprite);
+        }
+
+        let atlas = pack_sprites(sprites.clone(), 0)?;
+        let (cell_width, cell_height) = sprites
+            .iter()
+            .map(|s| s.dimensions())
+            .fold((0, 0), |(mw, mh), (w, h)| (mw.max(w), mh.max(h)));
+        let cols = (sprites.len() as f32).sqrt().ceil() as u32;
+
+        let frames = names
+            .into_iter()
+            .enumerate()
+            .map(|(idx, name)| {
+                let frame = SpriteFrame {
+                    x: (idx as u32 % cols) * cell_width,
+                    y: (idx as u32 / cols) * cell_height,
+                    width: cell_width,
+                    height: cell_height,
+                };
+                (name, frame)
+            })
+            .collect();
+
+        let mut png = Vec::new();
+        atlas.write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)?;
+
+        Ok((
+            png,
+            SpriteSheetMetadata {
+                frames,
+                padding: 0,
+                format: "rgba8".to_string(),
+            },
+        ))
+    }
+
     /// Extract sprites from a sprite sheet
     pub fn extract_sprites(
         sheet: &DynamicImage,
@@ -901,3 +951,42 @@ pub struct SpriteFrame {
     pub width: u32,
     pub height: u32,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::sprite_sheets::pack_sprite_sheet;
+    use image::{GenericImageView, RgbaImage};
+
+    fn png_frame(width: u32, height: u32) -> Vec<u8> {
+        let mut png = Vec::new();
+        RgbaImage::new(width, height)
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+        png
+    }
+
+    #[test]
+    fn test_pack_sprite_sheet_pads_to_largest_frame() {
+        let frames = vec![
+            ("idle".to_string(), png_frame(16, 16)),
+            ("walk".to_string(), png_frame(32, 8)),
+            ("jump".to_string(), png_frame(8, 24)),
+        ];
+        let (png, meta) = pack_sprite_sheet(frames).unwrap();
+
+        // Three frames make a 2x2 grid of 32x24 cells
+        let atlas = image::load_from_memory(&png).unwrap();
+        assert_eq!(atlas.dimensions(), (64, 48));
+
+        let rect = |name: &str| {
+            let frame = &meta.frames[name];
+            (frame.x, frame.y, frame.width, frame.height)
+        };
+        assert_eq!(rect("idle"), (0, 0, 32, 24));
+        assert_eq!(rect("walk"), (32, 0, 32, 24));
+        assert_eq!(rect("jump"), (0, 24, 32, 24));
+
+        let json = serde_json::to_value(&meta).unwrap();
+        assert_eq!(json["frames"]["walk"]["x"], 32);
+    }
+}
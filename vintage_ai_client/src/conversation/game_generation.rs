@@ -106,7 +106,8 @@ impl GameGenerationExt for ConversationManager {
                         Some(MessageConfig {
                             model: "gpt-4-turbo".to_string(),
                             temperature: 0.3,
-                            max_tokens: 4000,
+                            max_tokens: Some(4000),
+                            ..Default::default()
                         }),
                     )
                     .await?;
@@ -336,7 +337,8 @@ async fn generate_style_guide(
                 Some(MessageConfig {
                     model: "gpt-4-turbo".to_string(),
                     temperature: 0.3,
-                    max_tokens: 3000,
+                    max_tokens: Some(3000),
+                    ..Default::default()
                 }),
             )
             .await?;
@@ -377,7 +379,8 @@ async fn generate_world(
                 Some(MessageConfig {
                     model: "gpt-4-turbo".to_string(),
                     temperature: 0.5,
-                    max_tokens: 4000,
+                    max_tokens: Some(4000),
+                    ..Default::default()
                 }),
             )
             .await?;
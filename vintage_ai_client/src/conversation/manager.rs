@@ -6,8 +6,10 @@ use async_openai::{
     config::OpenAIConfig,
     types::chat::{
         ChatCompletionRequestAssistantMessageArgs, ChatCompletionRequestMessage,
+        ChatCompletionRequestMessageContentPartImage, ChatCompletionRequestMessageContentPartText,
         ChatCompletionRequestSystemMessageArgs, ChatCompletionRequestUserMessageArgs,
-        CreateChatCompletionRequestArgs,
+        ChatCompletionRequestUserMessageContent, ChatCompletionRequestUserMessageContentPart,
+        CreateChatCompletionRequestArgs, ImageDetail, ImageUrl,
     },
 };
 use chrono::Utc;
@@ -19,7 +21,12 @@ use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
-use crate::{AiGenerator, tokens::TokenCounter};
+use crate::{
+    AiGenerator,
+    stub::StubProvider,
+    telemetry::{CallRecord, Telemetry},
+    tokens::TokenCounter,
+};
 
 use super::types::*;
 
@@ -31,6 +38,8 @@ pub struct ConversationManager {
     pub(crate) conversations: Arc<Mutex<HashMap<String, Conversation>>>,
     pub(crate) template_env: Arc<Mutex<Option<Environment<'static>>>>,
     pub(crate) templates_dir: Option<PathBuf>,
+    pub(crate) stub: Option<Arc<StubProvider>>,
+    pub(crate) telemetry: Telemetry,
 }
 
 impl ConversationManager {
@@ -42,9 +51,23 @@ impl ConversationManager {
             conversations: Arc::new(Mutex::new(HashMap::new())),
             template_env: Arc::new(Mutex::new(None)),
             templates_dir: None,
+            stub: None,
+            telemetry: Telemetry::default(),
         }
     }
 
+    /// Answer from canned fixtures instead of the API
+    pub fn with_stub(mut self, stub: Option<Arc<StubProvider>>) -> Self {
+        self.stub = stub;
+        self
+    }
+
+    /// Report calls to a shared telemetry handle
+    pub fn with_telemetry(mut self, telemetry: Telemetry) -> Self {
+        self.telemetry = telemetry;
+        self
+    }
+
     /// Initialize template environment for game generation
     pub async fn init_templates(&mut self, templates_dir: PathBuf) -> Result<()> {
         let mut env = Environment::new();
@@ -88,6 +111,7 @@ impl ConversationManager {
                 content: system_prompt.clone(),
                 timestamp: now,
                 tokens: self.estimate_tokens(system_prompt).await?,
+                attachment: None,
             });
         }
 
@@ -125,12 +149,55 @@ impl ConversationManager {
             .await
     }
 
+    /// Send a message with an attached reference image
+    pub async fn send_message_with_image(
+        &self,
+        conversation_id: &str,
+        message: String,
+        attachment: ImageAttachment,
+    ) -> Result<String> {
+        self.send_user_message(
+            conversation_id,
+            message,
+            Some(attachment),
+            Some(MessageConfig::vision()),
+        )
+        .await
+    }
+
+    /// Send a message with an attached reference image and get a streaming response
+    pub async fn send_message_with_image_stream(
+        &self,
+        conversation_id: &str,
+        message: String,
+        attachment: ImageAttachment,
+    ) -> Result<impl Stream<Item = Result<String>> + use<'_>> {
+        self.stream_user_message(
+            conversation_id,
+            message,
+            Some(attachment),
+            Some(MessageConfig::vision()),
+        )
+        .await
+    }
+
     /// Send a message with custom configuration
     pub async fn send_message_with_config(
         &self,
         conversation_id: &str,
         message: String,
         config: Option<MessageConfig>,
+    ) -> Result<String> {
+        self.send_user_message(conversation_id, message, None, config)
+            .await
+    }
+
+    async fn send_user_message(
+        &self,
+        conversation_id: &str,
+        message: String,
+        attachment: Option<ImageAttachment>,
+        config: Option<MessageConfig>,
     ) -> Result<String> {
         let mut conversations = self.conversations.lock().await;
         let conversation = conversations
@@ -139,24 +206,58 @@ impl ConversationManager {
 
         // Add user message
         let user_tokens = self.estimate_tokens(&message).await?;
+        let prompt = message.clone();
         conversation.messages.push_back(ConversationMessage {
             role: MessageRole::User,
-            content: message.clone(),
+            content: message,
             timestamp: Utc::now(),
             tokens: user_tokens,
+            attachment,
+        });
+
+        let assistant_message = match &self.stub {
+            Some(stub) => stub.respond(&prompt).to_string(),
+            None => self.request_completion(conversation, config).await?,
+        };
+
+        // Add assistant message
+        let assistant_tokens = self.estimate_tokens(&assistant_message).await?;
+        conversation.messages.push_back(ConversationMessage {
+            role: MessageRole::Assistant,
+            content: assistant_message.clone(),
+            timestamp: Utc::now(),
+            tokens: assistant_tokens,
+            attachment: None,
         });
 
+        // Trim context if needed
+        self.trim_context(conversation);
+
+        conversation.updated_at = Utc::now();
+
+        Ok(assistant_message)
+    }
+
+    /// Ask the API for the next assistant reply, recording token usage
+    async fn request_completion(
+        &self,
+        conversation: &mut Conversation,
+        config: Option<MessageConfig>,
+    ) -> Result<String> {
+        let started = std::time::Instant::now();
+
         // Prepare messages for API
         let api_messages = self.prepare_api_messages(conversation)?;
 
         // Create request with optional custom config
         let config = config.unwrap_or_default();
-        let request = CreateChatCompletionRequestArgs::default()
+        let mut request = CreateChatCompletionRequestArgs::default();
+        request
             .model(config.model.as_str())
             .messages(api_messages)
-            .temperature(config.temperature)
-            .max_tokens(config.max_tokens)
-            .build()?;
+            .temperature(config.temperature);
+        config.apply_limits(&mut request);
+        let request = request.build()?;
 
         // Make API call
         let response = self
@@ -175,8 +276,11 @@ impl ConversationManager {
 
         // Track tokens
         let model_name = config.model.as_str();
+        let mut record = CallRecord::api(0, 0.0, started.elapsed());
         if let Some(usage) = response.usage {
-            self.token_counter
+            record.tokens = usage.total_tokens as usize;
+            record.cost = self
+                .token_counter
                 .lock()
                 .await
                 .record_usage(
@@ -188,20 +292,7 @@ impl ConversationManager {
 
             conversation.total_tokens += usage.total_tokens as usize;
         }
-
-        // Add assistant message
-        let assistant_tokens = self.estimate_tokens(&assistant_message).await?;
-        conversation.messages.push_back(ConversationMessage {
-            role: MessageRole::Assistant,
-            content: assistant_message.clone(),
-            timestamp: Utc::now(),
-            tokens: assistant_tokens,
-        });
-
-        // Trim context if needed
-        self.trim_context(conversation);
-
-        conversation.updated_at = Utc::now();
+        self.record_call(record);
 
         Ok(assistant_message)
     }
@@ -212,6 +303,17 @@ impl ConversationManager {
         conversation_id: &str,
         message: String,
         config: Option<MessageConfig>,
+    ) -> Result<impl Stream<Item = Result<String>> + use<'_>> {
+        self.stream_user_message(conversation_id, message, None, config)
+            .await
+    }
+
+    async fn stream_user_message(
+        &self,
+        conversation_id: &str,
+        message: String,
+        attachment: Option<ImageAttachment>,
+        config: Option<MessageConfig>,
     ) -> Result<impl Stream<Item = Result<String>> + use<'_>> {
         let mut conversations = self.conversations.lock().await;
         let conversation = conversations
@@ -220,11 +322,16 @@ impl ConversationManager {
 
         // Add user message
         let user_tokens = self.estimate_tokens(&message).await?;
+        let stub_reply = self
+            .stub
+            .as_ref()
+            .map(|stub| stub.respond(&message).to_string());
         conversation.messages.push_back(ConversationMessage {
             role: MessageRole::User,
-            content: message.clone(),
+            content: message,
             timestamp: Utc::now(),
             tokens: user_tokens,
+            attachment,
         });
 
         // Prepare messages for API
@@ -232,15 +339,20 @@ impl ConversationManager {
 
         // Create request with optional custom config
         let config = config.unwrap_or_default();
-        let request = CreateChatCompletionRequestArgs::default()
+        let mut request = CreateChatCompletionRequestArgs::default();
+        request
             .model(config.model.as_str())
             .messages(api_messages)
             .temperature(config.temperature)
-            .max_tokens(config.max_tokens)
-            .stream(true)
-            .build()?;
-
-        let mut stream = self.client.chat().create_stream(request).await?;
+            .stream(true);
+        config.apply_limits(&mut request);
+        let request = request.build()?;
+
+        // Offline mode answers with the whole stub reply as a single chunk
+        let mut stream = match stub_reply {
+            Some(_) => None,
+            None => Some(self.client.chat().create_stream(request).await?),
+        };
 
         let conversation_id = conversation_id.to_string();
         let conversations_arc = self.conversations.clone();
@@ -250,7 +362,15 @@ impl ConversationManager {
         Ok(async_stream::try_stream! {
             let mut full_response = String::new();
 
-            while let Some(result) = stream.next().await {
+            if let Some(reply) = stub_reply {
+                full_response.push_str(&reply);
+                yield reply;
+            }
+
+            while let Some(result) = match stream.as_mut() {
+                Some(stream) => stream.next().await,
+                None => None,
+            } {
                 match result {
                     Ok(response) => {
                         if let Some(choice) = response.choices.first()
@@ -276,6 +396,7 @@ impl ConversationManager {
                     content: full_response,
                     timestamp: Utc::now(),
                     tokens: assistant_tokens,
+                    attachment: None,
                 });
 
                 // Trim context
@@ -317,6 +438,33 @@ impl ConversationManager {
         Ok(())
     }
 
+    /// Replace the system prompt used for subsequent messages
+    pub async fn set_system_prompt(
+        &self,
+        conversation_id: &str,
+        system_prompt: String,
+    ) -> Result<()> {
+        let tokens = self.estimate_tokens(&system_prompt).await?;
+        let mut conversations = self.conversations.lock().await;
+        let conversation = conversations
+            .get_mut(conversation_id)
+            .ok_or_else(|| anyhow::anyhow!("Conversation not found"))?;
+
+        conversation
+            .messages
+            .retain(|m| !matches!(m.role, MessageRole::System));
+        conversation.messages.push_front(ConversationMessage {
+            role: MessageRole::System,
+            content: system_prompt.clone(),
+            timestamp: Utc::now(),
+            tokens,
+            attachment: None,
+        });
+        conversation.context.system_prompt = Some(system_prompt);
+        conversation.updated_at = Utc::now();
+        Ok(())
+    }
+
     /// Get conversation history
     pub async fn get_conversation(&self, conversation_id: &str) -> Result<Conversation> {
         let conversations = self.conversations.lock().await;
@@ -405,12 +553,7 @@ impl ConversationManager {
         {
             match msg.role {
                 MessageRole::User => {
-                    messages.push(
-                        ChatCompletionRequestUserMessageArgs::default()
-                            .content(msg.content.as_str())
-                            .build()?
-                            .into(),
-                    );
+                    messages.push(user_api_message(msg)?);
                 }
                 MessageRole::Assistant => {
                     messages.push(
@@ -487,4 +630,149 @@ impl AiGenerator for ConversationManager {
         // No cache to clear for conversations
         Ok(())
     }
+
+    fn name(&self) -> &'static str {
+        "conversation"
+    }
+
+    fn telemetry(&self) -> &Telemetry {
+        &self.telemetry
+    }
+}
+
+/// Build the API message for a user turn, as multimodal content when it carries an image
+fn user_api_message(msg: &ConversationMessage) -> Result<ChatCompletionRequestMessage> {
+    let Some(attachment) = &msg.attachment else {
+        return Ok(ChatCompletionRequestUserMessageArgs::default()
+            .content(msg.content.as_str())
+            .build()?
+            .into());
+    };
+
+    let parts = vec![
+        ChatCompletionRequestUserMessageContentPart::Text(
+            ChatCompletionRequestMessageContentPartText {
+                text: msg.content.clone(),
+            },
+        ),
+        ChatCompletionRequestUserMessageContentPart::ImageUrl(
+            ChatCompletionRequestMessageContentPartImage {
+                image_url: ImageUrl {
+                    url: attachment.data_url(),
+                    detail: Some(ImageDetail::Auto),
+                },
+            },
+        ),
+    ];
+
+    Ok(ChatCompletionRequestUserMessageArgs::default()
+        .content(ChatCompletionRequestUserMessageContent::Array(parts))
+        .build()?
+        .into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::Engine;
+
+    fn user_message(attachment: Option<ImageAttachment>) -> ConversationMessage {
+        ConversationMessage {
+            role: MessageRole::User,
+            content: "Make the palette feel like this".to_string(),
+            timestamp: Utc::now(),
+            tokens: 0,
+            attachment,
+        }
+    }
+
+    #[test]
+    fn test_attachment_produces_multimodal_request() {
+        let png = {
+            let img = image::RgbaImage::new(2048, 512);
+            let mut bytes = Vec::new();
+            image::DynamicImage::ImageRgba8(img)
+                .write_to(
+                    &mut std::io::Cursor::new(&mut bytes),
+                    image::ImageFormat::Png,
+                )
+                .unwrap();
+            bytes
+        };
+        let attachment = ImageAttachment::from_bytes("moodboard.png", &png).unwrap();
+
+        // Large images are downscaled before upload
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(&attachment.data)
+            .unwrap();
+        let decoded = image::load_from_memory(&bytes).unwrap();
+        assert_eq!(decoded.width(), MAX_ATTACHMENT_DIMENSION);
+
+        let request = user_api_message(&user_message(Some(attachment.clone()))).unwrap();
+        let json = serde_json::to_value(&request).unwrap();
+        let content = json["content"].as_array().expect("multimodal content");
+        assert_eq!(content[0]["text"], "Make the palette feel like this");
+        assert_eq!(content[1]["image_url"]["url"], attachment.data_url());
+
+        let plain = serde_json::to_value(user_api_message(&user_message(None)).unwrap()).unwrap();
+        assert!(plain["content"].is_string());
+    }
+
+    #[tokio::test]
+    async fn test_stop_and_max_tokens_reach_the_request() {
+        let (api_base, requests) = crate::test_support::mock_openai_recording(vec![
+            r#"{"id": "chatcmpl-1", "object": "chat.completion", "created": 0,
+                "model": "gpt-4-turbo",
+                "choices": [{"index": 0, "finish_reason": "stop", "logprobs": null,
+                             "message": {"role": "assistant", "content": "Skyvault"}}],
+                "usage": {"prompt_tokens": 20, "completion_tokens": 2, "total_tokens": 22}}"#,
+            r#"{"id": "chatcmpl-2", "object": "chat.completion", "created": 0,
+                "model": "gpt-4-turbo",
+                "choices": [{"index": 0, "finish_reason": "stop", "logprobs": null,
+                             "message": {"role": "assistant", "content": "A heist in the clouds."}}],
+                "usage": {"prompt_tokens": 30, "completion_tokens": 6, "total_tokens": 36}}"#,
+        ])
+        .await;
+        let client = Arc::new(Client::with_config(
+            OpenAIConfig::new()
+                .with_api_key("test")
+                .with_api_base(api_base),
+        ));
+        let manager = ConversationManager::new(client, Arc::new(Mutex::new(TokenCounter::new())));
+        let id = manager
+            .start_conversation(
+                "Titles".to_string(),
+                crate::conversation::game_design_context(),
+            )
+            .await
+            .unwrap();
+
+        let title = manager
+            .send_message_with_config(
+                &id,
+                "Name the game".to_string(),
+                Some(MessageConfig {
+                    max_tokens: Some(12),
+                    stop: vec!["\n".to_string()],
+                    ..MessageConfig::default()
+                }),
+            )
+            .await
+            .unwrap();
+        assert_eq!(title, "Skyvault");
+        manager
+            .send_message(&id, "Pitch it".to_string())
+            .await
+            .unwrap();
+
+        let requests = requests.lock().unwrap();
+        let capped: serde_json::Value = serde_json::from_str(&requests[0]).unwrap();
+        assert_eq!(capped["max_tokens"], 12);
+        assert_eq!(capped["stop"], serde_json::json!(["\n"]));
+
+        // Without overrides the provider defaults apply
+        let default: serde_json::Value = serde_json::from_str(&requests[1]).unwrap();
+        assert!(default.get("max_tokens").is_none());
+        assert!(default.get("stop").is_none());
+    }
 }
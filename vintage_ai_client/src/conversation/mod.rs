@@ -19,7 +19,8 @@ pub use starters::{
 };
 pub use types::{
     BlendContext, Conversation, ConversationContext, ConversationMessage, ConversationSummary,
-    GameConceptContext, GenerationPhase, GenerationProgress, MessageConfig, MessageRole,
+    GameConceptContext, GenerationPhase, GenerationProgress, ImageAttachment,
+    MAX_ATTACHMENT_DIMENSION, MessageConfig, MessageRole,
 };
 
 // Re-export game generation methods
@@ -1,10 +1,15 @@
 //! Type definitions for conversation management
 
-use async_openai::types::chat::Role;
+use anyhow::{Context, Result};
+use async_openai::types::chat::{CreateChatCompletionRequestArgs, Role};
+use base64::Engine;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, VecDeque};
 
+/// Longest edge allowed for attached images; larger images are downscaled
+pub const MAX_ATTACHMENT_DIMENSION: u32 = 1024;
+
 /// A single conversation thread
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Conversation {
@@ -24,6 +29,48 @@ pub struct ConversationMessage {
     pub content: String,
     pub timestamp: DateTime<Utc>,
     pub tokens: usize,
+    /// Reference image sent alongside a user message
+    #[serde(default)]
+    pub attachment: Option<ImageAttachment>,
+}
+
+/// Image attached to a message for vision-capable models
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImageAttachment {
+    /// Original file name, for display
+    pub name: String,
+    /// MIME type of the encoded data
+    pub mime_type: String,
+    /// Base64-encoded image bytes
+    pub data: String,
+}
+
+impl ImageAttachment {
+    /// Decode an image file, downscaling it to fit `MAX_ATTACHMENT_DIMENSION`
+    pub fn from_bytes(name: impl Into<String>, bytes: &[u8]) -> Result<Self> {
+        let mut img = image::load_from_memory(bytes).context("Unsupported image format")?;
+        if img.width().max(img.height()) > MAX_ATTACHMENT_DIMENSION {
+            img = img.resize(
+                MAX_ATTACHMENT_DIMENSION,
+                MAX_ATTACHMENT_DIMENSION,
+                image::imageops::FilterType::Lanczos3,
+            );
+        }
+
+        let mut png = Vec::new();
+        img.write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)?;
+
+        Ok(Self {
+            name: name.into(),
+            mime_type: "image/png".to_string(),
+            data: base64::engine::general_purpose::STANDARD.encode(png),
+        })
+    }
+
+    /// Inline data URL accepted by the chat completions API
+    pub fn data_url(&self) -> String {
+        format!("data:{};base64,{}", self.mime_type, self.data)
+    }
 }
 
 /// Role in conversation
@@ -119,7 +166,10 @@ pub struct ConversationSummary {
 pub struct MessageConfig {
     pub model: String,
     pub temperature: f32,
-    pub max_tokens: u32,
+    /// Cap on reply length; `None` leaves it to the provider
+    pub max_tokens: Option<u32>,
+    /// Sequences that end the reply early, e.g. `"\n"` for one-line titles
+    pub stop: Vec<String>,
 }
 
 impl Default for MessageConfig {
@@ -127,7 +177,28 @@ impl Default for MessageConfig {
         Self {
             model: "gpt-4-turbo".to_string(),
             temperature: 0.8,
-            max_tokens: 2000,
+            max_tokens: None,
+            stop: Vec::new(),
+        }
+    }
+}
+
+impl MessageConfig {
+    /// Configuration for messages that carry image attachments
+    pub fn vision() -> Self {
+        Self {
+            model: "gpt-4o".to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// Set the token cap and stop sequences on a request, if any were given
+    pub(crate) fn apply_limits(&self, request: &mut CreateChatCompletionRequestArgs) {
+        if let Some(max_tokens) = self.max_tokens {
+            request.max_tokens(max_tokens);
+        }
+        if !self.stop.is_empty() {
+            request.stop(self.stop.clone());
         }
     }
 }
@@ -9,21 +9,101 @@
 use anyhow::{Context, Result};
 use async_openai::{Client, config::OpenAIConfig, types::embeddings::CreateEmbeddingRequestArgs};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
 use super::{
     AiConfig, AiGenerator,
     cache::{AiCache, CachedData},
+    telemetry::{CallRecord, Telemetry},
     tokens::TokenCounter,
 };
 
+/// The provider, model and dimension an embedding was produced in.
+/// Vectors from different spaces aren't comparable as-is.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct EmbeddingSpace {
+    pub provider: String,
+    pub model: String,
+    pub dimension: usize,
+}
+
+impl EmbeddingSpace {
+    pub fn new(provider: impl Into<String>, model: impl Into<String>, dimension: usize) -> Self {
+        Self {
+            provider: provider.into(),
+            model: model.into(),
+            dimension,
+        }
+    }
+}
+
+impl std::fmt::Display for EmbeddingSpace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{} ({}d)", self.provider, self.model, self.dimension)
+    }
+}
+
+/// An embedding vector tagged with the space it lives in
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Embedding {
+    pub space: EmbeddingSpace,
+    pub vector: Vec<f32>,
+}
+
+impl Embedding {
+    pub fn new(space: EmbeddingSpace, vector: Vec<f32>) -> Self {
+        Self { space, vector }
+    }
+
+    /// Truncate or zero-pad to `dimension` and re-normalize. Truncation
+    /// keeps the leading components, which is what providers with
+    /// shortenable embeddings (e.g. text-embedding-3) put first.
+    pub fn project(&self, dimension: usize) -> Vec<f32> {
+        let mut projected = self.vector.clone();
+        projected.resize(dimension, 0.0);
+
+        let norm = projected.iter().map(|x| x * x).sum::<f32>().sqrt();
+        if norm > 0.0 {
+            projected.iter_mut().for_each(|x| *x /= norm);
+        }
+        projected
+    }
+
+    /// Cosine similarity, refusing to compare embeddings from different
+    /// spaces unless `projection` names a common dimension to project both to
+    pub fn similarity(&self, other: &Embedding, projection: Option<usize>) -> Result<f32> {
+        if self.space == other.space {
+            return Ok(EmbeddingsGenerator::cosine_similarity(
+                &self.vector,
+                &other.vector,
+            ));
+        }
+
+        match projection {
+            Some(dimension) => Ok(EmbeddingsGenerator::cosine_similarity(
+                &self.project(dimension),
+                &other.project(dimension),
+            )),
+            None => anyhow::bail!(
+                "Cannot compare embeddings from different spaces: {} vs {}; \
+                 configure a projection to a common dimension",
+                self.space,
+                other.space
+            ),
+        }
+    }
+}
+
 /// Embeddings generator for semantic similarity
 #[derive(Clone)]
 pub struct EmbeddingsGenerator {
     client: Arc<Client<OpenAIConfig>>,
     cache: Arc<Mutex<AiCache>>,
     token_counter: Arc<Mutex<TokenCounter>>,
+    telemetry: Telemetry,
+    projection: Option<usize>,
 }
 
 #[async_trait::async_trait]
@@ -47,6 +127,14 @@ impl AiGenerator for EmbeddingsGenerator {
     async fn clear_cache(&self, key: &str) -> Result<()> {
         self.cache.lock().await.clear(key).await
     }
+
+    fn name(&self) -> &'static str {
+        "embeddings"
+    }
+
+    fn telemetry(&self) -> &Telemetry {
+        &self.telemetry
+    }
 }
 
 impl EmbeddingsGenerator {
@@ -60,27 +148,44 @@ impl EmbeddingsGenerator {
             client,
             cache,
             token_counter,
+            telemetry: Telemetry::default(),
+            projection: None,
         }
     }
 
+    /// Report calls to a shared telemetry handle
+    pub fn with_telemetry(mut self, telemetry: Telemetry) -> Self {
+        self.telemetry = telemetry;
+        self
+    }
+
+    /// Allow comparing embeddings from different spaces by projecting both
+    /// to `dimension`
+    pub fn with_projection(mut self, dimension: usize) -> Self {
+        self.projection = Some(dimension);
+        self
+    }
+
     /// Generate embeddings for a single text
     pub async fn generate(&self, text: &str, config: &AiConfig) -> Result<Vec<f32>> {
+        Ok(self.embed(text, config).await?.vector)
+    }
+
+    /// Generate an embedding for a single text, tagged with its space
+    pub async fn embed(&self, text: &str, config: &AiConfig) -> Result<Embedding> {
+        let started = std::time::Instant::now();
+
         // Check cache first
         let cache_key = format!("embedding:{}:{}", config.embedding_model, text);
 
         if let Some(cached) = self.cache.lock().await.get(&cache_key).await
             && let CachedData::Embedding(embedding) = cached.data
         {
+            self.record_call(CallRecord::cache_hit(started.elapsed()));
             return Ok(embedding);
         }
 
-        // Determine embedding model based on config
-        let model = match config.embedding_model.as_str() {
-            "text-embedding-3-small" => "text-embedding-3-small",
-            "text-embedding-3-large" => "text-embedding-3-large",
-            "text-embedding-ada-002" => "text-embedding-ada-002",
-            _ => "text-embedding-3-small", // Default
-        };
+        let model = embedding_model(config);
 
         let request = CreateEmbeddingRequestArgs::default()
             .model(model)
@@ -95,15 +200,17 @@ impl EmbeddingsGenerator {
             .context("Failed to generate embedding")?;
 
         // Extract the embedding vector
-        let embedding = response
+        let vector = response
             .data
             .first()
             .ok_or_else(|| anyhow::anyhow!("No embedding returned"))?
             .embedding
             .clone();
+        let embedding = Embedding::new(space(config, model, vector.len()), vector);
 
         // Track token usage
-        self.token_counter
+        let cost = self
+            .token_counter
             .lock()
             .await
             .record_usage(
@@ -112,6 +219,11 @@ impl EmbeddingsGenerator {
                 0, // No completion tokens for embeddings
             )
             .await?;
+        self.record_call(CallRecord::api(
+            response.usage.total_tokens as usize,
+            cost,
+            started.elapsed(),
+        ));
 
         // Cache the result
         self.cache
@@ -120,7 +232,7 @@ impl EmbeddingsGenerator {
             .put(
                 cache_key,
                 CachedData::Embedding(embedding.clone()),
-                std::collections::HashMap::new(),
+                cache_params(&embedding.space),
             )
             .await?;
 
@@ -133,13 +245,18 @@ impl EmbeddingsGenerator {
         texts: Vec<&str>,
         config: &AiConfig,
     ) -> Result<Vec<Vec<f32>>> {
+        Ok(self
+            .embed_batch(texts, config)
+            .await?
+            .into_iter()
+            .map(|embedding| embedding.vector)
+            .collect())
+    }
+
+    /// Generate embeddings for multiple texts in batch, tagged with their space
+    pub async fn embed_batch(&self, texts: Vec<&str>, config: &AiConfig) -> Result<Vec<Embedding>> {
         // OpenAI supports batch embedding requests
-        let model = match config.embedding_model.as_str() {
-            "text-embedding-3-small" => "text-embedding-3-small",
-            "text-embedding-3-large" => "text-embedding-3-large",
-            "text-embedding-ada-002" => "text-embedding-ada-002",
-            _ => "text-embedding-3-small",
-        };
+        let model = embedding_model(config);
 
         let request = CreateEmbeddingRequestArgs::default()
             .model(model)
@@ -161,7 +278,11 @@ impl EmbeddingsGenerator {
             .await?;
 
         // Extract all embeddings
-        let embeddings: Vec<Vec<f32>> = response.data.into_iter().map(|e| e.embedding).collect();
+        let embeddings: Vec<Embedding> = response
+            .data
+            .into_iter()
+            .map(|e| Embedding::new(space(config, model, e.embedding.len()), e.embedding))
+            .collect();
 
         // Cache individual results
         for (idx, text) in texts.iter().enumerate() {
@@ -173,7 +294,7 @@ impl EmbeddingsGenerator {
                     .put(
                         cache_key,
                         CachedData::Embedding(embedding.clone()),
-                        std::collections::HashMap::new(),
+                        cache_params(&embedding.space),
                     )
                     .await?;
             }
@@ -208,6 +329,12 @@ impl EmbeddingsGenerator {
         dot_product / (norm_a * norm_b)
     }
 
+    /// Cosine similarity between tagged embeddings, projecting mismatched
+    /// spaces when `with_projection` was set and failing otherwise
+    pub fn similarity(&self, a: &Embedding, b: &Embedding) -> Result<f32> {
+        a.similarity(b, self.projection)
+    }
+
     /// Find most similar texts from a collection
     pub async fn find_similar(
         &self,
@@ -217,20 +344,20 @@ impl EmbeddingsGenerator {
         top_k: usize,
     ) -> Result<Vec<(String, f32)>> {
         // Get query embedding
-        let query_embedding = self.generate(query, config).await?;
+        let query_embedding = self.embed(query, config).await?;
 
         // Get candidate embeddings
-        let candidate_embeddings = self.generate_batch(candidates.clone(), config).await?;
+        let candidate_embeddings = self.embed_batch(candidates.clone(), config).await?;
 
         // Calculate similarities
-        let mut similarities: Vec<(String, f32)> = candidates
+        let mut similarities = candidates
             .into_iter()
             .zip(candidate_embeddings)
             .map(|(text, embedding)| {
-                let similarity = Self::cosine_similarity(&query_embedding, &embedding);
-                (text.to_string(), similarity)
+                let similarity = self.similarity(&query_embedding, &embedding)?;
+                Ok((text.to_string(), similarity))
             })
-            .collect();
+            .collect::<Result<Vec<(String, f32)>>>()?;
 
         // Sort by similarity (descending)
         similarities.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
@@ -241,6 +368,28 @@ impl EmbeddingsGenerator {
     }
 }
 
+/// Determine embedding model based on config
+fn embedding_model(config: &AiConfig) -> &'static str {
+    match config.embedding_model.as_str() {
+        "text-embedding-3-small" => "text-embedding-3-small",
+        "text-embedding-3-large" => "text-embedding-3-large",
+        "text-embedding-ada-002" => "text-embedding-ada-002",
+        _ => "text-embedding-3-small", // Default
+    }
+}
+
+fn space(config: &AiConfig, model: &str, dimension: usize) -> EmbeddingSpace {
+    EmbeddingSpace::new(config.ai_provider.clone(), model, dimension)
+}
+
+fn cache_params(space: &EmbeddingSpace) -> HashMap<String, serde_json::Value> {
+    HashMap::from([
+        ("provider".to_string(), space.provider.clone().into()),
+        ("model".to_string(), space.model.clone().into()),
+        ("dimension".to_string(), space.dimension.into()),
+    ])
+}
+
 /// Configuration specifically for embeddings
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EmbeddingConfig {
@@ -267,3 +416,31 @@ impl From<&AiConfig> for EmbeddingConfig {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mismatched_spaces_need_projection() {
+        let small = Embedding::new(
+            EmbeddingSpace::new("openai", "text-embedding-3-small", 4),
+            vec![1.0, 0.0, 0.5, 0.5],
+        );
+        let large = Embedding::new(
+            EmbeddingSpace::new("openai", "text-embedding-3-large", 6),
+            vec![1.0, 0.0, 0.0, 0.0, 0.3, 0.3],
+        );
+
+        let err = small.similarity(&large, None).unwrap_err().to_string();
+        assert!(err.contains("openai/text-embedding-3-small (4d)"));
+        assert!(err.contains("openai/text-embedding-3-large (6d)"));
+
+        // Projecting to the leading two components lines both up with [1, 0]
+        let similarity = small.similarity(&large, Some(2)).unwrap();
+        assert!((similarity - 1.0).abs() < 1e-6);
+
+        // Same space needs no projection
+        assert!((small.similarity(&small, None).unwrap() - 1.0).abs() < 1e-6);
+    }
+}
@@ -28,6 +28,7 @@ use super::{
     AiConfig, AiGenerator,
     cache::{AiCache, ImageCache},
     consistency::{Color, ColorPalette, StyleManager},
+    telemetry::{CallRecord, Telemetry},
     tokens::TokenCounter,
 };
 
@@ -41,6 +42,9 @@ pub struct ImageGenerator {
     style_manager: Arc<Mutex<StyleManager>>,
     batch_semaphore: Arc<Semaphore>,
     template_env: Arc<Mutex<Environment<'static>>>,
+    telemetry: Telemetry,
+    /// Bypass the cache, see `with_preview`
+    preview: bool,
 }
 
 /// Configuration for image generation
@@ -58,6 +62,8 @@ pub struct ImageConfig {
     pub response_format: ImageResponseFormat,
     /// Style consistency mode
     pub enforce_consistency: bool,
+    /// Skip the image cache lookup for this call
+    pub no_cache: bool,
 }
 
 impl Default for ImageConfig {
@@ -69,6 +75,7 @@ impl Default for ImageConfig {
             n: 1,
             response_format: ImageResponseFormat::B64Json,
             enforce_consistency: true,
+            no_cache: false,
         }
     }
 }
@@ -100,6 +107,7 @@ impl ImageConfig {
             n: 1,
             response_format: ImageResponseFormat::B64Json,
             enforce_consistency: config.optimize_costs,
+            no_cache: false,
         }
     }
 
@@ -126,6 +134,7 @@ impl ImageConfig {
             n: 1,
             response_format: ImageResponseFormat::B64Json,
             enforce_consistency: true,
+            no_cache: false,
         }
     }
 
@@ -138,6 +147,7 @@ impl ImageConfig {
             n: 1,
             response_format: ImageResponseFormat::B64Json,
             enforce_consistency: true,
+            no_cache: false,
         }
     }
 
@@ -150,6 +160,7 @@ impl ImageConfig {
             n: 1,
             response_format: ImageResponseFormat::B64Json,
             enforce_consistency: true,
+            no_cache: false,
         }
     }
 
@@ -162,6 +173,7 @@ impl ImageConfig {
             n: 1,
             response_format: ImageResponseFormat::B64Json,
             enforce_consistency: true,
+            no_cache: false,
         }
     }
 
@@ -174,6 +186,7 @@ impl ImageConfig {
             n: 1,
             response_format: ImageResponseFormat::B64Json,
             enforce_consistency: true,
+            no_cache: false,
         }
     }
 }
@@ -218,9 +231,24 @@ impl ImageGenerator {
             style_manager,
             batch_semaphore: Arc::new(Semaphore::new(3)), // Max 3 concurrent image generations
             template_env: Arc::new(Mutex::new(env)),
+            telemetry: Telemetry::default(),
+            preview: false,
         }
     }
 
+    /// Report calls to a shared telemetry handle
+    pub fn with_telemetry(mut self, telemetry: Telemetry) -> Self {
+        self.telemetry = telemetry;
+        self
+    }
+
+    /// Throwaway previews for prompt iteration: results are neither read
+    /// from nor written to the cache. Usage and cost are still recorded.
+    pub fn with_preview(mut self, preview: bool) -> Self {
+        self.preview = preview;
+        self
+    }
+
     /// Generate a style guide that establishes visual consistency
     pub async fn generate_style_guide(&self, concept: &GameConcept) -> Result<Vec<u8>> {
         let style_config = self.style_manager.lock().await.get_style().await;
@@ -416,6 +444,8 @@ impl ImageGenerator {
 
     /// Generate a single image
     pub async fn generate_single(&self, prompt: &str, config: ImageConfig) -> Result<Vec<u8>> {
+        let started = std::time::Instant::now();
+
         // Check cache first
         let mut params = HashMap::new();
         params.insert("model".to_string(), format!("{:?}", config.model));
@@ -428,11 +458,14 @@ impl ImageGenerator {
             .await
             .generate_key("image", prompt, &params);
 
-        if let Some(cached_data) = self
-            .image_cache
-            .get_image(&cache_key, super::cache::ImageFormat::Png)
-            .await
+        if !config.no_cache
+            && !self.preview
+            && let Some(cached_data) = self
+                .image_cache
+                .get_image(&cache_key, super::cache::ImageFormat::Png)
+                .await
         {
+            self.record_call(CallRecord::cache_hit(started.elapsed()));
             return Ok(cached_data);
         }
 
@@ -479,11 +512,13 @@ impl ImageGenerator {
         };
         let model_name = format!("dall-e-3-{}x{}-{}", width, height, quality_str);
 
-        self.token_counter
+        let cost = self
+            .token_counter
             .lock()
             .await
             .record_image_generation(&model_name, width, height, 1)
             .await?;
+        self.record_call(CallRecord::api(0, cost, started.elapsed()));
 
         // Cache result
         let cache_params: HashMap<String, serde_json::Value> = params
@@ -491,9 +526,11 @@ impl ImageGenerator {
             .map(|(k, v)| (k, serde_json::Value::String(v)))
             .collect();
 
-        self.image_cache
-            .put_image(cache_key, image_bytes.clone(), cache_params)
-            .await?;
+        if !self.preview {
+            self.image_cache
+                .put_image(cache_key, image_bytes.clone(), cache_params)
+                .await?;
+        }
 
         Ok(image_bytes)
     }
@@ -720,6 +757,26 @@ impl AiGenerator for ImageGenerator {
     async fn clear_cache(&self, key: &str) -> Result<()> {
         self.cache.lock().await.clear(key).await
     }
+
+    fn name(&self) -> &'static str {
+        "image"
+    }
+
+    fn telemetry(&self) -> &Telemetry {
+        &self.telemetry
+    }
+
+    async fn generate_asset(&self, prompt: &str) -> Result<Vec<u8>> {
+        self.generate_single(prompt, ImageConfig::default()).await
+    }
+
+    async fn regenerate_asset(&self, prompt: &str) -> Result<Vec<u8>> {
+        let config = ImageConfig {
+            no_cache: true,
+            ..ImageConfig::default()
+        };
+        self.generate_single(prompt, config).await
+    }
 }
 
 /// Sprite generation request
@@ -819,6 +876,13 @@ pub fn pack_sprites(sprites: Vec<DynamicImage>, padding: u32) -> Result<DynamicI
     super::consistency::sprite_sheets::pack_sprites(sprites, padding)
 }
 
+/// Pack named, encoded frames into a PNG sprite sheet with frame metadata
+pub fn pack_sprite_sheet(
+    frames: Vec<(String, Vec<u8>)>,
+) -> Result<(Vec<u8>, super::consistency::SpriteSheetMetadata)> {
+    super::consistency::sprite_sheets::pack_sprite_sheet(frames)
+}
+
 /// Recoloring utilities for cost optimization
 pub mod recoloring {
     use super::*;
@@ -4,8 +4,11 @@
 //! - Text generation (game descriptions, narratives, code)
 //! - Image generation (sprites, tilesets, UI elements)
 //! - Audio generation (music, sound effects)
+//! - Background music tracks from a music-generation API
+//! - Voice synthesis configuration for character dialogue
 //! - Real-time conversation and blend calculations
 //! - Token counting and cost optimization
+//! - Optional prompt-injection filtering of user text
 //! - Intelligent caching to reduce API calls
 
 pub mod audio;
@@ -15,9 +18,18 @@ pub mod consistency;
 pub mod conversation;
 pub mod embeddings;
 pub mod game_types;
+pub mod guardrails;
 pub mod image;
+pub mod music;
+pub mod partial_json;
+pub mod stub;
+pub mod telemetry;
 pub mod text;
 pub mod tokens;
+pub mod voice;
+
+#[cfg(test)]
+pub(crate) mod test_support;
 
 use anyhow::Result;
 use async_openai::{Client, config::OpenAIConfig};
@@ -35,6 +47,10 @@ pub struct AiService {
     pub token_counter: Arc<Mutex<tokens::TokenCounter>>,
     /// Style consistency manager for visual coherence
     pub style_manager: Arc<Mutex<consistency::StyleManager>>,
+    /// Canned responses used instead of the API in offline mode
+    pub stub: Option<Arc<stub::StubProvider>>,
+    /// Call, cost and latency totals reported by every generator
+    pub telemetry: telemetry::Telemetry,
 }
 
 impl AiService {
@@ -48,13 +64,29 @@ impl AiService {
             cache: Arc::new(Mutex::new(cache::AiCache::new()?)),
             token_counter: Arc::new(Mutex::new(tokens::TokenCounter::new())),
             style_manager: Arc::new(Mutex::new(consistency::StyleManager::new())),
+            stub: None,
+            telemetry: telemetry::Telemetry::new(),
         })
     }
 
     /// Initialize from environment variables
+    ///
+    /// Uses OPENAI_API_KEY, or the offline stub when `GAME_GEN_OFFLINE=1`.
     pub fn from_env() -> Result<Self> {
-        // This will use OPENAI_API_KEY from environment
-        Self::new()
+        let mut service = Self::new()?;
+        service.stub = stub::StubProvider::from_env()?.map(Arc::new);
+        Ok(service)
+    }
+
+    /// Report generator calls to an existing telemetry handle
+    pub fn with_telemetry(mut self, telemetry: telemetry::Telemetry) -> Self {
+        self.telemetry = telemetry;
+        self
+    }
+
+    /// Whether requests are answered by the offline stub
+    pub fn is_offline(&self) -> bool {
+        self.stub.is_some()
     }
 
     /// Get a reference to the text generation service
@@ -64,6 +96,8 @@ impl AiService {
             self.cache.clone(),
             self.token_counter.clone(),
         )
+        .with_stub(self.stub.clone())
+        .with_telemetry(self.telemetry.clone())
     }
 
     /// Get a reference to the image generation service
@@ -74,6 +108,7 @@ impl AiService {
             self.token_counter.clone(),
             self.style_manager.clone(),
         )
+        .with_telemetry(self.telemetry.clone())
     }
 
     /// Get a reference to the audio generation service
@@ -83,11 +118,26 @@ impl AiService {
             self.cache.clone(),
             self.token_counter.clone(),
         )
+        .with_telemetry(self.telemetry.clone())
+    }
+
+    /// Get a music generator that composes through `backend`
+    pub fn music(&self, backend: Arc<dyn music::MusicBackend>) -> music::MusicGenerator {
+        music::MusicGenerator::new(backend, self.cache.clone(), self.token_counter.clone())
+            .with_telemetry(self.telemetry.clone())
+    }
+
+    /// Get a voice generator, synthesizing through ElevenLabs unless another
+    /// backend is registered with `with_backend`
+    pub fn voice(&self) -> voice::VoiceGenerator {
+        voice::VoiceGenerator::new(self.cache.clone()).with_telemetry(self.telemetry.clone())
     }
 
     /// Get a reference to the conversation service
     pub fn conversation(&self) -> conversation::ConversationManager {
         conversation::ConversationManager::new(self.client.clone(), self.token_counter.clone())
+            .with_stub(self.stub.clone())
+            .with_telemetry(self.telemetry.clone())
     }
 
     /// Get a reference to the embeddings service
@@ -97,6 +147,7 @@ impl AiService {
             self.cache.clone(),
             self.token_counter.clone(),
         )
+        .with_telemetry(self.telemetry.clone())
     }
 }
 
@@ -114,6 +165,28 @@ pub trait AiGenerator: Send + Sync {
 
     /// Clear cache for specific key
     async fn clear_cache(&self, key: &str) -> Result<()>;
+
+    /// Name calls are reported under in telemetry
+    fn name(&self) -> &'static str;
+
+    /// Telemetry handle this generator reports to
+    fn telemetry(&self) -> &telemetry::Telemetry;
+
+    /// Report a finished call to telemetry
+    fn record_call(&self, record: telemetry::CallRecord) {
+        self.telemetry().record(self.name(), record);
+    }
+
+    /// Generate a standalone asset from a prompt, returning its bytes
+    async fn generate_asset(&self, _prompt: &str) -> Result<Vec<u8>> {
+        anyhow::bail!("The {} generator does not produce assets", self.name())
+    }
+
+    /// Generate the asset again, skipping any cached result for `prompt`.
+    /// Generators without a cache can rely on the default.
+    async fn regenerate_asset(&self, prompt: &str) -> Result<Vec<u8>> {
+        self.generate_asset(prompt).await
+    }
 }
 
 /// Configuration for AI services
@@ -0,0 +1,292 @@
+//! Background music generation
+//!
+//! Produces ambient and background tracks from a mood prompt through a
+//! pluggable music API, caching every track by prompt and duration.
+
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+use super::{
+    AiGenerator,
+    cache::{AiCache, CachedData},
+    telemetry::{CallRecord, Telemetry},
+    tokens::TokenCounter,
+};
+
+/// Rough USD cost per second of generated audio, used for estimates
+pub const DEFAULT_COST_PER_SECOND: f64 = 0.001;
+
+/// Length used when a track is requested without one
+pub const DEFAULT_TRACK_SECONDS: f32 = 60.0;
+
+/// Music-generation API, e.g. Suno or ElevenLabs music
+#[async_trait::async_trait]
+pub trait MusicBackend: Send + Sync {
+    /// Compose a track for `prompt`, returning encoded audio
+    async fn compose(&self, prompt: &str, duration_secs: f32) -> Result<Vec<u8>>;
+}
+
+/// ElevenLabs music endpoint
+pub const ELEVENLABS_MUSIC_API_URL: &str = "https://api.elevenlabs.io/v1/music";
+
+/// Composes tracks through the ElevenLabs music API
+#[derive(Clone)]
+pub struct ElevenLabsMusicBackend {
+    client: reqwest::Client,
+    api_key: String,
+    api_url: String,
+}
+
+impl ElevenLabsMusicBackend {
+    pub fn new(api_key: impl Into<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            api_key: api_key.into(),
+            api_url: ELEVENLABS_MUSIC_API_URL.to_string(),
+        }
+    }
+
+    /// Uses ELEVENLABS_API_KEY; requests fail if it isn't set
+    pub fn from_env() -> Self {
+        Self::new(std::env::var("ELEVENLABS_API_KEY").unwrap_or_default())
+    }
+
+    /// Send requests to another endpoint, e.g. a proxy
+    pub fn with_api_url(mut self, api_url: impl Into<String>) -> Self {
+        self.api_url = api_url.into();
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl MusicBackend for ElevenLabsMusicBackend {
+    async fn compose(&self, prompt: &str, duration_secs: f32) -> Result<Vec<u8>> {
+        if self.api_key.is_empty() {
+            anyhow::bail!("ElevenLabs API key not set (ELEVENLABS_API_KEY)");
+        }
+
+        let body = serde_json::json!({
+            "prompt": prompt.trim(),
+            "music_length_ms": (duration_secs * 1000.0).round() as u64,
+        });
+        let response = self
+            .client
+            .post(&self.api_url)
+            .header("xi-api-key", &self.api_key)
+            .json(&body)
+            .send()
+            .await
+            .context("Failed to reach ElevenLabs")?
+            .error_for_status()
+            .context("ElevenLabs rejected the music request")?;
+
+        Ok(response.bytes().await?.to_vec())
+    }
+}
+
+/// Music generator for ambient and background tracks
+#[derive(Clone)]
+pub struct MusicGenerator {
+    backend: Arc<dyn MusicBackend>,
+    cache: Arc<Mutex<AiCache>>,
+    token_counter: Arc<Mutex<TokenCounter>>,
+    telemetry: Telemetry,
+    /// Bypass the cache, see `with_preview`
+    preview: bool,
+    cost_per_second: f64,
+}
+
+impl MusicGenerator {
+    pub fn new(
+        backend: Arc<dyn MusicBackend>,
+        cache: Arc<Mutex<AiCache>>,
+        token_counter: Arc<Mutex<TokenCounter>>,
+    ) -> Self {
+        Self {
+            backend,
+            cache,
+            token_counter,
+            telemetry: Telemetry::default(),
+            preview: false,
+            cost_per_second: DEFAULT_COST_PER_SECOND,
+        }
+    }
+
+    /// Report calls to a shared telemetry handle
+    pub fn with_telemetry(mut self, telemetry: Telemetry) -> Self {
+        self.telemetry = telemetry;
+        self
+    }
+
+    /// Throwaway previews for prompt iteration: results are neither read
+    /// from nor written to the cache. Usage and cost are still recorded.
+    pub fn with_preview(mut self, preview: bool) -> Self {
+        self.preview = preview;
+        self
+    }
+
+    /// Override the per-second price used for cost tracking
+    pub fn with_cost_per_second(mut self, cost_per_second: f64) -> Self {
+        self.cost_per_second = cost_per_second;
+        self
+    }
+
+    /// Cache key for a track
+    pub async fn cache_key(&self, prompt: &str, duration_secs: f32) -> String {
+        let mut params = HashMap::new();
+        params.insert("duration".to_string(), duration_secs.to_string());
+        self.cache
+            .lock()
+            .await
+            .generate_key("music", prompt.trim(), &params)
+    }
+
+    /// Generate a track for a mood or prompt, reusing a cached one if present
+    pub async fn generate_track(&self, prompt: &str, duration_secs: f32) -> Result<Vec<u8>> {
+        let started = std::time::Instant::now();
+        let cache_key = self.cache_key(prompt, duration_secs).await;
+
+        if !self.preview
+            && let Some(cached) = self.cache.lock().await.get(&cache_key).await
+            && let CachedData::Audio(audio) = cached.data
+        {
+            self.record_call(CallRecord::cache_hit(started.elapsed()));
+            return Ok(audio);
+        }
+
+        let audio = self.backend.compose(prompt, duration_secs).await?;
+        let cost = duration_secs as f64 * self.cost_per_second;
+        self.record_call(CallRecord::api(0, cost, started.elapsed()));
+
+        let mut cache_params = HashMap::new();
+        cache_params.insert(
+            "duration".to_string(),
+            serde_json::Value::String(duration_secs.to_string()),
+        );
+        if !self.preview {
+            self.cache
+                .lock()
+                .await
+                .put(cache_key, CachedData::Audio(audio.clone()), cache_params)
+                .await?;
+        }
+
+        Ok(audio)
+    }
+}
+
+#[async_trait::async_trait]
+impl AiGenerator for MusicGenerator {
+    async fn estimate_tokens(&self, request: &str) -> Result<usize> {
+        // Music APIs bill by duration; the prompt size is only informational
+        let counter = self.token_counter.lock().await;
+        counter.count_tokens(request, "gpt-4")
+    }
+
+    async fn estimate_cost(&self, _request: &str) -> Result<f64> {
+        Ok(DEFAULT_TRACK_SECONDS as f64 * self.cost_per_second)
+    }
+
+    async fn is_cached(&self, key: &str) -> bool {
+        self.cache.lock().await.get(key).await.is_some()
+    }
+
+    async fn clear_cache(&self, key: &str) -> Result<()> {
+        self.cache.lock().await.clear(key).await
+    }
+
+    fn name(&self) -> &'static str {
+        "music"
+    }
+
+    fn telemetry(&self) -> &Telemetry {
+        &self.telemetry
+    }
+
+    async fn generate_asset(&self, prompt: &str) -> Result<Vec<u8>> {
+        self.generate_track(prompt, DEFAULT_TRACK_SECONDS).await
+    }
+
+    async fn regenerate_asset(&self, prompt: &str) -> Result<Vec<u8>> {
+        let cache_key = self.cache_key(prompt, DEFAULT_TRACK_SECONDS).await;
+        self.clear_cache(&cache_key).await?;
+        self.generate_track(prompt, DEFAULT_TRACK_SECONDS).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::CacheConfig;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Backend that returns the prompt as audio and counts calls
+    #[derive(Default)]
+    struct CountingBackend {
+        calls: AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl MusicBackend for CountingBackend {
+        async fn compose(&self, prompt: &str, _duration_secs: f32) -> Result<Vec<u8>> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(prompt.as_bytes().to_vec())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_elevenlabs_backend_posts_prompt_and_length() {
+        let (url, requests) = crate::test_support::mock_openai_recording(vec!["ID3-track"]).await;
+        let backend = ElevenLabsMusicBackend::new("test-key").with_api_url(format!("{url}/music"));
+
+        let audio = backend
+            .compose("  Tense dungeon crawl ", 12.5)
+            .await
+            .unwrap();
+
+        assert_eq!(audio, b"ID3-track");
+        let body: serde_json::Value = serde_json::from_str(&requests.lock().unwrap()[0]).unwrap();
+        assert_eq!(body["prompt"], "Tense dungeon crawl");
+        assert_eq!(body["music_length_ms"], 12500);
+    }
+
+    #[tokio::test]
+    async fn test_elevenlabs_backend_requires_api_key() {
+        let error = ElevenLabsMusicBackend::new("")
+            .compose("Calm forest", 10.0)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("ELEVENLABS_API_KEY"));
+    }
+
+    #[tokio::test]
+    async fn test_track_is_cached_and_reserved() {
+        let cache = AiCache::with_config(CacheConfig {
+            cache_dir: std::env::temp_dir().join("vintage_ai_client_music_tests"),
+            ..CacheConfig::default()
+        })
+        .unwrap();
+        let backend = Arc::new(CountingBackend::default());
+        let generator = MusicGenerator::new(
+            backend.clone(),
+            Arc::new(Mutex::new(cache)),
+            Arc::new(Mutex::new(TokenCounter::new())),
+        );
+
+        // Unique prompt per run so tracks cached by a previous run don't count
+        let prompt = format!("Calm forest ambience {}", uuid::Uuid::new_v4());
+        let first = generator.generate_track(&prompt, 30.0).await.unwrap();
+        let second = generator.generate_track(&prompt, 30.0).await.unwrap();
+
+        assert_eq!(first, second);
+        assert_eq!(backend.calls.load(Ordering::SeqCst), 1);
+        let key = generator.cache_key(&prompt, 30.0).await;
+        assert!(generator.is_cached(&key).await);
+
+        let stats = generator.telemetry().snapshot().total;
+        assert_eq!(stats.calls, 2);
+        assert_eq!(stats.cache_hits, 1);
+    }
+}
@@ -0,0 +1,250 @@
+//! Lenient parsing of JSON replies from models
+//!
+//! Structured replies arrive a few tokens at a time. `PartialJson` closes
+//! whatever strings, arrays and objects are still open so callers can show
+//! the fields that have arrived, then parses strictly once the stream ends.
+//! `repair_json` cleans up a finished reply that wraps its JSON in prose or
+//! code fences.
+
+use anyhow::{Context, Result, anyhow};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+/// Accumulates a streamed JSON reply
+#[derive(Debug, Clone, Default)]
+pub struct PartialJson {
+    buffer: String,
+}
+
+impl PartialJson {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a chunk and return the best reading of everything so far
+    pub fn push(&mut self, chunk: &str) -> Option<Value> {
+        self.buffer.push_str(chunk);
+        self.value()
+    }
+
+    /// Everything received so far
+    pub fn text(&self) -> &str {
+        &self.buffer
+    }
+
+    /// The received JSON with open containers closed and any trailing
+    /// fragment that can't be completed (half a key, `tru`) dropped
+    pub fn value(&self) -> Option<Value> {
+        parse_partial(&self.buffer)
+    }
+
+    /// `value` deserialized as `T`, e.g. a struct whose fields all have defaults
+    pub fn partial<T: DeserializeOwned>(&self) -> Option<T> {
+        serde_json::from_value(self.value()?).ok()
+    }
+
+    /// Parse the complete reply strictly, once the stream has closed
+    pub fn finish<T: DeserializeOwned>(&self) -> Result<T> {
+        serde_json::from_str(&repair_json(&self.buffer)?)
+            .context("Failed to parse structured output")
+    }
+}
+
+/// Leniently parse JSON that may have been cut off part way through
+pub fn parse_partial(text: &str) -> Option<Value> {
+    let text = strip_fences(text);
+    let start = text.find(['{', '['])?;
+    let text = &text[start..];
+
+    // Longest prefix that can be closed into valid JSON wins
+    let mut end = text.len();
+    loop {
+        let prefix = text[..end].trim_end().trim_end_matches(',');
+        if let Some(closing) = closing_for(prefix)
+            && let Ok(value) = serde_json::from_str(&format!("{prefix}{closing}"))
+        {
+            return Some(value);
+        }
+        end = text[..end].char_indices().next_back()?.0;
+        if end == 0 {
+            return None;
+        }
+    }
+}
+
+/// Characters that close every string and container `prefix` leaves open,
+/// or `None` when it ends mid-escape
+fn closing_for(prefix: &str) -> Option<String> {
+    let mut open = Vec::new();
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for c in prefix.chars() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '{' => open.push('}'),
+            '[' => open.push(']'),
+            '}' | ']' => {
+                open.pop();
+            }
+            _ => {}
+        }
+    }
+
+    if escaped {
+        return None;
+    }
+    let mut closing = String::new();
+    if in_string {
+        closing.push('"');
+    }
+    closing.extend(open.iter().rev());
+    Some(closing)
+}
+
+/// Pull the JSON out of a model reply: the outermost object or array, with
+/// any code fence or surrounding prose dropped and trailing commas removed.
+///
+/// Fails with the original reply when no valid JSON can be recovered.
+pub fn repair_json(raw: &str) -> Result<String> {
+    let unrecoverable =
+        |reason: &dyn std::fmt::Display| anyhow!("Unrecoverable JSON ({reason}) in reply:\n{raw}");
+
+    let text = strip_fences(raw);
+    let start = text
+        .find(['{', '['])
+        .ok_or_else(|| unrecoverable(&"no object or array"))?;
+    let end = outermost_end(&text[start..]).ok_or_else(|| unrecoverable(&"unclosed value"))?;
+    let repaired = strip_trailing_commas(&text[start..start + end]);
+
+    match serde_json::from_str::<Value>(&repaired) {
+        Ok(_) => Ok(repaired),
+        Err(e) => Err(unrecoverable(&e)),
+    }
+}
+
+/// Byte length of the object or array `text` opens with, if it is closed
+fn outermost_end(text: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for (index, c) in text.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '{' | '[' => depth += 1,
+            '}' | ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(index + 1);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Drop commas that directly precede a closing brace or bracket
+fn strip_trailing_commas(text: &str) -> String {
+    let mut repaired = String::with_capacity(text.len());
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for (index, c) in text.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+        } else if c == '"' {
+            in_string = true;
+        } else if c == ',' && text[index + 1..].trim_start().starts_with(['}', ']']) {
+            continue;
+        }
+        repaired.push(c);
+    }
+    repaired
+}
+
+/// Drop a Markdown code fence around the JSON, including an unclosed one
+fn strip_fences(text: &str) -> &str {
+    text.trim()
+        .trim_start_matches("```json")
+        .trim_start_matches("```")
+        .trim_end_matches("```")
+        .trim()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_partial_closes_open_values() {
+        assert_eq!(
+            parse_partial("```json\n{\"title\": \"Sky"),
+            Some(json!({"title": "Sky"}))
+        );
+        assert_eq!(
+            parse_partial(r#"{"title": "Skyvault", "mechanics": ["Grapple", "#),
+            Some(json!({"title": "Skyvault", "mechanics": ["Grapple"]}))
+        );
+        // Half a key or literal is dropped rather than guessed at
+        assert_eq!(
+            parse_partial(r#"{"title": "Skyvault", "gen"#),
+            Some(json!({"title": "Skyvault"}))
+        );
+        assert_eq!(parse_partial(r#"{"done": tru"#), Some(json!({})));
+        assert_eq!(parse_partial("Thinking..."), None);
+    }
+
+    #[test]
+    fn test_repair_json_recovers_wrapped_replies() {
+        let expected = json!({"title": "Skyvault", "mechanics": ["Grapple", "Glide"]});
+        let repaired =
+            |raw: &str| -> Value { serde_json::from_str(&repair_json(raw).unwrap()).unwrap() };
+
+        let fenced =
+            "```json\n{\"title\": \"Skyvault\", \"mechanics\": [\"Grapple\", \"Glide\"]}\n```";
+        assert_eq!(repaired(fenced), expected);
+
+        let prose = "Sure! Here is the spec you asked for:\n\
+            {\"title\": \"Skyvault\", \"mechanics\": [\"Grapple\", \"Glide\"]}\n\
+            Let me know if you want changes.";
+        assert_eq!(repaired(prose), expected);
+
+        let trailing = r#"{"title": "Skyvault", "mechanics": ["Grapple", "Glide",],}"#;
+        assert_eq!(repaired(trailing), expected);
+
+        // Commas inside strings are left alone
+        assert_eq!(
+            repaired(r#"{"title": "Sky, ]"}"#),
+            json!({"title": "Sky, ]"})
+        );
+
+        let error = repair_json(r#"{"title": Skyvault}"#).unwrap_err();
+        assert!(error.to_string().contains(r#"{"title": Skyvault}"#));
+    }
+}
@@ -0,0 +1,123 @@
+//! Offline provider with canned responses
+//!
+//! Lets CI and demos run without API keys. Set `GAME_GEN_OFFLINE=1` to use
+//! it, and optionally `GAME_GEN_FIXTURES` to a JSON fixtures file:
+//!
+//! ```json
+//! {
+//!   "fixtures": [{ "contains": "boss", "response": "A clockwork dragon." }],
+//!   "default_response": "Offline mode: no fixture matched."
+//! }
+//! ```
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Environment variable that switches AI services to the stub provider
+pub const OFFLINE_ENV: &str = "GAME_GEN_OFFLINE";
+/// Environment variable pointing at a JSON fixtures file
+pub const FIXTURES_ENV: &str = "GAME_GEN_FIXTURES";
+
+const DEFAULT_RESPONSE: &str =
+    "Offline mode is enabled, so this is a placeholder response instead of AI output.";
+
+/// A canned response returned when the prompt contains `contains`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StubFixture {
+    pub contains: String,
+    pub response: String,
+}
+
+/// Deterministic provider that answers from fixtures instead of an API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StubProvider {
+    /// Checked in order; the first fixture whose key appears in the prompt wins
+    #[serde(default)]
+    pub fixtures: Vec<StubFixture>,
+    /// Returned when no fixture matches
+    #[serde(default = "default_response")]
+    pub default_response: String,
+}
+
+fn default_response() -> String {
+    DEFAULT_RESPONSE.to_string()
+}
+
+impl Default for StubProvider {
+    fn default() -> Self {
+        Self {
+            fixtures: Vec::new(),
+            default_response: default_response(),
+        }
+    }
+}
+
+impl StubProvider {
+    /// Add a fixture
+    pub fn with_fixture(
+        mut self,
+        contains: impl Into<String>,
+        response: impl Into<String>,
+    ) -> Self {
+        self.fixtures.push(StubFixture {
+            contains: contains.into(),
+            response: response.into(),
+        });
+        self
+    }
+
+    /// Load fixtures from a JSON file
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read fixtures from {}", path.display()))?;
+        serde_json::from_str(&content).context("Failed to parse stub fixtures")
+    }
+
+    /// The stub to use if offline mode is enabled in the environment
+    pub fn from_env() -> Result<Option<Self>> {
+        if std::env::var(OFFLINE_ENV).as_deref() != Ok("1") {
+            return Ok(None);
+        }
+
+        match std::env::var(FIXTURES_ENV) {
+            Ok(path) => Self::from_file(Path::new(&path)).map(Some),
+            Err(_) => Ok(Some(Self::default())),
+        }
+    }
+
+    /// Response for `prompt`
+    pub fn respond(&self, prompt: &str) -> &str {
+        self.fixtures
+            .iter()
+            .find(|fixture| prompt.contains(&fixture.contains))
+            .map_or(&self.default_response, |fixture| &fixture.response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stub_matches_fixture_or_falls_back() {
+        let path =
+            std::env::temp_dir().join(format!("stub_fixtures_{}.json", uuid::Uuid::new_v4()));
+        std::fs::write(
+            &path,
+            r#"{"fixtures": [{"contains": "boss", "response": "A clockwork dragon."}],
+                "default_response": "No fixture."}"#,
+        )
+        .unwrap();
+
+        let stub = StubProvider::from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(stub.respond("Design the final boss"), "A clockwork dragon.");
+        assert_eq!(stub.respond("Name the hero"), "No fixture.");
+        assert_eq!(
+            StubProvider::default().respond("Anything"),
+            DEFAULT_RESPONSE
+        );
+    }
+}
@@ -0,0 +1,164 @@
+//! Cost and latency telemetry shared by all generators
+//!
+//! Every generator created from the same `AiService` reports into one
+//! `Telemetry` handle, so spend and timing can be read in one place.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// One generator call
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CallRecord {
+    /// Served from cache without an API request
+    pub cache_hit: bool,
+    /// Prompt plus completion tokens
+    pub tokens: usize,
+    /// Cost in USD
+    pub cost: f64,
+    pub latency: Duration,
+}
+
+impl CallRecord {
+    /// A call answered from cache
+    pub fn cache_hit(latency: Duration) -> Self {
+        Self {
+            cache_hit: true,
+            latency,
+            ..Self::default()
+        }
+    }
+
+    /// A call that went to the API
+    pub fn api(tokens: usize, cost: f64, latency: Duration) -> Self {
+        Self {
+            cache_hit: false,
+            tokens,
+            cost,
+            latency,
+        }
+    }
+}
+
+/// Aggregated calls for one generator, or for all of them
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GeneratorStats {
+    pub calls: u64,
+    pub cache_hits: u64,
+    pub tokens: u64,
+    pub cost: f64,
+    pub total_latency: Duration,
+}
+
+impl GeneratorStats {
+    fn add(&mut self, record: &CallRecord) {
+        self.calls += 1;
+        self.cache_hits += record.cache_hit as u64;
+        self.tokens += record.tokens as u64;
+        self.cost += record.cost;
+        self.total_latency += record.latency;
+    }
+
+    fn merge(&mut self, other: &GeneratorStats) {
+        self.calls += other.calls;
+        self.cache_hits += other.cache_hits;
+        self.tokens += other.tokens;
+        self.cost += other.cost;
+        self.total_latency += other.total_latency;
+    }
+
+    /// Fraction of calls served from cache
+    pub fn cache_hit_ratio(&self) -> f64 {
+        if self.calls == 0 {
+            0.0
+        } else {
+            self.cache_hits as f64 / self.calls as f64
+        }
+    }
+
+    pub fn average_latency(&self) -> Duration {
+        if self.calls == 0 {
+            Duration::ZERO
+        } else {
+            self.total_latency / self.calls as u32
+        }
+    }
+}
+
+/// Point-in-time copy of the recorded telemetry
+#[derive(Debug, Clone, Default)]
+pub struct TelemetrySnapshot {
+    pub total: GeneratorStats,
+    /// Keyed by `AiGenerator::name`
+    pub generators: BTreeMap<String, GeneratorStats>,
+}
+
+/// Shared handle that generators report calls to. Clones report to the same
+/// totals.
+#[derive(Clone, Default)]
+#[cfg_attr(feature = "bevy", derive(bevy::prelude::Resource))]
+pub struct Telemetry {
+    generators: Arc<Mutex<BTreeMap<String, GeneratorStats>>>,
+}
+
+impl Telemetry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&self, generator: &str, record: CallRecord) {
+        if let Ok(mut generators) = self.generators.lock() {
+            generators
+                .entry(generator.to_string())
+                .or_default()
+                .add(&record);
+        }
+    }
+
+    pub fn snapshot(&self) -> TelemetrySnapshot {
+        let generators = self
+            .generators
+            .lock()
+            .map(|generators| generators.clone())
+            .unwrap_or_default();
+
+        let mut total = GeneratorStats::default();
+        for stats in generators.values() {
+            total.merge(stats);
+        }
+
+        TelemetrySnapshot { total, generators }
+    }
+
+    pub fn reset(&self) {
+        if let Ok(mut generators) = self.generators.lock() {
+            generators.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_aggregates_calls() {
+        let telemetry = Telemetry::new();
+        telemetry.record(
+            "text",
+            CallRecord::api(150, 0.25, Duration::from_millis(300)),
+        );
+        telemetry.record("image", CallRecord::cache_hit(Duration::from_millis(100)));
+
+        let snapshot = telemetry.snapshot();
+        assert_eq!(snapshot.total.calls, 2);
+        assert_eq!(snapshot.total.cache_hits, 1);
+        assert_eq!(snapshot.total.tokens, 150);
+        assert_eq!(snapshot.total.cost, 0.25);
+        assert_eq!(snapshot.total.cache_hit_ratio(), 0.5);
+        assert_eq!(snapshot.total.average_latency(), Duration::from_millis(200));
+
+        assert_eq!(snapshot.generators["text"].calls, 1);
+        assert_eq!(snapshot.generators["image"].cache_hit_ratio(), 1.0);
+    }
+}
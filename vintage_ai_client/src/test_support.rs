@@ -0,0 +1,102 @@
+//! Shared helpers for tests that talk to a fake OpenAI endpoint
+
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Serve a single canned JSON response, standing in for the OpenAI API
+pub async fn mock_openai(body: &'static str) -> String {
+    mock_openai_sequence(vec![body]).await
+}
+
+/// Serve canned JSON responses in order, one per request
+pub async fn mock_openai_sequence(bodies: Vec<&'static str>) -> String {
+    mock_openai_recording(bodies).await.0
+}
+
+/// Like `mock_openai_sequence`, also returning the request bodies received
+pub async fn mock_openai_recording(bodies: Vec<&'static str>) -> (String, Arc<Mutex<Vec<String>>>) {
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let received = requests.clone();
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        for body in bodies {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let request = read_request(&mut socket).await;
+            received.lock().unwrap().push(request);
+
+            // Close after each response so every request gets a fresh connection
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nConnection: close\r\nContent-Length: {}\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+        }
+    });
+
+    (format!("http://{addr}/v1"), requests)
+}
+
+/// Serve one streamed reply, sending each JSON chunk as a server-sent event
+/// followed by `[DONE]`
+pub async fn mock_openai_stream(chunks: Vec<&'static str>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        read_request(&mut socket).await;
+
+        // Multi-line chunks become one event with several `data:` lines
+        let mut body: String = chunks
+            .iter()
+            .map(|chunk| {
+                let lines: String = chunk
+                    .lines()
+                    .map(|line| format!("data: {line}\n"))
+                    .collect();
+                lines + "\n"
+            })
+            .collect();
+        body.push_str("data: [DONE]\n\n");
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nConnection: close\r\nContent-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        socket.write_all(response.as_bytes()).await.unwrap();
+    });
+
+    format!("http://{addr}/v1")
+}
+
+/// Read the full request so the client isn't cut off mid-upload. Returns
+/// its body.
+async fn read_request(socket: &mut TcpStream) -> String {
+    let mut request = Vec::new();
+    let mut buf = [0u8; 4096];
+    loop {
+        let n = socket.read(&mut buf).await.unwrap();
+        request.extend_from_slice(&buf[..n]);
+        let text = String::from_utf8_lossy(&request);
+        if let Some(header_end) = text.find("\r\n\r\n") {
+            let content_length = text[..header_end]
+                .lines()
+                .find_map(|line| {
+                    let (name, value) = line.split_once(':')?;
+                    name.eq_ignore_ascii_case("content-length")
+                        .then(|| value.trim().parse::<usize>().ok())?
+                })
+                .unwrap_or(0);
+            if request.len() >= header_end + 4 + content_length {
+                return String::from_utf8_lossy(&request[header_end + 4..]).into_owned();
+            }
+        }
+        if n == 0 {
+            return String::new();
+        }
+    }
+}
@@ -12,11 +12,13 @@ use async_openai::{
     Client,
     config::OpenAIConfig,
     types::chat::{
+        ChatCompletionRequestAssistantMessageArgs, ChatCompletionRequestMessage,
         ChatCompletionRequestSystemMessageArgs, ChatCompletionRequestUserMessageArgs,
-        CreateChatCompletionRequestArgs,
+        CreateChatCompletionRequestArgs, FinishReason,
     },
 };
-use serde::{Deserialize, Serialize};
+use futures::{Stream, StreamExt};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::Mutex;
@@ -24,15 +26,34 @@ use tokio::sync::Mutex;
 use super::{
     AiGenerator,
     cache::{AiCache, CachedData},
-    tokens::TokenCounter,
+    partial_json::{PartialJson, repair_json},
+    stub::StubProvider,
+    telemetry::{CallRecord, Telemetry},
+    tokens::{Message, TokenCounter},
 };
 
+/// Prompt asking the model to pick up a response that hit the token limit
+const CONTINUE_PROMPT: &str = "Your previous reply was cut off. Continue exactly where it stopped, without repeating anything.";
+
 /// Text generator for all text-based content
 #[derive(Clone)]
 pub struct TextGenerator {
     client: Arc<Client<OpenAIConfig>>,
     cache: Arc<Mutex<AiCache>>,
     token_counter: Arc<Mutex<TokenCounter>>,
+    stub: Option<Arc<StubProvider>>,
+    telemetry: Telemetry,
+    /// Bypass the cache, see `with_preview`
+    preview: bool,
+}
+
+/// A reading of a structured reply while it streams in
+#[derive(Debug, Clone, PartialEq)]
+pub enum StructuredUpdate<T> {
+    /// The fields received so far
+    Partial(T),
+    /// The whole reply, parsed strictly
+    Complete(T),
 }
 
 /// Configuration for text generation
@@ -52,6 +73,16 @@ pub struct TextConfig {
     pub presence_penalty: f32,
     /// System prompt for context
     pub system_prompt: Option<String>,
+    /// Skip the response cache lookup for this call
+    #[serde(default)]
+    pub no_cache: bool,
+    /// How many times a reply cut off by `max_tokens` is continued
+    #[serde(default = "default_max_continuations")]
+    pub max_continuations: u8,
+}
+
+fn default_max_continuations() -> u8 {
+    2
 }
 
 impl Default for TextConfig {
@@ -64,6 +95,8 @@ impl Default for TextConfig {
             frequency_penalty: 0.0,
             presence_penalty: 0.0,
             system_prompt: None,
+            no_cache: false,
+            max_continuations: default_max_continuations(),
         }
     }
 }
@@ -119,6 +152,32 @@ impl TextConfig {
         }
     }
 
+    /// Parameters that identify a request for caching. Everything that can
+    /// change the response is included, with the system prompt normalized.
+    fn cache_params(&self) -> HashMap<String, String> {
+        let mut params = HashMap::new();
+        params.insert("model".to_string(), self.model.clone());
+        params.insert("temperature".to_string(), self.temperature.to_string());
+        params.insert("max_tokens".to_string(), self.max_tokens.to_string());
+        params.insert("top_p".to_string(), self.top_p.to_string());
+        params.insert(
+            "frequency_penalty".to_string(),
+            self.frequency_penalty.to_string(),
+        );
+        params.insert(
+            "presence_penalty".to_string(),
+            self.presence_penalty.to_string(),
+        );
+        params.insert(
+            "max_continuations".to_string(),
+            self.max_continuations.to_string(),
+        );
+        if let Some(system) = &self.system_prompt {
+            params.insert("system_prompt".to_string(), system.trim().to_string());
+        }
+        params
+    }
+
     /// Configuration for world lore
     pub fn for_world_building() -> Self {
         Self {
@@ -147,31 +206,121 @@ impl TextGenerator {
             client,
             cache,
             token_counter,
+            stub: None,
+            telemetry: Telemetry::default(),
+            preview: false,
         }
     }
 
+    /// Answer from canned fixtures instead of the API
+    pub fn with_stub(mut self, stub: Option<Arc<StubProvider>>) -> Self {
+        self.stub = stub;
+        self
+    }
+
+    /// Report calls to a shared telemetry handle
+    pub fn with_telemetry(mut self, telemetry: Telemetry) -> Self {
+        self.telemetry = telemetry;
+        self
+    }
+
+    /// Throwaway previews for prompt iteration: results are neither read
+    /// from nor written to the cache. Usage and cost are still recorded.
+    pub fn with_preview(mut self, preview: bool) -> Self {
+        self.preview = preview;
+        self
+    }
+
     /// Generate text with caching and token tracking
+    ///
+    /// Responses are cached by model, system prompt, prompt and sampling
+    /// parameters. Set `config.no_cache` to force a fresh response; it still
+    /// replaces the cached one; a `with_preview` generator skips the cache
+    /// altogether. Replies cut off by `max_tokens` are finished
+    /// with `continue_response` before they're returned.
     pub async fn generate(&self, prompt: &str, config: TextConfig) -> Result<String> {
-        // Generate cache key
-        let mut params = HashMap::new();
-        params.insert("model".to_string(), config.model.clone());
-        params.insert("temperature".to_string(), config.temperature.to_string());
-        params.insert("max_tokens".to_string(), config.max_tokens.to_string());
+        if let Some(stub) = &self.stub {
+            return Ok(stub.respond(prompt).to_string());
+        }
+        let started = std::time::Instant::now();
 
+        // Generate cache key
+        let params = config.cache_params();
         let cache_key = self
             .cache
             .lock()
             .await
-            .generate_key("text", prompt, &params);
+            .generate_key("text", prompt.trim(), &params);
 
-        // Check cache first
-        if let Some(cached) = self.cache.lock().await.get(&cache_key).await
+        // Check cache first; hits cost nothing
+        if !config.no_cache
+            && !self.preview
+            && let Some(cached) = self.cache.lock().await.get(&cache_key).await
             && let CachedData::Text(text) = cached.data
         {
+            self.token_counter
+                .lock()
+                .await
+                .record_usage(&config.model, 0, 0)
+                .await?;
+            self.record_call(CallRecord::cache_hit(started.elapsed()));
             return Ok(text);
         }
 
-        // Build messages
+        let (mut text, truncated) = self
+            .complete(self.messages(prompt, None, &config)?, &config, started)
+            .await?;
+        if truncated {
+            text = self.continue_response(prompt, text, &config).await?;
+        }
+
+        // Cache result
+        let cache_params: HashMap<String, serde_json::Value> = params
+            .into_iter()
+            .map(|(k, v)| (k, serde_json::Value::String(v)))
+            .collect();
+
+        if !self.preview {
+            self.cache
+                .lock()
+                .await
+                .put(cache_key, CachedData::Text(text.clone()), cache_params)
+                .await?;
+        }
+
+        Ok(text)
+    }
+
+    /// Complete a reply that stopped at `max_tokens`, re-prompting with the
+    /// partial output up to `config.max_continuations` times and returning
+    /// all the pieces joined together
+    pub async fn continue_response(
+        &self,
+        prompt: &str,
+        partial: String,
+        config: &TextConfig,
+    ) -> Result<String> {
+        let mut text = partial;
+        for _ in 0..config.max_continuations {
+            let started = std::time::Instant::now();
+            let messages = self.messages(prompt, Some(&text), config)?;
+            let (piece, truncated) = self.complete(messages, config, started).await?;
+            text.push_str(&piece);
+            if !truncated {
+                break;
+            }
+        }
+        Ok(text)
+    }
+
+    /// System and user messages for `prompt`, plus the partial reply and a
+    /// request to continue it when there is one
+    fn messages(
+        &self,
+        prompt: &str,
+        partial: Option<&str>,
+        config: &TextConfig,
+    ) -> Result<Vec<ChatCompletionRequestMessage>> {
         let mut messages = Vec::new();
 
         if let Some(system) = &config.system_prompt {
@@ -190,7 +339,41 @@ impl TextGenerator {
                 .into(),
         );
 
-        // Create request
+        if let Some(partial) = partial {
+            messages.push(
+                ChatCompletionRequestAssistantMessageArgs::default()
+                    .content(partial)
+                    .build()?
+                    .into(),
+            );
+            messages.push(
+                ChatCompletionRequestUserMessageArgs::default()
+                    .content(CONTINUE_PROMPT)
+                    .build()?
+                    .into(),
+            );
+        }
+
+        Ok(messages)
+    }
+
+    /// The messages `generate` would send for `prompt`, for token estimates
+    fn request_messages(&self, prompt: &str, config: &TextConfig) -> Result<Vec<Message>> {
+        Ok(self
+            .messages(prompt, None, config)?
+            .iter()
+            .map(Message::from)
+            .collect())
+    }
+
+    /// Make one API call, tracking its tokens. Returns the reply and whether
+    /// it was cut off by the token limit.
+    async fn complete(
+        &self,
+        messages: Vec<ChatCompletionRequestMessage>,
+        config: &TextConfig,
+        started: std::time::Instant,
+    ) -> Result<(String, bool)> {
         let request = CreateChatCompletionRequestArgs::default()
             .model(&config.model)
             .messages(messages)
@@ -201,7 +384,6 @@ impl TextGenerator {
             .presence_penalty(config.presence_penalty)
             .build()?;
 
-        // Make API call
         let response = self
             .client
             .chat()
@@ -209,16 +391,18 @@ impl TextGenerator {
             .await
             .context("Failed to generate text")?;
 
-        // Extract text
-        let text = response
-            .choices
-            .first()
+        let choice = response.choices.first();
+        let text = choice
             .and_then(|choice| choice.message.content.clone())
             .unwrap_or_default();
+        let truncated =
+            choice.and_then(|choice| choice.finish_reason) == Some(FinishReason::Length);
 
-        // Track tokens
+        let mut record = CallRecord::api(0, 0.0, started.elapsed());
         if let Some(usage) = response.usage {
-            self.token_counter
+            record.tokens = usage.total_tokens as usize;
+            record.cost = self
+                .token_counter
                 .lock()
                 .await
                 .record_usage(
@@ -228,20 +412,9 @@ impl TextGenerator {
                 )
                 .await?;
         }
+        self.record_call(record);
 
-        // Cache result
-        let cache_params: HashMap<String, serde_json::Value> = params
-            .into_iter()
-            .map(|(k, v)| (k, serde_json::Value::String(v)))
-            .collect();
-
-        self.cache
-            .lock()
-            .await
-            .put(cache_key, CachedData::Text(text.clone()), cache_params)
-            .await?;
-
-        Ok(text)
+        Ok((text, truncated))
     }
 
     /// Generate multiple related texts (e.g., character descriptions)
@@ -292,15 +465,129 @@ impl TextGenerator {
 
         let json_text = self.generate(&structured_prompt, config).await?;
 
-        // Try to parse JSON, cleaning if necessary
-        let cleaned = json_text
-            .trim()
-            .trim_start_matches("```json")
-            .trim_start_matches("```")
-            .trim_end_matches("```")
-            .trim();
+        // Models still wrap JSON in prose or fences now and then
+        let repaired = repair_json(&json_text)?;
+        serde_json::from_str(&repaired)
+            .with_context(|| format!("Failed to parse structured output:\n{json_text}"))
+    }
+
+    /// Stream a reply chunk by chunk. Streamed replies skip the cache, and
+    /// offline mode sends the whole stub response as one chunk.
+    pub async fn generate_stream(
+        &self,
+        prompt: &str,
+        config: TextConfig,
+    ) -> Result<impl Stream<Item = Result<String>> + Send + use<>> {
+        let started = std::time::Instant::now();
+        let stub_reply = self
+            .stub
+            .as_ref()
+            .map(|stub| stub.respond(prompt).to_string());
+
+        let mut stream = match stub_reply {
+            Some(_) => None,
+            None => {
+                let request = CreateChatCompletionRequestArgs::default()
+                    .model(&config.model)
+                    .messages(self.messages(prompt, None, &config)?)
+                    .temperature(config.temperature)
+                    .max_tokens(config.max_tokens)
+                    .top_p(config.top_p)
+                    .frequency_penalty(config.frequency_penalty)
+                    .presence_penalty(config.presence_penalty)
+                    .stream(true)
+                    .build()?;
+                Some(
+                    self.client
+                        .chat()
+                        .create_stream(request)
+                        .await
+                        .context("Failed to start text stream")?,
+                )
+            }
+        };
+        // Streams don't report usage, so the reply is counted as it arrives
+        let usage = match stream {
+            Some(_) => {
+                let messages = self.request_messages(prompt, &config)?;
+                let prompt_tokens = self
+                    .token_counter
+                    .lock()
+                    .await
+                    .estimate_request_tokens(&messages, &config.model);
+                Some((self.token_counter.clone(), config.model, prompt_tokens))
+            }
+            None => None,
+        };
+        let telemetry = self.telemetry.clone();
+
+        Ok(async_stream::try_stream! {
+            if let Some(reply) = stub_reply {
+                yield reply;
+            }
+
+            let mut reply = String::new();
+            while let Some(result) = match stream.as_mut() {
+                Some(stream) => stream.next().await,
+                None => None,
+            } {
+                let response = result.map_err(|e| anyhow::anyhow!("Stream error: {e}"))?;
+                if let Some(content) = response
+                    .choices
+                    .first()
+                    .and_then(|choice| choice.delta.content.clone())
+                {
+                    reply.push_str(&content);
+                    yield content;
+                }
+            }
 
-        serde_json::from_str(cleaned).context("Failed to parse structured output")
+            let mut record = CallRecord::api(0, 0.0, started.elapsed());
+            if let Some((token_counter, model, prompt_tokens)) = usage {
+                let counter = token_counter.lock().await;
+                let completion_tokens = counter.estimate_tokens(&reply, &model);
+                record.tokens = prompt_tokens + completion_tokens;
+                record.cost = counter
+                    .record_usage(&model, prompt_tokens, completion_tokens)
+                    .await?;
+            }
+            telemetry.record("text", record);
+        })
+    }
+
+    /// Like `generate_structured`, but reports the fields parsed so far each
+    /// time a chunk changes them, ending with the strictly parsed reply
+    pub async fn generate_structured_stream<T: DeserializeOwned + Send + 'static>(
+        &self,
+        prompt: &str,
+        config: TextConfig,
+    ) -> Result<impl Stream<Item = Result<StructuredUpdate<T>>> + Send + use<T>> {
+        let structured_prompt = format!(
+            "{prompt}\n\nIMPORTANT: Respond ONLY with valid JSON, no additional text or formatting."
+        );
+
+        let mut config = config;
+        config.temperature = config.temperature.min(0.5); // Lower temperature for structured output
+
+        let mut chunks = Box::pin(self.generate_stream(&structured_prompt, config).await?);
+
+        Ok(async_stream::try_stream! {
+            let mut json = PartialJson::new();
+            let mut last = None;
+
+            while let Some(chunk) = chunks.next().await {
+                let value = json.push(&chunk?);
+                if value.is_none() || value == last {
+                    continue;
+                }
+                if let Some(partial) = json.partial::<T>() {
+                    yield StructuredUpdate::Partial(partial);
+                }
+                last = value;
+            }
+
+            yield StructuredUpdate::Complete(json.finish::<T>()?);
+        })
     }
 
     /// Generate with specific style consistency
@@ -327,13 +614,17 @@ impl TextGenerator {
 #[async_trait::async_trait]
 impl AiGenerator for TextGenerator {
     async fn estimate_tokens(&self, request: &str) -> Result<usize> {
+        let config = TextConfig::default();
+        let messages = self.request_messages(request, &config)?;
         let counter = self.token_counter.lock().await;
-        counter.count_tokens(request, "gpt-3.5-turbo")
+        Ok(counter.estimate_request_tokens(&messages, &config.model))
     }
 
     async fn estimate_cost(&self, request: &str) -> Result<f64> {
+        let config = TextConfig::default();
+        let messages = self.request_messages(request, &config)?;
         let counter = self.token_counter.lock().await;
-        counter.estimate_cost(request, "gpt-3.5-turbo", 1000)
+        Ok(counter.estimate_request_cost(&messages, &config.model, config.max_tokens as usize))
     }
 
     async fn is_cached(&self, key: &str) -> bool {
@@ -343,6 +634,28 @@ impl AiGenerator for TextGenerator {
     async fn clear_cache(&self, key: &str) -> Result<()> {
         self.cache.lock().await.clear(key).await
     }
+
+    fn name(&self) -> &'static str {
+        "text"
+    }
+
+    fn telemetry(&self) -> &Telemetry {
+        &self.telemetry
+    }
+
+    async fn generate_asset(&self, prompt: &str) -> Result<Vec<u8>> {
+        let text = self.generate(prompt, TextConfig::default()).await?;
+        Ok(text.into_bytes())
+    }
+
+    async fn regenerate_asset(&self, prompt: &str) -> Result<Vec<u8>> {
+        let config = TextConfig {
+            no_cache: true,
+            ..TextConfig::default()
+        };
+        let text = self.generate(prompt, config).await?;
+        Ok(text.into_bytes())
+    }
 }
 
 /// Specialized generators for game content
@@ -490,3 +803,168 @@ pub mod templates {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::CacheConfig;
+    use crate::test_support::{mock_openai, mock_openai_sequence, mock_openai_stream};
+
+    #[tokio::test]
+    async fn test_identical_request_is_served_from_cache() {
+        let api_base = mock_openai(
+            r#"{"id": "chatcmpl-1", "object": "chat.completion", "created": 0,
+                "model": "gpt-3.5-turbo",
+                "choices": [{"index": 0, "finish_reason": "stop", "logprobs": null,
+                             "message": {"role": "assistant", "content": "A cozy dungeon"}}],
+                "usage": {"prompt_tokens": 12, "completion_tokens": 3, "total_tokens": 15}}"#,
+        )
+        .await;
+        let client = Arc::new(Client::with_config(
+            OpenAIConfig::new()
+                .with_api_key("test")
+                .with_api_base(api_base),
+        ));
+        let cache = AiCache::with_config(CacheConfig {
+            cache_dir: std::env::temp_dir().join("vintage_ai_client_text_tests"),
+            ..CacheConfig::default()
+        })
+        .unwrap();
+        let token_counter = Arc::new(Mutex::new(TokenCounter::new()));
+        let generator =
+            TextGenerator::new(client, Arc::new(Mutex::new(cache)), token_counter.clone());
+
+        // Unique prompt per run so a response cached by a previous run can't mask the mock
+        let prompt = format!("Describe a dungeon {}", uuid::Uuid::new_v4());
+        let config = TextConfig {
+            system_prompt: Some("You are terse.".to_string()),
+            ..TextConfig::default()
+        };
+
+        let first = generator.generate(&prompt, config.clone()).await.unwrap();
+        let tokens_after_first = token_counter.lock().await.get_stats().await.prompt_tokens;
+
+        // The mock only answers once, so this must come from the cache
+        let second = generator.generate(&prompt, config.clone()).await.unwrap();
+        assert_eq!(first, "A cozy dungeon");
+        assert_eq!(second, first);
+        let stats = token_counter.lock().await.get_stats().await;
+        assert_eq!(stats.prompt_tokens, tokens_after_first);
+
+        // A different system prompt is a different request
+        let other_system = TextConfig {
+            system_prompt: Some("You are verbose.".to_string()),
+            ..config.clone()
+        };
+        assert!(generator.generate(&prompt, other_system).await.is_err());
+
+        let uncached = TextConfig {
+            no_cache: true,
+            ..config
+        };
+        assert!(generator.generate(&prompt, uncached).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_streamed_reply_is_counted_in_telemetry() {
+        let api_base = mock_openai_stream(vec![
+            r#"{"id": "chatcmpl-1", "object": "chat.completion.chunk", "created": 0,
+                "model": "gpt-3.5-turbo",
+                "choices": [{"index": 0, "finish_reason": null,
+                             "delta": {"role": "assistant", "content": "A cozy "}}]}"#,
+            r#"{"id": "chatcmpl-1", "object": "chat.completion.chunk", "created": 0,
+                "model": "gpt-3.5-turbo",
+                "choices": [{"index": 0, "finish_reason": "stop",
+                             "delta": {"content": "dungeon"}}]}"#,
+        ])
+        .await;
+        let client = Arc::new(Client::with_config(
+            OpenAIConfig::new()
+                .with_api_key("test")
+                .with_api_base(api_base),
+        ));
+        let cache = AiCache::with_config(CacheConfig {
+            cache_dir: std::env::temp_dir().join("vintage_ai_client_text_tests"),
+            ..CacheConfig::default()
+        })
+        .unwrap();
+        let token_counter = Arc::new(Mutex::new(TokenCounter::new()));
+        let telemetry = Telemetry::default();
+        let generator =
+            TextGenerator::new(client, Arc::new(Mutex::new(cache)), token_counter.clone())
+                .with_telemetry(telemetry.clone());
+
+        let chunks: Vec<String> = generator
+            .generate_stream("Describe a dungeon", TextConfig::default())
+            .await
+            .unwrap()
+            .map(Result::unwrap)
+            .collect()
+            .await;
+        assert_eq!(chunks.concat(), "A cozy dungeon");
+
+        let stats = token_counter.lock().await.get_stats().await;
+        assert!(stats.prompt_tokens > 0);
+        assert!(stats.completion_tokens > 0);
+        let text = &telemetry.snapshot().generators["text"];
+        assert_eq!(text.tokens, stats.prompt_tokens + stats.completion_tokens);
+        assert!(text.cost > 0.0);
+        assert_eq!(text.cost, stats.total_cost);
+    }
+
+    #[tokio::test]
+    async fn test_truncated_reply_is_continued() {
+        let api_base = mock_openai_sequence(vec![
+            r#"{"id": "chatcmpl-1", "object": "chat.completion", "created": 0,
+                "model": "gpt-3.5-turbo",
+                "choices": [{"index": 0, "finish_reason": "length", "logprobs": null,
+                             "message": {"role": "assistant", "content": "The hero enters the "}}],
+                "usage": {"prompt_tokens": 12, "completion_tokens": 5, "total_tokens": 17}}"#,
+            r#"{"id": "chatcmpl-2", "object": "chat.completion", "created": 0,
+                "model": "gpt-3.5-turbo",
+                "choices": [{"index": 0, "finish_reason": "stop", "logprobs": null,
+                             "message": {"role": "assistant", "content": "haunted castle."}}],
+                "usage": {"prompt_tokens": 20, "completion_tokens": 3, "total_tokens": 23}}"#,
+        ])
+        .await;
+        let client = Arc::new(Client::with_config(
+            OpenAIConfig::new()
+                .with_api_key("test")
+                .with_api_base(api_base),
+        ));
+        let cache = AiCache::with_config(CacheConfig {
+            cache_dir: std::env::temp_dir().join("vintage_ai_client_text_tests"),
+            ..CacheConfig::default()
+        })
+        .unwrap();
+        let generator = TextGenerator::new(
+            client,
+            Arc::new(Mutex::new(cache)),
+            Arc::new(Mutex::new(TokenCounter::new())),
+        );
+
+        let config = TextConfig {
+            no_cache: true,
+            ..TextConfig::default()
+        };
+        let text = generator
+            .generate("Open the story", config.clone())
+            .await
+            .unwrap();
+        assert_eq!(text, "The hero enters the haunted castle.");
+
+        // With no continuations allowed, the cut-off text comes back as is
+        let partial = generator
+            .continue_response(
+                "Open the story",
+                "The hero".to_string(),
+                &TextConfig {
+                    max_continuations: 0,
+                    ..config
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(partial, "The hero");
+    }
+}
@@ -4,11 +4,56 @@
 //! Supports all OpenAI models and their pricing
 
 use anyhow::{Context, Result};
+use async_openai::types::chat::ChatCompletionRequestMessage;
 use std::collections::HashMap;
 use std::sync::Arc;
-use tiktoken_rs::{CoreBPE, cl100k_base, p50k_base, r50k_base};
+use tiktoken_rs::{CoreBPE, cl100k_base, o200k_base, p50k_base, r50k_base};
 use tokio::sync::Mutex;
 
+/// Tokens every chat message adds on top of its role and content
+const TOKENS_PER_MESSAGE: usize = 3;
+
+/// Tokens that prime every chat reply
+const REPLY_PRIMING_TOKENS: usize = 3;
+
+/// Rough characters per token, for models without a known tokenizer
+const CHARS_PER_TOKEN: usize = 4;
+
+/// A chat message as sent to the model, for estimating request size
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    pub role: String,
+    pub content: String,
+}
+
+impl Message {
+    pub fn new(role: impl Into<String>, content: impl Into<String>) -> Self {
+        Self {
+            role: role.into(),
+            content: content.into(),
+        }
+    }
+}
+
+impl From<&ChatCompletionRequestMessage> for Message {
+    /// Role and text as they go over the wire; image and audio parts are
+    /// left out
+    fn from(message: &ChatCompletionRequestMessage) -> Self {
+        let json = serde_json::to_value(message).unwrap_or_default();
+        let role = json["role"].as_str().unwrap_or_default();
+        let content = match &json["content"] {
+            serde_json::Value::String(text) => text.clone(),
+            serde_json::Value::Array(parts) => parts
+                .iter()
+                .filter_map(|part| part["text"].as_str())
+                .collect::<Vec<_>>()
+                .join("\n"),
+            _ => String::new(),
+        };
+        Self::new(role, content)
+    }
+}
+
 /// Token counter for tracking usage and costs
 pub struct TokenCounter {
     /// Token encoders for different models
@@ -233,6 +278,7 @@ impl TokenCounter {
         encoders.insert("cl100k_base".to_string(), Arc::new(cl100k_base().unwrap()));
         encoders.insert("p50k_base".to_string(), Arc::new(p50k_base().unwrap()));
         encoders.insert("r50k_base".to_string(), Arc::new(r50k_base().unwrap()));
+        encoders.insert("o200k_base".to_string(), Arc::new(o200k_base().unwrap()));
 
         Self {
             encoders,
@@ -248,6 +294,47 @@ impl TokenCounter {
         Ok(tokens.len())
     }
 
+    /// Prompt tokens a chat request with `messages` will be billed for,
+    /// including the system prompt, history and per-message overhead.
+    ///
+    /// Models without a known tokenizer fall back to ~4 characters a token.
+    pub fn estimate_request_tokens(&self, messages: &[Message], model: &str) -> usize {
+        messages
+            .iter()
+            .map(|message| {
+                TOKENS_PER_MESSAGE
+                    + self.estimate_tokens(&message.role, model)
+                    + self.estimate_tokens(&message.content, model)
+            })
+            .sum::<usize>()
+            + REPLY_PRIMING_TOKENS
+    }
+
+    /// Tokens in `text`, falling back to ~4 characters a token for models
+    /// without a known tokenizer
+    pub fn estimate_tokens(&self, text: &str, model: &str) -> usize {
+        match encoder_name_for_model(model).and_then(|name| self.encoders.get(name)) {
+            Some(encoder) => encoder.encode_with_special_tokens(text).len(),
+            None => text.len().div_ceil(CHARS_PER_TOKEN),
+        }
+    }
+
+    /// Estimate the cost of a chat request before sending it, assuming the
+    /// reply uses all of `max_completion_tokens`
+    pub fn estimate_request_cost(
+        &self,
+        messages: &[Message],
+        model: &str,
+        max_completion_tokens: usize,
+    ) -> f64 {
+        let Some(pricing) = self.pricing.models.get(model) else {
+            return 0.0;
+        };
+        let prompt_tokens = self.estimate_request_tokens(messages, model);
+        (prompt_tokens as f64 / 1000.0) * pricing.prompt_cost_per_1k
+            + (max_completion_tokens as f64 / 1000.0) * pricing.completion_cost_per_1k
+    }
+
     /// Estimate tokens for an image
     pub fn estimate_image_tokens(&self, width: u32, height: u32) -> usize {
         // Rough estimation based on image dimensions
@@ -258,13 +345,13 @@ impl TokenCounter {
         base_tokens + dimension_tokens
     }
 
-    /// Record token usage
+    /// Record token usage, returning its cost in USD
     pub async fn record_usage(
         &self,
         model: &str,
         prompt_tokens: usize,
         completion_tokens: usize,
-    ) -> Result<()> {
+    ) -> Result<f64> {
         let mut stats = self.stats.lock().await;
 
         // Update token counts
@@ -276,39 +363,41 @@ impl TokenCounter {
             (prompt_tokens + completion_tokens) as u64;
 
         // Calculate cost
+        let mut total_cost = 0.0;
         if let Some(pricing) = self.pricing.models.get(model) {
             let prompt_cost = (prompt_tokens as f64 / 1000.0) * pricing.prompt_cost_per_1k;
             let completion_cost =
                 (completion_tokens as f64 / 1000.0) * pricing.completion_cost_per_1k;
-            let total_cost = prompt_cost + completion_cost;
+            total_cost = prompt_cost + completion_cost;
 
             stats.total_cost += total_cost;
             *stats.cost_by_model.entry(model.to_string()).or_insert(0.0) += total_cost;
         }
 
-        Ok(())
+        Ok(total_cost)
     }
 
-    /// Record image generation
+    /// Record image generation, returning its cost in USD
     pub async fn record_image_generation(
         &self,
         model: &str,
         width: u32,
         height: u32,
         count: usize,
-    ) -> Result<()> {
+    ) -> Result<f64> {
         let mut stats = self.stats.lock().await;
 
+        let mut total_cost = 0.0;
         if let Some(pricing) = self.pricing.models.get(model)
             && let Some(image_cost) = pricing.image_cost
         {
-            let total_cost = image_cost * count as f64;
+            total_cost = image_cost * count as f64;
             stats.total_cost += total_cost;
             *stats.cost_by_model.entry(model.to_string()).or_insert(0.0) += total_cost;
             stats.image_tokens += self.estimate_image_tokens(width, height) as u64 * count as u64;
         }
 
-        Ok(())
+        Ok(total_cost)
     }
 
     /// Record embedding usage
@@ -359,14 +448,8 @@ impl TokenCounter {
 
     /// Get the appropriate encoder for a model
     fn get_encoder_for_model(&self, model: &str) -> Result<&Arc<CoreBPE>> {
-        // Determine which encoder to use based on model name
-        let encoder_name = if model.starts_with("gpt-4") || model.starts_with("gpt-3.5-turbo") {
-            "cl100k_base"
-        } else if model.contains("davinci") || model.contains("curie") {
-            "p50k_base"
-        } else {
-            "cl100k_base" // Default to newest encoder
-        };
+        // Unknown models get the most common encoder
+        let encoder_name = encoder_name_for_model(model).unwrap_or("cl100k_base");
 
         self.encoders
             .get(encoder_name)
@@ -374,6 +457,28 @@ impl TokenCounter {
     }
 }
 
+/// Tokenizer `model` uses, if it is one we know
+fn encoder_name_for_model(model: &str) -> Option<&'static str> {
+    const O200K_PREFIXES: [&str; 6] = ["gpt-4o", "gpt-4.1", "gpt-5", "o1", "o3", "o4"];
+    const CL100K_PREFIXES: [&str; 3] = ["gpt-4", "gpt-3.5", "text-embedding-"];
+
+    if O200K_PREFIXES
+        .iter()
+        .any(|prefix| model.starts_with(prefix))
+    {
+        Some("o200k_base")
+    } else if CL100K_PREFIXES
+        .iter()
+        .any(|prefix| model.starts_with(prefix))
+    {
+        Some("cl100k_base")
+    } else if model.contains("davinci") || model.contains("curie") {
+        Some("p50k_base")
+    } else {
+        None
+    }
+}
+
 /// Token optimization strategies
 pub struct TokenOptimizer {
     /// Maximum context window sizes by model
@@ -586,3 +691,50 @@ pub async fn analyze_usage_for_optimizations(
 
     suggestions
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_openai::types::chat::{
+        ChatCompletionRequestSystemMessageArgs, ChatCompletionRequestUserMessageArgs,
+    };
+
+    #[test]
+    fn test_request_estimate_matches_tokenizer() {
+        let counter = TokenCounter::new();
+        // "hello" and " world" in cl100k_base
+        assert_eq!(counter.count_tokens("hello world", "gpt-4").unwrap(), 2);
+
+        let user: ChatCompletionRequestMessage = ChatCompletionRequestUserMessageArgs::default()
+            .content("hello world")
+            .build()
+            .unwrap()
+            .into();
+        let messages = [Message::from(&user)];
+        assert_eq!(messages[0], Message::new("user", "hello world"));
+        // Message overhead + "user" + 2 content tokens + reply priming
+        assert_eq!(
+            counter.estimate_request_tokens(&messages, "gpt-4"),
+            3 + 1 + 2 + 3
+        );
+
+        // The system prompt is billed too
+        let system: ChatCompletionRequestMessage =
+            ChatCompletionRequestSystemMessageArgs::default()
+                .content("You are a game designer.")
+                .build()
+                .unwrap()
+                .into();
+        let with_system = [Message::from(&system), Message::from(&user)];
+        assert!(
+            counter.estimate_request_tokens(&with_system, "gpt-4")
+                > counter.estimate_request_tokens(&messages, "gpt-4")
+        );
+
+        // Unknown models fall back to characters / 4
+        assert_eq!(
+            counter.estimate_request_tokens(&messages, "local-llama"),
+            3 + 1 + 3 + 3
+        );
+    }
+}
@@ -0,0 +1,1123 @@
+//! Voice synthesis configuration for character dialogue
+//!
+//! Features:
+//! - Per-voice synthesis parameters (stability, similarity, style)
+//! - Emotion presets mapped to sensible parameter values
+//! - Text normalization so numbers and abbreviations read naturally
+//! - Stable cache keys so identical lines are never synthesized twice
+//! - Cancellable batch synthesis that keeps finished lines
+//! - Cache warm-checks so a batch can skip lines that are already voiced
+//! - Role presets so archetypes like "villain" share one voice config
+//! - Provider-specific parameters passed through to the request body
+//! - Pluggable synthesis backends, with ElevenLabs as the default
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::sync::Mutex;
+
+use super::AiGenerator;
+use super::cache::{AiCache, CachedData};
+use super::telemetry::{CallRecord, Telemetry};
+
+/// Emotional delivery for a spoken line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum VoiceEmotion {
+    #[default]
+    Neutral,
+    Angry,
+    Sad,
+    Excited,
+    Whisper,
+}
+
+impl VoiceEmotion {
+    /// Stable identifier used in cache keys and logs
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VoiceEmotion::Neutral => "neutral",
+            VoiceEmotion::Angry => "angry",
+            VoiceEmotion::Sad => "sad",
+            VoiceEmotion::Excited => "excited",
+            VoiceEmotion::Whisper => "whisper",
+        }
+    }
+}
+
+/// Configuration for voice synthesis
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VoiceConfig {
+    /// Provider voice identifier
+    pub voice_id: String,
+    /// Synthesis model identifier
+    pub model_id: String,
+    /// Voice stability (0.0 - 1.0); lower values are more expressive
+    pub stability: f32,
+    /// How closely to match the original voice (0.0 - 1.0)
+    pub similarity_boost: f32,
+    /// Style exaggeration (0.0 - 1.0)
+    pub style: f32,
+    /// Boost similarity to the original speaker
+    pub use_speaker_boost: bool,
+    /// Emotional delivery preset
+    pub emotion: VoiceEmotion,
+    /// Normalize numbers and abbreviations before synthesis
+    pub normalize: bool,
+    /// Provider-specific request fields, e.g. OpenAI's `speed` or
+    /// `instructions`. Keys a provider doesn't accept are dropped.
+    pub extra: HashMap<String, Value>,
+}
+
+/// Text-to-speech API a request body is built for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VoiceProvider {
+    OpenAi,
+    Azure,
+    ElevenLabs,
+}
+
+impl VoiceProvider {
+    /// Top-level request fields `VoiceConfig::extra` may set
+    pub fn extra_keys(&self) -> &'static [&'static str] {
+        match self {
+            VoiceProvider::OpenAi | VoiceProvider::Azure => {
+                &["speed", "instructions", "response_format"]
+            }
+            VoiceProvider::ElevenLabs => &[
+                "language_code",
+                "seed",
+                "previous_text",
+                "next_text",
+                "apply_text_normalization",
+            ],
+        }
+    }
+}
+
+impl Default for VoiceConfig {
+    fn default() -> Self {
+        Self {
+            voice_id: "narrator".to_string(),
+            model_id: "eleven_multilingual_v2".to_string(),
+            stability: 0.5,
+            similarity_boost: 0.75,
+            style: 0.0,
+            use_speaker_boost: true,
+            emotion: VoiceEmotion::Neutral,
+            normalize: true,
+            extra: HashMap::new(),
+        }
+    }
+}
+
+impl VoiceConfig {
+    /// Create a config for a specific voice
+    pub fn new(voice_id: impl Into<String>) -> Self {
+        Self {
+            voice_id: voice_id.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Apply an emotion preset, overriding stability, similarity and style
+    pub fn with_emotion(mut self, emotion: VoiceEmotion) -> Self {
+        let (stability, similarity_boost, style) = match emotion {
+            VoiceEmotion::Neutral => (0.5, 0.75, 0.0),
+            VoiceEmotion::Angry => (0.25, 0.8, 0.7),
+            VoiceEmotion::Sad => (0.7, 0.7, 0.35),
+            VoiceEmotion::Excited => (0.3, 0.75, 0.6),
+            VoiceEmotion::Whisper => (0.85, 0.6, 0.15),
+        };
+
+        self.stability = stability;
+        self.similarity_boost = similarity_boost;
+        self.style = style;
+        self.emotion = emotion;
+        self
+    }
+
+    /// Pass a provider-specific field through to the request body
+    pub fn with_extra(mut self, key: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.extra.insert(key.into(), value.into());
+        self
+    }
+
+    /// JSON body for a synthesis request to `provider`, with any `extra`
+    /// fields that provider accepts merged in
+    pub fn request_body(&self, provider: VoiceProvider, text: &str) -> Value {
+        let mut body = match provider {
+            VoiceProvider::OpenAi | VoiceProvider::Azure => json!({
+                "model": self.model_id,
+                "voice": self.voice_id,
+                "input": text,
+            }),
+            VoiceProvider::ElevenLabs => json!({
+                "text": text,
+                "model_id": self.model_id,
+                "voice_settings": {
+                    "stability": self.stability,
+                    "similarity_boost": self.similarity_boost,
+                    "style": self.style,
+                    "use_speaker_boost": self.use_speaker_boost,
+                },
+            }),
+        };
+
+        let supported = provider.extra_keys();
+        if let Value::Object(fields) = &mut body {
+            for (key, value) in &self.extra {
+                if supported.contains(&key.as_str()) {
+                    fields.insert(key.clone(), value.clone());
+                }
+            }
+        }
+        body
+    }
+
+    /// Prepare a line for synthesis, normalizing it if enabled
+    pub fn prepare_text(&self, text: &str) -> String {
+        if self.normalize {
+            normalize_for_tts(text)
+        } else {
+            text.to_string()
+        }
+    }
+
+    /// Parameters that affect the synthesized audio, for cache keying
+    pub fn cache_params(&self) -> HashMap<String, String> {
+        let mut params = HashMap::new();
+        params.insert("voice_id".to_string(), self.voice_id.clone());
+        params.insert("model_id".to_string(), self.model_id.clone());
+        params.insert("stability".to_string(), self.stability.to_string());
+        params.insert(
+            "similarity_boost".to_string(),
+            self.similarity_boost.to_string(),
+        );
+        params.insert("style".to_string(), self.style.to_string());
+        params.insert(
+            "use_speaker_boost".to_string(),
+            self.use_speaker_boost.to_string(),
+        );
+        params.insert("emotion".to_string(), self.emotion.as_str().to_string());
+        for (key, value) in &self.extra {
+            params.insert(format!("extra.{key}"), value.to_string());
+        }
+        params
+    }
+
+    /// Generate the cache key for synthesizing `text` with this config
+    pub fn cache_key(&self, cache: &AiCache, text: &str) -> String {
+        cache.generate_key("voice", text, &self.cache_params())
+    }
+}
+
+/// Whether each `(text, config)` line is in `cache`, see
+/// `VoiceGenerator::cached_lines`
+async fn lines_in_cache(cache: &AiCache, items: &[(String, VoiceConfig)]) -> Vec<bool> {
+    let mut cached = Vec::with_capacity(items.len());
+    for (text, config) in items {
+        let key = config.cache_key(cache, &config.prepare_text(text));
+        cached.push(cache.get(&key).await.is_some());
+    }
+    cached
+}
+
+/// Text-to-speech backend, e.g. ElevenLabs or a local Piper or espeak
+/// engine for fully offline voice
+#[async_trait::async_trait]
+pub trait TtsBackend: Send + Sync {
+    /// Synthesize already-prepared text, returning encoded audio
+    async fn synthesize(&self, text: &str, config: &VoiceConfig) -> Result<Vec<u8>>;
+
+    /// Estimated cost in USD of synthesizing `text`. Free by default, as
+    /// local engines are.
+    fn estimate_cost(&self, _text: &str, _config: &VoiceConfig) -> f64 {
+        0.0
+    }
+}
+
+/// Base URL of the ElevenLabs text-to-speech API
+pub const ELEVENLABS_API_URL: &str = "https://api.elevenlabs.io/v1/text-to-speech";
+
+/// ElevenLabs price per 1,000 characters synthesized, in USD
+pub const ELEVENLABS_COST_PER_1K_CHARS: f64 = 0.30;
+
+/// Default backend, synthesizing through the ElevenLabs API
+#[derive(Clone)]
+pub struct ElevenLabsBackend {
+    client: reqwest::Client,
+    api_key: String,
+}
+
+impl ElevenLabsBackend {
+    pub fn new(api_key: impl Into<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            api_key: api_key.into(),
+        }
+    }
+
+    /// Uses ELEVENLABS_API_KEY; requests fail if it isn't set
+    pub fn from_env() -> Self {
+        Self::new(std::env::var("ELEVENLABS_API_KEY").unwrap_or_default())
+    }
+}
+
+#[async_trait::async_trait]
+impl TtsBackend for ElevenLabsBackend {
+    async fn synthesize(&self, text: &str, config: &VoiceConfig) -> Result<Vec<u8>> {
+        if self.api_key.is_empty() {
+            anyhow::bail!("ElevenLabs API key not set (ELEVENLABS_API_KEY)");
+        }
+
+        let response = self
+            .client
+            .post(format!("{ELEVENLABS_API_URL}/{}", config.voice_id))
+            .header("xi-api-key", &self.api_key)
+            .json(&config.request_body(VoiceProvider::ElevenLabs, text))
+            .send()
+            .await
+            .context("Failed to reach ElevenLabs")?
+            .error_for_status()
+            .context("ElevenLabs rejected the synthesis request")?;
+
+        Ok(response.bytes().await?.to_vec())
+    }
+
+    fn estimate_cost(&self, text: &str, _config: &VoiceConfig) -> f64 {
+        text.chars().count() as f64 / 1000.0 * ELEVENLABS_COST_PER_1K_CHARS
+    }
+}
+
+/// Voice generator for single dialogue lines, caching every line by text
+/// and config
+#[derive(Clone)]
+pub struct VoiceGenerator {
+    backend: Arc<dyn TtsBackend>,
+    cache: Arc<Mutex<AiCache>>,
+    telemetry: Telemetry,
+    /// Bypass the cache, see `with_preview`
+    preview: bool,
+}
+
+impl VoiceGenerator {
+    /// Synthesize through ElevenLabs until another backend is registered
+    pub fn new(cache: Arc<Mutex<AiCache>>) -> Self {
+        Self {
+            backend: Arc::new(ElevenLabsBackend::from_env()),
+            cache,
+            telemetry: Telemetry::default(),
+            preview: false,
+        }
+    }
+
+    /// Register the backend lines are synthesized with
+    pub fn with_backend(mut self, backend: Arc<dyn TtsBackend>) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    /// Report calls to a shared telemetry handle
+    pub fn with_telemetry(mut self, telemetry: Telemetry) -> Self {
+        self.telemetry = telemetry;
+        self
+    }
+
+    /// Throwaway previews for prompt iteration: results are neither read
+    /// from nor written to the cache. Usage and cost are still recorded.
+    pub fn with_preview(mut self, preview: bool) -> Self {
+        self.preview = preview;
+        self
+    }
+
+    /// Cache key for voicing `text` with `config`
+    pub async fn cache_key(&self, text: &str, config: &VoiceConfig) -> String {
+        config.cache_key(&*self.cache.lock().await, &config.prepare_text(text))
+    }
+
+    /// Whether each `(text, config)` line is already in the cache, without
+    /// synthesizing anything. Uses the same key as `generate_voice`.
+    pub async fn cached_lines(&self, items: &[(String, VoiceConfig)]) -> Vec<bool> {
+        lines_in_cache(&*self.cache.lock().await, items).await
+    }
+
+    /// Voice `lines` in order with this generator's backend, cache and
+    /// telemetry, calling `progress(done, total)` as each line finishes. See
+    /// `synthesize_batch_with_progress`; the cache stays locked until the
+    /// batch is done.
+    pub async fn generate_batch(
+        &self,
+        lines: &[VoiceLine],
+        cancel: &AtomicBool,
+        progress: impl Fn(usize, usize) + Send + Sync,
+    ) -> VoiceBatchResult {
+        let cache = self.cache.lock().await;
+        synthesize_batch_with_progress(
+            self.backend.as_ref(),
+            &cache,
+            lines,
+            cancel,
+            &self.telemetry,
+            progress,
+        )
+        .await
+    }
+
+    /// Voice a line, reusing cached audio if present
+    pub async fn generate_voice(&self, text: &str, config: &VoiceConfig) -> Result<Vec<u8>> {
+        let started = std::time::Instant::now();
+        let text = config.prepare_text(text);
+        let cache_key = config.cache_key(&*self.cache.lock().await, &text);
+
+        if !self.preview
+            && let Some(cached) = self.cache.lock().await.get(&cache_key).await
+            && let CachedData::Audio(audio) = cached.data
+        {
+            self.telemetry
+                .record("voice", CallRecord::cache_hit(started.elapsed()));
+            return Ok(audio);
+        }
+
+        let audio = self.backend.synthesize(&text, config).await?;
+        let cost = self.backend.estimate_cost(&text, config);
+        self.telemetry
+            .record("voice", CallRecord::api(0, cost, started.elapsed()));
+
+        let params = config
+            .cache_params()
+            .into_iter()
+            .map(|(k, v)| (k, Value::String(v)))
+            .collect();
+        if !self.preview {
+            self.cache
+                .lock()
+                .await
+                .put(cache_key, CachedData::Audio(audio.clone()), params)
+                .await?;
+        }
+
+        Ok(audio)
+    }
+}
+
+/// Asset requests carry just the line, so they're voiced with
+/// `VoiceConfig::default()`
+#[async_trait::async_trait]
+impl AiGenerator for VoiceGenerator {
+    async fn estimate_tokens(&self, _request: &str) -> Result<usize> {
+        // Speech is billed by character, not token
+        Ok(0)
+    }
+
+    async fn estimate_cost(&self, request: &str) -> Result<f64> {
+        let config = VoiceConfig::default();
+        Ok(self
+            .backend
+            .estimate_cost(&config.prepare_text(request), &config))
+    }
+
+    async fn is_cached(&self, key: &str) -> bool {
+        self.cache.lock().await.get(key).await.is_some()
+    }
+
+    async fn clear_cache(&self, key: &str) -> Result<()> {
+        self.cache.lock().await.clear(key).await
+    }
+
+    fn name(&self) -> &'static str {
+        "voice"
+    }
+
+    fn telemetry(&self) -> &Telemetry {
+        &self.telemetry
+    }
+
+    async fn generate_asset(&self, prompt: &str) -> Result<Vec<u8>> {
+        self.generate_voice(prompt, &VoiceConfig::default()).await
+    }
+
+    async fn regenerate_asset(&self, prompt: &str) -> Result<Vec<u8>> {
+        let config = VoiceConfig::default();
+        let cache_key = self.cache_key(prompt, &config).await;
+        self.clear_cache(&cache_key).await?;
+        self.generate_voice(prompt, &config).await
+    }
+}
+
+/// A single line of dialogue to voice
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VoiceLine {
+    pub id: String,
+    pub text: String,
+    pub config: VoiceConfig,
+}
+
+/// Outcome of a batch, which may have stopped early
+#[derive(Debug, Clone, Default)]
+pub struct VoiceBatchResult {
+    /// Ids of lines whose audio is in the cache
+    pub completed: Vec<String>,
+    /// Ids of lines that failed, with the error
+    pub failed: Vec<(String, String)>,
+    /// Whether the batch was cancelled before every line was attempted
+    pub cancelled: bool,
+}
+
+/// Synthesize `lines` in order, caching each one as soon as it finishes.
+///
+/// `cancel` is checked between lines, so a cancelled batch keeps every line
+/// completed so far. Lines already in the cache are not synthesized again.
+/// Each line is reported to `telemetry` under `"voice"`.
+pub async fn synthesize_batch(
+    backend: &dyn TtsBackend,
+    cache: &AiCache,
+    lines: &[VoiceLine],
+    cancel: &AtomicBool,
+    telemetry: &Telemetry,
+) -> VoiceBatchResult {
+    synthesize_batch_with_progress(backend, cache, lines, cancel, telemetry, |_, _| {}).await
+}
+
+/// `synthesize_batch`, calling `progress(done, total)` as each line finishes,
+/// whether it was voiced, taken from the cache or failed.
+///
+/// `progress` runs inline on the batch's task between lines, so it must not
+/// block; forward the counts over a channel to update a UI.
+pub async fn synthesize_batch_with_progress(
+    backend: &dyn TtsBackend,
+    cache: &AiCache,
+    lines: &[VoiceLine],
+    cancel: &AtomicBool,
+    telemetry: &Telemetry,
+    progress: impl Fn(usize, usize) + Send + Sync,
+) -> VoiceBatchResult {
+    let mut result = VoiceBatchResult::default();
+    let total = lines.len();
+    let report = |result: &VoiceBatchResult| {
+        progress(result.completed.len() + result.failed.len(), total);
+    };
+    let items: Vec<(String, VoiceConfig)> = lines
+        .iter()
+        .map(|line| (line.text.clone(), line.config.clone()))
+        .collect();
+    let cached = lines_in_cache(cache, &items).await;
+
+    for (line, cached) in lines.iter().zip(cached) {
+        if cancel.load(Ordering::Relaxed) {
+            result.cancelled = true;
+            break;
+        }
+
+        let started = std::time::Instant::now();
+        if cached {
+            telemetry.record("voice", CallRecord::cache_hit(started.elapsed()));
+            result.completed.push(line.id.clone());
+            report(&result);
+            continue;
+        }
+
+        let text = line.config.prepare_text(&line.text);
+        let key = line.config.cache_key(cache, &text);
+
+        let synthesized = backend.synthesize(&text, &line.config).await;
+        let cost = backend.estimate_cost(&text, &line.config);
+        telemetry.record("voice", CallRecord::api(0, cost, started.elapsed()));
+        let audio = match synthesized {
+            Ok(audio) => audio,
+            Err(e) => {
+                result.failed.push((line.id.clone(), e.to_string()));
+                report(&result);
+                continue;
+            }
+        };
+
+        let params = line
+            .config
+            .cache_params()
+            .into_iter()
+            .map(|(k, v)| (k, serde_json::Value::String(v)))
+            .collect();
+        match cache.put(key, CachedData::Audio(audio), params).await {
+            Ok(()) => result.completed.push(line.id.clone()),
+            Err(e) => result.failed.push((line.id.clone(), e.to_string())),
+        }
+        report(&result);
+    }
+
+    result
+}
+
+/// The voice used for every line spoken by a character role
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RolePreset {
+    pub role: String,
+    pub config: VoiceConfig,
+}
+
+/// Voice configs by character role, so archetypes like "narrator",
+/// "villain" and "hero" are set up once instead of per line. Loadable from
+/// a JSON file:
+///
+/// ```json
+/// {
+///   "presets": [{ "role": "villain", "config": { "voice_id": "grim", "emotion": "Angry" } }],
+///   "default": { "voice_id": "narrator" }
+/// }
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RoleVoices {
+    /// Checked in order; roles match case-insensitively
+    #[serde(default)]
+    pub presets: Vec<RolePreset>,
+    /// Used for roles without a preset
+    #[serde(default)]
+    pub default: VoiceConfig,
+}
+
+impl RoleVoices {
+    /// Add or replace the preset for `role`
+    pub fn with_preset(mut self, role: impl Into<String>, config: VoiceConfig) -> Self {
+        let role = role.into();
+        match self
+            .presets
+            .iter_mut()
+            .find(|preset| preset.role.eq_ignore_ascii_case(&role))
+        {
+            Some(preset) => preset.config = config,
+            None => self.presets.push(RolePreset { role, config }),
+        }
+        self
+    }
+
+    /// Load role presets from a JSON file
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read role presets from {}", path.display()))?;
+        serde_json::from_str(&content).context("Failed to parse role presets")
+    }
+
+    /// The preset for `role`, or the default config if it has none
+    pub fn config_for(&self, role: &str) -> &VoiceConfig {
+        let role = role.trim();
+        self.presets
+            .iter()
+            .find(|preset| preset.role.eq_ignore_ascii_case(role))
+            .map_or(&self.default, |preset| &preset.config)
+    }
+
+    /// A batch line voiced with `role`'s config
+    pub fn line(&self, id: impl Into<String>, role: &str, text: impl Into<String>) -> VoiceLine {
+        VoiceLine {
+            id: id.into(),
+            text: text.into(),
+            config: self.config_for(role).clone(),
+        }
+    }
+}
+
+/// Voices lines by character role instead of by explicit config, through
+/// `voices` so lines are cached the same way
+pub struct RoleVoiceGenerator {
+    pub voices: VoiceGenerator,
+    pub roles: RoleVoices,
+}
+
+impl RoleVoiceGenerator {
+    pub fn new(voices: VoiceGenerator, roles: RoleVoices) -> Self {
+        Self { voices, roles }
+    }
+
+    /// Voice `text` with the preset for `role`, falling back to the default
+    pub async fn generate_for_role(&self, role: &str, text: &str) -> Result<Vec<u8>> {
+        self.voices
+            .generate_voice(text, self.roles.config_for(role))
+            .await
+    }
+}
+
+/// Replacement table applied to text before synthesis
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TtsNormalizer {
+    /// Ordered (pattern, spoken form) pairs; earlier entries win
+    pub replacements: Vec<(String, String)>,
+    /// Spell out digit runs as words
+    pub expand_numbers: bool,
+}
+
+impl Default for TtsNormalizer {
+    fn default() -> Self {
+        let replacements = [
+            ("Lv.", "Level"),
+            ("Lvl", "Level"),
+            ("HP", "H P"),
+            ("MP", "M P"),
+            ("XP", "experience"),
+            ("EXP", "experience"),
+            ("ATK", "attack"),
+            ("DEF", "defense"),
+            ("vs.", "versus"),
+            ("%", " percent"),
+            ("&", " and "),
+            ("+", " plus "),
+        ];
+
+        Self {
+            replacements: replacements
+                .iter()
+                .map(|(from, to)| (from.to_string(), to.to_string()))
+                .collect(),
+            expand_numbers: true,
+        }
+    }
+}
+
+impl TtsNormalizer {
+    /// Add or override a replacement
+    pub fn with_replacement(mut self, from: impl Into<String>, to: impl Into<String>) -> Self {
+        let from = from.into();
+        let to = to.into();
+        if let Some(existing) = self.replacements.iter_mut().find(|(f, _)| *f == from) {
+            existing.1 = to;
+        } else {
+            self.replacements.insert(0, (from, to));
+        }
+        self
+    }
+
+    /// Normalize text so it reads naturally when spoken
+    pub fn normalize(&self, text: &str) -> String {
+        let mut result = text.to_string();
+
+        for (from, to) in &self.replacements {
+            let mut pattern = regex::escape(from);
+            // Only match whole words for abbreviations, not substrings
+            if from.starts_with(|c: char| c.is_alphanumeric()) {
+                pattern = format!(r"\b{pattern}");
+            }
+            if from.ends_with(|c: char| c.is_alphanumeric()) {
+                pattern = format!(r"{pattern}\b");
+            }
+            if let Ok(re) = Regex::new(&pattern) {
+                result = re.replace_all(&result, to.as_str()).into_owned();
+            }
+        }
+
+        if self.expand_numbers
+            && let Ok(re) = Regex::new(r"\d+")
+        {
+            result = re
+                .replace_all(&result, |caps: &regex::Captures| {
+                    caps[0]
+                        .parse::<u64>()
+                        .map(number_to_words)
+                        .unwrap_or_else(|_| caps[0].to_string())
+                })
+                .into_owned();
+        }
+
+        result.split_whitespace().collect::<Vec<_>>().join(" ")
+    }
+}
+
+/// Normalize text with the default game replacement table
+pub fn normalize_for_tts(text: &str) -> String {
+    TtsNormalizer::default().normalize(text)
+}
+
+/// Spell out a number in English words
+fn number_to_words(n: u64) -> String {
+    const ONES: [&str; 20] = [
+        "zero",
+        "one",
+        "two",
+        "three",
+        "four",
+        "five",
+        "six",
+        "seven",
+        "eight",
+        "nine",
+        "ten",
+        "eleven",
+        "twelve",
+        "thirteen",
+        "fourteen",
+        "fifteen",
+        "sixteen",
+        "seventeen",
+        "eighteen",
+        "nineteen",
+    ];
+    const TENS: [&str; 10] = [
+        "", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+    ];
+    const SCALES: [(u64, &str); 4] = [
+        (1_000_000_000_000, "trillion"),
+        (1_000_000_000, "billion"),
+        (1_000_000, "million"),
+        (1_000, "thousand"),
+    ];
+
+    fn below_thousand(n: u64) -> String {
+        let mut parts = Vec::new();
+        if n >= 100 {
+            parts.push(format!("{} hundred", ONES[(n / 100) as usize]));
+        }
+        let rest = n % 100;
+        if rest >= 20 {
+            let tens = TENS[(rest / 10) as usize];
+            if rest.is_multiple_of(10) {
+                parts.push(tens.to_string());
+            } else {
+                parts.push(format!("{}-{}", tens, ONES[(rest % 10) as usize]));
+            }
+        } else if rest > 0 || parts.is_empty() {
+            parts.push(ONES[rest as usize].to_string());
+        }
+        parts.join(" ")
+    }
+
+    if n < 1000 {
+        return below_thousand(n);
+    }
+
+    let mut parts = Vec::new();
+    let mut remaining = n;
+    for (scale, name) in SCALES {
+        if remaining >= scale {
+            parts.push(format!("{} {}", number_to_words(remaining / scale), name));
+            remaining %= scale;
+        }
+    }
+    if remaining > 0 {
+        parts.push(below_thousand(remaining));
+    }
+    parts.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::CacheConfig;
+
+    fn test_cache() -> AiCache {
+        AiCache::with_config(CacheConfig {
+            cache_dir: std::env::temp_dir().join("vintage_ai_client_voice_tests"),
+            ..CacheConfig::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_emotions_produce_distinct_configs_and_keys() {
+        let cache = test_cache();
+        let angry = VoiceConfig::new("hero").with_emotion(VoiceEmotion::Angry);
+        let sad = VoiceConfig::new("hero").with_emotion(VoiceEmotion::Sad);
+
+        assert_ne!(angry.stability, sad.stability);
+        assert_ne!(angry.style, sad.style);
+
+        let text = "You will pay for this!";
+        assert_ne!(angry.cache_key(&cache, text), sad.cache_key(&cache, text));
+    }
+
+    #[test]
+    fn test_normalize_for_tts() {
+        assert_eq!(normalize_for_tts("Lv. 3"), "Level three");
+        assert_eq!(normalize_for_tts("HP: 42%"), "H P: forty-two percent");
+
+        let config = VoiceConfig::default();
+        assert_eq!(config.prepare_text("Lv. 3"), "Level three");
+
+        let raw = VoiceConfig {
+            normalize: false,
+            ..VoiceConfig::default()
+        };
+        assert_eq!(raw.prepare_text("Lv. 3"), "Lv. 3");
+    }
+
+    #[test]
+    fn test_extra_fields_reach_only_providers_that_accept_them() {
+        let config = VoiceConfig::new("alloy").with_extra("speed", 1.25);
+
+        let openai = config.request_body(VoiceProvider::OpenAi, "Welcome, traveler.");
+        assert_eq!(openai["speed"], json!(1.25));
+        assert_eq!(openai["voice"], json!("alloy"));
+
+        let elevenlabs = config.request_body(VoiceProvider::ElevenLabs, "Welcome, traveler.");
+        assert!(elevenlabs.get("speed").is_none());
+        assert_eq!(elevenlabs["voice_settings"]["stability"], json!(0.5));
+
+        // Extras change the audio, so they change the cache key too
+        let cache = test_cache();
+        assert_ne!(
+            config.cache_key(&cache, "Welcome, traveler."),
+            VoiceConfig::new("alloy").cache_key(&cache, "Welcome, traveler.")
+        );
+    }
+
+    /// Backend that returns the text as audio and counts calls
+    #[derive(Default)]
+    struct CountingBackend {
+        calls: std::sync::atomic::AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl TtsBackend for CountingBackend {
+        async fn synthesize(&self, text: &str, _config: &VoiceConfig) -> Result<Vec<u8>> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(text.as_bytes().to_vec())
+        }
+
+        fn estimate_cost(&self, _text: &str, _config: &VoiceConfig) -> f64 {
+            0.25
+        }
+    }
+
+    #[tokio::test]
+    async fn test_generate_voice_uses_registered_backend_and_caches() {
+        let backend = Arc::new(CountingBackend::default());
+        let generator =
+            VoiceGenerator::new(Arc::new(Mutex::new(test_cache()))).with_backend(backend.clone());
+        let config = VoiceConfig::new("piper-en");
+
+        // Unique text per run so lines cached by a previous run don't count
+        let text = format!("Halt, traveler {}", uuid::Uuid::new_v4());
+        let first = generator.generate_voice(&text, &config).await.unwrap();
+        let second = generator.generate_voice(&text, &config).await.unwrap();
+
+        assert_eq!(first, config.prepare_text(&text).into_bytes());
+        assert_eq!(first, second);
+        assert_eq!(backend.calls.load(Ordering::SeqCst), 1);
+
+        let stats = generator.telemetry.snapshot().total;
+        assert_eq!(stats.calls, 2);
+        assert_eq!(stats.cache_hits, 1);
+        assert_eq!(stats.cost, 0.25);
+    }
+
+    #[tokio::test]
+    async fn test_voice_line_assets_are_voiced_and_cached() {
+        let backend = Arc::new(CountingBackend::default());
+        let generator =
+            VoiceGenerator::new(Arc::new(Mutex::new(test_cache()))).with_backend(backend.clone());
+
+        let line = format!("Welcome, traveler {}", uuid::Uuid::new_v4());
+        let audio = generator.generate_asset(&line).await.unwrap();
+        assert_eq!(
+            audio,
+            VoiceConfig::default().prepare_text(&line).into_bytes()
+        );
+        let key = generator.cache_key(&line, &VoiceConfig::default()).await;
+        assert!(generator.is_cached(&key).await);
+
+        generator.regenerate_asset(&line).await.unwrap();
+        assert_eq!(backend.calls.load(Ordering::SeqCst), 2);
+        assert_eq!(generator.estimate_cost(&line).await.unwrap(), 0.25);
+    }
+
+    #[tokio::test]
+    async fn test_preview_neither_reads_nor_writes_cache() {
+        let backend = Arc::new(CountingBackend::default());
+        let generator =
+            VoiceGenerator::new(Arc::new(Mutex::new(test_cache()))).with_backend(backend.clone());
+        let preview = generator.clone().with_preview(true);
+        let config = VoiceConfig::new("piper-en");
+
+        // Unique text per run so lines cached by a previous run don't count
+        let text = format!("Who goes there {}", uuid::Uuid::new_v4());
+        let key = generator.cache_key(&text, &config).await;
+
+        preview.generate_voice(&text, &config).await.unwrap();
+        assert!(generator.cache.lock().await.get(&key).await.is_none());
+
+        // A cached line is synthesized again rather than served stale
+        generator.generate_voice(&text, &config).await.unwrap();
+        preview.generate_voice(&text, &config).await.unwrap();
+        assert_eq!(backend.calls.load(Ordering::SeqCst), 3);
+
+        let stats = preview.telemetry.snapshot().total;
+        assert_eq!(stats.calls, 3);
+        assert_eq!(stats.cache_hits, 0);
+    }
+
+    /// Backend that requests cancellation once it has voiced `cancel_after` lines
+    struct CancellingBackend<'a> {
+        calls: std::sync::atomic::AtomicUsize,
+        cancel_after: usize,
+        cancel: &'a AtomicBool,
+    }
+
+    #[async_trait::async_trait]
+    impl TtsBackend for CancellingBackend<'_> {
+        async fn synthesize(&self, text: &str, _config: &VoiceConfig) -> Result<Vec<u8>> {
+            let calls = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+            if calls == self.cancel_after {
+                self.cancel.store(true, Ordering::SeqCst);
+            }
+            Ok(text.as_bytes().to_vec())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_batch_keeps_completed_lines() {
+        let cache = test_cache();
+        let cancel = AtomicBool::new(false);
+        let backend = CancellingBackend {
+            calls: Default::default(),
+            cancel_after: 2,
+            cancel: &cancel,
+        };
+
+        // Unique text per run so lines cached by a previous run don't count
+        let run = uuid::Uuid::new_v4();
+        let lines: Vec<VoiceLine> = (0..5)
+            .map(|i| VoiceLine {
+                id: format!("line_{i}"),
+                text: format!("Line {run} number {i}"),
+                config: VoiceConfig::new("hero"),
+            })
+            .collect();
+
+        let result =
+            synthesize_batch(&backend, &cache, &lines, &cancel, &Telemetry::default()).await;
+        assert!(result.cancelled);
+        assert_eq!(result.completed, vec!["line_0", "line_1"]);
+        assert!(result.failed.is_empty());
+
+        let mut cached = 0;
+        for line in &lines {
+            let key = line
+                .config
+                .cache_key(&cache, &line.config.prepare_text(&line.text));
+            if cache.get(&key).await.is_some() {
+                cached += 1;
+            }
+        }
+        assert_eq!(cached, 2);
+    }
+
+    #[tokio::test]
+    async fn test_batch_progress_counts_up_to_total() {
+        let backend = Arc::new(CountingBackend::default());
+        let generator =
+            VoiceGenerator::new(Arc::new(Mutex::new(test_cache()))).with_backend(backend.clone());
+
+        let run = uuid::Uuid::new_v4();
+        let lines: Vec<VoiceLine> = (0..4)
+            .map(|i| VoiceLine {
+                id: format!("line_{i}"),
+                text: format!("Progress {run} line {i}"),
+                config: VoiceConfig::new("hero"),
+            })
+            .collect();
+        // One line is already cached and still counts towards progress
+        generator
+            .generate_voice(&lines[0].text, &lines[0].config)
+            .await
+            .unwrap();
+
+        let reports = std::sync::Mutex::new(Vec::new());
+        let result = generator
+            .generate_batch(&lines, &AtomicBool::new(false), |done, total| {
+                reports.lock().unwrap().push((done, total))
+            })
+            .await;
+
+        assert_eq!(result.completed.len(), 4);
+        assert_eq!(backend.calls.load(Ordering::SeqCst), 4);
+        assert_eq!(
+            reports.into_inner().unwrap(),
+            vec![(1, 4), (2, 4), (3, 4), (4, 4)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cached_lines_reports_only_voiced_lines() {
+        let backend = Arc::new(CountingBackend::default());
+        let generator =
+            VoiceGenerator::new(Arc::new(Mutex::new(test_cache()))).with_backend(backend.clone());
+        let config = VoiceConfig::new("innkeeper");
+
+        let run = uuid::Uuid::new_v4();
+        let voiced = format!("Welcome back {run}, Lv. 3 hero");
+        generator.generate_voice(&voiced, &config).await.unwrap();
+
+        let cached = generator
+            .cached_lines(&[
+                (voiced, config.clone()),
+                (format!("Never said {run}"), config),
+            ])
+            .await;
+        assert_eq!(cached, vec![true, false]);
+        assert_eq!(backend.calls.load(Ordering::SeqCst), 1);
+    }
+
+    /// Backend whose "audio" is the voice id it was asked to use
+    struct VoiceIdBackend;
+
+    #[async_trait::async_trait]
+    impl TtsBackend for VoiceIdBackend {
+        async fn synthesize(&self, _text: &str, config: &VoiceConfig) -> Result<Vec<u8>> {
+            Ok(config.voice_id.as_bytes().to_vec())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_role_presets_fall_back_to_default() {
+        let path = std::env::temp_dir().join(format!("role_presets_{}.json", uuid::Uuid::new_v4()));
+        std::fs::write(
+            &path,
+            r#"{
+                "presets": [{"role": "villain", "config": {"voice_id": "grim", "emotion": "Angry"}}],
+                "default": {"voice_id": "storyteller"}
+            }"#,
+        )
+        .unwrap();
+        let roles = RoleVoices::from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(roles.config_for("Villain").emotion, VoiceEmotion::Angry);
+        let voices = VoiceGenerator::new(Arc::new(Mutex::new(test_cache())))
+            .with_backend(Arc::new(VoiceIdBackend));
+        let generator = RoleVoiceGenerator::new(voices, roles);
+
+        let villain = generator
+            .generate_for_role("villain", "You fool!")
+            .await
+            .unwrap();
+        assert_eq!(villain, b"grim");
+        let unknown = generator
+            .generate_for_role("shopkeeper", "Welcome!")
+            .await
+            .unwrap();
+        assert_eq!(unknown, b"storyteller");
+    }
+
+    #[tokio::test]
+    async fn test_role_lines_are_cached_like_explicit_ones() {
+        let backend = Arc::new(CountingBackend::default());
+        let voices =
+            VoiceGenerator::new(Arc::new(Mutex::new(test_cache()))).with_backend(backend.clone());
+        let roles = RoleVoices::default();
+        let config = roles.config_for("guard").clone();
+        let generator = RoleVoiceGenerator::new(voices, roles);
+
+        // Unique text per run so lines cached by a previous run don't count
+        let text = format!("Stop right there {}", uuid::Uuid::new_v4());
+        generator.generate_for_role("guard", &text).await.unwrap();
+        generator.generate_for_role("guard", &text).await.unwrap();
+        assert_eq!(backend.calls.load(Ordering::SeqCst), 1);
+
+        // Shares cache entries with `VoiceGenerator` for the same config
+        let key = generator.voices.cache_key(&text, &config).await;
+        let cache = generator.voices.cache.lock().await;
+        assert!(cache.get(&key).await.is_some());
+    }
+}
@@ -0,0 +1,177 @@
+//! Code generation for Bevy projects built on the bevy-ai-toolkit template
+
+use crate::wizard::config::ProjectConfig;
+
+/// Which bevy-ai-toolkit modules a game's enemies use
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AiNeeds {
+    pub state_machine: bool,
+    pub behavior_tree: bool,
+    pub utility: bool,
+    pub targeting: bool,
+}
+
+impl AiNeeds {
+    /// Infer AI needs from the project's combat design
+    pub fn from_config(config: &ProjectConfig) -> Self {
+        let Some(combat) = &config.features.combat_system else {
+            return Self::default();
+        };
+
+        Self {
+            state_machine: true,
+            // Turn-based enemies only act on their turn, so don't need a tree
+            behavior_tree: combat.combat_type != "TurnBased",
+            utility: !combat.special_abilities.is_empty(),
+            targeting: true,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// `main.rs` for a generated Bevy game, adding `AiToolkitPlugin` and an
+/// example enemy that uses only the modules in `needs`
+pub fn bevy_main_rs(needs: &AiNeeds) -> String {
+    let mut imports = vec![
+        "use bevy::prelude::*;".to_string(),
+        "use bevy_ai_toolkit::AiToolkitPlugin;".to_string(),
+    ];
+    if needs.state_machine {
+        imports.push(
+            "use bevy_ai_toolkit::state_machine::{StateMachine, StateMachineAppExt};".to_string(),
+        );
+    }
+    if needs.behavior_tree {
+        imports.push("use bevy_ai_toolkit::behavior_tree::BehaviorTree;".to_string());
+    }
+    if needs.behavior_tree || needs.utility {
+        imports.push("use bevy_ai_toolkit::navigation::*;".to_string());
+    }
+    if needs.utility {
+        imports.push("use bevy_ai_toolkit::utility_ai::{scorers::*, *};".to_string());
+    }
+    if needs.targeting {
+        imports.push("use bevy_ai_toolkit::targeting::{Target, Targetable, Vision};".to_string());
+    }
+
+    let mut code = imports.join("\n");
+    code.push_str("\n\n");
+
+    if needs.state_machine {
+        code.push_str(
+            "#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]\n\
+             enum EnemyState {\n    Idle,\n    Chasing,\n    Attacking,\n}\n\n",
+        );
+    }
+
+    code.push_str(
+        "fn main() {\n    App::new()\n        .add_plugins(DefaultPlugins)\n        .add_plugins(AiToolkitPlugin)\n",
+    );
+    if needs.state_machine {
+        code.push_str("        .add_state_machine::<EnemyState>()\n");
+    }
+    code.push_str("        .add_systems(Startup, setup)\n        .run();\n}\n\n");
+
+    code.push_str("fn setup(mut commands: Commands) {\n");
+    if needs.targeting {
+        code.push_str(
+            "    commands.spawn((SpatialBundle::default(), Targetable, Name::new(\"Player\")));\n\n",
+        );
+    }
+
+    let mut components = vec![
+        "        SpatialBundle {\n            transform: Transform::from_xyz(5.0, 0.0, 5.0),\n            ..default()\n        }"
+            .to_string(),
+    ];
+    if needs.state_machine {
+        components.push("        StateMachine::new(EnemyState::Idle)".to_string());
+    }
+    if needs.targeting {
+        components.push(
+            "        Vision {\n            range: 10.0,\n            field_of_view: 360.0,\n        }"
+                .to_string(),
+        );
+        components.push("        Target::default()".to_string());
+    }
+    if needs.behavior_tree {
+        components.push(
+            "        BehaviorTree {\n            root: Box::new(ChaseTarget),\n        }"
+                .to_string(),
+        );
+    }
+    if needs.utility {
+        let scorer = if needs.targeting {
+            "DistanceScorer { max_range: 10.0 }"
+        } else {
+            "Constant(0.5)"
+        };
+        components.push(format!(
+            "        UtilityAi {{\n            considerations: vec![Consideration::named(\n                \"chase\",\n                Box::new({scorer}),\n                Box::new(ChaseTargetAction),\n            )],\n        }}"
+        ));
+        // `run_utility_ai` only drives entities that have a runner
+        components.push("        UtilityRunner::default()".to_string());
+    }
+    components.push("        Name::new(\"Enemy\")".to_string());
+
+    code.push_str("    commands.spawn((\n");
+    for component in components {
+        code.push_str(&component);
+        code.push_str(",\n");
+    }
+    code.push_str("    ));\n}\n");
+    code
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_targeting_spec_references_vision_and_target() {
+        let code = bevy_main_rs(&AiNeeds {
+            targeting: true,
+            ..Default::default()
+        });
+
+        assert!(code.contains("AiToolkitPlugin"));
+        assert!(code.contains("Vision {"));
+        assert!(code.contains("Target::default()"));
+        assert!(!code.contains("BehaviorTree"));
+        assert!(!code.contains("StateMachine"));
+        assert!(!code.contains("UtilityAi"));
+    }
+
+    #[test]
+    fn test_utility_enemy_is_driven_by_runner() {
+        let code = bevy_main_rs(&AiNeeds {
+            utility: true,
+            targeting: true,
+            ..Default::default()
+        });
+
+        assert!(code.contains("Box::new(ChaseTargetAction)"));
+        assert!(code.contains("UtilityRunner::default()"));
+        // Plugins schedule targeting and the AI; the game adds no systems of its own
+        assert!(code.contains(".add_plugins(AiToolkitPlugin)"));
+        assert!(!code.contains("add_systems(Update"));
+    }
+
+    #[test]
+    fn test_needs_follow_combat_design() {
+        let mut config = ProjectConfig::default();
+        assert!(AiNeeds::from_config(&config).is_empty());
+
+        config.features.combat_system = Some(crate::wizard::config::CombatConfig {
+            combat_type: "TurnBased".to_string(),
+            damage_numbers: true,
+            combos: false,
+            special_abilities: vec!["Fireball".to_string()],
+        });
+        let needs = AiNeeds::from_config(&config);
+        assert!(needs.state_machine && needs.targeting && needs.utility);
+        assert!(!needs.behavior_tree);
+    }
+}
@@ -1,11 +1,17 @@
 use serde::{Deserialize, Serialize};
 
-use crate::wizard::config::ProjectConfig;
+use super::codegen::AiNeeds;
+use super::stages::{StageGraph, stage};
+use crate::wizard::config::{BlendWeights, DesignDecision, ProjectConfig};
 use futures::{Stream, StreamExt};
+use std::collections::HashMap;
 
 // Import from vintage_ai_client - updated to new API
 use vintage_ai_client::{
-    AiService, conversation::ConversationContext, game_types::GameConfig, text::TextConfig,
+    AiService,
+    conversation::{ConversationContext, ConversationManager, ImageAttachment},
+    game_types::GameConfig,
+    text::{StructuredUpdate, TextConfig},
 };
 
 /// Progress tracking for game generation
@@ -16,7 +22,7 @@ pub struct GenerationProgress {
     pub message: String,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum GenerationPhase {
     // Core phases
     Initializing,
@@ -44,6 +50,74 @@ pub enum GenerationPhase {
     ComposingMusic,
 }
 
+/// Temperature and nucleus sampling for a request
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SamplingParams {
+    pub temperature: f32,
+    pub top_p: f32,
+}
+
+impl SamplingParams {
+    pub const fn new(temperature: f32, top_p: f32) -> Self {
+        Self { temperature, top_p }
+    }
+}
+
+/// Per-stage sampling: creative stages run hot, structured ones cold, and
+/// anything without an override uses `default`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StageSamplingConfig {
+    pub default: SamplingParams,
+    pub overrides: HashMap<GenerationPhase, SamplingParams>,
+}
+
+impl Default for StageSamplingConfig {
+    fn default() -> Self {
+        let creative = SamplingParams::new(0.9, 0.95);
+        let structured = SamplingParams::new(0.0, 1.0);
+
+        let mut overrides = HashMap::new();
+        for phase in [
+            GenerationPhase::Design,
+            GenerationPhase::GameDesign,
+            GenerationPhase::DesigningCore,
+            GenerationPhase::WorldGeneration,
+            GenerationPhase::DialogWriting,
+            GenerationPhase::WritingDialogue,
+            GenerationPhase::MusicComposition,
+            GenerationPhase::ComposingMusic,
+        ] {
+            overrides.insert(phase, creative);
+        }
+        for phase in [
+            GenerationPhase::CodeGeneration,
+            GenerationPhase::AiSystems,
+            GenerationPhase::Integration,
+        ] {
+            overrides.insert(phase, structured);
+        }
+
+        Self {
+            default: SamplingParams::new(0.7, 1.0),
+            overrides,
+        }
+    }
+}
+
+impl StageSamplingConfig {
+    pub fn for_stage(&self, phase: GenerationPhase) -> SamplingParams {
+        self.overrides.get(&phase).copied().unwrap_or(self.default)
+    }
+
+    /// Override `config`'s sampling with this stage's values
+    pub fn apply(&self, phase: GenerationPhase, mut config: TextConfig) -> TextConfig {
+        let params = self.for_stage(phase);
+        config.temperature = params.temperature;
+        config.top_p = params.top_p;
+        config
+    }
+}
+
 /// Conversation message for UI display
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConversationMessage {
@@ -67,19 +141,180 @@ impl ConversationState {
     }
 }
 
+/// Designer persona used when the user hasn't customized the system prompt
+pub const DEFAULT_GAME_DESIGN_SYSTEM_PROMPT: &str = "You are an expert vintage game designer specializing in 8-bit and 16-bit era RPGs. \
+    Help design games that capture the charm of classics like Final Fantasy, Dragon Quest, \
+    and Chrono Trigger. Focus on pixel art aesthetics, chiptune music, and engaging gameplay.";
+
+/// Decisions that must be justified in `GenerateDesignRationale` mode
+pub const REQUIRED_RATIONALE_CATEGORIES: [&str; 2] = ["genre", "core_mechanic"];
+
+const DESIGN_RATIONALE_INSTRUCTIONS: &str = "When you propose a game design, end your reply with a single JSON object \
+    of the form {\"spec\": {...}, \"rationale\": [{\"category\": \"genre\", \"decision\": \"...\", \"rationale\": \"...\"}]}. \
+    Give one short rationale per decision, covering at least the genre and core_mechanic categories.";
+
+/// How design proposals should be returned
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DesignOutputMode {
+    #[default]
+    SpecOnly,
+    /// Return the structured spec plus a short justification per decision
+    GenerateDesignRationale,
+}
+
+impl DesignOutputMode {
+    /// Extend a system prompt with the instructions this mode needs
+    pub fn apply_to(self, system_prompt: &str) -> String {
+        match self {
+            DesignOutputMode::SpecOnly => system_prompt.to_string(),
+            DesignOutputMode::GenerateDesignRationale => {
+                format!("{system_prompt}\n\n{DESIGN_RATIONALE_INSTRUCTIONS}")
+            }
+        }
+    }
+}
+
+/// A structured design proposal and the reasoning behind it
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DesignProposal {
+    #[serde(default)]
+    pub spec: serde_json::Value,
+    #[serde(default)]
+    pub rationale: Vec<DesignDecision>,
+}
+
+impl DesignProposal {
+    /// Parse the JSON proposal embedded in a model response
+    pub fn from_response(response: &str) -> Option<Self> {
+        let start = response.find('{')?;
+        let end = response.rfind('}')?;
+        serde_json::from_str(response.get(start..=end)?).ok()
+    }
+
+    /// Required categories that have no non-empty rationale
+    pub fn missing_rationale(&self) -> Vec<&'static str> {
+        REQUIRED_RATIONALE_CATEGORIES
+            .into_iter()
+            .filter(|category| {
+                !self.rationale.iter().any(|decision| {
+                    decision.category == *category && !decision.rationale.trim().is_empty()
+                })
+            })
+            .collect()
+    }
+}
+
+/// Instructions for turning a finished conversation into a spec
+const SPEC_EXTRACTION_PROMPT: &str = "Extract the game design agreed in the conversation below. \
+    Respond with a JSON object of the form {\"title\": \"...\", \"genre\": \"...\", \
+    \"mechanics\": [\"...\"], \"visual_style\": \"...\"}. Use an empty string or list for \
+    anything that wasn't decided.";
+
+const TITLE_PROMPT: &str = "Give this game idea a title of 3 to 5 words. \
+    Respond with the title only, without quotes or punctuation.";
+
+const RECONCILE_PREMISES_PROMPT: &str = "Two game premises are being blended into one game. \
+    Write a single premise of 2 to 4 sentences that keeps the strongest ideas of both and \
+    resolves any contradictions between them, leaning toward the premise with more weight. \
+    Respond with the premise only.";
+
+/// Longest auto-generated session title, in words
+const MAX_TITLE_WORDS: usize = 5;
+
+/// First line of a model's title reply, without quotes or trailing
+/// punctuation and cut to `MAX_TITLE_WORDS`
+fn clean_title(reply: &str) -> String {
+    let line = reply
+        .lines()
+        .find(|line| !line.trim().is_empty())
+        .unwrap_or("");
+    line.trim()
+        .trim_matches(|c: char| matches!(c, '"' | '\'' | '*' | '#' | '.' | '!'))
+        .split_whitespace()
+        .take(MAX_TITLE_WORDS)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+const SUGGESTIONS_PROMPT: &str = "Suggest 3 short follow-up messages the user could send next \
+    to keep designing the game in the conversation below. Write them in the user's voice, \
+    one per line, under 12 words each, with no numbering.";
+
+/// Follow-up prompts offered after each reply
+pub const SUGGESTION_COUNT: usize = 3;
+
+/// Suggestions are a throwaway nicety, so they use a cheap model
+const SUGGESTION_MODEL: &str = "gpt-4o-mini";
+
+/// Up to `SUGGESTION_COUNT` suggestions from a model's reply, one per line,
+/// without list markers or quotes
+fn parse_suggestions(reply: &str) -> Vec<String> {
+    reply
+        .lines()
+        .map(|line| {
+            strip_list_marker(line.trim())
+                .trim()
+                .trim_matches('"')
+                .trim()
+        })
+        .filter(|line| !line.is_empty())
+        .take(SUGGESTION_COUNT)
+        .map(str::to_string)
+        .collect()
+}
+
+/// `line` without a leading "-", "*", "1." or "1)"
+fn strip_list_marker(line: &str) -> &str {
+    if let Some(rest) = line.strip_prefix(['-', '*']) {
+        return rest;
+    }
+    let number = line.trim_start_matches(|c: char| c.is_ascii_digit());
+    match number.strip_prefix(['.', ')']) {
+        Some(rest) if number.len() < line.len() => rest,
+        _ => line,
+    }
+}
+
+/// The core spec fields pulled out of a freeform conversation
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExtractedSpec {
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub genre: String,
+    #[serde(default)]
+    pub mechanics: Vec<String>,
+    #[serde(default)]
+    pub visual_style: String,
+}
+
 pub struct GameGenerator {
     ai_service: AiService,
+    // Shared so conversations started here can be continued by later calls
+    conversation_manager: ConversationManager,
     project_config: Option<ProjectConfig>,
+    // Language/engine label the generated code should use, e.g. "Rust (Bevy)"
+    target: Option<String>,
+    sampling: StageSamplingConfig,
 }
 
 impl GameGenerator {
     pub async fn new() -> anyhow::Result<Self> {
-        let ai_service = AiService::from_env()?;
+        Ok(Self::with_service(AiService::from_env()?))
+    }
+
+    /// Build a generator around an existing AI service, e.g. one that
+    /// reports to shared telemetry
+    pub fn with_service(ai_service: AiService) -> Self {
+        let conversation_manager = ai_service.conversation();
 
-        Ok(Self {
+        Self {
             ai_service,
+            conversation_manager,
             project_config: None,
-        })
+            target: None,
+            sampling: StageSamplingConfig::default(),
+        }
     }
 
     /// Set the project configuration (the "Bible" from wizard)
@@ -87,13 +322,41 @@ impl GameGenerator {
         self.project_config = Some(config);
     }
 
+    /// Set the language and engine generated code should target
+    pub fn set_target(&mut self, target: String) {
+        self.target = Some(target);
+    }
+
+    /// `main.rs` wiring in the bevy-ai-toolkit modules the project needs, for
+    /// Bevy targets with a project config
+    pub fn bevy_main_rs(&self) -> Option<String> {
+        let config = self.project_config.as_ref()?;
+        if !self.target.as_deref()?.contains("Bevy") {
+            return None;
+        }
+        Some(super::codegen::bevy_main_rs(&AiNeeds::from_config(config)))
+    }
+
+    /// Set the temperature/top_p used for each generation stage
+    pub fn set_stage_sampling(&mut self, sampling: StageSamplingConfig) {
+        self.sampling = sampling;
+    }
+
     /// Start a game design conversation
+    ///
+    /// `system_prompt` replaces the default designer persona; project context
+    /// is appended either way. An `attachment` sends the first message to a
+    /// vision-capable model.
     pub async fn start_game_design_conversation(
         &self,
         initial_prompt: &str,
+        system_prompt: Option<&str>,
+        attachment: Option<ImageAttachment>,
     ) -> anyhow::Result<(String, String)> {
         // Build system prompt based on project config
-        let system_prompt = self.build_game_design_system_prompt();
+        let system_prompt = self.build_game_design_system_prompt(
+            system_prompt.unwrap_or(DEFAULT_GAME_DESIGN_SYSTEM_PROMPT),
+        );
 
         // Create conversation context
         let context = ConversationContext {
@@ -109,15 +372,28 @@ impl GameGenerator {
         };
 
         // Start conversation using ConversationManager
-        let conversation_manager = self.ai_service.conversation();
+        let conversation_manager = &self.conversation_manager;
         let conversation_id = conversation_manager
             .start_conversation("Game Design".to_string(), context)
             .await?;
 
         // Send initial message
-        let response = conversation_manager
-            .send_message(&conversation_id, initial_prompt.to_string())
-            .await?;
+        let response = match attachment {
+            Some(attachment) => {
+                conversation_manager
+                    .send_message_with_image(
+                        &conversation_id,
+                        initial_prompt.to_string(),
+                        attachment,
+                    )
+                    .await?
+            }
+            None => {
+                conversation_manager
+                    .send_message(&conversation_id, initial_prompt.to_string())
+                    .await?
+            }
+        };
 
         Ok((conversation_id, response))
     }
@@ -129,7 +405,7 @@ impl GameGenerator {
         user_input: &str,
     ) -> anyhow::Result<(String, bool, Option<GameConfig>)> {
         // Get AI response using ConversationManager
-        let conversation_manager = self.ai_service.conversation();
+        let conversation_manager = &self.conversation_manager;
         let response = conversation_manager
             .send_message(conversation_id, user_input.to_string())
             .await?;
@@ -145,22 +421,140 @@ impl GameGenerator {
         &self,
         conversation_id: &str,
         user_input: &str,
+        attachment: Option<ImageAttachment>,
     ) -> anyhow::Result<impl Stream<Item = anyhow::Result<String>> + use<'_>> {
-        let conversation_manager = self.ai_service.conversation();
+        let conversation_manager = &self.conversation_manager;
         let conversation_id = conversation_id.to_string();
         let user_input = user_input.to_string();
 
         Ok(async_stream::try_stream! {
-            let stream = conversation_manager
-                .send_message_stream(&conversation_id, user_input)
-                .await?;
-            futures::pin_mut!(stream);
-            while let Some(item) = stream.next().await {
-                yield item?;
+            match attachment {
+                Some(attachment) => {
+                    let stream = conversation_manager
+                        .send_message_with_image_stream(&conversation_id, user_input, attachment)
+                        .await?;
+                    futures::pin_mut!(stream);
+                    while let Some(item) = stream.next().await {
+                        yield item?;
+                    }
+                }
+                None => {
+                    let stream = conversation_manager
+                        .send_message_stream(&conversation_id, user_input)
+                        .await?;
+                    futures::pin_mut!(stream);
+                    while let Some(item) = stream.next().await {
+                        yield item?;
+                    }
+                }
             }
         })
     }
 
+    /// Replace the system prompt for an ongoing conversation
+    pub async fn set_system_prompt(
+        &self,
+        conversation_id: &str,
+        system_prompt: &str,
+    ) -> anyhow::Result<()> {
+        self.conversation_manager
+            .set_system_prompt(
+                conversation_id,
+                self.build_game_design_system_prompt(system_prompt),
+            )
+            .await
+    }
+
+    /// Ask the model to summarize a conversation transcript as a structured spec
+    pub async fn extract_spec(&self, transcript: &str) -> anyhow::Result<ExtractedSpec> {
+        let prompt = format!("{SPEC_EXTRACTION_PROMPT}\n\nConversation:\n{transcript}");
+        let config = TextConfig {
+            temperature: 0.2,
+            ..TextConfig::default()
+        };
+        self.ai_service
+            .text()
+            .generate_structured(&prompt, config)
+            .await
+    }
+
+    /// Like `extract_spec`, but reports fields as the model writes them so
+    /// they can be shown before the whole spec has arrived
+    pub async fn extract_spec_stream(
+        &self,
+        transcript: &str,
+    ) -> anyhow::Result<
+        impl Stream<Item = anyhow::Result<StructuredUpdate<ExtractedSpec>>> + Send + use<>,
+    > {
+        let prompt = format!("{SPEC_EXTRACTION_PROMPT}\n\nConversation:\n{transcript}");
+        let config = TextConfig {
+            temperature: 0.2,
+            ..TextConfig::default()
+        };
+        self.ai_service
+            .text()
+            .generate_structured_stream(&prompt, config)
+            .await
+    }
+
+    /// Ask the model for a short title summarizing a game idea. The prompt
+    /// only depends on `first_message`, so the text cache answers repeats.
+    pub async fn suggest_title(&self, first_message: &str) -> anyhow::Result<String> {
+        let prompt = format!("{TITLE_PROMPT}\n\nGame idea:\n{first_message}");
+        let config = TextConfig {
+            temperature: 0.3,
+            max_tokens: 20,
+            ..TextConfig::default()
+        };
+        let title = clean_title(&self.ai_service.text().generate(&prompt, config).await?);
+        anyhow::ensure!(!title.is_empty(), "Model returned an empty title");
+        Ok(title)
+    }
+
+    /// Ask the model to merge the premises of two specs being blended with
+    /// `blend_specs` into one that doesn't contradict itself
+    pub async fn reconcile_premises(
+        &self,
+        first: &str,
+        second: &str,
+        weights: BlendWeights,
+    ) -> anyhow::Result<String> {
+        let share = weights.first_share() * 100.0;
+        let prompt = format!(
+            "{RECONCILE_PREMISES_PROMPT}\n\nPremise A ({share:.0}% weight):\n{first}\n\n\
+             Premise B ({:.0}% weight):\n{second}",
+            100.0 - share
+        );
+        let config = TextConfig {
+            temperature: 0.7,
+            max_tokens: 200,
+            ..TextConfig::default()
+        };
+        let premise = self.ai_service.text().generate(&prompt, config).await?;
+        let premise = premise.trim();
+        anyhow::ensure!(!premise.is_empty(), "Model returned an empty premise");
+        Ok(premise.to_string())
+    }
+
+    /// Ask a cheap model for follow-up messages the user might send next.
+    /// The text cache answers repeats of the same conversation.
+    pub async fn suggest_follow_ups(&self, transcript: &str) -> anyhow::Result<Vec<String>> {
+        let prompt = format!("{SUGGESTIONS_PROMPT}\n\nConversation:\n{transcript}");
+        let config = TextConfig {
+            model: SUGGESTION_MODEL.to_string(),
+            temperature: 0.7,
+            max_tokens: 100,
+            ..TextConfig::default()
+        };
+        let reply = self.ai_service.text().generate(&prompt, config).await?;
+        Ok(parse_suggestions(&reply))
+    }
+
+    /// Transcribe recorded voice input into prompt text
+    pub async fn transcribe_audio(&self, audio: Vec<u8>) -> anyhow::Result<String> {
+        self.ai_service.audio().transcribe_audio(audio).await
+    }
+
     /// Generate full game with progress tracking
     pub async fn generate_full_game<F>(
         &self,
@@ -170,8 +564,8 @@ impl GameGenerator {
     where
         F: Fn(GenerationProgress) + Send + 'static,
     {
-        let text_generator = self.ai_service.text();
-        let text_config = TextConfig::for_game_description();
+        let graph = StageGraph::default_pipeline();
+        let layers = graph.layers()?;
 
         // Initialize
         progress_callback(GenerationProgress {
@@ -180,71 +574,28 @@ impl GameGenerator {
             message: "Starting game generation...".to_string(),
         });
 
-        // Design core
-        progress_callback(GenerationProgress {
-            phase: GenerationPhase::DesigningCore,
-            progress: 0.1,
-            message: "Designing core game mechanics...".to_string(),
-        });
-
-        let core_prompt = format!(
-            "Generate the core game design document for: {}. Include mechanics, story outline, and character descriptions.",
-            config.name
-        );
-        let core_design = text_generator
-            .generate(&core_prompt, text_config.clone())
-            .await?;
-
-        // Generate assets descriptions
-        progress_callback(GenerationProgress {
-            phase: GenerationPhase::GeneratingAssets,
-            progress: 0.3,
-            message: "Generating asset descriptions...".to_string(),
-        });
-
-        let assets_prompt = format!(
-            "Based on this design: {}\n\nDescribe the visual assets needed: sprites, tilesets, UI elements.",
-            core_design.chars().take(1000).collect::<String>()
-        );
-        let _assets_desc = text_generator
-            .generate(&assets_prompt, text_config.clone())
-            .await?;
-
-        // Writing dialogue
-        progress_callback(GenerationProgress {
-            phase: GenerationPhase::WritingDialogue,
-            progress: 0.5,
-            message: "Writing character dialogue...".to_string(),
-        });
+        // Stages in a layer don't depend on each other, so run them together
+        let mut outputs: HashMap<String, String> = HashMap::new();
+        let mut started = 0;
+        for layer in layers {
+            for name in &layer {
+                let (phase, message) = stage_progress(name);
+                progress_callback(GenerationProgress {
+                    phase,
+                    progress: 0.1 + 0.8 * started as f32 / graph.len() as f32,
+                    message: message.to_string(),
+                });
+                started += 1;
+            }
 
-        let dialogue_config = TextConfig::for_dialogue();
-        let dialogue_prompt = format!(
-            "Write sample dialogue for key characters in: {}",
-            config.name
-        );
-        let _dialogue = text_generator
-            .generate(&dialogue_prompt, dialogue_config)
+            let results = futures::future::try_join_all(
+                layer
+                    .iter()
+                    .map(|name| self.run_stage(name, config, &outputs)),
+            )
             .await?;
-
-        // Composing music descriptions
-        progress_callback(GenerationProgress {
-            phase: GenerationPhase::ComposingMusic,
-            progress: 0.7,
-            message: "Describing musical themes...".to_string(),
-        });
-
-        let music_prompt = format!(
-            "Describe the musical themes and sound design for: {}",
-            config.name
-        );
-        let _music = text_generator.generate(&music_prompt, text_config).await?;
-
-        // Finalize
-        progress_callback(GenerationProgress {
-            phase: GenerationPhase::Finalizing,
-            progress: 0.9,
-            message: "Finalizing game package...".to_string(),
-        });
+            outputs.extend(layer.into_iter().zip(results));
+        }
 
         // Complete
         progress_callback(GenerationProgress {
@@ -253,7 +604,77 @@ impl GameGenerator {
             message: "Game generation complete!".to_string(),
         });
 
-        Ok(core_design)
+        Ok(outputs.remove(stage::DESIGN_CORE).unwrap_or_default())
+    }
+
+    /// Run one stage of `StageGraph::default_pipeline`, given the outputs of
+    /// the stages before it
+    async fn run_stage(
+        &self,
+        name: &str,
+        config: &GameConfig,
+        outputs: &HashMap<String, String>,
+    ) -> anyhow::Result<String> {
+        let text_generator = self.ai_service.text();
+        let text_config = TextConfig::for_game_description();
+        let sampling = &self.sampling;
+
+        match name {
+            stage::DESIGN_CORE => {
+                let core_prompt = format!(
+                    "Generate the core game design document for: {}. Include mechanics, story outline, and character descriptions.",
+                    config.name
+                );
+                text_generator
+                    .generate(
+                        &core_prompt,
+                        sampling.apply(GenerationPhase::DesigningCore, text_config),
+                    )
+                    .await
+            }
+            stage::ASSETS => {
+                let core_design = outputs
+                    .get(stage::DESIGN_CORE)
+                    .map(String::as_str)
+                    .unwrap_or_default();
+                let assets_prompt = format!(
+                    "Based on this design: {}\n\nDescribe the visual assets needed: sprites, tilesets, UI elements.",
+                    core_design.chars().take(1000).collect::<String>()
+                );
+                text_generator
+                    .generate(
+                        &assets_prompt,
+                        sampling.apply(GenerationPhase::AssetGeneration, text_config),
+                    )
+                    .await
+            }
+            stage::DIALOGUE => {
+                let dialogue_config =
+                    sampling.apply(GenerationPhase::DialogWriting, TextConfig::for_dialogue());
+                let dialogue_prompt = format!(
+                    "Write sample dialogue for key characters in: {}",
+                    config.name
+                );
+                text_generator
+                    .generate(&dialogue_prompt, dialogue_config)
+                    .await
+            }
+            stage::MUSIC => {
+                let music_prompt = format!(
+                    "Describe the musical themes and sound design for: {}",
+                    config.name
+                );
+                text_generator
+                    .generate(
+                        &music_prompt,
+                        sampling.apply(GenerationPhase::MusicComposition, text_config),
+                    )
+                    .await
+            }
+            stage::CODE_GEN => Ok(self.bevy_main_rs().unwrap_or_default()),
+            stage::FINALIZE => Ok(String::new()),
+            other => anyhow::bail!("No runner for generation stage `{other}`"),
+        }
     }
 
     /// Load a game template
@@ -274,7 +695,7 @@ impl GameGenerator {
         &self,
         conversation_id: &str,
     ) -> anyhow::Result<Vec<(String, String)>> {
-        let conversation_manager = self.ai_service.conversation();
+        let conversation_manager = &self.conversation_manager;
         let conversation = conversation_manager
             .get_conversation(conversation_id)
             .await?;
@@ -297,10 +718,13 @@ impl GameGenerator {
 
     // Helper methods
 
-    fn build_game_design_system_prompt(&self) -> String {
-        let base = "You are an expert vintage game designer specializing in 8-bit and 16-bit era RPGs. \
-                    Help design games that capture the charm of classics like Final Fantasy, Dragon Quest, \
-                    and Chrono Trigger. Focus on pixel art aesthetics, chiptune music, and engaging gameplay.";
+    fn build_game_design_system_prompt(&self, base: &str) -> String {
+        let base = match &self.target {
+            Some(target) => {
+                format!("{base}\n\nAll code and project structure must target {target}.")
+            }
+            None => base.to_string(),
+        };
 
         if let Some(config) = &self.project_config {
             let name = config
@@ -320,7 +744,7 @@ impl GameGenerator {
                 "{base}\n\nProject context:\n- Name: {name}\n- Description: {description}\n- Genre: {genre}\n- Tagline: {tagline}"
             )
         } else {
-            base.to_string()
+            base
         }
     }
 
@@ -342,3 +766,89 @@ impl GameGenerator {
         (false, None)
     }
 }
+
+/// Progress phase and message reported when a pipeline stage starts
+fn stage_progress(name: &str) -> (GenerationPhase, &'static str) {
+    match name {
+        stage::DESIGN_CORE => (
+            GenerationPhase::DesigningCore,
+            "Designing core game mechanics...",
+        ),
+        stage::ASSETS => (
+            GenerationPhase::GeneratingAssets,
+            "Generating asset descriptions...",
+        ),
+        stage::DIALOGUE => (
+            GenerationPhase::WritingDialogue,
+            "Writing character dialogue...",
+        ),
+        stage::MUSIC => (
+            GenerationPhase::ComposingMusic,
+            "Describing musical themes...",
+        ),
+        stage::CODE_GEN => (GenerationPhase::CodeGeneration, "Wiring in AI modules..."),
+        _ => (GenerationPhase::Finalizing, "Finalizing game package..."),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rationale_mode_response_covers_required_decisions() {
+        let prompt = DesignOutputMode::GenerateDesignRationale.apply_to("Be helpful.");
+        assert!(prompt.starts_with("Be helpful."));
+        assert!(prompt.contains("rationale"));
+        assert_eq!(
+            DesignOutputMode::SpecOnly.apply_to("Be helpful."),
+            "Be helpful."
+        );
+
+        let response = r#"Here's a first pass at your design.
+
+{"spec": {"genre": "Action RPG", "core_mechanic": "Time-rewind combat"},
+ "rationale": [
+   {"category": "genre", "decision": "Action RPG", "rationale": "You asked for fast, skill-based fights."},
+   {"category": "core_mechanic", "decision": "Time-rewind combat", "rationale": "Fits the clockwork setting."}
+ ]}"#;
+
+        let proposal = DesignProposal::from_response(response).unwrap();
+        assert_eq!(proposal.spec["genre"], "Action RPG");
+        assert!(proposal.missing_rationale().is_empty());
+
+        let partial = DesignProposal::from_response(
+            r#"{"spec": {}, "rationale": [{"category": "genre", "decision": "Puzzle", "rationale": " "}]}"#,
+        )
+        .unwrap();
+        assert_eq!(partial.missing_rationale(), vec!["genre", "core_mechanic"]);
+    }
+
+    #[test]
+    fn test_streamed_spec_fills_in_progressively() {
+        use vintage_ai_client::partial_json::PartialJson;
+
+        let mut json = PartialJson::new();
+        json.push(
+            r#"```json
+{"title": "Skyvault", "genre": "Stealth rogue"#,
+        );
+        let first: ExtractedSpec = json.partial().unwrap();
+        assert_eq!(first.title, "Skyvault");
+        assert_eq!(first.genre, "Stealth rogue");
+        assert!(first.mechanics.is_empty());
+        assert!(json.finish::<ExtractedSpec>().is_err());
+
+        json.push(
+            r#"like", "mechanics": ["Grappling hooks"], "visual_style": "Pixel art"}
+```"#,
+        );
+        let second: ExtractedSpec = json.partial().unwrap();
+        assert_eq!(second.genre, "Stealth roguelike");
+        assert_eq!(second.mechanics, vec!["Grappling hooks"]);
+
+        let spec: ExtractedSpec = json.finish().unwrap();
+        assert_eq!(spec, second);
+        assert_eq!(spec.visual_style, "Pixel art");
+    }
+}
@@ -1,15 +1,20 @@
 // Module declarations for metaprompts
+pub mod codegen;
 pub mod conversation;
 pub mod generator;
+pub mod stages;
 pub mod types;
 pub mod validation;
 pub mod watcher;
 
 // Re-exports for convenience
+pub use codegen::{AiNeeds, bevy_main_rs};
 pub use conversation::{SimpleMessage, WizardConversationState};
 pub use generator::{
     ConversationMessage, ConversationState, GameGenerator, GenerationPhase, GenerationProgress,
+    SamplingParams, StageSamplingConfig,
 };
+pub use stages::{StageGraph, StageGraphError, StageNode};
 pub use types::{ArtStyle, ColorPalette, GameConfig, WorldConfig};
 pub use validation::{PromptValidator, ValidationResult};
 pub use watcher::{GenerationQueue, PromptWatcher};
@@ -0,0 +1,207 @@
+//! Generation stages as a dependency graph.
+//!
+//! Each stage names the stages it needs. `StageGraph::layers` sorts them so
+//! every stage comes after its prerequisites, grouping stages that don't
+//! depend on each other so they can run concurrently.
+
+use std::collections::{HashMap, HashSet};
+
+/// Names of the stages in `StageGraph::default_pipeline`
+pub mod stage {
+    pub const DESIGN_CORE: &str = "design_core";
+    pub const ASSETS: &str = "assets";
+    pub const DIALOGUE: &str = "dialogue";
+    pub const MUSIC: &str = "music";
+    pub const CODE_GEN: &str = "code_gen";
+    pub const FINALIZE: &str = "finalize";
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StageNode {
+    pub name: String,
+    pub depends_on: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum StageGraphError {
+    #[error("stage `{0}` is declared more than once")]
+    Duplicate(String),
+
+    #[error("stage `{stage}` depends on unknown stage `{dependency}`")]
+    UnknownDependency { stage: String, dependency: String },
+
+    #[error("stage dependency cycle: {}", .0.join(" -> "))]
+    Cycle(Vec<String>),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct StageGraph {
+    stages: Vec<StageNode>,
+}
+
+impl StageGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The stages `GameGenerator::generate_full_game` runs
+    pub fn default_pipeline() -> Self {
+        let mut graph = Self::new();
+        graph
+            .add_stage(stage::DESIGN_CORE, &[])
+            .add_stage(stage::ASSETS, &[stage::DESIGN_CORE])
+            .add_stage(stage::DIALOGUE, &[stage::DESIGN_CORE])
+            .add_stage(stage::MUSIC, &[stage::DESIGN_CORE])
+            .add_stage(stage::CODE_GEN, &[stage::DESIGN_CORE])
+            .add_stage(
+                stage::FINALIZE,
+                &[
+                    stage::ASSETS,
+                    stage::DIALOGUE,
+                    stage::MUSIC,
+                    stage::CODE_GEN,
+                ],
+            );
+        graph
+    }
+
+    pub fn add_stage(&mut self, name: impl Into<String>, depends_on: &[&str]) -> &mut Self {
+        self.stages.push(StageNode {
+            name: name.into(),
+            depends_on: depends_on.iter().map(|s| s.to_string()).collect(),
+        });
+        self
+    }
+
+    pub fn stages(&self) -> &[StageNode] {
+        &self.stages
+    }
+
+    pub fn len(&self) -> usize {
+        self.stages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stages.is_empty()
+    }
+
+    /// Stages grouped into layers: every stage's dependencies are in earlier
+    /// layers, so the stages within a layer can run in parallel. Stages keep
+    /// their declaration order within a layer.
+    pub fn layers(&self) -> Result<Vec<Vec<String>>, StageGraphError> {
+        let mut names = HashSet::new();
+        for node in &self.stages {
+            if !names.insert(node.name.as_str()) {
+                return Err(StageGraphError::Duplicate(node.name.clone()));
+            }
+        }
+        for node in &self.stages {
+            if let Some(dependency) = node
+                .depends_on
+                .iter()
+                .find(|dependency| !names.contains(dependency.as_str()))
+            {
+                return Err(StageGraphError::UnknownDependency {
+                    stage: node.name.clone(),
+                    dependency: dependency.clone(),
+                });
+            }
+        }
+
+        let mut done: HashSet<&str> = HashSet::new();
+        let mut remaining: Vec<&StageNode> = self.stages.iter().collect();
+        let mut layers = Vec::new();
+
+        while !remaining.is_empty() {
+            let (ready, blocked): (Vec<&StageNode>, Vec<&StageNode>) =
+                remaining.into_iter().partition(|node| {
+                    node.depends_on
+                        .iter()
+                        .all(|dependency| done.contains(dependency.as_str()))
+                });
+            if ready.is_empty() {
+                return Err(StageGraphError::Cycle(find_cycle(&blocked)));
+            }
+
+            done.extend(ready.iter().map(|node| node.name.as_str()));
+            layers.push(ready.iter().map(|node| node.name.clone()).collect());
+            remaining = blocked;
+        }
+
+        Ok(layers)
+    }
+
+    /// One valid run order, prerequisites first
+    pub fn order(&self) -> Result<Vec<String>, StageGraphError> {
+        Ok(self.layers()?.into_iter().flatten().collect())
+    }
+}
+
+/// Follow unmet dependencies through the stages left after sorting until
+/// one repeats. Every one of them waits on another, so this always loops.
+fn find_cycle(blocked: &[&StageNode]) -> Vec<String> {
+    let by_name: HashMap<&str, &StageNode> = blocked
+        .iter()
+        .map(|node| (node.name.as_str(), *node))
+        .collect();
+
+    let mut path: Vec<&str> = Vec::new();
+    let mut current = blocked[0];
+    loop {
+        if let Some(start) = path.iter().position(|name| *name == current.name) {
+            let mut cycle: Vec<String> = path[start..].iter().map(|s| s.to_string()).collect();
+            cycle.push(current.name.clone());
+            return cycle;
+        }
+        path.push(&current.name);
+        current = current
+            .depends_on
+            .iter()
+            .find_map(|dependency| by_name.get(dependency.as_str()).copied())
+            .expect("blocked stages always wait on another blocked stage");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dependents_run_after_prerequisites_and_cycles_error() {
+        let mut graph = StageGraph::new();
+        // Declared before its prerequisite on purpose
+        graph.add_stage("packaging", &[stage::CODE_GEN]);
+        for node in StageGraph::default_pipeline().stages() {
+            let depends_on: Vec<&str> = node.depends_on.iter().map(String::as_str).collect();
+            graph.add_stage(&node.name, &depends_on);
+        }
+
+        let layers = graph.layers().unwrap();
+        let layer_of = |name: &str| {
+            layers
+                .iter()
+                .position(|layer| layer.iter().any(|s| s == name))
+        };
+        assert!(layer_of("packaging").unwrap() > layer_of(stage::CODE_GEN).unwrap());
+        // Independent stages share a layer
+        assert_eq!(layer_of(stage::ASSETS), layer_of(stage::MUSIC));
+
+        graph.add_stage("music_mix", &["packaging"]);
+        graph
+            .stages
+            .iter_mut()
+            .find(|node| node.name == stage::CODE_GEN)
+            .unwrap()
+            .depends_on
+            .push("music_mix".to_string());
+        assert_eq!(
+            graph.order().unwrap_err(),
+            StageGraphError::Cycle(vec![
+                "packaging".to_string(),
+                "code_gen".to_string(),
+                "music_mix".to_string(),
+                "packaging".to_string(),
+            ])
+        );
+    }
+}
@@ -43,8 +43,11 @@ pub struct ProjectConfig {
     pub game_specification: Option<GameSpecification>,
 }
 
+/// Newest project manifest version this build can read
+pub const MANIFEST_VERSION: &str = "0.1.0";
+
 fn default_version() -> String {
-    "0.1.0".to_string()
+    MANIFEST_VERSION.to_string()
 }
 
 /// Simplified game specification for list mode display
@@ -206,6 +209,9 @@ pub struct AiContext {
     pub world_lore: Option<String>,
     pub character_concepts: Vec<CharacterConcept>,
     pub level_themes: Vec<LevelTheme>,
+    /// Custom system prompt for the design conversation, if the user edited it
+    #[serde(default)]
+    pub system_prompt: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -221,6 +227,7 @@ pub struct DesignDecision {
     pub category: String,
     pub decision: String,
     pub rationale: String,
+    #[serde(default)]
     pub alternatives_considered: Vec<String>,
 }
 
@@ -246,6 +253,9 @@ pub struct WizardState {
     pub completed_steps: Vec<String>,
     pub is_complete: bool,
     pub conversation_started: bool,
+    /// "guided" or "freeform", recorded when the mode is chosen
+    #[serde(default)]
+    pub mode: Option<String>,
 }
 
 impl ProjectConfig {
@@ -398,6 +408,18 @@ impl ConfigManager {
         })
     }
 
+    /// Open a previously generated project directory to continue editing it
+    pub fn open_project(project_dir: &Path) -> Result<Self> {
+        let config_path = project_dir.join("project.toml");
+        let config = ProjectConfig::load(&config_path)?;
+        check_manifest_version(&config.version)?;
+
+        Ok(Self {
+            config_path,
+            config,
+        })
+    }
+
     /// Set the target programming language
     pub fn set_language(&mut self, language: &str) -> Result<()> {
         // Store language in technical settings
@@ -414,6 +436,7 @@ impl ConfigManager {
     pub fn set_wizard_mode(&mut self, mode: &str) -> Result<()> {
         // Store mode in wizard state
         self.config.wizard_state.current_step = mode.to_string();
+        self.config.wizard_state.mode = Some(mode.to_string());
         self.config
             .wizard_state
             .completed_steps
@@ -428,13 +451,7 @@ impl ConfigManager {
         self.config.version = self.config.metadata.version.clone();
 
         // Create game specification summary
-        self.config.game_specification = Some(GameSpecification {
-            title: self.config.basic_info.name.clone(),
-            genre: self.config.basic_info.genre.clone(),
-            theme: self.config.basic_info.tagline.clone(),
-            art_style: self.config.visual_style.color_mood.clone(),
-            key_features: self.collect_key_features(),
-        });
+        self.config.game_specification = Some(self.game_specification());
 
         self.config.metadata.last_modified = chrono::Utc::now();
 
@@ -457,6 +474,17 @@ impl ConfigManager {
         self.config_path.exists()
     }
 
+    /// Summary of the game as currently configured, including unsaved edits
+    pub fn game_specification(&self) -> GameSpecification {
+        GameSpecification {
+            title: self.config.basic_info.name.clone(),
+            genre: self.config.basic_info.genre.clone(),
+            theme: self.config.basic_info.tagline.clone(),
+            art_style: self.config.visual_style.color_mood.clone(),
+            key_features: self.collect_key_features(),
+        }
+    }
+
     fn collect_key_features(&self) -> Vec<String> {
         let mut features = Vec::new();
 
@@ -502,3 +530,296 @@ impl ProjectConfig {
         Ok(config)
     }
 }
+
+/// Accept manifests from the same major version that aren't newer than this build
+fn check_manifest_version(version: &str) -> Result<()> {
+    fn major_minor(version: &str) -> Option<(u64, u64)> {
+        let mut parts = version.split('.');
+        Some((parts.next()?.parse().ok()?, parts.next()?.parse().ok()?))
+    }
+
+    let (Some(found), Some(supported)) = (major_minor(version), major_minor(MANIFEST_VERSION))
+    else {
+        anyhow::bail!("Invalid project manifest version '{version}'");
+    };
+    if found.0 != supported.0 || found.1 > supported.1 {
+        anyhow::bail!(
+            "Project manifest version {version} is not supported (expected {MANIFEST_VERSION} or older)"
+        );
+    }
+    Ok(())
+}
+
+/// How much each spec counts when blending two projects
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BlendWeights {
+    pub first: f32,
+    pub second: f32,
+}
+
+impl Default for BlendWeights {
+    fn default() -> Self {
+        Self {
+            first: 0.5,
+            second: 0.5,
+        }
+    }
+}
+
+impl BlendWeights {
+    /// Share of the first spec, from 0.0 to 1.0
+    pub fn first_share(&self) -> f32 {
+        let total = self.first + self.second;
+        if total > 0.0 { self.first / total } else { 0.5 }
+    }
+
+    fn mix(&self, first: f32, second: f32) -> f32 {
+        let share = self.first_share();
+        first * share + second * (1.0 - share)
+    }
+}
+
+/// Merge two projects into a new one.
+///
+/// Lists such as mechanics, references and effects are unioned without
+/// duplicates, numeric settings are averaged by weight, features either
+/// spec has are kept and single-choice fields come from the heavier spec.
+/// Differing premises are both kept in the description, heavier first,
+/// for `GameGenerator::reconcile_premises` to merge into one.
+pub fn blend_specs(a: &ProjectConfig, b: &ProjectConfig, weights: BlendWeights) -> ProjectConfig {
+    let (major, minor) = if weights.first_share() >= 0.5 {
+        (a, b)
+    } else {
+        (b, a)
+    };
+
+    let basic_info = BasicInfo {
+        name: blend_titles(&major.basic_info.name, &minor.basic_info.name),
+        tagline: major.basic_info.tagline.clone(),
+        description: blend_text(&major.basic_info.description, &minor.basic_info.description),
+        genre: blend_text_with(&major.basic_info.genre, &minor.basic_info.genre, " / "),
+        target_audience: major.basic_info.target_audience.clone(),
+        inspiration_notes: format!(
+            "Blend of {} and {}",
+            display_name(&a.basic_info.name),
+            display_name(&b.basic_info.name)
+        ),
+    };
+
+    let gameplay = GameplayDesign {
+        core_mechanics: union(
+            &major.gameplay.core_mechanics,
+            &minor.gameplay.core_mechanics,
+        ),
+        victory_conditions: union(
+            &major.gameplay.victory_conditions,
+            &minor.gameplay.victory_conditions,
+        ),
+        unique_mechanics: union(
+            &major.gameplay.unique_mechanics,
+            &minor.gameplay.unique_mechanics,
+        ),
+        difficulty_curve: DifficultyCurve {
+            starting_difficulty: weights.mix(
+                a.gameplay.difficulty_curve.starting_difficulty,
+                b.gameplay.difficulty_curve.starting_difficulty,
+            ),
+            ramp_speed: weights.mix(
+                a.gameplay.difficulty_curve.ramp_speed,
+                b.gameplay.difficulty_curve.ramp_speed,
+            ),
+            max_difficulty: weights.mix(
+                a.gameplay.difficulty_curve.max_difficulty,
+                b.gameplay.difficulty_curve.max_difficulty,
+            ),
+            adaptive: a.gameplay.difficulty_curve.adaptive || b.gameplay.difficulty_curve.adaptive,
+        },
+        ..major.gameplay.clone()
+    };
+
+    let visual_style = VisualStyle {
+        reference_games: union(
+            &major.visual_style.reference_games,
+            &minor.visual_style.reference_games,
+        ),
+        special_effects: union(
+            &major.visual_style.special_effects,
+            &minor.visual_style.special_effects,
+        ),
+        sprite_size: weights
+            .mix(
+                a.visual_style.sprite_size as f32,
+                b.visual_style.sprite_size as f32,
+            )
+            .round() as u32,
+        ..major.visual_style.clone()
+    };
+
+    let mut custom_features = major.features.custom_features.clone();
+    for feature in &minor.features.custom_features {
+        if !custom_features
+            .iter()
+            .any(|existing| existing.name.eq_ignore_ascii_case(&feature.name))
+        {
+            custom_features.push(feature.clone());
+        }
+    }
+    let features = Features {
+        combat_system: major
+            .features
+            .combat_system
+            .clone()
+            .or_else(|| minor.features.combat_system.clone()),
+        inventory_system: major
+            .features
+            .inventory_system
+            .clone()
+            .or_else(|| minor.features.inventory_system.clone()),
+        dialogue_system: major
+            .features
+            .dialogue_system
+            .clone()
+            .or_else(|| minor.features.dialogue_system.clone()),
+        crafting_system: major
+            .features
+            .crafting_system
+            .clone()
+            .or_else(|| minor.features.crafting_system.clone()),
+        save_system: a.features.save_system || b.features.save_system,
+        day_night_cycle: a.features.day_night_cycle || b.features.day_night_cycle,
+        weather_effects: a.features.weather_effects || b.features.weather_effects,
+        minimap: a.features.minimap || b.features.minimap,
+        achievements: a.features.achievements || b.features.achievements,
+        custom_features,
+    };
+
+    let technical = TechnicalSettings {
+        target_platforms: union(
+            &major.technical.target_platforms,
+            &minor.technical.target_platforms,
+        ),
+        ..major.technical.clone()
+    };
+
+    let decision = basic_info.inspiration_notes.clone();
+    let mut blended = ProjectConfig {
+        name: Some(basic_info.name.clone()),
+        description: Some(basic_info.description.clone()),
+        basic_info,
+        gameplay,
+        visual_style,
+        features,
+        technical,
+        ..ProjectConfig::default()
+    };
+    blended.add_design_decision(
+        "blend",
+        &decision,
+        &format!(
+            "Weighted {:.0}% / {:.0}%",
+            weights.first_share() * 100.0,
+            (1.0 - weights.first_share()) * 100.0
+        ),
+    );
+    blended
+}
+
+/// "Star Quest × Dungeon Delve", or the one title if the other is empty or the same
+fn blend_titles(major: &str, minor: &str) -> String {
+    blend_text_with(major, minor, " × ")
+}
+
+fn blend_text(major: &str, minor: &str) -> String {
+    blend_text_with(major, minor, "\n\n")
+}
+
+/// Both values joined by `separator`, dropping empty or repeated ones
+fn blend_text_with(major: &str, minor: &str, separator: &str) -> String {
+    let (major, minor) = (major.trim(), minor.trim());
+    if minor.is_empty() || major.eq_ignore_ascii_case(minor) {
+        major.to_string()
+    } else if major.is_empty() {
+        minor.to_string()
+    } else {
+        format!("{major}{separator}{minor}")
+    }
+}
+
+fn display_name(name: &str) -> &str {
+    if name.trim().is_empty() {
+        "an untitled project"
+    } else {
+        name.trim()
+    }
+}
+
+/// `first` followed by the entries of `second` it doesn't already have,
+/// ignoring case
+fn union(first: &[String], second: &[String]) -> Vec<String> {
+    let mut merged: Vec<String> = Vec::with_capacity(first.len() + second.len());
+    for item in first.iter().chain(second) {
+        let item = item.trim();
+        if !item.is_empty() && !merged.iter().any(|seen| seen.eq_ignore_ascii_case(item)) {
+            merged.push(item.to_string());
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(name: &str, genre: &str, mechanics: &[&str], sprite_size: u32) -> ProjectConfig {
+        let mut config = ProjectConfig::default();
+        config.basic_info.name = name.to_string();
+        config.basic_info.genre = genre.to_string();
+        config.gameplay.core_mechanics = mechanics.iter().map(|m| m.to_string()).collect();
+        config.visual_style.sprite_size = sprite_size;
+        config
+    }
+
+    #[test]
+    fn test_blend_unions_mechanics_and_combines_titles() {
+        let mut space = spec("Star Quest", "Action RPG", &["Combat", "Space travel"], 16);
+        space.basic_info.description = "Pilots race across a dying galaxy.".to_string();
+        let mut dungeon = spec("Dungeon Delve", "action rpg", &["combat", "Looting"], 32);
+        dungeon.basic_info.description =
+            "Heroes dig for treasure beneath a cursed town.".to_string();
+        dungeon.features.save_system = true;
+
+        let blended = blend_specs(&space, &dungeon, BlendWeights::default());
+
+        assert_eq!(blended.basic_info.name, "Star Quest × Dungeon Delve");
+        assert_eq!(blended.basic_info.genre, "Action RPG");
+        assert_eq!(
+            blended.gameplay.core_mechanics,
+            vec!["Combat", "Space travel", "Looting"]
+        );
+        assert_eq!(blended.visual_style.sprite_size, 24);
+        assert!(blended.features.save_system);
+        assert!(blended.basic_info.description.contains("dying galaxy"));
+        assert!(blended.basic_info.description.contains("cursed town"));
+
+        // The heavier spec leads the title and supplies single choices
+        let leaning = blend_specs(
+            &space,
+            &dungeon,
+            BlendWeights {
+                first: 1.0,
+                second: 3.0,
+            },
+        );
+        assert_eq!(leaning.basic_info.name, "Dungeon Delve × Star Quest");
+        assert_eq!(leaning.visual_style.sprite_size, 28);
+
+        // An untitled spec doesn't leave a dangling separator
+        let untitled = blend_specs(
+            &space,
+            &spec("", "Puzzle", &[], 16),
+            BlendWeights::default(),
+        );
+        assert_eq!(untitled.basic_info.name, "Star Quest");
+        assert_eq!(untitled.basic_info.genre, "Action RPG / Puzzle");
+    }
+}
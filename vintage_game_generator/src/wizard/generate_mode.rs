@@ -2,11 +2,12 @@ use crate::wizard::pipeline::GenerationPipeline;
 use crate::wizard::{
     AppDirectories, SwitchModeEvent,
     config::ConfigManager,
-    state::{AppState, LogLevel, WizardStep},
+    state::{AppState, LogLevel, SPEC_FIELDS, WizardStep},
     steps::{
-        draw_welcome_step,
+        WelcomeAction, draw_language_step, draw_welcome_step,
         freeform::{
-            ConversationStream, FreeformModeState, render_freeform_mode, setup_freeform_mode,
+            AiAvailability, ConversationSessions, ConversationState, ConversationStream,
+            FreeformModeState, render_freeform_mode, setup_freeform_mode,
         },
         guided::{GuidedModeState, render_guided_mode, setup_guided_mode},
     },
@@ -30,16 +31,107 @@ pub struct LogEntry {
     pub message: String,
 }
 
+/// Ctrl+Z undoes the last field edit; Ctrl+Y or Ctrl+Shift+Z redoes it
+fn handle_undo_shortcuts(ctx: &egui::Context, app_state: &mut AppState) {
+    let undo = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Z);
+    let redo = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Y);
+    let redo_shift = egui::KeyboardShortcut::new(
+        egui::Modifiers::COMMAND | egui::Modifiers::SHIFT,
+        egui::Key::Z,
+    );
+
+    // Check the shifted shortcut first; consuming Ctrl+Z would also match it
+    if ctx.input_mut(|i| i.consume_shortcut(&redo_shift) || i.consume_shortcut(&redo)) {
+        app_state.redo();
+    } else if ctx.input_mut(|i| i.consume_shortcut(&undo)) {
+        app_state.undo();
+    }
+}
+
+/// Single-line editor for a form field. Typing is kept as a draft, with the
+/// text edit's own undo, until the field loses focus; only then is it
+/// recorded with `set_field` as one undoable edit.
+fn field_editor(ui: &mut egui::Ui, app_state: &mut AppState, field: &str) {
+    let draft_id = egui::Id::new(("field_draft", field));
+    let saved = app_state.field(field).unwrap_or_default();
+    let mut draft = ui
+        .data_mut(|d| d.get_temp::<String>(draft_id))
+        .unwrap_or_else(|| saved.clone());
+
+    let response = ui.text_edit_singleline(&mut draft);
+    if response.lost_focus() {
+        ui.data_mut(|d| d.remove::<String>(draft_id));
+        if draft != saved {
+            app_state.set_field(field, draft);
+        }
+    } else if response.has_focus() {
+        ui.data_mut(|d| d.insert_temp(draft_id, draft));
+    }
+}
+
+/// Ask the model to merge the premises of a just-blended project
+fn request_premise_reconciliation(app_state: &mut AppState, pipeline: &GenerationPipeline) {
+    let Some((first, second, weights)) = app_state.premises_to_reconcile.take() else {
+        return;
+    };
+
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    app_state.premise_rx = Some(rx);
+
+    let generator_arc = pipeline.generator.clone();
+    pipeline.runtime.spawn(async move {
+        let generator_lock = generator_arc.lock().await;
+        let result = match generator_lock.as_ref() {
+            Some(generator) => generator
+                .reconcile_premises(&first, &second, weights)
+                .await
+                .map_err(|e| e.to_string()),
+            None => Err("AI Generator not initialized".to_string()),
+        };
+        let _ = tx.send(result);
+    });
+}
+
+/// Save the reconciled premise once it arrives. Without one the blend keeps
+/// both premises in its description.
+fn poll_premise_reconciliation(app_state: &mut AppState) {
+    let Some(rx) = app_state.premise_rx.as_mut() else {
+        return;
+    };
+
+    let result = match rx.try_recv() {
+        Ok(result) => result,
+        Err(tokio::sync::oneshot::error::TryRecvError::Empty) => return,
+        Err(tokio::sync::oneshot::error::TryRecvError::Closed) => {
+            Err("Premise request was dropped".to_string())
+        }
+    };
+    app_state.premise_rx = None;
+
+    match result
+        .map_err(anyhow::Error::msg)
+        .and_then(|premise| app_state.apply_reconciled_premise(premise))
+    {
+        Ok(()) => app_state.add_log(LogLevel::Success, "Merged the blended premises".to_string()),
+        Err(e) => app_state.add_log(
+            LogLevel::Warning,
+            format!("Kept both premises of the blend: {e}"),
+        ),
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn draw_generate_ui(
     mut contexts: EguiContexts,
     mut app_state: ResMut<AppState>,
     directories: Res<AppDirectories>,
-    pipeline: Res<GenerationPipeline>,
+    mut pipeline: ResMut<GenerationPipeline>,
     _switch_mode_events: MessageWriter<SwitchModeEvent>,
     guided_state: Option<ResMut<GuidedModeState>>,
     freeform_state: Option<ResMut<FreeformModeState>>,
     stream_res: Option<ResMut<ConversationStream>>,
+    sessions: Option<ResMut<ConversationSessions>>,
+    availability: Option<Res<AiAvailability>>,
     commands: Commands,
     mut exit_events: MessageWriter<AppExit>,
 ) {
@@ -51,6 +143,17 @@ pub fn draw_generate_ui(
             // EguiContext not ready yet - this happens in the first frame
             // Don't log as error since this is expected behavior
             trace!("EguiContext not ready yet, skipping frame");
+
+            // Freeform mode gives up visibly if the context stays away
+            if app_state.wizard_step == WizardStep::FreeformMode
+                && let Some(mut freeform_state) = freeform_state
+                && freeform_state.record_context_failure()
+            {
+                error!(
+                    "egui context unavailable for {} frames in freeform mode, showing recovery screen",
+                    freeform_state.context_failures
+                );
+            }
             return;
         }
     };
@@ -66,6 +169,9 @@ pub fn draw_generate_ui(
         app_state.set_config_manager(config_manager);
     }
 
+    request_premise_reconciliation(&mut app_state, &pipeline);
+    poll_premise_reconciliation(&mut app_state);
+
     // Handle exit dialog
     if app_state.show_exit_dialog {
         egui::Window::new("Exit Confirmation")
@@ -102,13 +208,77 @@ pub fn draw_generate_ui(
     match &app_state.wizard_step {
         WizardStep::Welcome => {
             debug!("Drawing welcome step");
+            let projects = directories.list_project_dirs().unwrap_or_default();
+            let mut loaded_project = false;
             draw_wizard_frame_with_state(ctx, &mut app_state, |ui, state| {
-                let wizard_mode_action = draw_welcome_step(ui, &mut state.config_manager);
+                if let Some(error) = &state.error_message {
+                    ui.colored_label(egui::Color32::from_rgb(255, 100, 100), error);
+                }
+                let wizard_mode_action =
+                    draw_welcome_step(ui, &mut state.config_manager, &projects);
                 if let Some(action) = wizard_mode_action {
                     info!("Welcome action selected: {:?}", action);
+                    loaded_project = matches!(action, WelcomeAction::LoadProject(_));
                     state.set_wizard_mode(action);
                 }
             });
+            // A loaded project may already have a language chosen
+            if loaded_project && let Some(target) = app_state.target() {
+                pipeline.set_target(target);
+            }
+        }
+        WizardStep::SelectLanguage => {
+            debug!("Drawing language step");
+            let mut target = None;
+            draw_wizard_frame_with_state(ctx, &mut app_state, |ui, state| {
+                if let Some(error) = &state.error_message {
+                    ui.colored_label(egui::Color32::from_rgb(255, 100, 100), error);
+                }
+                if let Some(choice) = draw_language_step(ui, &mut state.config_manager) {
+                    info!("Language selected: {:?}", choice);
+                    target = state.set_language(choice).ok();
+                }
+            });
+            if let Some(target) = target {
+                pipeline.set_target(target);
+            }
+        }
+        WizardStep::Review => {
+            // Focused text edits keep their own undo history
+            if !ctx.wants_keyboard_input() {
+                handle_undo_shortcuts(ctx, &mut app_state);
+            }
+
+            draw_wizard_frame_with_state(ctx, &mut app_state, |ui, state| {
+                ui.heading("Review & Generate");
+                ui.separator();
+                egui::Grid::new("review_summary").show(ui, |ui| {
+                    for (label, value) in state.review_summary() {
+                        ui.strong(label);
+                        match SPEC_FIELDS
+                            .iter()
+                            .find(|(_, spec_label)| *spec_label == label)
+                        {
+                            Some((field, _)) => field_editor(ui, state, field),
+                            None => {
+                                ui.label(value);
+                            }
+                        }
+                        ui.end_row();
+                    }
+                });
+
+                let rationale = state.design_rationale();
+                if !rationale.is_empty() {
+                    ui.separator();
+                    ui.heading("Why these choices");
+                    for decision in rationale {
+                        ui.strong(format!("{}: {}", decision.category, decision.decision));
+                        ui.label(&decision.rationale);
+                        ui.add_space(4.0);
+                    }
+                }
+            });
         }
         WizardStep::GuidedMode => {
             debug!("Drawing guided mode step");
@@ -130,14 +300,42 @@ pub fn draw_generate_ui(
         }
         WizardStep::FreeformMode => {
             debug!("Drawing freeform mode step");
-            if let (Some(freeform_state), Some(stream_res)) = (freeform_state, stream_res) {
+            // Lets an extracted spec be undone without leaving the conversation
+            if !ctx.wants_keyboard_input() {
+                handle_undo_shortcuts(ctx, &mut app_state);
+            }
+            if let (Some(mut freeform_state), Some(stream_res), Some(mut sessions)) =
+                (freeform_state, stream_res, sessions)
+            {
+                if freeform_state.context_failures > 0 {
+                    freeform_state.record_context_success();
+                }
+
+                // Restore saved sessions the first time freeform mode is drawn
+                if sessions.save_dir.is_none() {
+                    *sessions = ConversationSessions::load(
+                        directories.project_dir.join("conversations"),
+                        &mut freeform_state.conversation,
+                    );
+
+                    // Fall back to the conversation stored in a loaded project
+                    if freeform_state.conversation.history.is_empty()
+                        && let Some(config_manager) = &app_state.config_manager
+                    {
+                        freeform_state.conversation =
+                            ConversationState::load_from(&config_manager.config.ai_context);
+                    }
+                }
+
                 render_freeform_mode(
                     contexts,
                     app_state,
                     freeform_state,
                     commands,
-                    pipeline,
+                    pipeline.into(),
                     stream_res,
+                    sessions,
+                    availability.as_deref().copied().unwrap_or_default(),
                 );
             } else {
                 warn!("No freeform state found, setting up freeform mode");
@@ -281,3 +479,51 @@ fn draw_wizard_frame_with_state(
         });
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wizard::AppMode;
+    use crate::wizard::steps::freeform::{CONTEXT_FAILURE_LIMIT, FreeformStep};
+
+    #[test]
+    fn test_missing_context_in_freeform_shows_error_step() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app_state = AppState::new();
+        app_state.wizard_step = WizardStep::FreeformMode;
+
+        let mut app = App::new();
+        app.init_resource::<bevy_egui::EguiUserTextures>()
+            .add_message::<SwitchModeEvent>()
+            .add_message::<AppExit>()
+            .insert_resource(app_state)
+            .insert_resource(AppDirectories {
+                base_dir: dir.path().to_path_buf(),
+                project_dir: dir.path().to_path_buf(),
+                config_file: None,
+                prompts_dir: dir.path().join("prompts"),
+                assets_dir: dir.path().join("assets"),
+                mode: AppMode::Generate,
+            })
+            .insert_resource(GenerationPipeline::new())
+            .insert_resource(FreeformModeState {
+                current_step: FreeformStep::Conversation,
+                ..Default::default()
+            })
+            .add_systems(Update, draw_generate_ui);
+
+        // No egui context exists, so every frame fails to get one
+        for _ in 0..CONTEXT_FAILURE_LIMIT - 1 {
+            app.update();
+        }
+        let state = app.world().resource::<FreeformModeState>();
+        assert_eq!(state.current_step, FreeformStep::Conversation);
+
+        app.update();
+        let state = app.world().resource::<FreeformModeState>();
+        assert_eq!(
+            state.current_step,
+            FreeformStep::RenderError(Box::new(FreeformStep::Conversation))
+        );
+    }
+}
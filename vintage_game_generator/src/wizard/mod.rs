@@ -15,6 +15,7 @@ pub mod overlay;
 pub mod pipeline;
 pub mod state;
 pub mod steps;
+pub mod telemetry_overlay;
 pub mod watchers;
 
 pub use directories::AppDirectories;
@@ -32,6 +33,7 @@ impl Plugin for WizardPlugin {
         app.insert_resource(AppState::new())
             .insert_resource(GenerationPipeline::new())
             .insert_resource(watchers::ConfigModificationTracker::default())
+            .init_resource::<telemetry_overlay::TelemetryOverlay>()
             .add_message::<SwitchModeEvent>()
             .add_systems(Startup, setup_app)
             .add_systems(Update, handle_mode_switch);
@@ -46,7 +48,13 @@ impl Plugin for WizardPlugin {
                 watchers::check_prompt_changes.run_if(in_mode(AppMode::Generate)),
                 pipeline::process_generation_queue.run_if(in_mode(AppMode::Generate)),
                 steps::freeform::process_conversation_stream.run_if(in_mode(AppMode::Generate)),
+                steps::freeform::sync_game_context.run_if(in_mode(AppMode::Generate)),
                 list_mode::draw_list_ui.run_if(in_mode(AppMode::List)),
+                (
+                    telemetry_overlay::toggle_telemetry_overlay,
+                    telemetry_overlay::draw_telemetry_overlay,
+                )
+                    .chain(),
             ),
         );
 
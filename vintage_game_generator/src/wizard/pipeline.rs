@@ -1,13 +1,21 @@
-use crate::metaprompts::{GameGenerator, GenerationPhase};
+use crate::metaprompts::{GameGenerator, GenerationPhase, StageSamplingConfig};
 use crate::wizard::{
     directories::AppDirectories,
     state::{AppState, LogLevel},
+    steps::LanguageChoice,
 };
-use anyhow::Result;
+use anyhow::{Result, anyhow};
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::runtime::Runtime;
 use tokio::sync::Mutex;
+use vintage_ai_client::{
+    AiGenerator, AiService, music::ElevenLabsMusicBackend, telemetry::Telemetry,
+};
 
 #[derive(Clone, Resource)]
 pub struct GenerationPipeline {
@@ -15,6 +23,322 @@ pub struct GenerationPipeline {
     pub generator: Arc<Mutex<Option<GameGenerator>>>,
     pub current_task: Option<GenerationTask>,
     pub rate_limiter: RateLimiter,
+    pub target: Option<GenerationTarget>,
+    /// Temperature/top_p per generation stage
+    pub sampling: StageSamplingConfig,
+    /// Calls, cost and latency reported by every generator
+    pub telemetry: Telemetry,
+    /// Produces individual assets such as sprites and voice lines
+    pub asset_generator: Option<Arc<dyn AssetGenerator>>,
+    /// Requests behind every asset generated so far, by asset id
+    pub generated_assets: Arc<std::sync::Mutex<HashMap<String, AssetRequest>>>,
+    /// Total telemetry cost, in dollars, past which asset generation stops
+    pub cost_ceiling: Option<f64>,
+}
+
+/// Language and engine the generated project is built for
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GenerationTarget {
+    pub language: LanguageChoice,
+    pub engine: &'static str,
+    /// Scaffolding copied from `templates/` into the generated project, if any
+    pub scaffold_template: Option<&'static str>,
+}
+
+impl GenerationTarget {
+    /// Resolve the target for a language, rejecting ones we can't generate yet
+    pub fn for_language(language: LanguageChoice) -> Result<Self, String> {
+        match language {
+            LanguageChoice::Rust => Ok(Self {
+                language,
+                engine: "Bevy",
+                scaffold_template: Some("bevy-ai-toolkit"),
+            }),
+            LanguageChoice::Python => Ok(Self {
+                language,
+                engine: "Pygame",
+                scaffold_template: None,
+            }),
+            LanguageChoice::Ruby => Err(
+                "Ruby generation is not supported yet. Choose Rust (Bevy) or Python (Pygame)."
+                    .to_string(),
+            ),
+        }
+    }
+
+    /// Human-readable label, e.g. "Rust (Bevy)"
+    pub fn label(&self) -> String {
+        format!("{} ({})", self.language.display_name(), self.engine)
+    }
+}
+
+/// Kind of asset produced during generation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AssetKind {
+    /// Project code scaffold; everything else depends on it
+    Scaffold,
+    Sprite,
+    VoiceLine,
+    Music,
+    /// Asset kinds added by extensions, handled by whatever generator is
+    /// registered for them
+    Custom(&'static str),
+}
+
+impl AssetKind {
+    /// Whether a failure should abort the whole generation
+    pub fn is_critical(&self) -> bool {
+        matches!(self, AssetKind::Scaffold)
+    }
+
+    /// Stable name recorded in the asset manifest
+    pub fn label(&self) -> &'static str {
+        match self {
+            AssetKind::Scaffold => "scaffold",
+            AssetKind::Sprite => "sprite",
+            AssetKind::VoiceLine => "voice_line",
+            AssetKind::Music => "music",
+            AssetKind::Custom(name) => name,
+        }
+    }
+}
+
+/// One asset to generate
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssetRequest {
+    pub id: String,
+    pub kind: AssetKind,
+    pub prompt: String,
+}
+
+impl AssetRequest {
+    pub fn new(id: impl Into<String>, kind: AssetKind, prompt: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            kind,
+            prompt: prompt.into(),
+        }
+    }
+}
+
+/// An asset produced by `GenerationPipeline::regenerate_asset`
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeneratedAsset {
+    pub request: AssetRequest,
+    pub bytes: Vec<u8>,
+}
+
+/// An asset that failed to generate, with the error
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssetFailure {
+    pub request: AssetRequest,
+    pub error: String,
+}
+
+/// Outcome of generating a batch of assets
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GenerationReport {
+    pub succeeded: Vec<AssetRequest>,
+    pub failed: Vec<AssetFailure>,
+}
+
+impl GenerationReport {
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+/// Asset generation stopped because spending passed the pipeline's
+/// `cost_ceiling`. `report` holds the assets finished before it tripped.
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("Generation budget exceeded: spent ${spent:.2} of ${ceiling:.2}")]
+pub struct BudgetExceeded {
+    pub spent: f64,
+    pub ceiling: f64,
+    pub report: GenerationReport,
+}
+
+/// One generated file as recorded in `manifest.json`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    /// Path relative to the project's asset directory
+    pub path: String,
+    pub kind: String,
+    pub prompt: String,
+    /// SHA-256 of the file contents, hex encoded
+    pub hash: String,
+}
+
+/// Every generated asset, by id, so later runs and downstream tools can tell
+/// which files are current without generating them again
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AssetManifest {
+    pub assets: BTreeMap<String, ManifestEntry>,
+}
+
+impl AssetManifest {
+    pub const FILE_NAME: &'static str = "manifest.json";
+
+    /// Read the manifest in `dir`, or an empty one if there isn't one yet
+    pub fn load(dir: &Path) -> Result<Self> {
+        let path = dir.join(Self::FILE_NAME);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let json = std::fs::read_to_string(&path)?;
+        serde_json::from_str(&json)
+            .map_err(|e| anyhow!("Invalid asset manifest {}: {e}", path.display()))
+    }
+
+    pub fn save(&self, dir: &Path) -> Result<()> {
+        std::fs::create_dir_all(dir)?;
+        std::fs::write(
+            dir.join(Self::FILE_NAME),
+            serde_json::to_string_pretty(self)?,
+        )?;
+        Ok(())
+    }
+
+    pub fn record(&mut self, request: &AssetRequest, bytes: &[u8]) {
+        self.assets.insert(
+            request.id.clone(),
+            ManifestEntry {
+                path: request.id.clone(),
+                kind: request.kind.label().to_string(),
+                prompt: request.prompt.clone(),
+                hash: content_hash(bytes),
+            },
+        );
+    }
+
+    /// Whether `request` was generated from the same prompt and its file in
+    /// `dir` still matches the recorded hash
+    pub fn is_current(&self, request: &AssetRequest, dir: &Path) -> bool {
+        let Some(entry) = self.assets.get(&request.id) else {
+            return false;
+        };
+        entry.prompt == request.prompt
+            && entry.kind == request.kind.label()
+            && std::fs::read(dir.join(&entry.path))
+                .is_ok_and(|bytes| content_hash(&bytes) == entry.hash)
+    }
+}
+
+/// Hex-encoded SHA-256 of an asset's contents
+pub fn content_hash(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// Generates and saves a single asset
+#[async_trait::async_trait]
+pub trait AssetGenerator: Send + Sync {
+    async fn generate(&self, request: &AssetRequest) -> Result<()>;
+
+    /// Whether the asset already exists, e.g. in the cache
+    async fn is_generated(&self, _request: &AssetRequest) -> bool {
+        false
+    }
+
+    /// Generate the asset again without consulting any cache, replace the
+    /// saved copy and return the new bytes
+    async fn regenerate(&self, request: &AssetRequest) -> Result<Vec<u8>> {
+        Err(anyhow!("Regenerating {} is not supported", request.id))
+    }
+}
+
+/// Which `AiGenerator` handles each asset kind
+///
+/// Generated bytes are written to `output_dir/<asset id>` when an output
+/// directory is set, and assets already on disk count as generated. Each
+/// write is recorded in the directory's `AssetManifest`, and assets the
+/// manifest shows are unchanged are skipped instead of generated again.
+#[derive(Clone, Default)]
+pub struct AiGeneratorRegistry {
+    generators: HashMap<AssetKind, Arc<dyn AiGenerator>>,
+    pub output_dir: Option<PathBuf>,
+}
+
+impl AiGeneratorRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Built-in generators from `service`: text for the scaffold, images for
+    /// sprites, voice for voice lines and ElevenLabs music for tracks
+    pub fn with_defaults(service: &AiService) -> Self {
+        let mut registry = Self::new();
+        registry.register(AssetKind::Scaffold, Arc::new(service.text()));
+        registry.register(AssetKind::Sprite, Arc::new(service.image()));
+        registry.register(AssetKind::VoiceLine, Arc::new(service.voice()));
+        registry.register(
+            AssetKind::Music,
+            Arc::new(service.music(Arc::new(ElevenLabsMusicBackend::from_env()))),
+        );
+        registry
+    }
+
+    pub fn with_output_dir(mut self, dir: PathBuf) -> Self {
+        self.output_dir = Some(dir);
+        self
+    }
+
+    /// Route `kind` to `generator`, replacing any previous registration
+    pub fn register(&mut self, kind: AssetKind, generator: Arc<dyn AiGenerator>) {
+        self.generators.insert(kind, generator);
+    }
+
+    pub fn get(&self, kind: AssetKind) -> Option<&Arc<dyn AiGenerator>> {
+        self.generators.get(&kind)
+    }
+
+    fn generator_for(&self, request: &AssetRequest) -> Result<&Arc<dyn AiGenerator>> {
+        self.get(request.kind)
+            .ok_or_else(|| anyhow!("No generator registered for {:?}", request.kind))
+    }
+
+    fn save(&self, request: &AssetRequest, bytes: &[u8]) -> Result<()> {
+        if let Some(dir) = &self.output_dir {
+            std::fs::create_dir_all(dir)?;
+            std::fs::write(dir.join(&request.id), bytes)?;
+
+            let mut manifest = AssetManifest::load(dir)?;
+            manifest.record(request, bytes);
+            manifest.save(dir)?;
+        }
+        Ok(())
+    }
+
+    /// Whether the manifest shows `request` is already on disk, unchanged
+    fn is_current(&self, request: &AssetRequest) -> bool {
+        self.output_dir.as_ref().is_some_and(|dir| {
+            AssetManifest::load(dir).is_ok_and(|manifest| manifest.is_current(request, dir))
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl AssetGenerator for AiGeneratorRegistry {
+    async fn generate(&self, request: &AssetRequest) -> Result<()> {
+        if self.is_current(request) {
+            return Ok(());
+        }
+        let generator = self.generator_for(request)?;
+        let bytes = generator.generate_asset(&request.prompt).await?;
+        self.save(request, &bytes)
+    }
+
+    async fn is_generated(&self, request: &AssetRequest) -> bool {
+        self.output_dir
+            .as_ref()
+            .is_some_and(|dir| dir.join(&request.id).exists())
+    }
+
+    async fn regenerate(&self, request: &AssetRequest) -> Result<Vec<u8>> {
+        let generator = self.generator_for(request)?;
+        let bytes = generator.regenerate_asset(&request.prompt).await?;
+        self.save(request, &bytes)?;
+        Ok(bytes)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -53,18 +377,199 @@ impl GenerationPipeline {
                 last_request: None,
                 min_delay_ms: 1000, // 1 second between requests
             },
+            target: None,
+            sampling: StageSamplingConfig::default(),
+            telemetry: Telemetry::new(),
+            asset_generator: None,
+            generated_assets: Arc::new(std::sync::Mutex::new(HashMap::new())),
+            cost_ceiling: None,
         }
     }
 
-    pub fn initialize_generator(
+    pub fn set_asset_generator(&mut self, generator: Arc<dyn AssetGenerator>) {
+        self.asset_generator = Some(generator);
+    }
+
+    /// Stop generating assets once `telemetry` reports more than `dollars`
+    /// spent in total
+    pub fn set_cost_ceiling(&mut self, dollars: f64) {
+        self.cost_ceiling = Some(dollars);
+    }
+
+    /// Fails with `BudgetExceeded` if spending has passed the cost ceiling
+    fn check_budget(&self, report: &GenerationReport) -> Result<()> {
+        let Some(ceiling) = self.cost_ceiling else {
+            return Ok(());
+        };
+        let spent = self.telemetry.snapshot().total.cost;
+        if spent > ceiling {
+            return Err(BudgetExceeded {
+                spent,
+                ceiling,
+                report: report.clone(),
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Generate every asset, carrying on past individual failures.
+    ///
+    /// Failed assets are listed in the report so they can be retried on
+    /// their own. Critical assets such as the code scaffold still abort
+    /// the run with an error, as does passing the cost ceiling, which is
+    /// checked before each asset and reported as `BudgetExceeded`.
+    pub fn generate_assets(&self, requests: &[AssetRequest]) -> Result<GenerationReport> {
+        let generator = self
+            .asset_generator
+            .clone()
+            .ok_or_else(|| anyhow!("No asset generator configured"))?;
+
+        self.runtime.block_on(async move {
+            let mut report = GenerationReport::default();
+            for request in requests {
+                self.check_budget(&report)?;
+                match generator.generate(request).await {
+                    Ok(()) => {
+                        self.remember_asset(request);
+                        report.succeeded.push(request.clone());
+                    }
+                    Err(e) if request.kind.is_critical() => {
+                        return Err(e.context(format!("Failed to generate {}", request.id)));
+                    }
+                    Err(e) => report.failed.push(AssetFailure {
+                        request: request.clone(),
+                        error: e.to_string(),
+                    }),
+                }
+            }
+            Ok(report)
+        })
+    }
+
+    /// Re-run only the failed assets in `report` and merge the results.
+    ///
+    /// Assets that have been generated since, e.g. by another run that
+    /// filled the cache, count as succeeded without being generated again.
+    pub fn retry_failed(&self, report: &GenerationReport) -> GenerationReport {
+        let Some(generator) = self.asset_generator.clone() else {
+            return report.clone();
+        };
+
+        self.runtime.block_on(async move {
+            let mut merged = GenerationReport {
+                succeeded: report.succeeded.clone(),
+                failed: Vec::new(),
+            };
+            for failure in &report.failed {
+                let request = &failure.request;
+                if generator.is_generated(request).await {
+                    merged.succeeded.push(request.clone());
+                    continue;
+                }
+                match generator.generate(request).await {
+                    Ok(()) => {
+                        self.remember_asset(request);
+                        merged.succeeded.push(request.clone());
+                    }
+                    Err(e) => merged.failed.push(AssetFailure {
+                        request: request.clone(),
+                        error: e.to_string(),
+                    }),
+                }
+            }
+            merged
+        })
+    }
+
+    /// Generate one previously generated asset again, e.g. after the user
+    /// rejected it, replacing it in the project and leaving every other
+    /// asset alone.
+    ///
+    /// The cache is bypassed so the result is new even for the same prompt.
+    /// A `prompt_override` replaces the original prompt for this and any
+    /// later regeneration of the asset.
+    pub fn regenerate_asset(
         &self,
+        asset_id: &str,
+        prompt_override: Option<&str>,
+    ) -> Result<GeneratedAsset> {
+        let generator = self
+            .asset_generator
+            .clone()
+            .ok_or_else(|| anyhow!("No asset generator configured"))?;
+        let mut request = self
+            .generated_assets
+            .lock()
+            .unwrap()
+            .get(asset_id)
+            .cloned()
+            .ok_or_else(|| anyhow!("No generated asset with id {asset_id}"))?;
+        if let Some(prompt) = prompt_override {
+            request.prompt = prompt.to_string();
+        }
+
+        let bytes = self
+            .runtime
+            .block_on(generator.regenerate(&request))
+            .map_err(|e| e.context(format!("Failed to regenerate {asset_id}")))?;
+        self.remember_asset(&request);
+        Ok(GeneratedAsset { request, bytes })
+    }
+
+    fn remember_asset(&self, request: &AssetRequest) {
+        self.generated_assets
+            .lock()
+            .unwrap()
+            .insert(request.id.clone(), request.clone());
+    }
+
+    /// Point generation at a language/engine so prompts and scaffolding match
+    pub fn set_target(&mut self, target: GenerationTarget) {
+        self.target = Some(target);
+
+        // A generator that is mid-request picks the target up on the next initialize
+        if let Ok(mut generator_lock) = self.generator.try_lock()
+            && let Some(generator) = generator_lock.as_mut()
+        {
+            generator.set_target(target.label());
+        }
+    }
+
+    /// Replace the per-stage sampling used for generation requests
+    pub fn set_stage_sampling(&mut self, sampling: StageSamplingConfig) {
+        self.sampling = sampling.clone();
+
+        if let Ok(mut generator_lock) = self.generator.try_lock()
+            && let Some(generator) = generator_lock.as_mut()
+        {
+            generator.set_stage_sampling(sampling);
+        }
+    }
+
+    pub fn initialize_generator(
+        &mut self,
         _api_key: String,
-        _directories: &AppDirectories,
+        directories: &AppDirectories,
     ) -> Result<()> {
         let generator_arc = self.generator.clone();
+        let target = self.target;
+        let sampling = self.sampling.clone();
+        let service = AiService::from_env()?.with_telemetry(self.telemetry.clone());
+
+        // Assets go through the built-in generators unless a caller set its own
+        if self.asset_generator.is_none() {
+            let registry = AiGeneratorRegistry::with_defaults(&service)
+                .with_output_dir(directories.assets_dir.clone());
+            self.set_asset_generator(Arc::new(registry));
+        }
 
         self.runtime.block_on(async move {
-            let new_generator = GameGenerator::new().await?;
+            let mut new_generator = GameGenerator::with_service(service);
+            if let Some(target) = target {
+                new_generator.set_target(target.label());
+            }
+            new_generator.set_stage_sampling(sampling);
             let mut generator_lock = generator_arc.lock().await;
             *generator_lock = Some(new_generator);
             Ok::<(), anyhow::Error>(())
@@ -169,9 +674,19 @@ fn start_phase_generation(
     _directories: &AppDirectories,
 ) {
     let current_phase = app_state.current_phase;
+    let Some(target) = pipeline.target else {
+        app_state.add_log(
+            LogLevel::Error,
+            "No target language selected; go back and choose one".to_string(),
+        );
+        return;
+    };
     app_state.add_log(
         LogLevel::Info,
-        format!("Starting generation for phase: {current_phase:?}"),
+        format!(
+            "Starting generation for phase: {current_phase:?} targeting {}",
+            target.label()
+        ),
     );
 
     // Mark that we're making a request
@@ -221,3 +736,380 @@ fn validate_prompt(content: &str) -> Vec<String> {
 
     errors
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wizard::state::WizardMode;
+    use vintage_ai_client::telemetry::CallRecord;
+
+    #[test]
+    fn test_language_selection_sets_pipeline_target() {
+        let mut app_state = AppState::new();
+        app_state.wizard_mode = WizardMode::Freeform;
+        let mut pipeline = GenerationPipeline::new();
+
+        let target = app_state.set_language(LanguageChoice::Rust).unwrap();
+        pipeline.set_target(target);
+
+        let target = pipeline.target.expect("target set");
+        assert_eq!(target.engine, "Bevy");
+        assert_eq!(target.scaffold_template, Some("bevy-ai-toolkit"));
+        assert!(
+            app_state
+                .review_summary()
+                .contains(&("Target", "Rust (Bevy)".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_stage_sampling_overrides_default() {
+        use crate::metaprompts::SamplingParams;
+        use vintage_ai_client::text::TextConfig;
+
+        let mut pipeline = GenerationPipeline::new();
+
+        let code = pipeline
+            .sampling
+            .apply(GenerationPhase::CodeGeneration, TextConfig::default());
+        assert_eq!(code.temperature, 0.0);
+
+        let premise = pipeline
+            .sampling
+            .apply(GenerationPhase::Design, TextConfig::default());
+        assert_eq!(premise.temperature, 0.9);
+        assert_eq!(premise.top_p, 0.95);
+
+        pipeline.set_stage_sampling(StageSamplingConfig {
+            default: SamplingParams::new(0.5, 0.8),
+            ..Default::default()
+        });
+        let packaging = pipeline.sampling.for_stage(GenerationPhase::Packaging);
+        assert_eq!(packaging, SamplingParams::new(0.5, 0.8));
+    }
+
+    #[test]
+    fn test_unsupported_language_is_rejected() {
+        let mut app_state = AppState::new();
+        app_state.wizard_mode = WizardMode::Guided;
+
+        assert!(app_state.set_language(LanguageChoice::Ruby).is_err());
+        assert_eq!(app_state.selected_language, None);
+        assert!(app_state.error_message.is_some());
+    }
+
+    /// Fails any asset whose id is listed, succeeding otherwise
+    struct FailingIds(Vec<&'static str>);
+
+    #[async_trait::async_trait]
+    impl AssetGenerator for FailingIds {
+        async fn generate(&self, request: &AssetRequest) -> Result<()> {
+            if self.0.contains(&request.id.as_str()) {
+                Err(anyhow!("{} timed out", request.id))
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    #[test]
+    fn test_failed_asset_does_not_abort_batch() {
+        let mut pipeline = GenerationPipeline::new();
+        pipeline.set_asset_generator(Arc::new(FailingIds(vec!["goblin"])));
+
+        let requests = [
+            AssetRequest::new("scaffold", AssetKind::Scaffold, "Bevy project"),
+            AssetRequest::new("hero", AssetKind::Sprite, "Knight sprite"),
+            AssetRequest::new("goblin", AssetKind::Sprite, "Goblin sprite"),
+            AssetRequest::new("intro", AssetKind::VoiceLine, "Welcome, traveler"),
+        ];
+        let report = pipeline.generate_assets(&requests).unwrap();
+
+        let succeeded: Vec<&str> = report.succeeded.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(succeeded, vec!["scaffold", "hero", "intro"]);
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(report.failed[0].request.id, "goblin");
+        assert!(report.failed[0].error.contains("timed out"));
+
+        // The scaffold is critical, so losing it fails the whole run
+        pipeline.set_asset_generator(Arc::new(FailingIds(vec!["scaffold"])));
+        assert!(pipeline.generate_assets(&requests).is_err());
+    }
+
+    /// Records which assets it was asked to generate
+    #[derive(Default)]
+    struct Recording {
+        generated: std::sync::Mutex<Vec<String>>,
+        cached: Vec<&'static str>,
+    }
+
+    #[async_trait::async_trait]
+    impl AssetGenerator for Recording {
+        async fn generate(&self, request: &AssetRequest) -> Result<()> {
+            self.generated.lock().unwrap().push(request.id.clone());
+            Ok(())
+        }
+
+        async fn is_generated(&self, request: &AssetRequest) -> bool {
+            self.cached.contains(&request.id.as_str())
+        }
+    }
+
+    #[test]
+    fn test_retry_failed_reruns_only_failures() {
+        let failure = |id: &str| AssetFailure {
+            request: AssetRequest::new(id, AssetKind::Sprite, "sprite"),
+            error: "timed out".to_string(),
+        };
+        let report = GenerationReport {
+            succeeded: vec![AssetRequest::new("hero", AssetKind::Sprite, "sprite")],
+            failed: vec![failure("goblin"), failure("slime")],
+        };
+
+        let generator = Arc::new(Recording::default());
+        let mut pipeline = GenerationPipeline::new();
+        pipeline.set_asset_generator(generator.clone());
+
+        let retried = pipeline.retry_failed(&report);
+        assert!(retried.is_complete());
+        assert_eq!(
+            *generator.generated.lock().unwrap(),
+            vec!["goblin", "slime"]
+        );
+        let succeeded: Vec<&str> = retried.succeeded.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(succeeded, vec!["hero", "goblin", "slime"]);
+
+        // Assets generated in the meantime are taken from the cache
+        let generator = Arc::new(Recording {
+            cached: vec!["slime"],
+            ..Recording::default()
+        });
+        pipeline.set_asset_generator(generator.clone());
+        assert!(pipeline.retry_failed(&report).is_complete());
+        assert_eq!(*generator.generated.lock().unwrap(), vec!["goblin"]);
+    }
+
+    /// Custom generator that echoes the prompt back as the asset
+    struct EchoGenerator {
+        telemetry: Telemetry,
+        prompts: std::sync::Mutex<Vec<String>>,
+    }
+
+    #[async_trait::async_trait]
+    impl AiGenerator for EchoGenerator {
+        async fn estimate_tokens(&self, request: &str) -> Result<usize> {
+            Ok(request.len())
+        }
+
+        async fn estimate_cost(&self, _request: &str) -> Result<f64> {
+            Ok(0.0)
+        }
+
+        async fn is_cached(&self, _key: &str) -> bool {
+            false
+        }
+
+        async fn clear_cache(&self, _key: &str) -> Result<()> {
+            Ok(())
+        }
+
+        fn name(&self) -> &'static str {
+            "echo"
+        }
+
+        fn telemetry(&self) -> &Telemetry {
+            &self.telemetry
+        }
+
+        async fn generate_asset(&self, prompt: &str) -> Result<Vec<u8>> {
+            self.prompts.lock().unwrap().push(prompt.to_string());
+            Ok(prompt.as_bytes().to_vec())
+        }
+    }
+
+    #[test]
+    fn test_registry_routes_custom_asset_kind() {
+        let echo = Arc::new(EchoGenerator {
+            telemetry: Telemetry::new(),
+            prompts: std::sync::Mutex::new(Vec::new()),
+        });
+        let mut registry = AiGeneratorRegistry::new();
+        registry.register(AssetKind::Custom("level_map"), echo.clone());
+
+        let mut pipeline = GenerationPipeline::new();
+        pipeline.set_asset_generator(Arc::new(registry));
+
+        let requests = [
+            AssetRequest::new("dungeon", AssetKind::Custom("level_map"), "Five rooms"),
+            AssetRequest::new("hero", AssetKind::Sprite, "Knight sprite"),
+        ];
+        let report = pipeline.generate_assets(&requests).unwrap();
+
+        assert_eq!(*echo.prompts.lock().unwrap(), vec!["Five rooms"]);
+        assert_eq!(report.succeeded[0].id, "dungeon");
+        // Nothing is registered for sprites in this registry
+        assert_eq!(report.failed[0].request.id, "hero");
+    }
+
+    /// Numbers every asset it produces, so each generation differs
+    #[derive(Default)]
+    struct Versioned {
+        telemetry: Telemetry,
+        calls: std::sync::atomic::AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl AiGenerator for Versioned {
+        async fn estimate_tokens(&self, request: &str) -> Result<usize> {
+            Ok(request.len())
+        }
+
+        async fn estimate_cost(&self, _request: &str) -> Result<f64> {
+            Ok(0.0)
+        }
+
+        async fn is_cached(&self, _key: &str) -> bool {
+            false
+        }
+
+        async fn clear_cache(&self, _key: &str) -> Result<()> {
+            Ok(())
+        }
+
+        fn name(&self) -> &'static str {
+            "versioned"
+        }
+
+        fn telemetry(&self) -> &Telemetry {
+            &self.telemetry
+        }
+
+        async fn generate_asset(&self, prompt: &str) -> Result<Vec<u8>> {
+            let call = self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(format!("{prompt} v{call}").into_bytes())
+        }
+    }
+
+    #[test]
+    fn test_regenerate_asset_replaces_only_that_asset() {
+        let dir =
+            std::env::temp_dir().join(format!("vintage_regenerate_asset_{}", std::process::id()));
+        let mut registry = AiGeneratorRegistry::new().with_output_dir(dir.clone());
+        registry.register(AssetKind::Sprite, Arc::new(Versioned::default()));
+        let mut pipeline = GenerationPipeline::new();
+        pipeline.set_asset_generator(Arc::new(registry));
+
+        let requests = [
+            AssetRequest::new("hero", AssetKind::Sprite, "Knight sprite"),
+            AssetRequest::new("goblin", AssetKind::Sprite, "Goblin sprite"),
+        ];
+        assert!(pipeline.generate_assets(&requests).unwrap().is_complete());
+        let hero = std::fs::read(dir.join("hero")).unwrap();
+        let goblin = std::fs::read(dir.join("goblin")).unwrap();
+
+        let regenerated = pipeline.regenerate_asset("hero", None).unwrap();
+        assert_ne!(regenerated.bytes, hero);
+        assert_eq!(std::fs::read(dir.join("hero")).unwrap(), regenerated.bytes);
+        assert_eq!(std::fs::read(dir.join("goblin")).unwrap(), goblin);
+
+        let tweaked = pipeline
+            .regenerate_asset("hero", Some("Knight sprite, red cape"))
+            .unwrap();
+        assert_eq!(tweaked.request.prompt, "Knight sprite, red cape");
+        assert!(
+            String::from_utf8(tweaked.bytes)
+                .unwrap()
+                .starts_with("Knight sprite, red cape")
+        );
+        assert_eq!(std::fs::read(dir.join("goblin")).unwrap(), goblin);
+
+        assert!(pipeline.regenerate_asset("dragon", None).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// Reports a fixed cost for every asset it generates
+    struct Billed {
+        telemetry: Telemetry,
+        cost: f64,
+    }
+
+    #[async_trait::async_trait]
+    impl AssetGenerator for Billed {
+        async fn generate(&self, _request: &AssetRequest) -> Result<()> {
+            self.telemetry.record(
+                "billed",
+                CallRecord::api(100, self.cost, std::time::Duration::ZERO),
+            );
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_cost_ceiling_halts_generation() {
+        let mut pipeline = GenerationPipeline::new();
+        pipeline.set_asset_generator(Arc::new(Billed {
+            telemetry: pipeline.telemetry.clone(),
+            cost: 0.4,
+        }));
+        pipeline.set_cost_ceiling(1.0);
+
+        let requests: Vec<AssetRequest> = (0..5)
+            .map(|i| AssetRequest::new(format!("sprite_{i}"), AssetKind::Sprite, "sprite"))
+            .collect();
+        let error = pipeline.generate_assets(&requests).unwrap_err();
+
+        // The third asset took spending to $1.20, so the fourth never started
+        let exceeded = error.downcast_ref::<BudgetExceeded>().unwrap();
+        assert!(exceeded.spent > exceeded.ceiling);
+        let completed: Vec<&str> = exceeded
+            .report
+            .succeeded
+            .iter()
+            .map(|r| r.id.as_str())
+            .collect();
+        assert_eq!(completed, vec!["sprite_0", "sprite_1", "sprite_2"]);
+        assert_eq!(pipeline.telemetry.snapshot().total.calls, 3);
+    }
+
+    #[test]
+    fn test_identical_spec_reuses_manifest_assets() {
+        let dir = std::env::temp_dir().join(format!("vintage_manifest_{}", std::process::id()));
+        let generator = Arc::new(Versioned::default());
+        let mut registry = AiGeneratorRegistry::new().with_output_dir(dir.clone());
+        registry.register(AssetKind::Sprite, generator.clone());
+        let mut pipeline = GenerationPipeline::new();
+        pipeline.set_asset_generator(Arc::new(registry));
+
+        let requests = [
+            AssetRequest::new("hero", AssetKind::Sprite, "Knight sprite"),
+            AssetRequest::new("goblin", AssetKind::Sprite, "Goblin sprite"),
+        ];
+        assert!(pipeline.generate_assets(&requests).unwrap().is_complete());
+        let first = AssetManifest::load(&dir).unwrap();
+        assert_eq!(first.assets["hero"].kind, "sprite");
+        assert_eq!(first.assets["hero"].prompt, "Knight sprite");
+        assert_eq!(
+            first.assets["hero"].hash,
+            content_hash(&std::fs::read(dir.join("hero")).unwrap())
+        );
+
+        // Versioned output differs per call, so equal hashes mean no new calls
+        assert!(pipeline.generate_assets(&requests).unwrap().is_complete());
+        assert_eq!(AssetManifest::load(&dir).unwrap(), first);
+        assert_eq!(generator.calls.load(std::sync::atomic::Ordering::SeqCst), 2);
+
+        // A changed prompt is generated again
+        let reworded = [AssetRequest::new(
+            "goblin",
+            AssetKind::Sprite,
+            "Goblin archer",
+        )];
+        pipeline.generate_assets(&reworded).unwrap();
+        assert_eq!(generator.calls.load(std::sync::atomic::Ordering::SeqCst), 3);
+        let second = AssetManifest::load(&dir).unwrap();
+        assert_eq!(second.assets["hero"], first.assets["hero"]);
+        assert_ne!(second.assets["goblin"].hash, first.assets["goblin"].hash);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,10 +1,14 @@
 use crate::metaprompts::GenerationPhase;
-use crate::wizard::config::ConfigManager;
+use crate::metaprompts::generator::ExtractedSpec;
+use crate::wizard::config::{
+    BlendWeights, ConfigManager, DesignDecision, ProjectConfig, blend_specs,
+};
+use crate::wizard::pipeline::GenerationTarget;
 use crate::wizard::steps::guided::GuidedModeExport;
 use crate::wizard::steps::{LanguageChoice, WelcomeAction};
 use bevy::prelude::*;
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum WizardStep {
@@ -42,6 +46,60 @@ pub struct AppState {
 
     // Configuration manager for persisting wizard state
     pub config_manager: Option<ConfigManager>,
+
+    /// Field edits that Ctrl+Z / Ctrl+Y step through
+    pub undo_stack: UndoStack,
+
+    /// Conflicting premises of a just-blended project, waiting to be sent to
+    /// the model for reconciling
+    pub premises_to_reconcile: Option<(String, String, BlendWeights)>,
+    /// Reconciled premise for the blended project, once the model answers
+    pub premise_rx: Option<tokio::sync::oneshot::Receiver<Result<String, String>>>,
+}
+
+/// Most field edits kept for undo; older ones are dropped
+pub const MAX_UNDO_DEPTH: usize = 100;
+
+/// Form fields describing the game, with the labels they're edited under
+pub const SPEC_FIELDS: [(&str, &str); 4] = [
+    ("title", "Title"),
+    ("genre", "Genre"),
+    ("mechanics", "Mechanics"),
+    ("visual_style", "Visual style"),
+];
+
+/// One change to a form field. `None` means the field was unset.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldEdit {
+    pub field: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+/// Undo and redo history of field edits
+#[derive(Debug, Default)]
+pub struct UndoStack {
+    undo: VecDeque<FieldEdit>,
+    redo: Vec<FieldEdit>,
+}
+
+impl UndoStack {
+    /// Record a new edit, discarding anything that could have been redone
+    pub fn push(&mut self, edit: FieldEdit) {
+        self.redo.clear();
+        self.undo.push_back(edit);
+        if self.undo.len() > MAX_UNDO_DEPTH {
+            self.undo.pop_front();
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -92,6 +150,121 @@ impl AppState {
             current_phase: GenerationPhase::Design,
             generation_logs: Vec::new(),
             config_manager: None,
+            undo_stack: UndoStack::default(),
+            premises_to_reconcile: None,
+            premise_rx: None,
+        }
+    }
+
+    /// Current value of a form field. With a project open, the `SPEC_FIELDS`
+    /// are read from its config, which is what generation uses.
+    pub fn field(&self, field: &str) -> Option<String> {
+        let value = match (self.config_manager.as_ref(), field) {
+            (Some(manager), "title") => manager.config.basic_info.name.clone(),
+            (Some(manager), "genre") => manager.config.basic_info.genre.clone(),
+            (Some(manager), "mechanics") => manager.config.gameplay.core_mechanics.join(", "),
+            (Some(manager), "visual_style") => manager.config.visual_style.color_mood.clone(),
+            _ => return self.form_data.get(field).cloned(),
+        };
+        (!value.is_empty()).then_some(value)
+    }
+
+    /// Change a form field, recording the edit so it can be undone
+    pub fn set_field(&mut self, field: impl Into<String>, value: impl Into<String>) {
+        let field = field.into();
+        let value = value.into();
+        if self.field(&field).as_deref() == Some(value.as_str()) {
+            return;
+        }
+
+        let before = self.field(&field);
+        self.write_field(&field, Some(value.clone()));
+        self.undo_stack.push(FieldEdit {
+            field,
+            before,
+            after: Some(value),
+        });
+    }
+
+    /// Revert the most recent field edit. Returns false if there was none.
+    pub fn undo(&mut self) -> bool {
+        let Some(edit) = self.undo_stack.undo.pop_back() else {
+            return false;
+        };
+        self.write_field(&edit.field, edit.before.clone());
+        self.undo_stack.redo.push(edit);
+        true
+    }
+
+    /// Reapply the most recently undone edit. Returns false if there was none.
+    pub fn redo(&mut self) -> bool {
+        let Some(edit) = self.undo_stack.redo.pop() else {
+            return false;
+        };
+        self.write_field(&edit.field, edit.after.clone());
+        self.undo_stack.undo.push_back(edit);
+        true
+    }
+
+    /// Fill the spec fields from one extracted from a conversation, leaving
+    /// fields it didn't decide alone. Returns the edits made, which can be
+    /// undone like any other.
+    pub fn apply_extracted_spec(&mut self, spec: &ExtractedSpec) -> Vec<FieldEdit> {
+        let mechanics: Vec<&str> = spec
+            .mechanics
+            .iter()
+            .map(|mechanic| mechanic.trim())
+            .filter(|mechanic| !mechanic.is_empty())
+            .collect();
+        let fields = [
+            ("title", spec.title.trim().to_string()),
+            ("genre", spec.genre.trim().to_string()),
+            ("mechanics", mechanics.join(", ")),
+            ("visual_style", spec.visual_style.trim().to_string()),
+        ];
+
+        let mut changes = Vec::new();
+        for (field, value) in fields {
+            if value.is_empty() || self.field(field).as_deref() == Some(value.as_str()) {
+                continue;
+            }
+            let before = self.field(field);
+            self.set_field(field, value.clone());
+            changes.push(FieldEdit {
+                field: field.to_string(),
+                before,
+                after: Some(value),
+            });
+        }
+        changes
+    }
+
+    fn write_field(&mut self, field: &str, value: Option<String>) {
+        let config = self
+            .config_manager
+            .as_mut()
+            .map(|manager| &mut manager.config);
+        match (config, field, value) {
+            (Some(config), "title", value) => config.basic_info.name = value.unwrap_or_default(),
+            (Some(config), "genre", value) => config.basic_info.genre = value.unwrap_or_default(),
+            (Some(config), "mechanics", value) => {
+                config.gameplay.core_mechanics = value
+                    .unwrap_or_default()
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|mechanic| !mechanic.is_empty())
+                    .map(str::to_string)
+                    .collect();
+            }
+            (Some(config), "visual_style", value) => {
+                config.visual_style.color_mood = value.unwrap_or_default();
+            }
+            (_, _, Some(value)) => {
+                self.form_data.insert(field.to_string(), value);
+            }
+            (_, _, None) => {
+                self.form_data.remove(field);
+            }
         }
     }
 
@@ -109,11 +282,108 @@ impl AppState {
                 self.wizard_mode = WizardMode::Freeform;
                 self.wizard_step = WizardStep::SelectLanguage;
             }
+            WelcomeAction::LoadProject(path) => {
+                if let Err(e) = self.load_project(&path) {
+                    self.error_message = Some(format!("Could not load project: {e:#}"));
+                }
+            }
+            WelcomeAction::BlendProjects(first, second) => {
+                if let Err(e) = self.blend_projects(&first, &second, BlendWeights::default()) {
+                    self.error_message = Some(format!("Could not blend projects: {e:#}"));
+                }
+            }
         }
     }
 
-    pub fn set_language(&mut self, choice: LanguageChoice) {
+    /// Start the open project over as a blend of two generated projects and
+    /// continue in guided mode. Differing premises are queued in
+    /// `premises_to_reconcile` for the model to merge.
+    pub fn blend_projects(
+        &mut self,
+        first_dir: &Path,
+        second_dir: &Path,
+        weights: BlendWeights,
+    ) -> anyhow::Result<()> {
+        let first = ProjectConfig::load(&first_dir.join("project.toml"))?;
+        let second = ProjectConfig::load(&second_dir.join("project.toml"))?;
+        let config_manager = self
+            .config_manager
+            .as_mut()
+            .ok_or_else(|| anyhow::anyhow!("No project is open to blend into"))?;
+
+        config_manager.config = blend_specs(&first, &second, weights);
+        config_manager.set_wizard_mode("guided")?;
+
+        let (first_premise, second_premise) = (
+            first.basic_info.description.trim(),
+            second.basic_info.description.trim(),
+        );
+        self.premises_to_reconcile = (!first_premise.is_empty()
+            && !second_premise.is_empty()
+            && !first_premise.eq_ignore_ascii_case(second_premise))
+        .then(|| {
+            (
+                first_premise.to_string(),
+                second_premise.to_string(),
+                weights,
+            )
+        });
+
+        self.wizard_mode = WizardMode::Guided;
+        self.wizard_step = WizardStep::SelectLanguage;
+        self.error_message = None;
+        Ok(())
+    }
+
+    /// Replace the blended project's premise with the model's reconciled one
+    pub fn apply_reconciled_premise(&mut self, premise: String) -> anyhow::Result<()> {
+        let Some(config_manager) = self.config_manager.as_mut() else {
+            return Ok(());
+        };
+        config_manager.config.basic_info.description = premise;
+        config_manager.save()
+    }
+
+    /// Restore a generated project's manifest and conversation to keep editing it
+    pub fn load_project(&mut self, project_dir: &Path) -> anyhow::Result<()> {
+        let config_manager = ConfigManager::open_project(project_dir)?;
+        let config = &config_manager.config;
+
+        self.wizard_mode = match config.wizard_state.mode.as_deref() {
+            Some("guided") => WizardMode::Guided,
+            Some("freeform") => WizardMode::Freeform,
+            // Older manifests don't record the mode; only freeform has a conversation
+            _ if !config.ai_context.conversation_history.is_empty() => WizardMode::Freeform,
+            _ => WizardMode::Guided,
+        };
+        self.selected_language = config
+            .technical
+            .target_platforms
+            .first()
+            .and_then(|language| LanguageChoice::parse(language));
+        self.wizard_step = match (&self.wizard_mode, self.selected_language) {
+            (_, None) => WizardStep::SelectLanguage,
+            (WizardMode::Freeform, Some(_)) => WizardStep::FreeformMode,
+            _ => WizardStep::GuidedMode,
+        };
+
+        self.config_manager = Some(config_manager);
+        self.error_message = None;
+        Ok(())
+    }
+
+    /// Select the target language, refusing ones generation can't produce
+    pub fn set_language(&mut self, choice: LanguageChoice) -> Result<GenerationTarget, String> {
+        let target = match GenerationTarget::for_language(choice) {
+            Ok(target) => target,
+            Err(message) => {
+                self.error_message = Some(message.clone());
+                return Err(message);
+            }
+        };
+
         self.selected_language = Some(choice);
+        self.error_message = None;
 
         // Move to next appropriate step based on mode
         match self.wizard_mode {
@@ -128,6 +398,68 @@ impl AppState {
                 error!("Language selected without mode selection");
             }
         }
+
+        Ok(target)
+    }
+
+    /// The generation target implied by the selected language
+    pub fn target(&self) -> Option<GenerationTarget> {
+        self.selected_language
+            .and_then(|language| GenerationTarget::for_language(language).ok())
+    }
+
+    /// Label/value rows shown on the review step
+    pub fn review_summary(&self) -> Vec<(&'static str, String)> {
+        let mode = match self.wizard_mode {
+            WizardMode::NotSelected => "Not selected",
+            WizardMode::Guided => "Guided",
+            WizardMode::Freeform => "Freeform",
+        };
+        let target = self
+            .target()
+            .map(|target| target.label())
+            .unwrap_or_else(|| "Not selected".to_string());
+
+        let mut summary = vec![("Mode", mode.to_string()), ("Target", target)];
+        for (field, label) in SPEC_FIELDS {
+            let value = self.field(field).unwrap_or_else(|| "Not set".to_string());
+            summary.push((label, value));
+        }
+        summary
+    }
+
+    /// Why the AI made its design choices, shown on the review step
+    pub fn design_rationale(&self) -> &[DesignDecision] {
+        self.config_manager
+            .as_ref()
+            .map(|manager| manager.config.ai_context.design_decisions.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Compact summary of the game decided so far, sent with every freeform
+    /// conversation request so replies stay consistent with earlier choices
+    pub fn game_spec_context(&self) -> Option<String> {
+        let spec = self.config_manager.as_ref()?.game_specification();
+        let mut lines: Vec<String> = [
+            ("Title", spec.title),
+            ("Genre", spec.genre),
+            ("Theme", spec.theme),
+            ("Art style", spec.art_style),
+            ("Key features", spec.key_features.join(", ")),
+        ]
+        .into_iter()
+        .filter(|(_, value)| !value.trim().is_empty())
+        .map(|(label, value)| format!("- {label}: {value}"))
+        .collect();
+
+        if lines.is_empty() {
+            return None;
+        }
+        lines.insert(
+            0,
+            "Game decided so far (keep answers consistent with it):".to_string(),
+        );
+        Some(lines.join("\n"))
     }
 
     pub fn can_go_back(&self) -> bool {
@@ -308,3 +640,175 @@ impl Default for WizardState {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_undo_and_redo_field_edit() {
+        let mut app_state = AppState::new();
+        app_state.set_field("genre", "Platformer");
+        app_state.set_field("genre", "Roguelike");
+
+        assert!(app_state.undo());
+        assert_eq!(app_state.field("genre").as_deref(), Some("Platformer"));
+        assert!(app_state.redo());
+        assert_eq!(app_state.field("genre").as_deref(), Some("Roguelike"));
+
+        assert!(app_state.undo());
+        assert!(app_state.undo());
+        assert_eq!(app_state.field("genre"), None);
+        assert!(!app_state.undo());
+
+        // A fresh edit drops the redo history
+        app_state.set_field("genre", "Shmup");
+        assert!(!app_state.redo());
+    }
+
+    #[test]
+    fn test_undo_depth_is_capped() {
+        let mut app_state = AppState::new();
+        for i in 0..MAX_UNDO_DEPTH + 10 {
+            app_state.set_field("name", i.to_string());
+        }
+
+        let mut undone = 0;
+        while app_state.undo() {
+            undone += 1;
+        }
+        assert_eq!(undone, MAX_UNDO_DEPTH);
+        assert_eq!(app_state.field("name").as_deref(), Some("9"));
+    }
+
+    #[test]
+    fn test_load_project_restores_title_and_spec() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config_manager = ConfigManager::new(dir.path(), Some("project")).unwrap();
+        config_manager.config.basic_info.name = "Star Quest".to_string();
+        config_manager.config.basic_info.genre = "Action RPG".to_string();
+        config_manager.set_wizard_mode("freeform").unwrap();
+        config_manager.set_language("rust").unwrap();
+        config_manager
+            .config
+            .add_conversation("user", "Make it spacey", "design");
+        config_manager.save().unwrap();
+
+        let mut app_state = AppState::new();
+        app_state.set_wizard_mode(WelcomeAction::LoadProject(dir.path().to_path_buf()));
+
+        assert_eq!(app_state.error_message, None);
+        assert_eq!(app_state.wizard_mode, WizardMode::Freeform);
+        assert_eq!(app_state.wizard_step, WizardStep::FreeformMode);
+        assert_eq!(app_state.selected_language, Some(LanguageChoice::Rust));
+
+        let config = &app_state.config_manager.as_ref().unwrap().config;
+        assert_eq!(config.name.as_deref(), Some("Star Quest"));
+        let spec = config.game_specification.as_ref().unwrap();
+        assert_eq!(spec.title, "Star Quest");
+        assert_eq!(spec.genre, "Action RPG");
+        assert_eq!(config.ai_context.conversation_history.len(), 1);
+    }
+
+    #[test]
+    fn test_extracted_spec_fills_project_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app_state = AppState::new();
+        app_state.set_config_manager(ConfigManager::new(dir.path(), Some("project")).unwrap());
+        let art_style_before = app_state
+            .config_manager
+            .as_ref()
+            .unwrap()
+            .game_specification()
+            .art_style;
+
+        app_state.apply_extracted_spec(&ExtractedSpec {
+            title: "Skyvault".to_string(),
+            genre: "Stealth roguelike".to_string(),
+            mechanics: vec!["Grappling hooks".to_string(), "Guard routines".to_string()],
+            visual_style: "Pixel art".to_string(),
+        });
+
+        let config_manager = app_state.config_manager.as_ref().unwrap();
+        let spec = config_manager.game_specification();
+        assert_eq!(spec.title, "Skyvault");
+        assert_eq!(spec.genre, "Stealth roguelike");
+        assert_eq!(spec.art_style, "Pixel art");
+        assert_eq!(
+            config_manager.config.gameplay.core_mechanics,
+            vec!["Grappling hooks", "Guard routines"]
+        );
+        assert!(
+            app_state
+                .review_summary()
+                .contains(&("Title", "Skyvault".to_string()))
+        );
+
+        // The config edits are undoable like any other field edit
+        assert!(app_state.undo());
+        let config_manager = app_state.config_manager.as_ref().unwrap();
+        assert_eq!(
+            config_manager.game_specification().art_style,
+            art_style_before
+        );
+    }
+
+    #[test]
+    fn test_blend_projects_starts_guided_project() {
+        let save = |name: &str, mechanics: &[&str], premise: &str| {
+            let dir = tempfile::tempdir().unwrap();
+            let mut config_manager = ConfigManager::new(dir.path(), Some("project")).unwrap();
+            config_manager.config.basic_info.name = name.to_string();
+            config_manager.config.basic_info.description = premise.to_string();
+            config_manager.config.gameplay.core_mechanics =
+                mechanics.iter().map(|m| m.to_string()).collect();
+            config_manager.save().unwrap();
+            dir
+        };
+        let space = save("Star Quest", &["Combat", "Space travel"], "Pilots race.");
+        let dungeon = save("Dungeon Delve", &["Combat", "Looting"], "Heroes dig.");
+
+        let current = tempfile::tempdir().unwrap();
+        let mut app_state = AppState::new();
+        app_state.set_config_manager(ConfigManager::new(current.path(), None).unwrap());
+        app_state.set_wizard_mode(WelcomeAction::BlendProjects(
+            space.path().to_path_buf(),
+            dungeon.path().to_path_buf(),
+        ));
+
+        assert_eq!(app_state.error_message, None);
+        assert_eq!(app_state.wizard_mode, WizardMode::Guided);
+        assert_eq!(app_state.wizard_step, WizardStep::SelectLanguage);
+        let config = &app_state.config_manager.as_ref().unwrap().config;
+        assert_eq!(config.basic_info.name, "Star Quest × Dungeon Delve");
+        assert_eq!(
+            config.gameplay.core_mechanics,
+            vec!["Combat", "Space travel", "Looting"]
+        );
+        let (first, second, _) = app_state.premises_to_reconcile.clone().unwrap();
+        assert_eq!(
+            (first.as_str(), second.as_str()),
+            ("Pilots race.", "Heroes dig.")
+        );
+
+        app_state
+            .apply_reconciled_premise("Pilots dig for stars.".to_string())
+            .unwrap();
+        let saved = ProjectConfig::load(&current.path().join("current.toml")).unwrap();
+        assert_eq!(saved.basic_info.description, "Pilots dig for stars.");
+    }
+
+    #[test]
+    fn test_load_project_rejects_newer_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config_manager = ConfigManager::new(dir.path(), Some("project")).unwrap();
+        config_manager.config.metadata.version = "9.0.0".to_string();
+        config_manager.save().unwrap();
+
+        let mut app_state = AppState::new();
+        app_state.set_wizard_mode(WelcomeAction::LoadProject(dir.path().to_path_buf()));
+
+        assert!(app_state.config_manager.is_none());
+        assert!(app_state.error_message.unwrap().contains("not supported"));
+    }
+}
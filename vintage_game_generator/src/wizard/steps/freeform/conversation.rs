@@ -1,16 +1,29 @@
 //! AI conversation interface for freeform mode
 
 use super::{
-    ConversationEntry, ConversationRole, ConversationStream, ConversationStreamEvent,
-    FreeformModeState,
+    AiAvailability, ConversationEntry, ConversationRole, ConversationSessions, ConversationState,
+    ConversationStream, ConversationStreamEvent, EntryStatus, FreeformModeState, MicRecording,
+    SessionId,
 };
+use crate::metaprompts::generator::{DEFAULT_GAME_DESIGN_SYSTEM_PROMPT, DesignOutputMode};
 use crate::wizard::pipeline::GenerationPipeline;
 use crate::wizard::state::AppState;
 use bevy::prelude::*;
 use bevy_egui::{EguiContexts, egui};
 use futures::StreamExt;
+use std::sync::LazyLock;
+use vintage_ai_client::conversation::{ImageAttachment, MessageConfig};
+use vintage_ai_client::text::StructuredUpdate;
+use vintage_ai_client::tokens::{Message, TokenCounter};
+
+// Loading the tokenizers is slow, so share one counter for cost estimates
+static TOKEN_COUNTER: LazyLock<TokenCounter> = LazyLock::new(TokenCounter::new);
+
+/// Reply length assumed for cost estimates when replies aren't capped
+const ESTIMATED_REPLY_TOKENS: u32 = 2000;
 
 /// Render the AI conversation interface
+#[allow(clippy::too_many_arguments)]
 pub fn render_conversation(
     mut contexts: EguiContexts,
     mut app_state: ResMut<AppState>,
@@ -18,11 +31,28 @@ pub fn render_conversation(
     _commands: Commands,
     pipeline: Res<GenerationPipeline>,
     mut stream_res: ResMut<ConversationStream>,
+    mut sessions: ResMut<ConversationSessions>,
+    availability: AiAvailability,
 ) {
     let Ok(ctx) = contexts.ctx_mut() else {
         return;
     };
 
+    // Keep repainting while tokens arrive so partial output shows immediately
+    if freeform_state.conversation.is_streaming {
+        ctx.request_repaint();
+    }
+
+    poll_transcription(&mut freeform_state);
+    poll_spec_extraction(&mut freeform_state, &mut app_state);
+    request_session_title(&mut freeform_state, &pipeline, &sessions);
+    poll_session_title(&mut freeform_state, &mut sessions);
+    request_suggestions(&mut freeform_state, &pipeline);
+    poll_suggestions(&mut freeform_state);
+    accept_dropped_image(ctx, &mut freeform_state);
+
+    let mut retry_requested = false;
+
     egui::CentralPanel::default().show(ctx, |ui| {
         // Header
         ui.horizontal(|ui| {
@@ -32,6 +62,14 @@ pub fn render_conversation(
         });
         ui.separator();
 
+        if let Some(banner) = availability.banner() {
+            ui.colored_label(egui::Color32::from_rgb(255, 170, 60), format!("⚠ {banner}"));
+            ui.separator();
+        }
+
+        render_session_switcher(ui, &mut freeform_state, &mut sessions);
+        render_settings(ui, &mut freeform_state);
+
         // Context summary
         if !freeform_state.conversation.context_summary.is_empty() {
             ui.group(|ui| {
@@ -47,7 +85,7 @@ pub fn render_conversation(
             .max_height(ui.available_height() - 100.0)
             .show(ui, |ui| {
                 for entry in &freeform_state.conversation.history {
-                    render_conversation_entry(ui, entry);
+                    retry_requested |= render_conversation_entry(ui, entry);
                     ui.add_space(10.0);
                 }
 
@@ -65,10 +103,42 @@ pub fn render_conversation(
                 if let Some(error) = &freeform_state.conversation.error_message {
                     ui.colored_label(egui::Color32::RED, format!("Error: {}", error));
                 }
+                if let Some(warning) = &freeform_state.conversation.guardrail_warning {
+                    ui.colored_label(egui::Color32::YELLOW, warning);
+                }
             });
 
         ui.separator();
 
+        render_spec_changes(ui, &mut freeform_state, &mut app_state);
+
+        if let Some(pending) = freeform_state.conversation.pending_send.clone() {
+            ui.group(|ui| {
+                ui.label(format!(
+                    "This will cost ~${:.2}, proceed?",
+                    pending.estimated_cost
+                ));
+                ui.horizontal(|ui| {
+                    if ui.button("Proceed").clicked() {
+                        freeform_state.conversation.pending_send = None;
+                        send_message(
+                            &mut freeform_state,
+                            &pipeline,
+                            stream_res.reborrow(),
+                            pending.message.clone(),
+                        );
+                    }
+                    if ui.button("Cancel").clicked() {
+                        // Give the message back so it can be trimmed down
+                        freeform_state.conversation.pending_send = None;
+                        freeform_state.conversation.current_input = pending.message.clone();
+                    }
+                });
+            });
+        }
+
+        render_suggestions(ui, &mut freeform_state);
+
         // Input area
         ui.horizontal(|ui| {
             let response = ui.text_edit_multiline(&mut freeform_state.conversation.current_input);
@@ -76,12 +146,19 @@ pub fn render_conversation(
             // Focus on the text input
             if response.lost_focus()
                 && ui.input(|i| i.key_pressed(egui::Key::Enter))
-                && !freeform_state.conversation.current_input.trim().is_empty()
-                && !freeform_state.conversation.is_processing
+                && let Some(message) = freeform_state.conversation.take_input(availability)
             {
-                send_message(&mut freeform_state, &pipeline, stream_res.reborrow());
+                request_send(
+                    &mut freeform_state,
+                    &pipeline,
+                    stream_res.reborrow(),
+                    message,
+                );
             }
 
+            render_push_to_talk(ui, &mut freeform_state, &pipeline);
+            render_pending_attachment(ui, &mut freeform_state);
+
             ui.vertical(|ui| {
                 if freeform_state.conversation.is_processing {
                     if ui.button("Cancel").clicked() {
@@ -89,10 +166,17 @@ pub fn render_conversation(
                         freeform_state.conversation.is_streaming = false;
                         stream_res.receiver = None;
                     }
-                } else if ui.button("Send").clicked()
-                    && !freeform_state.conversation.current_input.trim().is_empty()
+                } else if ui
+                    .add_enabled(availability.can_send(), egui::Button::new("Send"))
+                    .clicked()
+                    && let Some(message) = freeform_state.conversation.take_input(availability)
                 {
-                    send_message(&mut freeform_state, &pipeline, stream_res.reborrow());
+                    request_send(
+                        &mut freeform_state,
+                        &pipeline,
+                        stream_res.reborrow(),
+                        message,
+                    );
                 }
 
                 if ui.button("Export").clicked() {
@@ -109,17 +193,583 @@ pub fn render_conversation(
                 freeform_state.current_step = super::FreeformStep::Review;
             }
 
+            let conversation = &freeform_state.conversation;
+            let can_extract = availability.can_send()
+                && !conversation.history.is_empty()
+                && !conversation.is_processing
+                && conversation.spec_extraction_rx.is_none();
+            if ui
+                .add_enabled(can_extract, egui::Button::new("Extract Spec"))
+                .on_hover_text("Fill in the game spec from this conversation")
+                .clicked()
+            {
+                start_spec_extraction(&mut freeform_state, &pipeline);
+            }
+            if freeform_state.conversation.spec_extraction_rx.is_some() {
+                ui.spinner();
+            }
+
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                 if ui.button("Generate Game →").clicked() {
-                    // TODO: Start generation
-                    app_state.set_wizard_step(crate::wizard::state::WizardStep::Complete);
+                    go_to_review(&mut app_state, &freeform_state);
                 }
             });
         });
     });
+
+    if retry_requested && !freeform_state.conversation.is_processing && availability.can_send() {
+        retry_last_message(&mut freeform_state, &pipeline, stream_res.reborrow());
+    }
+}
+
+/// Move to the review step, carrying the conversation and its rationale along
+fn go_to_review(app_state: &mut AppState, freeform_state: &FreeformModeState) {
+    if let Some(config_manager) = &mut app_state.config_manager {
+        freeform_state
+            .conversation
+            .save_to(&mut config_manager.config.ai_context);
+    }
+    app_state.set_wizard_step(crate::wizard::state::WizardStep::Review);
+}
+
+/// What the last spec extraction changed, with a shortcut to review it
+fn render_spec_changes(
+    ui: &mut egui::Ui,
+    freeform_state: &mut FreeformModeState,
+    app_state: &mut AppState,
+) {
+    if let Some(preview) = &freeform_state.conversation.spec_preview {
+        ui.group(|ui| {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label("Extracting spec...");
+            });
+            let fields = [
+                ("title", preview.title.clone()),
+                ("genre", preview.genre.clone()),
+                ("mechanics", preview.mechanics.join(", ")),
+                ("visual_style", preview.visual_style.clone()),
+            ];
+            for (field, value) in fields.iter().filter(|(_, value)| !value.is_empty()) {
+                ui.horizontal_wrapped(|ui| {
+                    ui.strong(*field);
+                    ui.label(value);
+                });
+            }
+        });
+        ui.separator();
+    }
+
+    let Some(changes) = &freeform_state.conversation.spec_changes else {
+        return;
+    };
+
+    let mut dismissed = false;
+    ui.group(|ui| {
+        if changes.is_empty() {
+            ui.label("The extracted spec matches what you already have.");
+        } else {
+            ui.label("Extracted from the conversation:");
+            for change in changes {
+                let before = change.before.as_deref().unwrap_or("(unset)");
+                let after = change.after.as_deref().unwrap_or("(unset)");
+                ui.horizontal_wrapped(|ui| {
+                    ui.strong(&change.field);
+                    ui.colored_label(egui::Color32::from_rgb(220, 90, 90), before);
+                    ui.label("→");
+                    ui.colored_label(egui::Color32::from_rgb(90, 200, 90), after);
+                });
+            }
+        }
+        ui.horizontal(|ui| {
+            if ui.button("Review Spec →").clicked() {
+                go_to_review(app_state, freeform_state);
+                dismissed = true;
+            }
+            if ui.button("Dismiss").clicked() {
+                dismissed = true;
+            }
+        });
+    });
+    ui.separator();
+
+    if dismissed {
+        freeform_state.conversation.spec_changes = None;
+    }
+}
+
+/// Send the whole conversation off to be summarized as a structured spec,
+/// streaming fields back as they are written
+fn start_spec_extraction(freeform_state: &mut FreeformModeState, pipeline: &GenerationPipeline) {
+    let conversation = &mut freeform_state.conversation;
+    let transcript = conversation.transcript();
+
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+    conversation.spec_extraction_rx = Some(rx);
+    conversation.spec_changes = None;
+    conversation.spec_preview = None;
+
+    let generator_arc = pipeline.generator.clone();
+    pipeline.runtime.spawn(async move {
+        let generator_lock = generator_arc.lock().await;
+        let Some(generator) = generator_lock.as_ref() else {
+            let _ = tx.send(Err("AI Generator not initialized".to_string()));
+            return;
+        };
+
+        let mut updates = match generator.extract_spec_stream(&transcript).await {
+            Ok(updates) => Box::pin(updates),
+            Err(e) => {
+                let _ = tx.send(Err(e.to_string()));
+                return;
+            }
+        };
+        while let Some(update) = updates.next().await {
+            if tx.send(update.map_err(|e| e.to_string())).is_err() {
+                return;
+            }
+        }
+    });
+}
+
+/// Show streamed spec fields as they arrive, and apply the finished spec to
+/// the wizard's fields
+fn poll_spec_extraction(freeform_state: &mut FreeformModeState, app_state: &mut AppState) {
+    let conversation = &mut freeform_state.conversation;
+    let Some(rx) = conversation.spec_extraction_rx.as_mut() else {
+        return;
+    };
+
+    let result = loop {
+        match rx.try_recv() {
+            Ok(Ok(StructuredUpdate::Partial(spec))) => conversation.spec_preview = Some(spec),
+            Ok(Ok(StructuredUpdate::Complete(spec))) => break Ok(spec),
+            Ok(Err(e)) => break Err(e),
+            Err(tokio::sync::mpsc::error::TryRecvError::Empty) => return,
+            Err(tokio::sync::mpsc::error::TryRecvError::Disconnected) => {
+                break Err("Spec extraction was cancelled".to_string());
+            }
+        }
+    };
+    conversation.spec_extraction_rx = None;
+    conversation.spec_preview = None;
+
+    match result {
+        Ok(spec) => conversation.spec_changes = Some(app_state.apply_extracted_spec(&spec)),
+        Err(e) => conversation.error_message = Some(format!("Spec extraction failed: {e}")),
+    }
+}
+
+/// Once the first exchange finishes, ask for a title to name the session by
+fn request_session_title(
+    freeform_state: &mut FreeformModeState,
+    pipeline: &GenerationPipeline,
+    sessions: &ConversationSessions,
+) {
+    let conversation = &mut freeform_state.conversation;
+    if conversation.title_rx.is_some() || !sessions.active_has_default_name() {
+        return;
+    }
+    let Some(first_message) = conversation.untitled_first_message().map(str::to_string) else {
+        return;
+    };
+
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    conversation.titled_message = Some(first_message.clone());
+    conversation.title_rx = Some(rx);
+
+    let generator_arc = pipeline.generator.clone();
+    pipeline.runtime.spawn(async move {
+        let generator_lock = generator_arc.lock().await;
+        let result = match generator_lock.as_ref() {
+            Some(generator) => generator
+                .suggest_title(&first_message)
+                .await
+                .map_err(|e| e.to_string()),
+            None => Err("AI Generator not initialized".to_string()),
+        };
+        let _ = tx.send(result);
+    });
+}
+
+/// Rename the active session once its title arrives
+fn poll_session_title(freeform_state: &mut FreeformModeState, sessions: &mut ConversationSessions) {
+    let conversation = &mut freeform_state.conversation;
+    let Some(rx) = conversation.title_rx.as_mut() else {
+        return;
+    };
+
+    let result = match rx.try_recv() {
+        Ok(result) => result,
+        Err(tokio::sync::oneshot::error::TryRecvError::Empty) => return,
+        Err(tokio::sync::oneshot::error::TryRecvError::Closed) => {
+            Err("Title generation was cancelled".to_string())
+        }
+    };
+    conversation.title_rx = None;
+
+    // A missing title only costs the session its name, so don't surface it
+    match result {
+        Ok(title) => {
+            if sessions.apply_auto_title(&title) {
+                save_session(sessions, sessions.active(), conversation);
+            }
+        }
+        Err(e) => warn!("Session title generation failed: {e}"),
+    }
+}
+
+/// After each finished reply, ask for follow-up prompts unless the same
+/// conversation already has some cached
+fn request_suggestions(freeform_state: &mut FreeformModeState, pipeline: &GenerationPipeline) {
+    let conversation = &mut freeform_state.conversation;
+    let Some(transcript) = conversation.suggestions_to_request() else {
+        return;
+    };
+
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    conversation.suggestions_rx = Some(rx);
+
+    let generator_arc = pipeline.generator.clone();
+    pipeline.runtime.spawn(async move {
+        let generator_lock = generator_arc.lock().await;
+        let result = match generator_lock.as_ref() {
+            Some(generator) => generator
+                .suggest_follow_ups(&transcript)
+                .await
+                .map_err(|e| e.to_string()),
+            None => Err("AI Generator not initialized".to_string()),
+        };
+        let _ = tx.send((transcript, result));
+    });
+}
+
+/// Show suggestions once they arrive
+fn poll_suggestions(freeform_state: &mut FreeformModeState) {
+    let conversation = &mut freeform_state.conversation;
+    let Some(rx) = conversation.suggestions_rx.as_mut() else {
+        return;
+    };
+
+    let (transcript, result) = match rx.try_recv() {
+        Ok(received) => received,
+        Err(tokio::sync::oneshot::error::TryRecvError::Empty) => return,
+        Err(tokio::sync::oneshot::error::TryRecvError::Closed) => {
+            conversation.suggestions_rx = None;
+            return;
+        }
+    };
+    conversation.suggestions_rx = None;
+
+    // Cache failures as no suggestions so they aren't retried every frame
+    let suggestions = result.unwrap_or_else(|e| {
+        warn!("Suggestion generation failed: {e}");
+        Vec::new()
+    });
+    conversation.store_suggestions(transcript, suggestions);
+}
+
+/// Suggested follow-ups as chips that fill the input box when clicked
+fn render_suggestions(ui: &mut egui::Ui, freeform_state: &mut FreeformModeState) {
+    let conversation = &mut freeform_state.conversation;
+    if conversation.suggestions.is_empty() || conversation.is_processing {
+        return;
+    }
+
+    let mut clicked = None;
+    ui.horizontal_wrapped(|ui| {
+        ui.label("Try:");
+        for (index, suggestion) in conversation.suggestions.iter().enumerate() {
+            if ui.small_button(format!("💡 {suggestion}")).clicked() {
+                clicked = Some(index);
+            }
+        }
+    });
+    if let Some(index) = clicked {
+        conversation.use_suggestion(index);
+    }
+}
+
+/// Row for creating, renaming, switching and deleting conversation sessions
+fn render_session_switcher(
+    ui: &mut egui::Ui,
+    freeform_state: &mut FreeformModeState,
+    sessions: &mut ConversationSessions,
+) {
+    let conversation = &mut freeform_state.conversation;
+    // Streamed tokens always land in the active session, so don't switch mid-reply
+    let busy = conversation.is_processing;
+    let active = sessions.active();
+
+    ui.horizontal(|ui| {
+        ui.add_enabled_ui(!busy, |ui| {
+            let mut selected = active;
+            egui::ComboBox::from_label("Session")
+                .selected_text(sessions.active_name().to_string())
+                .show_ui(ui, |ui| {
+                    for (id, name) in sessions.iter() {
+                        ui.selectable_value(&mut selected, id, name);
+                    }
+                });
+            if selected != active {
+                save_session(sessions, active, conversation);
+                sessions.switch_to(selected, conversation);
+            }
+
+            let mut name = sessions.active_name().to_string();
+            let response = ui.add(egui::TextEdit::singleline(&mut name).desired_width(160.0));
+            if response.changed() {
+                sessions.rename(active, name);
+            }
+            if response.lost_focus() {
+                save_session(sessions, active, conversation);
+            }
+
+            if ui.button("➕ New").clicked() {
+                save_session(sessions, active, conversation);
+                let id = sessions.create(conversation);
+                save_session(sessions, id, conversation);
+            }
+            if ui.button("🗑 Delete").clicked() {
+                sessions.delete(active, conversation);
+            }
+        });
+    });
+}
+
+fn save_session(sessions: &ConversationSessions, id: SessionId, conversation: &ConversationState) {
+    if let Err(e) = sessions.save_session(id, conversation) {
+        warn!("Failed to save conversation session: {e}");
+    }
+}
+
+/// Collapsible panel for editing the system prompt used by later requests
+fn render_settings(ui: &mut egui::Ui, freeform_state: &mut FreeformModeState) {
+    let conversation = &mut freeform_state.conversation;
+
+    let response = egui::CollapsingHeader::new("⚙ Settings")
+        .open(Some(conversation.show_settings))
+        .show(ui, |ui| {
+            ui.label("System prompt (applies to the next message):");
+            ui.add(
+                egui::TextEdit::multiline(&mut conversation.system_prompt)
+                    .desired_rows(4)
+                    .desired_width(f32::INFINITY),
+            );
+            if ui.button("Reset to default").clicked() {
+                conversation.system_prompt = DEFAULT_GAME_DESIGN_SYSTEM_PROMPT.to_string();
+            }
+
+            let mut explain = conversation.output_mode == DesignOutputMode::GenerateDesignRationale;
+            if ui
+                .checkbox(&mut explain, "Explain each design decision")
+                .changed()
+            {
+                conversation.output_mode = if explain {
+                    DesignOutputMode::GenerateDesignRationale
+                } else {
+                    DesignOutputMode::SpecOnly
+                };
+            }
+
+            ui.checkbox(
+                &mut conversation.guardrails.enabled,
+                "Filter prompt-injection phrases from my messages",
+            );
+
+            ui.horizontal(|ui| {
+                ui.label("Confirm sends costing more than $");
+                ui.add(
+                    egui::DragValue::new(&mut conversation.cost_threshold)
+                        .speed(0.01)
+                        .range(0.0..=10.0)
+                        .max_decimals(2),
+                );
+            });
+        });
+
+    if response.header_response.clicked() {
+        conversation.show_settings = !conversation.show_settings;
+    }
+}
+
+/// Attach the first image dropped onto the window to the next message
+fn accept_dropped_image(ctx: &egui::Context, freeform_state: &mut FreeformModeState) {
+    let Some(file) = ctx.input(|i| i.raw.dropped_files.first().cloned()) else {
+        return;
+    };
+
+    let bytes = match (&file.bytes, &file.path) {
+        (Some(bytes), _) => Ok(bytes.to_vec()),
+        (None, Some(path)) => std::fs::read(path).map_err(anyhow::Error::from),
+        (None, None) => Err(anyhow::anyhow!("dropped file has no contents")),
+    };
+    let name = file
+        .path
+        .as_ref()
+        .and_then(|path| path.file_name())
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or(file.name);
+
+    match bytes.and_then(|bytes| ImageAttachment::from_bytes(name, &bytes)) {
+        Ok(attachment) => freeform_state.conversation.pending_attachment = Some(attachment),
+        Err(e) => {
+            freeform_state.conversation.error_message =
+                Some(format!("Could not attach image: {e}"));
+        }
+    }
+}
+
+/// Show the image waiting to be sent, or a hint on how to attach one
+fn render_pending_attachment(ui: &mut egui::Ui, freeform_state: &mut FreeformModeState) {
+    let conversation = &mut freeform_state.conversation;
+    match &conversation.pending_attachment {
+        Some(attachment) => {
+            ui.label(format!("📎 {}", attachment.name));
+            if ui.small_button("✖").on_hover_text("Remove image").clicked() {
+                conversation.pending_attachment = None;
+            }
+        }
+        None => {
+            ui.weak("📎 Drop an image to attach");
+        }
+    }
+}
+
+/// Hold-to-record microphone button; releasing it sends the clip for transcription
+fn render_push_to_talk(
+    ui: &mut egui::Ui,
+    freeform_state: &mut FreeformModeState,
+    pipeline: &GenerationPipeline,
+) {
+    let voice = &mut freeform_state.conversation.voice_input;
+
+    if voice.is_transcribing {
+        ui.spinner();
+        return;
+    }
+
+    let label = if voice.recording.is_some() {
+        "🔴 Listening..."
+    } else {
+        "🎤 Hold to talk"
+    };
+    let button = ui
+        .add(egui::Button::new(label).sense(egui::Sense::click_and_drag()))
+        .on_hover_text("Hold to record, release to transcribe into the prompt");
+
+    if button.is_pointer_button_down_on() {
+        if voice.recording.is_none() && !voice.start_failed {
+            match MicRecording::start() {
+                Ok(recording) => voice.recording = Some(recording),
+                Err(e) => {
+                    voice.start_failed = true;
+                    freeform_state.conversation.error_message =
+                        Some(format!("Voice input unavailable: {e}"));
+                }
+            }
+        }
+        ui.ctx().request_repaint();
+        return;
+    }
+    voice.start_failed = false;
+
+    let Some(recording) = voice.recording.take() else {
+        return;
+    };
+
+    let audio = match recording.stop() {
+        Ok(audio) => audio,
+        Err(e) => {
+            freeform_state.conversation.error_message = Some(format!("Recording failed: {e}"));
+            return;
+        }
+    };
+
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    voice.transcription_rx = Some(rx);
+    voice.is_transcribing = true;
+
+    let generator_arc = pipeline.generator.clone();
+    pipeline.runtime.spawn(async move {
+        let generator_lock = generator_arc.lock().await;
+        let result = match generator_lock.as_ref() {
+            Some(generator) => generator
+                .transcribe_audio(audio)
+                .await
+                .map_err(|e| e.to_string()),
+            None => Err("AI Generator not initialized".to_string()),
+        };
+        let _ = tx.send(result);
+    });
+}
+
+/// Move a finished transcription into the prompt box
+fn poll_transcription(freeform_state: &mut FreeformModeState) {
+    let conversation = &mut freeform_state.conversation;
+    let Some(rx) = conversation.voice_input.transcription_rx.as_mut() else {
+        return;
+    };
+
+    let result = match rx.try_recv() {
+        Ok(result) => result,
+        Err(tokio::sync::oneshot::error::TryRecvError::Empty) => return,
+        Err(tokio::sync::oneshot::error::TryRecvError::Closed) => {
+            Err("Transcription was cancelled".to_string())
+        }
+    };
+
+    conversation.voice_input.transcription_rx = None;
+    conversation.voice_input.is_transcribing = false;
+
+    match result {
+        Ok(text) => {
+            if !conversation.current_input.trim().is_empty() {
+                conversation.current_input.push(' ');
+            }
+            conversation.current_input.push_str(&text);
+        }
+        Err(e) => conversation.error_message = Some(format!("Transcription failed: {e}")),
+    }
+}
+
+/// Drop the failed response and resend the last user message
+fn retry_last_message(
+    freeform_state: &mut FreeformModeState,
+    pipeline: &GenerationPipeline,
+    stream_res: Mut<ConversationStream>,
+) {
+    let Some(message) = freeform_state
+        .conversation
+        .last_user_message()
+        .map(str::to_string)
+    else {
+        return;
+    };
+
+    let history = &mut freeform_state.conversation.history;
+    if history
+        .last()
+        .is_some_and(|entry| matches!(entry.status, EntryStatus::Errored(_)))
+    {
+        history.pop();
+    }
+    // The user message is re-added by send_message
+    if history
+        .last()
+        .is_some_and(|entry| entry.role == ConversationRole::User)
+    {
+        history.pop();
+    }
+
+    freeform_state.conversation.error_message = None;
+    send_message(freeform_state, pipeline, stream_res, message);
 }
 
-fn render_conversation_entry(ui: &mut egui::Ui, entry: &ConversationEntry) {
+/// Render a single entry; returns `true` if the user asked to retry it
+fn render_conversation_entry(ui: &mut egui::Ui, entry: &ConversationEntry) -> bool {
+    let mut retry = false;
+
     let (icon, color) = match entry.role {
         ConversationRole::User => ("👤", egui::Color32::from_rgb(100, 150, 255)),
         ConversationRole::Assistant => ("🤖", egui::Color32::from_rgb(100, 255, 150)),
@@ -128,9 +778,33 @@ fn render_conversation_entry(ui: &mut egui::Ui, entry: &ConversationEntry) {
 
     ui.horizontal(|ui| {
         ui.label(icon);
-        ui.colored_label(color, &entry.content);
+        if entry.role == ConversationRole::Assistant {
+            ui.vertical(|ui| super::render_markdown(ui, &entry.content, color));
+        } else {
+            ui.colored_label(color, &entry.content);
+        }
     });
 
+    if let Some(attachment) = &entry.attachment {
+        ui.weak(format!("📎 {attachment}"));
+    }
+
+    match &entry.status {
+        EntryStatus::Complete => {}
+        EntryStatus::Streaming => {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.weak("typing...");
+            });
+        }
+        EntryStatus::Errored(error) => {
+            ui.horizontal(|ui| {
+                ui.colored_label(egui::Color32::RED, format!("Response interrupted: {error}"));
+                retry = ui.button("Retry").clicked();
+            });
+        }
+    }
+
     if let Some(metadata) = &entry.metadata {
         ui.indent("metadata", |ui| {
             ui.label(format!("Topic: {}", metadata.topic));
@@ -142,26 +816,78 @@ fn render_conversation_entry(ui: &mut egui::Ui, entry: &ConversationEntry) {
             }
         });
     }
+
+    retry
+}
+
+/// Rough USD cost of sending `message` with the current history and settings,
+/// counted over the messages the request will actually carry
+fn estimate_send_cost(conversation: &ConversationState, message: &str) -> f64 {
+    let config = if conversation.pending_attachment.is_some() {
+        MessageConfig::vision()
+    } else {
+        MessageConfig::default()
+    };
+
+    let request = conversation.build_request(message.to_string());
+    let messages: Vec<Message> = std::iter::once(Message::new("system", request.system_prompt))
+        .chain(
+            conversation
+                .history
+                .iter()
+                .map(|entry| Message::new(entry.role.as_str(), entry.content.as_str())),
+        )
+        .chain(std::iter::once(Message::new("user", request.message)))
+        .collect();
+
+    TOKEN_COUNTER.estimate_request_cost(
+        &messages,
+        &config.model,
+        config.max_tokens.unwrap_or(ESTIMATED_REPLY_TOKENS) as usize,
+    )
+}
+
+/// Send `message`, or hold it for confirmation if it's over the cost threshold
+fn request_send(
+    freeform_state: &mut FreeformModeState,
+    pipeline: &GenerationPipeline,
+    stream_res: Mut<ConversationStream>,
+    message: String,
+) {
+    let estimated_cost = estimate_send_cost(&freeform_state.conversation, &message);
+    if let Some(message) = freeform_state
+        .conversation
+        .gate_send(message, estimated_cost)
+    {
+        send_message(freeform_state, pipeline, stream_res, message);
+    }
 }
 
 fn send_message(
     freeform_state: &mut FreeformModeState,
     pipeline: &GenerationPipeline,
     mut stream_res: Mut<ConversationStream>,
+    message: String,
 ) {
-    let message = freeform_state.conversation.current_input.trim().to_string();
+    let (message, flags) = freeform_state.conversation.guardrails.check(&message);
+    freeform_state.conversation.guardrail_warning = (!flags.is_empty()).then(|| {
+        let found: Vec<&str> = flags.iter().map(|flag| flag.description()).collect();
+        format!("Filtered from your message: {}", found.join(", "))
+    });
+
+    let request = freeform_state.conversation.build_request(message.clone());
+    freeform_state.conversation.pending_attachment = None;
 
     // Add user message to history
     freeform_state.conversation.history.push(ConversationEntry {
         role: ConversationRole::User,
-        content: message.clone(),
+        content: message,
         timestamp: std::time::SystemTime::now(),
         metadata: None,
+        status: EntryStatus::Complete,
+        attachment: request.attachment.as_ref().map(|a| a.name.clone()),
     });
 
-    // Clear input
-    freeform_state.conversation.current_input.clear();
-
     // Set processing state
     freeform_state.conversation.is_processing = true;
     freeform_state.conversation.is_streaming = true;
@@ -173,16 +899,27 @@ fn send_message(
     // Get a clone of the generator
     let generator_arc = pipeline.generator.clone();
     let runtime = pipeline.runtime.clone();
-    let conversation_id = freeform_state.conversation.conversation_id.clone();
 
     // Spawn async task for streaming
     runtime.spawn(async move {
         let generator_lock = generator_arc.lock().await;
         if let Some(generator) = generator_lock.as_ref() {
             // Start or continue conversation
-            if let Some(conversation_id_ref) = &conversation_id {
+            if let Some(conversation_id_ref) = &request.conversation_id {
+                if let Err(e) = generator
+                    .set_system_prompt(conversation_id_ref, &request.system_prompt)
+                    .await
+                {
+                    let _ = tx.send(ConversationStreamEvent::Error(e.to_string()));
+                    return;
+                }
+
                 let result = generator
-                    .continue_game_design_conversation_stream(conversation_id_ref, &message)
+                    .continue_game_design_conversation_stream(
+                        conversation_id_ref,
+                        &request.message,
+                        request.attachment,
+                    )
                     .await;
 
                 match result {
@@ -207,8 +944,17 @@ fn send_message(
                 }
             } else {
                 // If no conversation ID, start a new one first
-                match generator.start_game_design_conversation(&message).await {
-                    Ok((_new_id, initial_response)) => {
+                match generator
+                    .start_game_design_conversation(
+                        &request.message,
+                        Some(&request.system_prompt),
+                        request.attachment,
+                    )
+                    .await
+                {
+                    Ok((new_id, initial_response)) => {
+                        // Remember the id so follow-ups continue this conversation
+                        let _ = tx.send(ConversationStreamEvent::Started(new_id));
                         // Send the initial response
                         let _ = tx.send(ConversationStreamEvent::Token(initial_response));
                         let _ = tx.send(ConversationStreamEvent::Finished);
@@ -226,10 +972,24 @@ fn send_message(
     });
 }
 
+/// System that refreshes the game context sent with conversation requests
+/// whenever the game spec changes. Also runs on freeform changes, since
+/// switching sessions swaps in a conversation without the context.
+pub fn sync_game_context(app_state: Res<AppState>, mut freeform_state: ResMut<FreeformModeState>) {
+    if !app_state.is_changed() && !freeform_state.is_changed() {
+        return;
+    }
+    let context = app_state.game_spec_context().unwrap_or_default();
+    if freeform_state.conversation.context_summary != context {
+        freeform_state.conversation.context_summary = context;
+    }
+}
+
 /// System to process streaming conversation events
 pub fn process_conversation_stream(
     mut freeform_state: ResMut<FreeformModeState>,
     mut stream_res: ResMut<ConversationStream>,
+    sessions: Option<Res<ConversationSessions>>,
 ) {
     let receiver_ref = match &mut stream_res.receiver {
         Some(rx) => rx,
@@ -237,45 +997,12 @@ pub fn process_conversation_stream(
     };
 
     while let Ok(event) = receiver_ref.try_recv() {
-        match event {
-            ConversationStreamEvent::Token(token) => {
-                // If the last message is from Assistant and we are streaming, append to it
-                // Otherwise, create a new Assistant message
-                if freeform_state.conversation.is_streaming {
-                    if let Some(last_entry) = freeform_state.conversation.history.last_mut() {
-                        if last_entry.role == ConversationRole::Assistant {
-                            last_entry.content.push_str(&token);
-                        } else {
-                            freeform_state.conversation.history.push(ConversationEntry {
-                                role: ConversationRole::Assistant,
-                                content: token,
-                                timestamp: std::time::SystemTime::now(),
-                                metadata: None,
-                            });
-                        }
-                    } else {
-                        freeform_state.conversation.history.push(ConversationEntry {
-                            role: ConversationRole::Assistant,
-                            content: token,
-                            timestamp: std::time::SystemTime::now(),
-                            metadata: None,
-                        });
-                    }
-                }
-            }
-            ConversationStreamEvent::Finished => {
-                freeform_state.conversation.is_processing = false;
-                freeform_state.conversation.is_streaming = false;
-                stream_res.receiver = None;
-                return;
-            }
-            ConversationStreamEvent::Error(e) => {
-                freeform_state.conversation.error_message = Some(e);
-                freeform_state.conversation.is_processing = false;
-                freeform_state.conversation.is_streaming = false;
-                stream_res.receiver = None;
-                return;
+        if freeform_state.conversation.apply_stream_event(event) {
+            stream_res.receiver = None;
+            if let Some(sessions) = &sessions {
+                save_session(sessions, sessions.active(), &freeform_state.conversation);
             }
+            return;
         }
     }
 }
@@ -0,0 +1,243 @@
+//! Lightweight markdown rendering for AI responses
+//!
+//! Only the subset the design assistant actually produces is supported:
+//! headings, bullet lists, bold/italic/inline code, and fenced code blocks.
+
+use bevy_egui::egui;
+
+/// A block-level element of a parsed markdown document
+#[derive(Debug, Clone, PartialEq)]
+pub enum MarkdownBlock {
+    Heading {
+        level: u8,
+        spans: Vec<InlineSpan>,
+    },
+    Paragraph(Vec<InlineSpan>),
+    BulletItem(Vec<InlineSpan>),
+    CodeBlock {
+        language: Option<String>,
+        code: String,
+    },
+}
+
+/// A run of inline text sharing the same formatting
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct InlineSpan {
+    pub text: String,
+    pub bold: bool,
+    pub italic: bool,
+    pub code: bool,
+}
+
+/// Parse markdown text into blocks
+pub fn parse_markdown(text: &str) -> Vec<MarkdownBlock> {
+    let mut blocks = Vec::new();
+    let mut paragraph: Vec<&str> = Vec::new();
+    let mut lines = text.lines();
+
+    let flush_paragraph = |paragraph: &mut Vec<&str>, blocks: &mut Vec<MarkdownBlock>| {
+        if !paragraph.is_empty() {
+            blocks.push(MarkdownBlock::Paragraph(parse_inline(&paragraph.join(" "))));
+            paragraph.clear();
+        }
+    };
+
+    while let Some(line) = lines.next() {
+        let trimmed = line.trim_start();
+
+        if let Some(info) = trimmed.strip_prefix("```") {
+            flush_paragraph(&mut paragraph, &mut blocks);
+            let language = Some(info.trim().to_string()).filter(|lang| !lang.is_empty());
+            let mut code = Vec::new();
+            for code_line in lines.by_ref() {
+                if code_line.trim_start().starts_with("```") {
+                    break;
+                }
+                code.push(code_line);
+            }
+            blocks.push(MarkdownBlock::CodeBlock {
+                language,
+                code: code.join("\n"),
+            });
+        } else if trimmed.is_empty() {
+            flush_paragraph(&mut paragraph, &mut blocks);
+        } else if let Some((level, heading)) = parse_heading(trimmed) {
+            flush_paragraph(&mut paragraph, &mut blocks);
+            blocks.push(MarkdownBlock::Heading {
+                level,
+                spans: parse_inline(heading),
+            });
+        } else if let Some(item) = trimmed
+            .strip_prefix("- ")
+            .or_else(|| trimmed.strip_prefix("* "))
+            .or_else(|| trimmed.strip_prefix("+ "))
+        {
+            flush_paragraph(&mut paragraph, &mut blocks);
+            blocks.push(MarkdownBlock::BulletItem(parse_inline(item)));
+        } else {
+            paragraph.push(trimmed);
+        }
+    }
+    flush_paragraph(&mut paragraph, &mut blocks);
+
+    blocks
+}
+
+fn parse_heading(line: &str) -> Option<(u8, &str)> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    if (1..=6).contains(&level) {
+        line[level..]
+            .strip_prefix(' ')
+            .map(|rest| (level as u8, rest))
+    } else {
+        None
+    }
+}
+
+/// Split a line into spans on `**bold**`, `*italic*` and `` `code` `` markers
+pub fn parse_inline(text: &str) -> Vec<InlineSpan> {
+    let mut spans = Vec::new();
+    let mut current = InlineSpan::default();
+    let mut chars = text.chars().peekable();
+
+    let flush = |current: &mut InlineSpan, spans: &mut Vec<InlineSpan>| {
+        if !current.text.is_empty() {
+            spans.push(current.clone());
+            current.text.clear();
+        }
+    };
+
+    while let Some(c) = chars.next() {
+        match c {
+            '`' => {
+                flush(&mut current, &mut spans);
+                current.code = !current.code;
+            }
+            _ if current.code => current.text.push(c),
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                flush(&mut current, &mut spans);
+                current.bold = !current.bold;
+            }
+            '*' => {
+                flush(&mut current, &mut spans);
+                current.italic = !current.italic;
+            }
+            _ => current.text.push(c),
+        }
+    }
+    flush(&mut current, &mut spans);
+
+    spans
+}
+
+/// Render parsed markdown into the UI using `color` for body text
+pub fn render_markdown(ui: &mut egui::Ui, text: &str, color: egui::Color32) {
+    for (index, block) in parse_markdown(text).iter().enumerate() {
+        match block {
+            MarkdownBlock::Heading { level, spans } => {
+                let size = match level {
+                    1 => 22.0,
+                    2 => 19.0,
+                    _ => 16.0,
+                };
+                ui.horizontal_wrapped(|ui| {
+                    for span in spans {
+                        ui.label(styled_span(span, color).size(size).strong());
+                    }
+                });
+            }
+            MarkdownBlock::Paragraph(spans) => {
+                ui.horizontal_wrapped(|ui| {
+                    ui.spacing_mut().item_spacing.x = 0.0;
+                    for span in spans {
+                        ui.label(styled_span(span, color));
+                    }
+                });
+            }
+            MarkdownBlock::BulletItem(spans) => {
+                ui.horizontal_wrapped(|ui| {
+                    ui.spacing_mut().item_spacing.x = 0.0;
+                    ui.label(egui::RichText::new("  • ").color(color));
+                    for span in spans {
+                        ui.label(styled_span(span, color));
+                    }
+                });
+            }
+            MarkdownBlock::CodeBlock { language, code } => {
+                egui::Frame::group(ui.style())
+                    .fill(ui.visuals().extreme_bg_color)
+                    .show(ui, |ui| {
+                        ui.horizontal(|ui| {
+                            if let Some(language) = language {
+                                ui.weak(language);
+                            }
+                            ui.with_layout(
+                                egui::Layout::right_to_left(egui::Align::Center),
+                                |ui| {
+                                    if ui.small_button("Copy").clicked() {
+                                        ui.ctx().copy_text(code.clone());
+                                    }
+                                },
+                            );
+                        });
+                        egui::ScrollArea::horizontal()
+                            .id_salt(("code_block", index))
+                            .show(ui, |ui| {
+                                ui.label(egui::RichText::new(code).monospace());
+                            });
+                    });
+            }
+        }
+    }
+}
+
+fn styled_span(span: &InlineSpan, color: egui::Color32) -> egui::RichText {
+    let mut text = egui::RichText::new(&span.text).color(color);
+    if span.bold {
+        text = text.strong();
+    }
+    if span.italic {
+        text = text.italics();
+    }
+    if span.code {
+        text = text.code();
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fenced_code_block_captures_language() {
+        let blocks = parse_markdown(
+            "## Player movement\n\nUse a system:\n\n```rust\nfn move_player() {}\n```\n- fast\n",
+        );
+
+        assert_eq!(
+            blocks,
+            vec![
+                MarkdownBlock::Heading {
+                    level: 2,
+                    spans: parse_inline("Player movement"),
+                },
+                MarkdownBlock::Paragraph(parse_inline("Use a system:")),
+                MarkdownBlock::CodeBlock {
+                    language: Some("rust".to_string()),
+                    code: "fn move_player() {}".to_string(),
+                },
+                MarkdownBlock::BulletItem(parse_inline("fast")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_inline_formatting() {
+        let spans = parse_inline("a **bold** and *soft* `code`");
+        assert!(spans.iter().any(|s| s.text == "bold" && s.bold));
+        assert!(spans.iter().any(|s| s.text == "soft" && s.italic));
+        assert!(spans.iter().any(|s| s.text == "code" && s.code));
+    }
+}
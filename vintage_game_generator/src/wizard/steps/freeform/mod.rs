@@ -9,12 +9,19 @@ use bevy::prelude::*;
 use bevy_egui::{EguiContexts, egui};
 
 mod conversation;
+mod markdown;
+mod sessions;
 mod types;
+mod voice_input;
 
 pub use conversation::*;
+pub use markdown::*;
+pub use sessions::*;
 pub use types::*;
+pub use voice_input::*;
 
 /// Main entry point for rendering freeform mode
+#[allow(clippy::too_many_arguments)]
 pub fn render_freeform_mode(
     contexts: EguiContexts,
     app_state: ResMut<AppState>,
@@ -22,6 +29,8 @@ pub fn render_freeform_mode(
     commands: Commands,
     pipeline: Res<GenerationPipeline>,
     stream_res: ResMut<ConversationStream>,
+    sessions: ResMut<ConversationSessions>,
+    availability: AiAvailability,
 ) {
     // Route to appropriate sub-step
     match &freeform_state.current_step {
@@ -54,11 +63,29 @@ pub fn render_freeform_mode(
                 commands,
                 pipeline,
                 stream_res,
+                sessions,
+                availability,
             );
         }
+        FreeformStep::RenderError(_) => {
+            render_error_screen(contexts, freeform_state);
+        }
     }
 }
 
+fn render_error_screen(mut contexts: EguiContexts, mut freeform_state: ResMut<FreeformModeState>) {
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+    egui::CentralPanel::default().show(ctx, |ui| {
+        ui.heading("⚠ Display Interrupted");
+        ui.label("The UI context was unavailable for too long, so this step stopped rendering.");
+        if ui.button("Retry").clicked() {
+            freeform_state.retry_render();
+        }
+    });
+}
+
 fn ui_placeholder(mut contexts: EguiContexts, name: &str) {
     let Ok(ctx) = contexts.ctx_mut() else {
         return;
@@ -74,18 +101,27 @@ pub fn setup_freeform_mode(mut commands: Commands) {
     // Insert the freeform mode state
     commands.insert_resource(FreeformModeState::default());
     commands.insert_resource(ConversationStream::default());
+    commands.insert_resource(ConversationSessions::default());
 
-    // Initialize AI client if needed
-    if std::env::var("OPENAI_API_KEY").is_ok() {
-        // Initialize AI resources
-        info!("OpenAI API key found, AI conversation will be available");
-    } else {
-        warn!("No OpenAI API key found, AI features will be limited");
+    let availability = AiAvailability::detect();
+    match availability {
+        AiAvailability::Offline => {
+            info!("Offline mode enabled, AI responses will come from stub fixtures")
+        }
+        AiAvailability::Available => {
+            info!("OpenAI API key found, AI conversation will be available")
+        }
+        AiAvailability::Unavailable => {
+            warn!("No OpenAI API key found, AI conversation is disabled")
+        }
     }
+    commands.insert_resource(availability);
 }
 
 /// Cleanup resources when leaving freeform mode
 pub fn cleanup_freeform_mode(mut commands: Commands) {
     commands.remove_resource::<FreeformModeState>();
     commands.remove_resource::<ConversationStream>();
+    commands.remove_resource::<ConversationSessions>();
+    commands.remove_resource::<AiAvailability>();
 }
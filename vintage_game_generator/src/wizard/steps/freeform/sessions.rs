@@ -0,0 +1,337 @@
+//! Named conversation sessions so separate game ideas keep separate threads
+
+use super::ConversationState;
+use crate::wizard::config::AiContext;
+use anyhow::{Context, Result};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+pub type SessionId = u32;
+
+/// A named conversation thread
+pub struct ConversationSession {
+    pub name: String,
+    /// Stored thread. Left empty while the session is active, since the live
+    /// state is kept in `FreeformModeState::conversation`.
+    conversation: ConversationState,
+}
+
+impl ConversationSession {
+    fn new(name: String) -> Self {
+        Self {
+            name,
+            conversation: ConversationState::default(),
+        }
+    }
+}
+
+/// On-disk form of a single session
+#[derive(Serialize, Deserialize)]
+struct SavedSession {
+    name: String,
+    ai_context: AiContext,
+}
+
+/// All conversation sessions plus which one is shown
+#[derive(Resource)]
+pub struct ConversationSessions {
+    sessions: BTreeMap<SessionId, ConversationSession>,
+    active: SessionId,
+    next_id: SessionId,
+    /// Directory sessions are saved into, one file per session
+    pub save_dir: Option<PathBuf>,
+}
+
+impl Default for ConversationSessions {
+    fn default() -> Self {
+        let mut sessions = BTreeMap::new();
+        sessions.insert(0, ConversationSession::new(default_name(0)));
+        Self {
+            sessions,
+            active: 0,
+            next_id: 1,
+            save_dir: None,
+        }
+    }
+}
+
+fn default_name(id: SessionId) -> String {
+    format!("Game {}", id + 1)
+}
+
+impl ConversationSessions {
+    pub fn active(&self) -> SessionId {
+        self.active
+    }
+
+    pub fn active_name(&self) -> &str {
+        self.sessions
+            .get(&self.active)
+            .map(|session| session.name.as_str())
+            .unwrap_or_default()
+    }
+
+    /// Session ids and names in creation order
+    pub fn iter(&self) -> impl Iterator<Item = (SessionId, &str)> {
+        self.sessions
+            .iter()
+            .map(|(id, session)| (*id, session.name.as_str()))
+    }
+
+    /// Create an empty session and make it active
+    pub fn create(&mut self, current: &mut ConversationState) -> SessionId {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.sessions
+            .insert(id, ConversationSession::new(default_name(id)));
+        self.switch_to(id, current);
+        id
+    }
+
+    /// Stash the current thread and load another session into `current`
+    pub fn switch_to(&mut self, id: SessionId, current: &mut ConversationState) -> bool {
+        if id == self.active {
+            return true;
+        }
+        let Some(target) = self.sessions.get_mut(&id) else {
+            return false;
+        };
+
+        let incoming = std::mem::take(&mut target.conversation);
+        let outgoing = std::mem::replace(current, incoming);
+        if let Some(previous) = self.sessions.get_mut(&self.active) {
+            previous.conversation = outgoing;
+        }
+        self.active = id;
+        true
+    }
+
+    pub fn rename(&mut self, id: SessionId, name: impl Into<String>) -> bool {
+        match self.sessions.get_mut(&id) {
+            Some(session) => {
+                session.name = name.into();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Whether the active session still has its placeholder name
+    pub fn active_has_default_name(&self) -> bool {
+        self.active_name() == default_name(self.active)
+    }
+
+    /// Name the active session after its conversation, unless the user has
+    /// already given it a name of their own
+    pub fn apply_auto_title(&mut self, title: &str) -> bool {
+        if !self.active_has_default_name() {
+            return false;
+        }
+        self.rename(self.active, title)
+    }
+
+    /// Delete a session. Deleting the active one switches to another session,
+    /// creating a fresh one if it was the last.
+    pub fn delete(&mut self, id: SessionId, current: &mut ConversationState) -> bool {
+        if !self.sessions.contains_key(&id) {
+            return false;
+        }
+
+        if id == self.active {
+            match self.sessions.keys().copied().find(|&other| other != id) {
+                Some(other) => {
+                    self.switch_to(other, current);
+                }
+                None => {
+                    self.create(current);
+                }
+            }
+        }
+
+        self.sessions.remove(&id);
+        if let Some(dir) = &self.save_dir {
+            let _ = std::fs::remove_file(session_path(dir, id));
+        }
+        true
+    }
+
+    /// Save one session; the active session is read from `current`
+    pub fn save_session(&self, id: SessionId, current: &ConversationState) -> Result<()> {
+        let (Some(dir), Some(session)) = (&self.save_dir, self.sessions.get(&id)) else {
+            return Ok(());
+        };
+        let conversation = if id == self.active {
+            current
+        } else {
+            &session.conversation
+        };
+
+        let mut ai_context = AiContext::default();
+        conversation.save_to(&mut ai_context);
+        let content = toml::to_string_pretty(&SavedSession {
+            name: session.name.clone(),
+            ai_context,
+        })
+        .context("Failed to serialize conversation session")?;
+
+        std::fs::create_dir_all(dir).context("Failed to create conversations directory")?;
+        std::fs::write(session_path(dir, id), content)
+            .context("Failed to write conversation session")?;
+        Ok(())
+    }
+
+    /// Load every saved session in `dir`, making the first one active
+    pub fn load(dir: PathBuf, current: &mut ConversationState) -> Self {
+        let mut sessions = BTreeMap::new();
+        for (id, path) in saved_session_files(&dir) {
+            match read_session(&path) {
+                Ok(saved) => {
+                    let session = ConversationSession {
+                        name: saved.name,
+                        conversation: ConversationState::load_from(&saved.ai_context),
+                    };
+                    sessions.insert(id, session);
+                }
+                Err(e) => warn!("Skipping conversation session {}: {e}", path.display()),
+            }
+        }
+
+        let Some(&first) = sessions.keys().next() else {
+            return Self {
+                save_dir: Some(dir),
+                ..Self::default()
+            };
+        };
+        let next_id = sessions.keys().max().map_or(0, |max| max + 1);
+        if let Some(session) = sessions.get_mut(&first) {
+            *current = std::mem::take(&mut session.conversation);
+        }
+
+        Self {
+            sessions,
+            active: first,
+            next_id,
+            save_dir: Some(dir),
+        }
+    }
+}
+
+fn session_path(dir: &Path, id: SessionId) -> PathBuf {
+    dir.join(format!("session_{id}.toml"))
+}
+
+fn saved_session_files(dir: &Path) -> Vec<(SessionId, PathBuf)> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            let id = path
+                .file_stem()?
+                .to_str()?
+                .strip_prefix("session_")?
+                .parse()
+                .ok()?;
+            Some((id, path))
+        })
+        .collect()
+}
+
+fn read_session(path: &Path) -> Result<SavedSession> {
+    let content = std::fs::read_to_string(path)?;
+    Ok(toml::from_str(&content)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wizard::steps::freeform::ConversationStreamEvent;
+
+    fn say(conversation: &mut ConversationState, text: &str) {
+        conversation.apply_stream_event(ConversationStreamEvent::Token(text.to_string()));
+        conversation.apply_stream_event(ConversationStreamEvent::Finished);
+    }
+
+    #[test]
+    fn test_switching_preserves_each_thread() {
+        let mut sessions = ConversationSessions::default();
+        let mut current = ConversationState::default();
+        let first = sessions.active();
+        say(&mut current, "Space shooter ideas");
+
+        let second = sessions.create(&mut current);
+        assert!(current.history.is_empty());
+        say(&mut current, "Farming sim ideas");
+
+        assert!(sessions.switch_to(first, &mut current));
+        assert_eq!(current.history[0].content, "Space shooter ideas");
+
+        assert!(sessions.switch_to(second, &mut current));
+        assert_eq!(current.history[0].content, "Farming sim ideas");
+    }
+
+    #[test]
+    fn test_deleting_active_session_falls_back() {
+        let mut sessions = ConversationSessions::default();
+        let mut current = ConversationState::default();
+        let first = sessions.active();
+        say(&mut current, "Keep me");
+
+        let second = sessions.create(&mut current);
+        assert!(sessions.delete(second, &mut current));
+        assert_eq!(sessions.active(), first);
+        assert_eq!(current.history[0].content, "Keep me");
+
+        // Deleting the last session leaves a fresh one behind
+        assert!(sessions.delete(first, &mut current));
+        assert_eq!(sessions.iter().count(), 1);
+        assert_ne!(sessions.active(), first);
+        assert!(current.history.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_first_exchange_titles_session() {
+        use crate::metaprompts::generator::GameGenerator;
+        use crate::wizard::steps::freeform::{ConversationEntry, ConversationRole, EntryStatus};
+        use std::sync::Arc;
+        use vintage_ai_client::{AiService, stub::StubProvider};
+
+        let mut sessions = ConversationSessions::default();
+        let mut current = ConversationState::default();
+        current.history.push(ConversationEntry {
+            role: ConversationRole::User,
+            content: "A heist game in a floating city".to_string(),
+            timestamp: std::time::SystemTime::now(),
+            metadata: None,
+            status: EntryStatus::Complete,
+            attachment: None,
+        });
+        assert_eq!(current.untitled_first_message(), None);
+        say(&mut current, "Picture grappling between sky islands...");
+
+        let first_message = current.untitled_first_message().unwrap().to_string();
+        current.titled_message = Some(first_message.clone());
+        assert_eq!(current.untitled_first_message(), None);
+
+        let mut service = AiService::new().unwrap();
+        service.stub = Some(Arc::new(
+            StubProvider::default().with_fixture("floating city", "\"Skyvault Heist.\"\n"),
+        ));
+        let title = GameGenerator::with_service(service)
+            .suggest_title(&first_message)
+            .await
+            .unwrap();
+
+        assert!(sessions.apply_auto_title(&title));
+        assert_eq!(sessions.active_name(), "Skyvault Heist");
+
+        // A name the user chose is never overwritten
+        sessions.rename(sessions.active(), "My heist");
+        assert!(!sessions.apply_auto_title("Something Else"));
+        assert_eq!(sessions.active_name(), "My heist");
+    }
+}
@@ -1,7 +1,20 @@
 //! Types and data structures for freeform mode
 
+use crate::metaprompts::generator::{
+    DEFAULT_GAME_DESIGN_SYSTEM_PROMPT, DesignOutputMode, DesignProposal, ExtractedSpec,
+};
+use crate::wizard::config::{self, AiContext, DesignDecision};
+use crate::wizard::state::FieldEdit;
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use vintage_ai_client::conversation::ImageAttachment;
+use vintage_ai_client::guardrails::Guardrails;
+use vintage_ai_client::text::StructuredUpdate;
+
+/// Receives follow-up suggestions along with the transcript they were requested for
+pub type SuggestionsReceiver =
+    tokio::sync::oneshot::Receiver<(String, Result<Vec<String>, String>)>;
 
 /// The current step in the freeform wizard process
 #[derive(Debug, Clone, PartialEq, Default)]
@@ -15,8 +28,14 @@ pub enum FreeformStep {
     TechnicalSettings, // Performance, platforms
     Review,            // Review before conversation
     Conversation,      // AI conversation phase
+    /// egui was unavailable for too long; holds the step to retry
+    RenderError(Box<FreeformStep>),
 }
 
+/// Consecutive frames without an egui context before freeform mode gives up
+/// on the current step and shows the recovery screen
+pub const CONTEXT_FAILURE_LIMIT: u32 = 60;
+
 /// Main state for freeform mode
 #[derive(Resource, Default)]
 pub struct FreeformModeState {
@@ -24,6 +43,36 @@ pub struct FreeformModeState {
     pub game_config: FreeformGameConfig,
     pub conversation: ConversationState,
     pub export: Option<FreeformExport>,
+    /// Frames in a row the egui context couldn't be fetched
+    pub context_failures: u32,
+}
+
+impl FreeformModeState {
+    /// Count a frame without an egui context. Returns `true` on the frame
+    /// that moves to `FreeformStep::RenderError`.
+    pub fn record_context_failure(&mut self) -> bool {
+        self.context_failures += 1;
+        if self.context_failures < CONTEXT_FAILURE_LIMIT
+            || matches!(self.current_step, FreeformStep::RenderError(_))
+        {
+            return false;
+        }
+        let step = std::mem::take(&mut self.current_step);
+        self.current_step = FreeformStep::RenderError(Box::new(step));
+        true
+    }
+
+    pub fn record_context_success(&mut self) {
+        self.context_failures = 0;
+    }
+
+    /// Leave the recovery screen for the step that failed to render
+    pub fn retry_render(&mut self) {
+        if let FreeformStep::RenderError(step) = &mut self.current_step {
+            self.current_step = std::mem::take(step.as_mut());
+        }
+        self.context_failures = 0;
+    }
 }
 
 /// Game configuration being built through the wizard
@@ -67,8 +116,62 @@ pub struct FreeformGameConfig {
     pub multiplayer_settings: Option<MultiplayerSettings>,
 }
 
+/// Whether the conversation has an AI backend to talk to, decided during setup
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AiAvailability {
+    /// An OpenAI API key is set
+    Available,
+    /// Responses come from the offline stub fixtures
+    Offline,
+    #[default]
+    Unavailable,
+}
+
+impl AiAvailability {
+    /// Check the environment for offline mode or an API key
+    pub fn detect() -> Self {
+        Self::from_env(
+            std::env::var(vintage_ai_client::stub::OFFLINE_ENV).as_deref() == Ok("1"),
+            std::env::var("OPENAI_API_KEY").is_ok(),
+        )
+    }
+
+    pub fn from_env(offline: bool, has_api_key: bool) -> Self {
+        if offline {
+            Self::Offline
+        } else if has_api_key {
+            Self::Available
+        } else {
+            Self::Unavailable
+        }
+    }
+
+    pub fn can_send(&self) -> bool {
+        *self != Self::Unavailable
+    }
+
+    /// Explanation to show instead of failing on the first send
+    pub fn banner(&self) -> Option<String> {
+        (!self.can_send()).then(|| {
+            format!(
+                "AI unavailable — set OPENAI_API_KEY or enable offline stub ({}=1)",
+                vintage_ai_client::stub::OFFLINE_ENV
+            )
+        })
+    }
+}
+
+/// Estimated spend in USD above which a send asks for confirmation first
+pub const DEFAULT_COST_CONFIRMATION_THRESHOLD: f64 = 0.10;
+
+/// A message held back until the user confirms its estimated cost
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingSend {
+    pub message: String,
+    pub estimated_cost: f64,
+}
+
 /// Conversation state for AI interaction
-#[derive(Default)]
 pub struct ConversationState {
     pub conversation_id: Option<String>,
     pub history: Vec<ConversationEntry>,
@@ -77,6 +180,83 @@ pub struct ConversationState {
     pub is_streaming: bool,
     pub error_message: Option<String>,
     pub context_summary: String,
+    pub voice_input: super::VoiceInputState,
+    /// Persona and formatting instructions sent with every request
+    pub system_prompt: String,
+    pub show_settings: bool,
+    /// Reference image to send with the next message
+    pub pending_attachment: Option<ImageAttachment>,
+    /// Sends estimated above this many USD need confirming
+    pub cost_threshold: f64,
+    /// Message waiting on a cost confirmation
+    pub pending_send: Option<PendingSend>,
+    /// Whether replies should justify each design decision
+    pub output_mode: DesignOutputMode,
+    /// Reasoning from the most recent design proposal
+    pub design_rationale: Vec<DesignDecision>,
+    /// Prompt-injection filtering of user messages
+    pub guardrails: Guardrails,
+    /// What the guardrails removed from the last message, if anything
+    pub guardrail_warning: Option<String>,
+    /// Receives the spec being extracted from the conversation as it streams in
+    pub spec_extraction_rx: Option<
+        tokio::sync::mpsc::UnboundedReceiver<Result<StructuredUpdate<ExtractedSpec>, String>>,
+    >,
+    /// Fields extracted so far, shown until the whole spec has arrived
+    pub spec_preview: Option<ExtractedSpec>,
+    /// Fields the last spec extraction changed, shown until dismissed
+    pub spec_changes: Option<Vec<FieldEdit>>,
+    /// First user message a session title was requested for
+    pub titled_message: Option<String>,
+    /// Receives the generated session title
+    pub title_rx: Option<tokio::sync::oneshot::Receiver<Result<String, String>>>,
+    /// Follow-up prompts offered after the latest reply
+    pub suggestions: Vec<String>,
+    /// Suggestions already generated, keyed by the transcript they follow
+    pub suggestion_cache: HashMap<String, Vec<String>>,
+    /// Receives suggestions along with the transcript they were requested for
+    pub suggestions_rx: Option<SuggestionsReceiver>,
+}
+
+impl Default for ConversationState {
+    fn default() -> Self {
+        Self {
+            conversation_id: None,
+            history: Vec::new(),
+            current_input: String::new(),
+            is_processing: false,
+            is_streaming: false,
+            error_message: None,
+            context_summary: String::new(),
+            voice_input: Default::default(),
+            system_prompt: DEFAULT_GAME_DESIGN_SYSTEM_PROMPT.to_string(),
+            show_settings: false,
+            pending_attachment: None,
+            cost_threshold: DEFAULT_COST_CONFIRMATION_THRESHOLD,
+            pending_send: None,
+            output_mode: DesignOutputMode::default(),
+            design_rationale: Vec::new(),
+            guardrails: Guardrails::default(),
+            guardrail_warning: None,
+            spec_extraction_rx: None,
+            spec_preview: None,
+            spec_changes: None,
+            titled_message: None,
+            title_rx: None,
+            suggestions: Vec::new(),
+            suggestion_cache: HashMap::new(),
+            suggestions_rx: None,
+        }
+    }
+}
+
+/// Everything the background task needs to send one message
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConversationRequest {
+    pub conversation_id: Option<String>,
+    pub message: String,
+    pub system_prompt: String,
+    pub attachment: Option<ImageAttachment>,
 }
 
 #[derive(Clone)]
@@ -85,6 +265,246 @@ pub struct ConversationEntry {
     pub content: String,
     pub timestamp: std::time::SystemTime,
     pub metadata: Option<ConversationMetadata>,
+    pub status: EntryStatus,
+    /// File name of an image sent with this message
+    pub attachment: Option<String>,
+}
+
+/// Delivery state of a conversation entry
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum EntryStatus {
+    #[default]
+    Complete,
+    /// Tokens are still arriving from the stream
+    Streaming,
+    /// The stream failed part-way through
+    Errored(String),
+}
+
+impl ConversationState {
+    /// Build the payload for the next message using the current settings
+    pub fn build_request(&self, message: String) -> ConversationRequest {
+        ConversationRequest {
+            conversation_id: self.conversation_id.clone(),
+            message,
+            system_prompt: self.system_prompt_with_context(),
+            attachment: self.pending_attachment.clone(),
+        }
+    }
+
+    /// The system prompt followed by the current game context, if any
+    fn system_prompt_with_context(&self) -> String {
+        let system_prompt = self.output_mode.apply_to(&self.system_prompt);
+        if self.context_summary.is_empty() {
+            system_prompt
+        } else {
+            format!("{system_prompt}\n\n{}", self.context_summary)
+        }
+    }
+
+    /// Take the typed message for sending, clearing the input. Returns `None`,
+    /// leaving the input alone, if it's empty, a reply is in progress or no
+    /// AI is available.
+    pub fn take_input(&mut self, availability: AiAvailability) -> Option<String> {
+        let message = self.current_input.trim();
+        if message.is_empty() || self.is_processing || !availability.can_send() {
+            return None;
+        }
+        let message = message.to_string();
+        self.current_input.clear();
+        self.suggestions.clear();
+        Some(message)
+    }
+
+    /// The transcript to request suggestions for, once the assistant has
+    /// finished a reply that has none yet. Suggestions cached for the
+    /// transcript are shown straight away instead.
+    pub fn suggestions_to_request(&mut self) -> Option<String> {
+        if self.suggestions_rx.is_some() || self.is_processing {
+            return None;
+        }
+        let last = self.history.last()?;
+        if last.role != ConversationRole::Assistant || last.status != EntryStatus::Complete {
+            return None;
+        }
+
+        let transcript = self.transcript();
+        match self.suggestion_cache.get(&transcript) {
+            Some(cached) => {
+                if self.suggestions != *cached {
+                    self.suggestions = cached.clone();
+                }
+                None
+            }
+            None => Some(transcript),
+        }
+    }
+
+    /// Remember the suggestions generated for `transcript` and show them
+    pub fn store_suggestions(&mut self, transcript: String, suggestions: Vec<String>) {
+        self.suggestions = suggestions.clone();
+        self.suggestion_cache.insert(transcript, suggestions);
+    }
+
+    /// Fill the input box with a clicked suggestion
+    pub fn use_suggestion(&mut self, index: usize) -> bool {
+        let Some(suggestion) = self.suggestions.get(index) else {
+            return false;
+        };
+        self.current_input = suggestion.clone();
+        true
+    }
+
+    /// Decide whether `message` can be sent right away.
+    ///
+    /// Returns the message if its estimate is within `cost_threshold`;
+    /// otherwise holds it in `pending_send` until the user confirms.
+    pub fn gate_send(&mut self, message: String, estimated_cost: f64) -> Option<String> {
+        if estimated_cost <= self.cost_threshold {
+            return Some(message);
+        }
+        self.pending_send = Some(PendingSend {
+            message,
+            estimated_cost,
+        });
+        None
+    }
+
+    /// Write the conversation and its settings into the project file
+    pub fn save_to(&self, ai_context: &mut AiContext) {
+        ai_context.system_prompt = (self.system_prompt != DEFAULT_GAME_DESIGN_SYSTEM_PROMPT)
+            .then(|| self.system_prompt.clone());
+        ai_context.design_decisions = self.design_rationale.clone();
+        ai_context.conversation_history = self
+            .history
+            .iter()
+            .filter(|entry| entry.status == EntryStatus::Complete)
+            .map(|entry| config::ConversationEntry {
+                timestamp: entry.timestamp.into(),
+                role: entry.role.as_str().to_string(),
+                content: entry.content.clone(),
+                phase: "freeform".to_string(),
+            })
+            .collect();
+    }
+
+    /// Restore a conversation and its settings from the project file
+    pub fn load_from(ai_context: &AiContext) -> Self {
+        let history = ai_context
+            .conversation_history
+            .iter()
+            .map(|entry| ConversationEntry {
+                role: ConversationRole::from_role(&entry.role),
+                content: entry.content.clone(),
+                timestamp: entry.timestamp.into(),
+                metadata: None,
+                status: EntryStatus::Complete,
+                attachment: None,
+            })
+            .collect();
+
+        Self {
+            history,
+            system_prompt: ai_context
+                .system_prompt
+                .clone()
+                .unwrap_or_else(|| DEFAULT_GAME_DESIGN_SYSTEM_PROMPT.to_string()),
+            design_rationale: ai_context.design_decisions.clone(),
+            ..Default::default()
+        }
+    }
+
+    /// Apply a streamed event to the history.
+    ///
+    /// Returns `true` once the stream has ended, either normally or with an error.
+    pub fn apply_stream_event(&mut self, event: ConversationStreamEvent) -> bool {
+        match event {
+            ConversationStreamEvent::Started(conversation_id) => {
+                self.conversation_id = Some(conversation_id);
+                false
+            }
+            ConversationStreamEvent::Token(token) => {
+                match self.history.last_mut() {
+                    Some(entry)
+                        if entry.role == ConversationRole::Assistant
+                            && entry.status == EntryStatus::Streaming =>
+                    {
+                        entry.content.push_str(&token);
+                    }
+                    _ => self.history.push(ConversationEntry {
+                        role: ConversationRole::Assistant,
+                        content: token,
+                        timestamp: std::time::SystemTime::now(),
+                        metadata: None,
+                        status: EntryStatus::Streaming,
+                        attachment: None,
+                    }),
+                }
+                false
+            }
+            ConversationStreamEvent::Finished => {
+                if let Some(entry) = self.streaming_entry_mut() {
+                    entry.status = EntryStatus::Complete;
+                    if let Some(proposal) = DesignProposal::from_response(&entry.content)
+                        && !proposal.rationale.is_empty()
+                    {
+                        self.design_rationale = proposal.rationale;
+                    }
+                }
+                self.is_processing = false;
+                self.is_streaming = false;
+                true
+            }
+            ConversationStreamEvent::Error(e) => {
+                if let Some(entry) = self.streaming_entry_mut() {
+                    entry.status = EntryStatus::Errored(e.clone());
+                }
+                self.error_message = Some(e);
+                self.is_processing = false;
+                self.is_streaming = false;
+                true
+            }
+        }
+    }
+
+    fn streaming_entry_mut(&mut self) -> Option<&mut ConversationEntry> {
+        self.history
+            .last_mut()
+            .filter(|entry| entry.status == EntryStatus::Streaming)
+    }
+
+    /// Completed messages as "role: content" lines, for spec extraction
+    pub fn transcript(&self) -> String {
+        self.history
+            .iter()
+            .filter(|entry| entry.status == EntryStatus::Complete)
+            .map(|entry| format!("{}: {}", entry.role.as_str(), entry.content))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// The first user message, once the assistant has answered it and no
+    /// title has been requested for it yet
+    pub fn untitled_first_message(&self) -> Option<&str> {
+        let first = self
+            .history
+            .iter()
+            .find(|entry| entry.role == ConversationRole::User)?;
+        let answered = self.history.iter().any(|entry| {
+            entry.role == ConversationRole::Assistant && entry.status == EntryStatus::Complete
+        });
+        (answered && self.titled_message.as_deref() != Some(first.content.as_str()))
+            .then_some(first.content.as_str())
+    }
+
+    /// The most recent user message, used to retry a failed response
+    pub fn last_user_message(&self) -> Option<&str> {
+        self.history
+            .iter()
+            .rev()
+            .find(|entry| entry.role == ConversationRole::User)
+            .map(|entry| entry.content.as_str())
+    }
 }
 
 #[derive(Clone, PartialEq)]
@@ -94,6 +514,24 @@ pub enum ConversationRole {
     System,
 }
 
+impl ConversationRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConversationRole::User => "user",
+            ConversationRole::Assistant => "assistant",
+            ConversationRole::System => "system",
+        }
+    }
+
+    fn from_role(role: &str) -> Self {
+        match role {
+            "assistant" => ConversationRole::Assistant,
+            "system" => ConversationRole::System,
+            _ => ConversationRole::User,
+        }
+    }
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct ConversationMetadata {
     pub topic: String,
@@ -103,6 +541,8 @@ pub struct ConversationMetadata {
 
 /// Events for streaming conversation
 pub enum ConversationStreamEvent {
+    /// A new conversation was created with this id
+    Started(String),
     Token(String),
     Finished,
     Error(String),
@@ -304,3 +744,286 @@ pub enum MultiplayerType {
     OnlineCoop,
     Competitive,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repeated_context_failures_show_error_step() {
+        let mut state = FreeformModeState {
+            current_step: FreeformStep::Conversation,
+            ..Default::default()
+        };
+
+        // A success in between starts the count over
+        for _ in 0..CONTEXT_FAILURE_LIMIT - 1 {
+            assert!(!state.record_context_failure());
+        }
+        state.record_context_success();
+        for _ in 0..CONTEXT_FAILURE_LIMIT - 1 {
+            assert!(!state.record_context_failure());
+        }
+        assert_eq!(state.current_step, FreeformStep::Conversation);
+
+        assert!(state.record_context_failure());
+        assert_eq!(
+            state.current_step,
+            FreeformStep::RenderError(Box::new(FreeformStep::Conversation))
+        );
+        // Further failures don't nest the error step or log again
+        assert!(!state.record_context_failure());
+
+        state.retry_render();
+        assert_eq!(state.current_step, FreeformStep::Conversation);
+        assert_eq!(state.context_failures, 0);
+    }
+
+    #[test]
+    fn test_sending_blocked_without_api_key() {
+        let availability = AiAvailability::from_env(false, false);
+        assert_eq!(availability, AiAvailability::Unavailable);
+        assert!(availability.banner().unwrap().contains("OPENAI_API_KEY"));
+
+        let mut state = ConversationState {
+            current_input: " A stealth roguelike ".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(state.take_input(availability), None);
+        assert_eq!(state.current_input, " A stealth roguelike ");
+
+        let offline = AiAvailability::from_env(true, false);
+        assert_eq!(offline.banner(), None);
+        assert_eq!(
+            state.take_input(offline).as_deref(),
+            Some("A stealth roguelike")
+        );
+        assert!(state.current_input.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_extract_spec_from_scripted_conversation() {
+        use crate::metaprompts::generator::GameGenerator;
+        use std::sync::Arc;
+        use vintage_ai_client::{AiService, stub::StubProvider};
+
+        let mut state = ConversationState::default();
+        let user_message = |content: &str| ConversationEntry {
+            role: ConversationRole::User,
+            content: content.to_string(),
+            timestamp: std::time::SystemTime::now(),
+            metadata: None,
+            status: EntryStatus::Complete,
+            attachment: None,
+        };
+        state
+            .history
+            .push(user_message("I want a heist game in a floating city."));
+        state.apply_stream_event(ConversationStreamEvent::Token(
+            "How about 'Skyvault', a stealth roguelike?".to_string(),
+        ));
+        state.apply_stream_event(ConversationStreamEvent::Finished);
+        state.history.push(user_message(
+            "Yes, with grappling hooks and guard routines. Pixel art.",
+        ));
+
+        let transcript = state.transcript();
+        assert!(transcript.starts_with("user: I want a heist game"));
+        assert!(transcript.contains("assistant: How about 'Skyvault'"));
+
+        let mut service = AiService::new().unwrap();
+        service.stub = Some(Arc::new(StubProvider::default().with_fixture(
+            "grappling hooks",
+            r#"```json
+{"title": "Skyvault", "genre": "Stealth roguelike",
+ "mechanics": ["Grappling hooks", " Guard routines "], "visual_style": "Pixel art"}
+```"#,
+        )));
+        let spec = GameGenerator::with_service(service)
+            .extract_spec(&transcript)
+            .await
+            .unwrap();
+
+        let mut app_state = crate::wizard::state::AppState::new();
+        app_state.set_field("genre", "Platformer");
+        let changes = app_state.apply_extracted_spec(&spec);
+
+        assert_eq!(app_state.field("title").as_deref(), Some("Skyvault"));
+        assert_eq!(
+            app_state.field("genre").as_deref(),
+            Some("Stealth roguelike")
+        );
+        assert_eq!(
+            app_state.field("mechanics").as_deref(),
+            Some("Grappling hooks, Guard routines")
+        );
+        assert_eq!(
+            app_state.field("visual_style").as_deref(),
+            Some("Pixel art")
+        );
+
+        let genre = changes
+            .iter()
+            .find(|change| change.field == "genre")
+            .unwrap();
+        assert_eq!(genre.before.as_deref(), Some("Platformer"));
+        assert_eq!(changes.len(), 4);
+
+        // Extracting the same spec again changes nothing
+        assert!(app_state.apply_extracted_spec(&spec).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_clicking_suggestion_fills_input() {
+        use crate::metaprompts::generator::{GameGenerator, SUGGESTION_COUNT};
+        use std::sync::Arc;
+        use vintage_ai_client::{AiService, stub::StubProvider};
+
+        let mut state = ConversationState::default();
+        state.history.push(ConversationEntry {
+            role: ConversationRole::User,
+            content: "A cozy lighthouse keeper game".to_string(),
+            timestamp: std::time::SystemTime::now(),
+            metadata: None,
+            status: EntryStatus::Complete,
+            attachment: None,
+        });
+        assert_eq!(state.suggestions_to_request(), None);
+        state.apply_stream_event(ConversationStreamEvent::Token(
+            "Tend the lamp through storms and meet passing sailors.".to_string(),
+        ));
+        state.apply_stream_event(ConversationStreamEvent::Finished);
+
+        let mut service = AiService::new().unwrap();
+        service.stub = Some(Arc::new(StubProvider::default().with_fixture(
+            "lighthouse keeper",
+            "1. What happens during a storm?\n2. \"Add a fishing minigame\"\n- Who are the sailors?\n",
+        )));
+        let transcript = state.suggestions_to_request().unwrap();
+        let suggestions = GameGenerator::with_service(service)
+            .suggest_follow_ups(&transcript)
+            .await
+            .unwrap();
+        assert_eq!(suggestions.len(), SUGGESTION_COUNT);
+        state.store_suggestions(transcript, suggestions);
+
+        assert!(state.use_suggestion(1));
+        assert_eq!(state.current_input, "Add a fishing minigame");
+        assert!(!state.use_suggestion(SUGGESTION_COUNT));
+
+        // The same conversation is answered from the cache
+        state.suggestions.clear();
+        assert_eq!(state.suggestions_to_request(), None);
+        assert_eq!(state.suggestions[0], "What happens during a storm?");
+    }
+
+    #[test]
+    fn test_stream_chunks_accumulate() {
+        let mut state = ConversationState {
+            is_processing: true,
+            is_streaming: true,
+            ..Default::default()
+        };
+
+        for chunk in ["A retro ", "action ", "RPG"] {
+            assert!(!state.apply_stream_event(ConversationStreamEvent::Token(chunk.to_string())));
+        }
+        assert_eq!(state.history.len(), 1);
+        assert_eq!(state.history[0].status, EntryStatus::Streaming);
+
+        assert!(state.apply_stream_event(ConversationStreamEvent::Finished));
+        assert_eq!(state.history[0].content, "A retro action RPG");
+        assert_eq!(state.history[0].status, EntryStatus::Complete);
+        assert!(!state.is_streaming);
+    }
+
+    #[test]
+    fn test_stream_error_marks_entry() {
+        let mut state = ConversationState::default();
+        state.apply_stream_event(ConversationStreamEvent::Token("Part".to_string()));
+        assert!(state.apply_stream_event(ConversationStreamEvent::Error("timeout".to_string())));
+
+        assert_eq!(
+            state.history[0].status,
+            EntryStatus::Errored("timeout".to_string())
+        );
+        assert_eq!(state.error_message.as_deref(), Some("timeout"));
+    }
+
+    #[test]
+    fn test_system_prompt_included_in_next_request() {
+        let mut state = ConversationState::default();
+        let request = state.build_request("Hello".to_string());
+        assert_eq!(request.system_prompt, DEFAULT_GAME_DESIGN_SYSTEM_PROMPT);
+
+        state.system_prompt = "Answer only in bullet points.".to_string();
+        let request = state.build_request("Design a boss".to_string());
+        assert_eq!(request.system_prompt, "Answer only in bullet points.");
+        assert_eq!(request.message, "Design a boss");
+    }
+
+    #[test]
+    fn test_game_spec_injected_into_request() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config_manager =
+            crate::wizard::config::ConfigManager::new(dir.path(), Some("project")).unwrap();
+        config_manager.config.basic_info.name = "Star Quest".to_string();
+        config_manager.config.basic_info.genre = "Action RPG".to_string();
+        let mut app_state = crate::wizard::state::AppState::new();
+        app_state.set_config_manager(config_manager);
+
+        let state = ConversationState {
+            context_summary: app_state.game_spec_context().unwrap(),
+            ..Default::default()
+        };
+        let request = state.build_request("Design a boss".to_string());
+
+        assert!(
+            request
+                .system_prompt
+                .starts_with(DEFAULT_GAME_DESIGN_SYSTEM_PROMPT)
+        );
+        assert!(request.system_prompt.contains("Title: Star Quest"));
+        assert!(request.system_prompt.contains("Genre: Action RPG"));
+    }
+
+    #[test]
+    fn test_expensive_send_waits_for_confirmation() {
+        let mut state = ConversationState {
+            cost_threshold: 0.05,
+            ..Default::default()
+        };
+
+        let sent = state.gate_send("Short question".to_string(), 0.01);
+        assert_eq!(sent.as_deref(), Some("Short question"));
+        assert!(state.pending_send.is_none());
+
+        let sent = state.gate_send("Huge moodboard".to_string(), 0.20);
+        assert!(sent.is_none());
+        assert_eq!(
+            state.pending_send,
+            Some(PendingSend {
+                message: "Huge moodboard".to_string(),
+                estimated_cost: 0.20,
+            })
+        );
+    }
+
+    #[test]
+    fn test_system_prompt_round_trips_through_save() {
+        let mut state = ConversationState {
+            system_prompt: "Be terse.".to_string(),
+            ..Default::default()
+        };
+        state.apply_stream_event(ConversationStreamEvent::Token("Hi".to_string()));
+        state.apply_stream_event(ConversationStreamEvent::Finished);
+
+        let mut ai_context = AiContext::default();
+        state.save_to(&mut ai_context);
+        let restored = ConversationState::load_from(&ai_context);
+
+        assert_eq!(restored.system_prompt, "Be terse.");
+        assert_eq!(restored.history.len(), 1);
+        assert_eq!(restored.history[0].content, "Hi");
+    }
+}
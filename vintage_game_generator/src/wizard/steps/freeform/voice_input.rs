@@ -0,0 +1,158 @@
+//! Push-to-talk microphone capture for the freeform conversation
+//!
+//! Audio is captured on a dedicated thread (cpal streams are not `Send`) and
+//! encoded as 16-bit PCM WAV, which Whisper accepts directly.
+
+use anyhow::{Context, Result, anyhow, bail};
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+
+/// Voice input state for the conversation prompt box
+#[derive(Default)]
+pub struct VoiceInputState {
+    pub recording: Option<MicRecording>,
+    /// The microphone failed to start during the current press; not retried
+    /// until the button is released
+    pub start_failed: bool,
+    pub is_transcribing: bool,
+    pub transcription_rx: Option<tokio::sync::oneshot::Receiver<Result<String, String>>>,
+}
+
+/// An in-progress microphone recording
+pub struct MicRecording {
+    stop_tx: mpsc::Sender<()>,
+    handle: JoinHandle<Result<Vec<u8>>>,
+}
+
+impl MicRecording {
+    /// Start recording from the default input device
+    pub fn start() -> Result<Self> {
+        let (stop_tx, stop_rx) = mpsc::channel();
+        let (ready_tx, ready_rx) = mpsc::channel();
+
+        let handle = std::thread::spawn(move || record_until_stopped(stop_rx, ready_tx));
+
+        match ready_rx.recv() {
+            Ok(Ok(())) => Ok(Self { stop_tx, handle }),
+            Ok(Err(e)) => Err(anyhow!(e)),
+            Err(_) => Err(anyhow!("Recording thread exited before starting")),
+        }
+    }
+
+    /// Stop recording and return the captured audio as WAV bytes
+    pub fn stop(self) -> Result<Vec<u8>> {
+        let _ = self.stop_tx.send(());
+        self.handle
+            .join()
+            .map_err(|_| anyhow!("Recording thread panicked"))?
+    }
+}
+
+fn record_until_stopped(
+    stop_rx: mpsc::Receiver<()>,
+    ready_tx: mpsc::Sender<Result<(), String>>,
+) -> Result<Vec<u8>> {
+    let (stream, samples, sample_rate, channels) = match open_input_stream() {
+        Ok(opened) => {
+            let _ = ready_tx.send(Ok(()));
+            opened
+        }
+        Err(e) => {
+            let _ = ready_tx.send(Err(e.to_string()));
+            return Err(e);
+        }
+    };
+
+    // Block until push-to-talk is released (or the UI goes away)
+    let _ = stop_rx.recv();
+    drop(stream);
+
+    let samples = samples
+        .lock()
+        .map_err(|_| anyhow!("Sample buffer poisoned"))?;
+    if samples.is_empty() {
+        bail!("No audio was captured");
+    }
+    Ok(encode_wav(&samples, sample_rate, channels))
+}
+
+type OpenedStream = (cpal::Stream, Arc<Mutex<Vec<i16>>>, u32, u16);
+
+fn open_input_stream() -> Result<OpenedStream> {
+    let host = cpal::default_host();
+    let device = host
+        .default_input_device()
+        .context("No microphone available")?;
+    let config = device
+        .default_input_config()
+        .context("Failed to query microphone configuration")?;
+
+    let sample_rate = config.sample_rate().0;
+    let channels = config.channels();
+    let samples = Arc::new(Mutex::new(Vec::new()));
+    let err_fn = |e| tracing::error!("Microphone stream error: {e}");
+
+    let stream = match config.sample_format() {
+        cpal::SampleFormat::F32 => {
+            let samples = samples.clone();
+            device.build_input_stream(
+                &config.into(),
+                move |data: &[f32], _: &_| {
+                    if let Ok(mut buffer) = samples.lock() {
+                        buffer.extend(
+                            data.iter()
+                                .map(|s| (s.clamp(-1.0, 1.0) * i16::MAX as f32) as i16),
+                        );
+                    }
+                },
+                err_fn,
+                None,
+            )?
+        }
+        cpal::SampleFormat::I16 => {
+            let samples = samples.clone();
+            device.build_input_stream(
+                &config.into(),
+                move |data: &[i16], _: &_| {
+                    if let Ok(mut buffer) = samples.lock() {
+                        buffer.extend_from_slice(data);
+                    }
+                },
+                err_fn,
+                None,
+            )?
+        }
+        format => bail!("Unsupported microphone sample format: {format:?}"),
+    };
+
+    stream.play().context("Failed to start microphone")?;
+    Ok((stream, samples, sample_rate, channels))
+}
+
+/// Encode interleaved 16-bit samples as a PCM WAV file
+fn encode_wav(samples: &[i16], sample_rate: u32, channels: u16) -> Vec<u8> {
+    let data_len = (samples.len() * 2) as u32;
+    let byte_rate = sample_rate * channels as u32 * 2;
+    let mut wav = Vec::with_capacity(44 + data_len as usize);
+
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+    wav.extend_from_slice(b"WAVE");
+    wav.extend_from_slice(b"fmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    wav.extend_from_slice(&channels.to_le_bytes());
+    wav.extend_from_slice(&sample_rate.to_le_bytes());
+    wav.extend_from_slice(&byte_rate.to_le_bytes());
+    wav.extend_from_slice(&(channels * 2).to_le_bytes());
+    wav.extend_from_slice(&16u16.to_le_bytes());
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_len.to_le_bytes());
+    for sample in samples {
+        wav.extend_from_slice(&sample.to_le_bytes());
+    }
+
+    wav
+}
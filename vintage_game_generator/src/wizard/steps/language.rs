@@ -20,6 +20,14 @@ impl LanguageChoice {
         }
     }
 
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            LanguageChoice::Rust => "Rust",
+            LanguageChoice::Python => "Python",
+            LanguageChoice::Ruby => "Ruby",
+        }
+    }
+
     pub fn parse(s: &str) -> Option<Self> {
         match s {
             "rust" => Some(LanguageChoice::Rust),
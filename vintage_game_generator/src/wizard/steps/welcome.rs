@@ -4,16 +4,22 @@ use crate::wizard::overlay::{
     ClickableAreaConfig as ClickableImageConfig, show_image_with_overlays,
 };
 use bevy_egui::egui;
+use std::path::PathBuf;
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum WelcomeAction {
     GuidedMode,
     FreeformMode,
+    /// Resume a previously generated project directory
+    LoadProject(PathBuf),
+    /// Start a new project that blends two generated ones
+    BlendProjects(PathBuf, PathBuf),
 }
 
 pub fn draw_welcome_step(
     ui: &mut egui::Ui,
     config_manager: &mut Option<ConfigManager>,
+    projects: &[(PathBuf, Option<String>)],
 ) -> Option<WelcomeAction> {
     let mut action = None;
 
@@ -278,6 +284,13 @@ pub fn draw_welcome_step(
 
         ui.add_space(20.0);
 
+        if let Some(path) = draw_load_project(ui, projects) {
+            action = Some(WelcomeAction::LoadProject(path));
+        }
+        if let Some((first, second)) = draw_blend_projects(ui, projects) {
+            action = Some(WelcomeAction::BlendProjects(first, second));
+        }
+
         // Info text at bottom
         ui.separator();
         ui.add_space(10.0);
@@ -286,3 +299,88 @@ pub fn draw_welcome_step(
 
     action
 }
+
+fn project_label(path: &std::path::Path, name: &Option<String>) -> String {
+    name.clone().unwrap_or_else(|| {
+        path.file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    })
+}
+
+/// Two pickers over the saved projects and a button to blend them
+fn draw_blend_projects(
+    ui: &mut egui::Ui,
+    projects: &[(PathBuf, Option<String>)],
+) -> Option<(PathBuf, PathBuf)> {
+    if projects.len() < 2 {
+        return None;
+    }
+    let mut selected = None;
+
+    egui::CollapsingHeader::new(egui::RichText::new("🧬 Blend two projects").size(16.0)).show(
+        ui,
+        |ui| {
+            let picks_id = ui.make_persistent_id("blend_project_picks");
+            let (mut first, mut second) =
+                ui.data_mut(|d| d.get_temp::<(usize, usize)>(picks_id).unwrap_or((0, 1)));
+            // The project list may have shrunk since the picks were stored
+            first = first.min(projects.len() - 1);
+            second = second.min(projects.len() - 1);
+
+            for (label, pick) in [("First:", &mut first), ("Second:", &mut second)] {
+                ui.horizontal(|ui| {
+                    ui.label(label);
+                    egui::ComboBox::from_id_salt(("blend_project", label))
+                        .selected_text(project_label(&projects[*pick].0, &projects[*pick].1))
+                        .show_ui(ui, |ui| {
+                            for (index, (path, name)) in projects.iter().enumerate() {
+                                ui.selectable_value(pick, index, project_label(path, name));
+                            }
+                        });
+                });
+            }
+
+            ui.add_enabled_ui(first != second, |ui| {
+                if ui.button("Blend").clicked() {
+                    selected = Some((projects[first].0.clone(), projects[second].0.clone()));
+                }
+            });
+            ui.data_mut(|d| d.insert_temp(picks_id, (first, second)));
+        },
+    );
+
+    selected
+}
+
+/// Recent projects plus a path field for opening one from elsewhere
+fn draw_load_project(ui: &mut egui::Ui, projects: &[(PathBuf, Option<String>)]) -> Option<PathBuf> {
+    let mut selected = None;
+
+    egui::CollapsingHeader::new(egui::RichText::new("📂 Continue an existing project").size(16.0))
+        .default_open(!projects.is_empty())
+        .show(ui, |ui| {
+            for (path, name) in projects.iter().take(5) {
+                if ui
+                    .button(project_label(path, name))
+                    .on_hover_text(path.display().to_string())
+                    .clicked()
+                {
+                    selected = Some(path.clone());
+                }
+            }
+
+            let path_id = ui.make_persistent_id("load_project_path");
+            let mut path = ui.data_mut(|d| d.get_temp::<String>(path_id).unwrap_or_default());
+            ui.horizontal(|ui| {
+                ui.label("Project folder:");
+                ui.text_edit_singleline(&mut path);
+                if ui.button("Open").clicked() && !path.trim().is_empty() {
+                    selected = Some(PathBuf::from(path.trim()));
+                }
+            });
+            ui.data_mut(|d| d.insert_temp(path_id, path));
+        });
+
+    selected
+}
@@ -0,0 +1,72 @@
+//! Debug overlay showing AI spend and latency, toggled with F3
+
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, egui};
+use vintage_ai_client::telemetry::GeneratorStats;
+
+use super::GenerationPipeline;
+
+/// Whether the telemetry overlay is shown
+#[derive(Resource, Default)]
+pub struct TelemetryOverlay {
+    pub visible: bool,
+}
+
+pub fn toggle_telemetry_overlay(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut overlay: ResMut<TelemetryOverlay>,
+) {
+    if keys.just_pressed(KeyCode::F3) {
+        overlay.visible = !overlay.visible;
+    }
+}
+
+pub fn draw_telemetry_overlay(
+    mut contexts: EguiContexts,
+    overlay: Res<TelemetryOverlay>,
+    pipeline: Res<GenerationPipeline>,
+) {
+    if !overlay.visible {
+        return;
+    }
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+
+    let snapshot = pipeline.telemetry.snapshot();
+    egui::Window::new("AI Telemetry")
+        .anchor(egui::Align2::RIGHT_TOP, [-10.0, 10.0])
+        .resizable(false)
+        .show(ctx, |ui| {
+            egui::Grid::new("telemetry_grid")
+                .striped(true)
+                .show(ui, |ui| {
+                    for heading in [
+                        "Generator",
+                        "Calls",
+                        "Cache hits",
+                        "Tokens",
+                        "Cost",
+                        "Avg latency",
+                    ] {
+                        ui.strong(heading);
+                    }
+                    ui.end_row();
+
+                    for (name, stats) in &snapshot.generators {
+                        stats_row(ui, name, stats);
+                    }
+                    stats_row(ui, "Total", &snapshot.total);
+                });
+        });
+}
+
+fn stats_row(ui: &mut egui::Ui, name: &str, stats: &GeneratorStats) {
+    ui.label(name);
+    ui.label(stats.calls.to_string());
+    ui.label(format!("{:.0}%", stats.cache_hit_ratio() * 100.0));
+    ui.label(stats.tokens.to_string());
+    ui.label(format!("${:.4}", stats.cost));
+    ui.label(format!("{} ms", stats.average_latency().as_millis()));
+    ui.end_row();
+}
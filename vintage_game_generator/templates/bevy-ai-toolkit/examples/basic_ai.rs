@@ -4,6 +4,7 @@ use bevy_ai_toolkit::prelude::*;
 #[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
 enum EnemyState {
     Idle,
+    Patrolling,
     Chasing,
     Attacking,
 }
@@ -13,10 +14,48 @@ fn main() {
         .add_plugins(DefaultPlugins)
         .add_plugins(AiToolkitPlugin)
         .add_systems(Startup, setup)
-        .add_systems(Update, (update_enemy_state, update_enemy_behavior))
+        .add_systems(Update, report_enemy_state.after(tick_behavior_trees))
         .run();
 }
 
+/// Behavior leaf that switches the enemy into the given state.
+struct EnterState(EnemyState);
+
+impl BehaviorNode for EnterState {
+    fn tick(&mut self, entity: Entity, world: &mut World) -> NodeStatus {
+        match world.get_mut::<StateMachine<EnemyState>>(entity) {
+            Some(mut state_machine) => {
+                state_machine.transition_to(self.0);
+                NodeStatus::Success
+            }
+            None => NodeStatus::Failure,
+        }
+    }
+}
+
+/// Attack when the target is close, chase it while it's visible, else
+/// patrol if the enemy has an `IdleBehavior` and stand idle if not.
+fn enemy_tree() -> BehaviorTree {
+    BehaviorTree {
+        root: Box::new(Selector::new(vec![
+            Box::new(Sequence::new(vec![
+                Box::new(TargetInRange { range: 2.0 }),
+                Box::new(EnterState(EnemyState::Attacking)),
+            ])),
+            Box::new(Sequence::new(vec![
+                Box::new(HasTarget),
+                Box::new(EnterState(EnemyState::Chasing)),
+                Box::new(ChaseTarget),
+            ])),
+            Box::new(Sequence::new(vec![
+                Box::new(Wander),
+                Box::new(EnterState(EnemyState::Patrolling)),
+            ])),
+            Box::new(EnterState(EnemyState::Idle)),
+        ])),
+    }
+}
+
 fn setup(mut commands: Commands) {
     // Spawn player
     commands.spawn((
@@ -37,32 +76,17 @@ fn setup(mut commands: Commands) {
             field_of_view: 360.0,
         },
         Target::default(),
+        IdleBehavior::patrol(vec![
+            Vec3::new(5.0, 0.0, 5.0),
+            Vec3::new(5.0, 0.0, -5.0),
+            Vec3::new(-5.0, 0.0, -5.0),
+        ]),
+        enemy_tree(),
         Name::new("Enemy"),
     ));
 }
 
-fn update_enemy_state(
-    mut query: Query<(&mut StateMachine<EnemyState>, &Target, &GlobalTransform)>,
-    player_query: Query<&GlobalTransform, With<Targetable>>,
-) {
-    for (mut state_machine, target, transform) in query.iter_mut() {
-        if let Some(target_entity) = target.entity {
-            if let Ok(target_transform) = player_query.get(target_entity) {
-                let distance = transform.translation().distance(target_transform.translation());
-                
-                if distance < 2.0 {
-                    state_machine.transition_to(EnemyState::Attacking);
-                } else {
-                    state_machine.transition_to(EnemyState::Chasing);
-                }
-            }
-        } else {
-            state_machine.transition_to(EnemyState::Idle);
-        }
-    }
-}
-
-fn update_enemy_behavior(query: Query<(&StateMachine<EnemyState>, &Name)>) {
+fn report_enemy_state(query: Query<(&StateMachine<EnemyState>, &Name)>) {
     for (state_machine, name) in query.iter() {
         println!("{} is currently {:?}", name, state_machine.current_state);
     }
@@ -0,0 +1,145 @@
+//! Data-driven behavior trees.
+//!
+//! Designers lay out composites in RON, referring to leaves by the keys they
+//! were registered under:
+//!
+//! ```ron
+//! Selector([
+//!     Sequence([Leaf("target_in_range"), Leaf("attack")]),
+//!     Leaf("patrol"),
+//! ])
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{BehaviorNode, BehaviorTree, RandomSelector, Selector, Sequence};
+
+type LeafFactory = Box<dyn Fn() -> Box<dyn BehaviorNode> + Send + Sync>;
+
+/// Leaves that `BehaviorTreeDef`s can refer to by key.
+#[derive(Resource, Default)]
+pub struct BehaviorRegistry {
+    leaves: HashMap<String, LeafFactory>,
+}
+
+impl BehaviorRegistry {
+    pub fn register_leaf(
+        &mut self,
+        key: impl Into<String>,
+        factory: impl Fn() -> Box<dyn BehaviorNode> + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.leaves.insert(key.into(), Box::new(factory));
+        self
+    }
+}
+
+/// Serializable description of a behavior tree node and its subtree.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum BehaviorTreeDef {
+    Selector(Vec<BehaviorTreeDef>),
+    Sequence(Vec<BehaviorTreeDef>),
+    RandomSelector(Vec<BehaviorTreeDef>),
+    Leaf(String),
+}
+
+/// A definition referred to a leaf missing from the registry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TreeDefError {
+    UnknownLeaf(String),
+}
+
+impl fmt::Display for TreeDefError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TreeDefError::UnknownLeaf(key) => write!(f, "unknown leaf `{key}`"),
+        }
+    }
+}
+
+impl std::error::Error for TreeDefError {}
+
+impl BehaviorTreeDef {
+    pub fn from_ron(source: &str) -> Result<Self, ron::error::SpannedError> {
+        ron::from_str(source)
+    }
+
+    pub fn to_ron(&self) -> Result<String, ron::Error> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+    }
+
+    /// Instantiate the node and its subtree.
+    pub fn build_node(
+        &self,
+        registry: &BehaviorRegistry,
+    ) -> Result<Box<dyn BehaviorNode>, TreeDefError> {
+        let children = |defs: &[BehaviorTreeDef]| {
+            defs.iter()
+                .map(|def| def.build_node(registry))
+                .collect::<Result<Vec<_>, _>>()
+        };
+
+        Ok(match self {
+            BehaviorTreeDef::Selector(defs) => Box::new(Selector::new(children(defs)?)),
+            BehaviorTreeDef::Sequence(defs) => Box::new(Sequence::new(children(defs)?)),
+            BehaviorTreeDef::RandomSelector(defs) => Box::new(RandomSelector::new(children(defs)?)),
+            BehaviorTreeDef::Leaf(key) => {
+                let leaf = registry
+                    .leaves
+                    .get(key)
+                    .ok_or_else(|| TreeDefError::UnknownLeaf(key.clone()))?;
+                leaf()
+            }
+        })
+    }
+
+    /// Instantiate a whole tree rooted at this node.
+    pub fn build(&self, registry: &BehaviorRegistry) -> Result<BehaviorTree, TreeDefError> {
+        Ok(BehaviorTree {
+            root: self.build_node(registry)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::behavior_tree::NodeStatus;
+
+    struct Idle;
+
+    impl BehaviorNode for Idle {
+        fn tick(&mut self, _entity: Entity, _world: &mut World) -> NodeStatus {
+            NodeStatus::Success
+        }
+    }
+
+    #[test]
+    fn test_ron_tree_round_trips_and_builds() {
+        let mut registry = BehaviorRegistry::default();
+        registry.register_leaf("idle", || Box::new(Idle));
+
+        let def =
+            BehaviorTreeDef::from_ron(r#"Selector([Sequence([Leaf("idle")]), Leaf("idle")])"#)
+                .unwrap();
+        assert_eq!(
+            BehaviorTreeDef::from_ron(&def.to_ron().unwrap()).unwrap(),
+            def
+        );
+
+        let tree = def.build(&registry).unwrap();
+        assert_eq!(tree.root.name(), "Selector");
+        assert_eq!(tree.root.children().len(), 2);
+
+        assert_eq!(
+            BehaviorTreeDef::Leaf("attack".to_string())
+                .build(&registry)
+                .err()
+                .map(|e| e.to_string()),
+            Some("unknown leaf `attack`".to_string())
+        );
+    }
+}
@@ -0,0 +1,218 @@
+//! Reloads RON behavior trees when their files change, so trees can be tuned
+//! without restarting the game.
+//!
+//! Give an entity a `TreeSource` pointing at its `.ron` file. Saves are
+//! debounced, then every entity using that file gets a freshly built tree.
+//! Send a `ReloadBehaviorTree` event to reload a file immediately.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use bevy::prelude::*;
+
+use super::definition::{BehaviorRegistry, BehaviorTreeDef};
+use super::BehaviorTree;
+
+pub struct BehaviorTreeHotReloadPlugin;
+
+impl Plugin for BehaviorTreeHotReloadPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BehaviorRegistry>()
+            .init_resource::<TreeWatcher>()
+            .add_event::<ReloadBehaviorTree>()
+            .add_systems(Update, (watch_tree_files, reload_behavior_trees).chain());
+    }
+}
+
+/// The RON file an entity's `BehaviorTree` was built from.
+#[derive(Component, Debug, Clone, PartialEq, Eq)]
+pub struct TreeSource(pub PathBuf);
+
+/// Rebuild every tree loaded from `path`.
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct ReloadBehaviorTree {
+    pub path: PathBuf,
+}
+
+/// Tracks tree file modification times.
+#[derive(Resource, Debug)]
+pub struct TreeWatcher {
+    /// Seconds a file must go unchanged before it is reloaded, so an editor
+    /// writing several times per save only triggers one reload
+    pub debounce: f32,
+    modified: HashMap<PathBuf, SystemTime>,
+    /// Seconds since each changed file was last seen changing
+    pending: HashMap<PathBuf, f32>,
+}
+
+impl Default for TreeWatcher {
+    fn default() -> Self {
+        Self {
+            debounce: 0.25,
+            modified: HashMap::new(),
+            pending: HashMap::new(),
+        }
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+}
+
+/// Polls the files behind every `TreeSource` and requests a reload once a
+/// changed file has settled for `TreeWatcher::debounce` seconds.
+pub fn watch_tree_files(
+    time: Res<Time>,
+    mut watcher: ResMut<TreeWatcher>,
+    sources: Query<&TreeSource>,
+    mut reloads: EventWriter<ReloadBehaviorTree>,
+) {
+    let watcher = watcher.as_mut();
+
+    for TreeSource(path) in sources.iter() {
+        let Some(modified) = modified_time(path) else {
+            continue;
+        };
+        // On first sighting the entity's tree is already current
+        let previous = watcher.modified.insert(path.clone(), modified);
+        if previous.is_some_and(|previous| previous != modified) {
+            watcher.pending.insert(path.clone(), 0.0);
+        }
+    }
+
+    let delta = time.delta_seconds();
+    let debounce = watcher.debounce;
+    watcher.pending.retain(|path, quiet_for| {
+        *quiet_for += delta;
+        if *quiet_for < debounce {
+            return true;
+        }
+        reloads.send(ReloadBehaviorTree { path: path.clone() });
+        false
+    });
+}
+
+/// Rebuilds the trees named by `ReloadBehaviorTree` events and swaps them in,
+/// reset so they start from their first node. A file that fails to load is
+/// logged and the running trees are kept.
+pub fn reload_behavior_trees(
+    mut events: EventReader<ReloadBehaviorTree>,
+    registry: Res<BehaviorRegistry>,
+    mut trees: Query<(&TreeSource, &mut BehaviorTree)>,
+) {
+    for event in events.read() {
+        let def = match std::fs::read_to_string(&event.path) {
+            Ok(source) => match BehaviorTreeDef::from_ron(&source) {
+                Ok(def) => def,
+                Err(e) => {
+                    warn!("Failed to parse {}: {e}", event.path.display());
+                    continue;
+                }
+            },
+            Err(e) => {
+                warn!("Failed to read {}: {e}", event.path.display());
+                continue;
+            }
+        };
+
+        for (source, mut tree) in trees.iter_mut() {
+            if source.0 != event.path {
+                continue;
+            }
+            match def.build_node(&registry) {
+                Ok(mut root) => {
+                    root.reset();
+                    tree.root = root;
+                }
+                Err(e) => {
+                    warn!("Failed to build {}: {e}", event.path.display());
+                    break;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::behavior_tree::{BehaviorNode, NodeStatus};
+
+    struct Idle;
+
+    impl BehaviorNode for Idle {
+        fn tick(&mut self, _entity: Entity, _world: &mut World) -> NodeStatus {
+            NodeStatus::Success
+        }
+    }
+
+    struct Attack;
+
+    impl BehaviorNode for Attack {
+        fn tick(&mut self, _entity: Entity, _world: &mut World) -> NodeStatus {
+            NodeStatus::Running
+        }
+    }
+
+    fn shape(node: &dyn BehaviorNode) -> String {
+        let children: Vec<String> = node.children().into_iter().map(shape).collect();
+        if children.is_empty() {
+            node.name().to_string()
+        } else {
+            format!("{}({})", node.name(), children.join(", "))
+        }
+    }
+
+    #[test]
+    fn test_reload_replaces_running_tree() {
+        let path = std::env::temp_dir().join(format!(
+            "bevy_ai_toolkit_hot_reload_{}.ron",
+            std::process::id()
+        ));
+        std::fs::write(&path, r#"Sequence([Leaf("idle")])"#).unwrap();
+
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .add_plugins(BehaviorTreeHotReloadPlugin);
+        app.world
+            .resource_mut::<BehaviorRegistry>()
+            .register_leaf("idle", || Box::new(Idle))
+            .register_leaf("attack", || Box::new(Attack));
+
+        let tree = BehaviorTreeDef::from_ron(&std::fs::read_to_string(&path).unwrap())
+            .unwrap()
+            .build(app.world.resource::<BehaviorRegistry>())
+            .unwrap();
+        let guard = app.world.spawn((tree, TreeSource(path.clone()))).id();
+        let bystander = app
+            .world
+            .spawn(BehaviorTree {
+                root: Box::new(Idle),
+            })
+            .id();
+        app.update();
+
+        std::fs::write(&path, r#"Selector([Leaf("attack"), Leaf("idle")])"#).unwrap();
+        app.world
+            .send_event(ReloadBehaviorTree { path: path.clone() });
+        app.update();
+
+        let tree = app.world.get::<BehaviorTree>(guard).unwrap();
+        assert_eq!(shape(tree.root.as_ref()), "Selector(Attack, Idle)");
+        let untouched = app.world.get::<BehaviorTree>(bystander).unwrap();
+        assert_eq!(shape(untouched.root.as_ref()), "Idle");
+
+        // A broken edit leaves the running tree alone
+        std::fs::write(&path, "Selector([").unwrap();
+        app.world
+            .send_event(ReloadBehaviorTree { path: path.clone() });
+        app.update();
+        let tree = app.world.get::<BehaviorTree>(guard).unwrap();
+        assert_eq!(shape(tree.root.as_ref()), "Selector(Attack, Idle)");
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
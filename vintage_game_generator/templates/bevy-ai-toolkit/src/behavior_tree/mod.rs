@@ -1,10 +1,26 @@
+use crate::blackboard::Blackboard;
+use crate::rng::AiRng;
+use crate::update_rate::{should_update, AiUpdateRate};
 use bevy::prelude::*;
+use rand::seq::SliceRandom;
+use std::collections::VecDeque;
+use std::fmt::Write;
+
+pub mod definition;
+#[cfg(feature = "hot_reload")]
+pub mod hot_reload;
 
 pub struct BehaviorTreePlugin;
 
 impl Plugin for BehaviorTreePlugin {
-    fn build(&self, _app: &mut App) {
-        // Add behavior tree systems here
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AiRng>()
+            .init_resource::<BtTraceBuffer>()
+            // Trees read the targets and perception targeting wrote this frame
+            .add_systems(
+                Update,
+                tick_behavior_trees.after(crate::targeting::write_perception),
+            );
     }
 }
 
@@ -17,43 +33,1273 @@ pub enum NodeStatus {
 
 pub trait BehaviorNode: Send + Sync + 'static {
     fn tick(&mut self, entity: Entity, world: &mut World) -> NodeStatus;
+
+    /// Clear any in-progress state so the next tick starts from scratch.
+    fn reset(&mut self) {}
+
+    /// Label used when exporting the tree. Defaults to the type name.
+    fn name(&self) -> &str {
+        let name = std::any::type_name::<Self>();
+        let name = name.split('<').next().unwrap_or(name);
+        name.rsplit("::").next().unwrap_or(name)
+    }
+
+    /// Child nodes, in tick order.
+    fn children(&self) -> Vec<&dyn BehaviorNode> {
+        Vec::new()
+    }
+
+    /// Status returned by the most recent tick, if the node tracks it.
+    fn last_status(&self) -> Option<NodeStatus> {
+        None
+    }
+
+    /// Child that kept this node `Running` on its last tick. By default a
+    /// node's only child, or else the first child reporting `Running`.
+    fn running_child(&self) -> Option<&dyn BehaviorNode> {
+        let children = self.children();
+        match children[..] {
+            [only] => Some(only),
+            _ => children
+                .into_iter()
+                .find(|child| child.last_status() == Some(NodeStatus::Running)),
+        }
+    }
 }
 
+/// Predicate over the world used by condition-driven nodes.
+pub type ConditionFn = Box<dyn Fn(Entity, &World) -> bool + Send + Sync>;
+
 #[derive(Component)]
 pub struct BehaviorTree {
     pub root: Box<dyn BehaviorNode>,
 }
 
+impl BehaviorTree {
+    /// Graphviz DOT for the tree, with each node colored by its last status.
+    ///
+    /// Render with e.g. `dot -Tsvg tree.dot -o tree.svg`.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph BehaviorTree {\n    node [shape=box, style=filled];\n");
+        write_dot_node(self.root.as_ref(), &mut 0, &mut dot);
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Nodes from the root down to the one actually running, which may be a
+    /// leaf that doesn't track its status. Empty unless the root is `Running`.
+    pub fn running_path(&self) -> Vec<&dyn BehaviorNode> {
+        let mut path = Vec::new();
+        let mut node = self.root.as_ref();
+        if node.last_status() != Some(NodeStatus::Running) {
+            return path;
+        }
+        loop {
+            path.push(node);
+            match node.running_child() {
+                Some(child) => node = child,
+                None => return path,
+            }
+        }
+    }
+}
+
+/// Ticks every entity's `BehaviorTree` once, skipping entities whose
+/// `AiUpdateRate` isn't due this frame.
+pub fn tick_behavior_trees(world: &mut World) {
+    let entities: Vec<Entity> = world
+        .query_filtered::<Entity, With<BehaviorTree>>()
+        .iter(world)
+        .collect();
+
+    for entity in entities {
+        if !should_update(world.get::<AiUpdateRate>(entity)) {
+            continue;
+        }
+        // Take the tree out so its nodes can borrow the world mutably. An
+        // earlier entity's tree may have despawned this one.
+        let Some(mut tree) = world
+            .get_entity_mut(entity)
+            .and_then(|mut entity| entity.take::<BehaviorTree>())
+        else {
+            continue;
+        };
+        tree.root.tick(entity, world);
+        if world
+            .get_resource::<BtTraceBuffer>()
+            .is_some_and(|trace| trace.enabled)
+        {
+            let frame = world
+                .get_resource::<bevy::core::FrameCount>()
+                .map_or(0, |count| count.0);
+            let mut trace = world.resource_mut::<BtTraceBuffer>();
+            let root = tree.root.as_ref();
+            trace_node(root, root.name().to_string(), frame, entity, &mut trace);
+        }
+        // The tree may have despawned its own entity
+        if let Some(mut entity) = world.get_entity_mut(entity) {
+            entity.insert(tree);
+        }
+    }
+}
+
+/// One node's status after a tree tick.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BtTraceEntry {
+    pub frame: u32,
+    pub entity: Entity,
+    /// Node names from the root down, with each child's index among its
+    /// siblings, e.g. `Selector/Sequence[1]`
+    pub node_path: String,
+    pub status: NodeStatus,
+}
+
+/// Ring buffer of recent behavior tree outcomes for replaying an odd
+/// decision after the fact, without logging every tick.
+///
+/// While `enabled`, `tick_behavior_trees` records every node that tracks its
+/// last status, dropping the oldest entries beyond `capacity`.
+#[derive(Resource, Debug, Clone)]
+pub struct BtTraceBuffer {
+    pub enabled: bool,
+    pub capacity: usize,
+    entries: VecDeque<BtTraceEntry>,
+}
+
+impl Default for BtTraceBuffer {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            capacity: 256,
+            entries: VecDeque::new(),
+        }
+    }
+}
+
+impl BtTraceBuffer {
+    /// An enabled buffer keeping the last `capacity` entries.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            enabled: true,
+            capacity,
+            entries: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    pub fn record(&mut self, entry: BtTraceEntry) {
+        if self.capacity == 0 {
+            return;
+        }
+        while self.entries.len() >= self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+
+    /// Everything still buffered, oldest first.
+    pub fn entries(&self) -> impl Iterator<Item = &BtTraceEntry> {
+        self.entries.iter()
+    }
+
+    /// Buffered entries for one entity, oldest first.
+    pub fn for_entity(&self, entity: Entity) -> impl Iterator<Item = &BtTraceEntry> {
+        self.entries
+            .iter()
+            .filter(move |entry| entry.entity == entity)
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+/// Records `node` and its subtree. Children that haven't reported a status
+/// are still walked, since a node may track its status when its parent doesn't.
+fn trace_node(
+    node: &dyn BehaviorNode,
+    path: String,
+    frame: u32,
+    entity: Entity,
+    trace: &mut BtTraceBuffer,
+) {
+    if let Some(status) = node.last_status() {
+        trace.record(BtTraceEntry {
+            frame,
+            entity,
+            node_path: path.clone(),
+            status,
+        });
+    }
+    for (index, child) in node.children().into_iter().enumerate() {
+        let child_path = format!("{path}/{}[{index}]", child.name());
+        trace_node(child, child_path, frame, entity, trace);
+    }
+}
+
+/// Writes `node` and its subtree, returning the id assigned to `node`.
+fn write_dot_node(node: &dyn BehaviorNode, next_id: &mut usize, dot: &mut String) -> usize {
+    let id = *next_id;
+    *next_id += 1;
+
+    let color = match node.last_status() {
+        Some(NodeStatus::Success) => "palegreen",
+        Some(NodeStatus::Failure) => "lightcoral",
+        Some(NodeStatus::Running) => "khaki",
+        None => "white",
+    };
+    let label = node.name().replace('"', "\\\"");
+    let _ = writeln!(dot, "    n{id} [label=\"{label}\", fillcolor=\"{color}\"];");
+
+    for child in node.children() {
+        let child_id = write_dot_node(child, next_id, dot);
+        let _ = writeln!(dot, "    n{id} -> n{child_id};");
+    }
+    id
+}
+
+/// Ticks children in order until one succeeds.
+///
+/// With memory enabled, a child that returned `Running` is resumed on the
+/// next tick and children that already failed are skipped until reset.
 pub struct Selector {
     pub children: Vec<Box<dyn BehaviorNode>>,
+    pub memory: bool,
+    pub current: usize,
+    pub last_status: Option<NodeStatus>,
 }
 
-impl BehaviorNode for Selector {
-    fn tick(&mut self, entity: Entity, world: &mut World) -> NodeStatus {
-        for child in &mut self.children {
+impl Selector {
+    pub fn new(children: Vec<Box<dyn BehaviorNode>>) -> Self {
+        Self {
+            children,
+            memory: false,
+            current: 0,
+            last_status: None,
+        }
+    }
+
+    pub fn with_memory(mut self, memory: bool) -> Self {
+        self.memory = memory;
+        self
+    }
+
+    fn tick_children(&mut self, entity: Entity, world: &mut World) -> NodeStatus {
+        let start = if self.memory { self.current } else { 0 };
+        for (index, child) in self.children.iter_mut().enumerate().skip(start) {
             match child.tick(entity, world) {
-                NodeStatus::Success => return NodeStatus::Success,
-                NodeStatus::Running => return NodeStatus::Running,
+                NodeStatus::Success => {
+                    self.current = 0;
+                    return NodeStatus::Success;
+                }
+                NodeStatus::Running => {
+                    self.current = index;
+                    return NodeStatus::Running;
+                }
                 NodeStatus::Failure => continue,
             }
         }
+        self.current = 0;
         NodeStatus::Failure
     }
 }
 
+impl BehaviorNode for Selector {
+    fn tick(&mut self, entity: Entity, world: &mut World) -> NodeStatus {
+        let status = self.tick_children(entity, world);
+        self.last_status = Some(status);
+        status
+    }
+
+    fn reset(&mut self) {
+        self.current = 0;
+        self.children.iter_mut().for_each(|child| child.reset());
+    }
+
+    fn children(&self) -> Vec<&dyn BehaviorNode> {
+        self.children.iter().map(|child| child.as_ref()).collect()
+    }
+
+    fn last_status(&self) -> Option<NodeStatus> {
+        self.last_status
+    }
+
+    fn running_child(&self) -> Option<&dyn BehaviorNode> {
+        self.children.get(self.current).map(|child| child.as_ref())
+    }
+}
+
+/// Ticks children in order until one fails.
+///
+/// With memory enabled, the sequence resumes at the child that returned
+/// `Running` instead of re-running the children that already succeeded.
 pub struct Sequence {
     pub children: Vec<Box<dyn BehaviorNode>>,
+    pub memory: bool,
+    pub current: usize,
+    pub last_status: Option<NodeStatus>,
 }
 
-impl BehaviorNode for Sequence {
-    fn tick(&mut self, entity: Entity, world: &mut World) -> NodeStatus {
-        for child in &mut self.children {
+impl Sequence {
+    pub fn new(children: Vec<Box<dyn BehaviorNode>>) -> Self {
+        Self {
+            children,
+            memory: false,
+            current: 0,
+            last_status: None,
+        }
+    }
+
+    pub fn with_memory(mut self, memory: bool) -> Self {
+        self.memory = memory;
+        self
+    }
+
+    fn tick_children(&mut self, entity: Entity, world: &mut World) -> NodeStatus {
+        let start = if self.memory { self.current } else { 0 };
+        for (index, child) in self.children.iter_mut().enumerate().skip(start) {
             match child.tick(entity, world) {
                 NodeStatus::Success => continue,
-                NodeStatus::Running => return NodeStatus::Running,
-                NodeStatus::Failure => return NodeStatus::Failure,
+                NodeStatus::Running => {
+                    self.current = index;
+                    return NodeStatus::Running;
+                }
+                NodeStatus::Failure => {
+                    self.current = 0;
+                    return NodeStatus::Failure;
+                }
             }
         }
+        self.current = 0;
         NodeStatus::Success
     }
 }
+
+impl BehaviorNode for Sequence {
+    fn tick(&mut self, entity: Entity, world: &mut World) -> NodeStatus {
+        let status = self.tick_children(entity, world);
+        self.last_status = Some(status);
+        status
+    }
+
+    fn reset(&mut self) {
+        self.current = 0;
+        self.children.iter_mut().for_each(|child| child.reset());
+    }
+
+    fn children(&self) -> Vec<&dyn BehaviorNode> {
+        self.children.iter().map(|child| child.as_ref()).collect()
+    }
+
+    fn last_status(&self) -> Option<NodeStatus> {
+        self.last_status
+    }
+
+    fn running_child(&self) -> Option<&dyn BehaviorNode> {
+        self.children.get(self.current).map(|child| child.as_ref())
+    }
+}
+
+/// Ticks children in a random order until one succeeds.
+///
+/// The order is drawn from `AiRng` whenever the selector starts over and kept
+/// while a child is `Running`, so a running child is resumed next tick.
+pub struct RandomSelector {
+    pub children: Vec<Box<dyn BehaviorNode>>,
+    /// Child indices in the order they're tried; empty between runs.
+    pub order: Vec<usize>,
+    pub current: usize,
+    pub last_status: Option<NodeStatus>,
+}
+
+impl RandomSelector {
+    pub fn new(children: Vec<Box<dyn BehaviorNode>>) -> Self {
+        Self {
+            children,
+            order: Vec::new(),
+            current: 0,
+            last_status: None,
+        }
+    }
+
+    fn tick_children(&mut self, entity: Entity, world: &mut World) -> NodeStatus {
+        if self.order.is_empty() {
+            self.order = (0..self.children.len()).collect();
+            let mut rng = world.get_resource_or_insert_with(AiRng::default);
+            self.order.shuffle(&mut rng.0);
+        }
+
+        for position in self.current..self.order.len() {
+            match self.children[self.order[position]].tick(entity, world) {
+                NodeStatus::Success => {
+                    self.finish();
+                    return NodeStatus::Success;
+                }
+                NodeStatus::Running => {
+                    self.current = position;
+                    return NodeStatus::Running;
+                }
+                NodeStatus::Failure => continue,
+            }
+        }
+        self.finish();
+        NodeStatus::Failure
+    }
+
+    fn finish(&mut self) {
+        self.order.clear();
+        self.current = 0;
+    }
+}
+
+impl BehaviorNode for RandomSelector {
+    fn tick(&mut self, entity: Entity, world: &mut World) -> NodeStatus {
+        let status = self.tick_children(entity, world);
+        self.last_status = Some(status);
+        status
+    }
+
+    fn reset(&mut self) {
+        self.finish();
+        self.children.iter_mut().for_each(|child| child.reset());
+    }
+
+    fn children(&self) -> Vec<&dyn BehaviorNode> {
+        self.children.iter().map(|child| child.as_ref()).collect()
+    }
+
+    fn last_status(&self) -> Option<NodeStatus> {
+        self.last_status
+    }
+
+    fn running_child(&self) -> Option<&dyn BehaviorNode> {
+        let index = *self.order.get(self.current)?;
+        Some(self.children[index].as_ref())
+    }
+}
+
+/// Ticks its child, restarting it as needed, until the predicate holds.
+///
+/// Reports `Running` while the predicate is false and `Success` once it is true,
+/// resetting the child so it starts fresh next time.
+pub struct UntilCondition {
+    pub child: Box<dyn BehaviorNode>,
+    pub predicate: ConditionFn,
+    pub last_status: Option<NodeStatus>,
+}
+
+impl UntilCondition {
+    pub fn new(
+        child: Box<dyn BehaviorNode>,
+        predicate: impl Fn(Entity, &World) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            child,
+            predicate: Box::new(predicate),
+            last_status: None,
+        }
+    }
+}
+
+impl BehaviorNode for UntilCondition {
+    fn tick(&mut self, entity: Entity, world: &mut World) -> NodeStatus {
+        let status = if (self.predicate)(entity, world) {
+            self.child.reset();
+            NodeStatus::Success
+        } else {
+            if self.child.tick(entity, world) != NodeStatus::Running {
+                self.child.reset();
+            }
+            NodeStatus::Running
+        };
+        self.last_status = Some(status);
+        status
+    }
+
+    fn reset(&mut self) {
+        self.child.reset();
+    }
+
+    fn children(&self) -> Vec<&dyn BehaviorNode> {
+        vec![self.child.as_ref()]
+    }
+
+    fn last_status(&self) -> Option<NodeStatus> {
+        self.last_status
+    }
+}
+
+/// How many children must reach a status for a `Parallel` to report it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParallelPolicy {
+    RequireOne,
+    RequireAll,
+}
+
+impl ParallelPolicy {
+    fn is_met(self, count: usize, total: usize) -> bool {
+        match self {
+            ParallelPolicy::RequireOne => count > 0,
+            ParallelPolicy::RequireAll => count == total,
+        }
+    }
+}
+
+/// Ticks every child every tick.
+///
+/// Fails once the failure policy is met, otherwise succeeds once the success
+/// policy is met, and is `Running` until then. Finishing either way resets
+/// all children, aborting any that were still running.
+pub struct Parallel {
+    pub children: Vec<Box<dyn BehaviorNode>>,
+    pub success_policy: ParallelPolicy,
+    pub failure_policy: ParallelPolicy,
+    pub last_status: Option<NodeStatus>,
+}
+
+impl Parallel {
+    pub fn new(
+        children: Vec<Box<dyn BehaviorNode>>,
+        success_policy: ParallelPolicy,
+        failure_policy: ParallelPolicy,
+    ) -> Self {
+        Self {
+            children,
+            success_policy,
+            failure_policy,
+            last_status: None,
+        }
+    }
+}
+
+impl BehaviorNode for Parallel {
+    fn tick(&mut self, entity: Entity, world: &mut World) -> NodeStatus {
+        let (mut successes, mut failures) = (0, 0);
+        for child in self.children.iter_mut() {
+            match child.tick(entity, world) {
+                NodeStatus::Success => successes += 1,
+                NodeStatus::Failure => failures += 1,
+                NodeStatus::Running => {}
+            }
+        }
+
+        let total = self.children.len();
+        let status = if self.failure_policy.is_met(failures, total) {
+            NodeStatus::Failure
+        } else if self.success_policy.is_met(successes, total) {
+            NodeStatus::Success
+        } else {
+            NodeStatus::Running
+        };
+        if status != NodeStatus::Running {
+            self.reset();
+        }
+        self.last_status = Some(status);
+        status
+    }
+
+    fn reset(&mut self) {
+        self.children.iter_mut().for_each(|child| child.reset());
+    }
+
+    fn children(&self) -> Vec<&dyn BehaviorNode> {
+        self.children.iter().map(|child| child.as_ref()).collect()
+    }
+
+    fn last_status(&self) -> Option<NodeStatus> {
+        self.last_status
+    }
+}
+
+/// Runs `action` while `condition` keeps succeeding.
+///
+/// The condition is checked every tick alongside the action. The moment it
+/// fails the whole node fails and the action is reset mid-run; it succeeds
+/// when the action finishes with the condition still holding.
+pub fn monitor(
+    condition: Box<dyn BehaviorNode>,
+    action: Box<dyn BehaviorNode>,
+) -> Box<dyn BehaviorNode> {
+    Box::new(Parallel::new(
+        vec![condition, action],
+        ParallelPolicy::RequireAll,
+        ParallelPolicy::RequireOne,
+    ))
+}
+
+/// A node wrapping a single child whose status it transforms.
+///
+/// Implementors only provide the child slot and `decorate`; ticking, resetting
+/// and introspection come from the blanket `BehaviorNode` impl.
+pub trait Decorator: Send + Sync + 'static {
+    fn child(&self) -> &dyn BehaviorNode;
+
+    fn child_mut(&mut self) -> &mut dyn BehaviorNode;
+
+    /// Map the status the child just returned to this node's status.
+    fn decorate(&mut self, status: NodeStatus) -> NodeStatus;
+
+    /// Clear decorator state; the child is reset separately.
+    fn reset_decorator(&mut self) {}
+
+    /// Status returned by the most recent tick, if the decorator tracks it.
+    fn last_status(&self) -> Option<NodeStatus> {
+        None
+    }
+}
+
+impl<T: Decorator> BehaviorNode for T {
+    fn tick(&mut self, entity: Entity, world: &mut World) -> NodeStatus {
+        let status = self.child_mut().tick(entity, world);
+        self.decorate(status)
+    }
+
+    fn reset(&mut self) {
+        self.reset_decorator();
+        self.child_mut().reset();
+    }
+
+    fn children(&self) -> Vec<&dyn BehaviorNode> {
+        vec![self.child()]
+    }
+
+    fn last_status(&self) -> Option<NodeStatus> {
+        Decorator::last_status(self)
+    }
+}
+
+/// `outcome` once the child has finished, otherwise still `Running`.
+fn finish_as(status: NodeStatus, outcome: NodeStatus) -> NodeStatus {
+    match status {
+        NodeStatus::Running => NodeStatus::Running,
+        NodeStatus::Success | NodeStatus::Failure => outcome,
+    }
+}
+
+/// Ticks its child and reports `Success` once the child finishes, whatever the outcome.
+pub struct AlwaysSucceed {
+    pub child: Box<dyn BehaviorNode>,
+}
+
+impl Decorator for AlwaysSucceed {
+    fn child(&self) -> &dyn BehaviorNode {
+        self.child.as_ref()
+    }
+
+    fn child_mut(&mut self) -> &mut dyn BehaviorNode {
+        self.child.as_mut()
+    }
+
+    fn decorate(&mut self, status: NodeStatus) -> NodeStatus {
+        finish_as(status, NodeStatus::Success)
+    }
+
+    fn last_status(&self) -> Option<NodeStatus> {
+        self.child
+            .last_status()
+            .map(|status| finish_as(status, NodeStatus::Success))
+    }
+}
+
+/// Ticks its child and reports `Failure` once the child finishes, whatever the outcome.
+pub struct AlwaysFail {
+    pub child: Box<dyn BehaviorNode>,
+}
+
+impl Decorator for AlwaysFail {
+    fn child(&self) -> &dyn BehaviorNode {
+        self.child.as_ref()
+    }
+
+    fn child_mut(&mut self) -> &mut dyn BehaviorNode {
+        self.child.as_mut()
+    }
+
+    fn decorate(&mut self, status: NodeStatus) -> NodeStatus {
+        finish_as(status, NodeStatus::Failure)
+    }
+
+    fn last_status(&self) -> Option<NodeStatus> {
+        self.child
+            .last_status()
+            .map(|status| finish_as(status, NodeStatus::Failure))
+    }
+}
+
+/// Re-runs its child after a failure, giving up after `max_attempts` failures.
+pub struct RetryUntilSuccess {
+    pub child: Box<dyn BehaviorNode>,
+    pub max_attempts: u32,
+    pub attempts: u32,
+    pub last_status: Option<NodeStatus>,
+}
+
+impl RetryUntilSuccess {
+    pub fn new(child: Box<dyn BehaviorNode>, max_attempts: u32) -> Self {
+        Self {
+            child,
+            max_attempts,
+            attempts: 0,
+            last_status: None,
+        }
+    }
+}
+
+impl Decorator for RetryUntilSuccess {
+    fn child(&self) -> &dyn BehaviorNode {
+        self.child.as_ref()
+    }
+
+    fn child_mut(&mut self) -> &mut dyn BehaviorNode {
+        self.child.as_mut()
+    }
+
+    fn decorate(&mut self, status: NodeStatus) -> NodeStatus {
+        let status = match status {
+            NodeStatus::Success => {
+                self.attempts = 0;
+                NodeStatus::Success
+            }
+            NodeStatus::Running => NodeStatus::Running,
+            NodeStatus::Failure => {
+                self.attempts += 1;
+                if self.attempts >= self.max_attempts {
+                    self.attempts = 0;
+                    NodeStatus::Failure
+                } else {
+                    NodeStatus::Running
+                }
+            }
+        };
+        self.last_status = Some(status);
+        status
+    }
+
+    fn reset_decorator(&mut self) {
+        self.attempts = 0;
+    }
+
+    fn last_status(&self) -> Option<NodeStatus> {
+        self.last_status
+    }
+}
+
+/// Fails without ticking its child while the cooldown stored under `key` on
+/// the entity's `Blackboard` is running, so every node sharing the key (e.g.
+/// all dodge variants) honors one timer. Starting the child restarts it.
+pub struct SharedCooldown {
+    pub child: Box<dyn BehaviorNode>,
+    pub key: String,
+    /// Seconds after an activation during which the key is blocked
+    pub duration: f32,
+    pub last_status: Option<NodeStatus>,
+}
+
+impl SharedCooldown {
+    pub fn new(child: Box<dyn BehaviorNode>, key: impl Into<String>, duration: f32) -> Self {
+        Self {
+            child,
+            key: key.into(),
+            duration,
+            last_status: None,
+        }
+    }
+}
+
+impl BehaviorNode for SharedCooldown {
+    fn tick(&mut self, entity: Entity, world: &mut World) -> NodeStatus {
+        let now = world.resource::<Time>().elapsed_seconds();
+
+        // A child still running from this node's own activation keeps going
+        if self.last_status != Some(NodeStatus::Running) {
+            let last_activation = world
+                .get::<Blackboard>(entity)
+                .and_then(|blackboard| blackboard.get_float(&self.key));
+            if last_activation.is_some_and(|at| now - at < self.duration) {
+                self.last_status = Some(NodeStatus::Failure);
+                return NodeStatus::Failure;
+            }
+
+            match world.get_mut::<Blackboard>(entity) {
+                Some(mut blackboard) => blackboard.set(self.key.clone(), now),
+                None => {
+                    let mut blackboard = Blackboard::default();
+                    blackboard.set(self.key.clone(), now);
+                    world.entity_mut(entity).insert(blackboard);
+                }
+            }
+        }
+
+        let status = self.child.tick(entity, world);
+        self.last_status = Some(status);
+        status
+    }
+
+    fn reset(&mut self) {
+        self.child.reset();
+        self.last_status = None;
+    }
+
+    fn children(&self) -> Vec<&dyn BehaviorNode> {
+        vec![self.child.as_ref()]
+    }
+
+    fn last_status(&self) -> Option<NodeStatus> {
+        self.last_status
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+
+    struct Constant(NodeStatus);
+
+    impl BehaviorNode for Constant {
+        fn tick(&mut self, _entity: Entity, _world: &mut World) -> NodeStatus {
+            self.0
+        }
+    }
+
+    fn constant(status: NodeStatus) -> Box<dyn BehaviorNode> {
+        Box::new(Constant(status))
+    }
+
+    /// Returns each scripted status in turn, repeating the last one.
+    struct Scripted(Vec<NodeStatus>);
+
+    impl BehaviorNode for Scripted {
+        fn tick(&mut self, _entity: Entity, _world: &mut World) -> NodeStatus {
+            if self.0.len() > 1 {
+                self.0.remove(0)
+            } else {
+                self.0[0]
+            }
+        }
+    }
+
+    /// Despawns the entity it runs for.
+    struct Despawn;
+
+    impl BehaviorNode for Despawn {
+        fn tick(&mut self, entity: Entity, world: &mut World) -> NodeStatus {
+            world.despawn(entity);
+            NodeStatus::Success
+        }
+    }
+
+    #[test]
+    fn test_plugin_ticks_trees_past_despawned_entities() {
+        let mut app = App::new();
+        app.add_plugins(BehaviorTreePlugin);
+
+        let doomed = app
+            .world
+            .spawn(BehaviorTree {
+                root: Box::new(Despawn),
+            })
+            .id();
+        let ticks = Arc::new(AtomicU32::new(0));
+        app.world.spawn(BehaviorTree {
+            root: Box::new(Counting {
+                ticks: ticks.clone(),
+                resets: Arc::default(),
+            }),
+        });
+
+        app.update();
+        app.update();
+        assert!(app.world.get_entity(doomed).is_none());
+        assert_eq!(ticks.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn test_always_succeed() {
+        let mut world = World::new();
+        let entity = world.spawn_empty().id();
+
+        for status in [NodeStatus::Success, NodeStatus::Failure] {
+            let mut node = AlwaysSucceed {
+                child: constant(status),
+            };
+            assert_eq!(node.tick(entity, &mut world), NodeStatus::Success);
+        }
+
+        let mut running = AlwaysSucceed {
+            child: constant(NodeStatus::Running),
+        };
+        assert_eq!(running.tick(entity, &mut world), NodeStatus::Running);
+    }
+
+    #[test]
+    fn test_always_fail() {
+        let mut world = World::new();
+        let entity = world.spawn_empty().id();
+
+        for status in [NodeStatus::Success, NodeStatus::Failure] {
+            let mut node = AlwaysFail {
+                child: constant(status),
+            };
+            assert_eq!(node.tick(entity, &mut world), NodeStatus::Failure);
+        }
+
+        let mut running = AlwaysFail {
+            child: constant(NodeStatus::Running),
+        };
+        assert_eq!(running.tick(entity, &mut world), NodeStatus::Running);
+    }
+
+    #[test]
+    fn test_retry_until_success() {
+        let mut world = World::new();
+        let entity = world.spawn_empty().id();
+
+        let child = Scripted(vec![
+            NodeStatus::Failure,
+            NodeStatus::Failure,
+            NodeStatus::Success,
+        ]);
+        let mut node = RetryUntilSuccess::new(Box::new(child), 3);
+
+        assert_eq!(node.tick(entity, &mut world), NodeStatus::Running);
+        assert_eq!(node.tick(entity, &mut world), NodeStatus::Running);
+        assert_eq!(node.tick(entity, &mut world), NodeStatus::Success);
+        assert_eq!(node.attempts, 0);
+    }
+
+    #[test]
+    fn test_retry_gives_up_after_max_attempts() {
+        let mut world = World::new();
+        let entity = world.spawn_empty().id();
+
+        let mut node = RetryUntilSuccess::new(constant(NodeStatus::Failure), 2);
+
+        assert_eq!(node.tick(entity, &mut world), NodeStatus::Running);
+        assert_eq!(node.tick(entity, &mut world), NodeStatus::Failure);
+    }
+
+    /// Counts ticks and resets so tests can observe restarts.
+    struct Counting {
+        ticks: Arc<AtomicU32>,
+        resets: Arc<AtomicU32>,
+    }
+
+    impl BehaviorNode for Counting {
+        fn tick(&mut self, _entity: Entity, _world: &mut World) -> NodeStatus {
+            self.ticks.fetch_add(1, Ordering::Relaxed);
+            NodeStatus::Success
+        }
+
+        fn reset(&mut self) {
+            self.resets.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    #[derive(Component)]
+    struct TargetDead(bool);
+
+    #[test]
+    fn test_until_condition_succeeds_once_predicate_holds() {
+        let mut world = World::new();
+        let entity = world.spawn(TargetDead(false)).id();
+
+        let ticks = Arc::new(AtomicU32::new(0));
+        let resets = Arc::new(AtomicU32::new(0));
+        let child = Counting {
+            ticks: ticks.clone(),
+            resets: resets.clone(),
+        };
+        let mut node = UntilCondition::new(Box::new(child), |entity, world| {
+            world.get::<TargetDead>(entity).is_some_and(|dead| dead.0)
+        });
+
+        assert_eq!(node.tick(entity, &mut world), NodeStatus::Running);
+        assert_eq!(node.tick(entity, &mut world), NodeStatus::Running);
+        assert_eq!(ticks.load(Ordering::Relaxed), 2);
+
+        world.get_mut::<TargetDead>(entity).unwrap().0 = true;
+        let resets_before = resets.load(Ordering::Relaxed);
+        assert_eq!(node.tick(entity, &mut world), NodeStatus::Success);
+        assert_eq!(ticks.load(Ordering::Relaxed), 2);
+        assert_eq!(resets.load(Ordering::Relaxed), resets_before + 1);
+    }
+
+    /// Counts how often it is ticked, always reporting the same status.
+    struct Probe {
+        status: NodeStatus,
+        ticks: Arc<AtomicU32>,
+    }
+
+    fn probe(status: NodeStatus) -> (Box<dyn BehaviorNode>, Arc<AtomicU32>) {
+        let ticks = Arc::new(AtomicU32::new(0));
+        let node = Probe {
+            status,
+            ticks: ticks.clone(),
+        };
+        (Box::new(node), ticks)
+    }
+
+    impl BehaviorNode for Probe {
+        fn tick(&mut self, _entity: Entity, _world: &mut World) -> NodeStatus {
+            self.ticks.fetch_add(1, Ordering::Relaxed);
+            self.status
+        }
+    }
+
+    #[test]
+    fn test_sequence_memory_resumes_running_child() {
+        let mut world = World::new();
+        let entity = world.spawn_empty().id();
+
+        for memory in [false, true] {
+            let (first, first_ticks) = probe(NodeStatus::Success);
+            let (middle, middle_ticks) = probe(NodeStatus::Running);
+            let (last, last_ticks) = probe(NodeStatus::Success);
+            let mut sequence = Sequence::new(vec![first, middle, last]).with_memory(memory);
+
+            assert_eq!(sequence.tick(entity, &mut world), NodeStatus::Running);
+            assert_eq!(sequence.tick(entity, &mut world), NodeStatus::Running);
+
+            let expected_first = if memory { 1 } else { 2 };
+            assert_eq!(first_ticks.load(Ordering::Relaxed), expected_first);
+            assert_eq!(middle_ticks.load(Ordering::Relaxed), 2);
+            assert_eq!(last_ticks.load(Ordering::Relaxed), 0);
+        }
+    }
+
+    #[test]
+    fn test_selector_memory_skips_failed_children() {
+        let mut world = World::new();
+        let entity = world.spawn_empty().id();
+
+        for memory in [false, true] {
+            let (first, first_ticks) = probe(NodeStatus::Failure);
+            let (middle, _) = probe(NodeStatus::Running);
+            let mut selector = Selector::new(vec![first, middle]).with_memory(memory);
+
+            assert_eq!(selector.tick(entity, &mut world), NodeStatus::Running);
+            assert_eq!(selector.tick(entity, &mut world), NodeStatus::Running);
+
+            let expected_first = if memory { 1 } else { 2 };
+            assert_eq!(first_ticks.load(Ordering::Relaxed), expected_first);
+
+            selector.reset();
+            selector.tick(entity, &mut world);
+            assert_eq!(first_ticks.load(Ordering::Relaxed), expected_first + 1);
+        }
+    }
+
+    #[test]
+    fn test_to_dot_lists_nodes_and_edges() {
+        let mut world = World::new();
+        let entity = world.spawn_empty().id();
+
+        let mut tree = BehaviorTree {
+            root: Box::new(Selector::new(vec![
+                constant(NodeStatus::Failure),
+                constant(NodeStatus::Success),
+            ])),
+        };
+        tree.root.tick(entity, &mut world);
+
+        let dot = tree.to_dot();
+        assert!(dot.starts_with("digraph BehaviorTree {"));
+        assert!(dot.contains("n0 [label=\"Selector\", fillcolor=\"palegreen\"];"));
+        assert!(dot.contains("n1 [label=\"Constant\", fillcolor=\"white\"];"));
+        assert!(dot.contains("n2 [label=\"Constant\", fillcolor=\"white\"];"));
+        assert!(dot.contains("n0 -> n1;"));
+        assert!(dot.contains("n0 -> n2;"));
+    }
+
+    #[test]
+    fn test_running_path_ends_at_the_running_leaf() {
+        let mut world = World::new();
+        let entity = world.spawn_empty().id();
+
+        let mut tree = BehaviorTree {
+            root: Box::new(Selector::new(vec![
+                constant(NodeStatus::Failure),
+                Box::new(Sequence::new(vec![
+                    constant(NodeStatus::Success),
+                    Box::new(AlwaysSucceed {
+                        child: constant(NodeStatus::Running),
+                    }),
+                ])),
+            ])),
+        };
+        assert!(tree.running_path().is_empty());
+
+        tree.root.tick(entity, &mut world);
+        let path: Vec<&str> = tree.running_path().iter().map(|node| node.name()).collect();
+        assert_eq!(path, ["Selector", "Sequence", "AlwaysSucceed", "Constant"]);
+    }
+
+    /// Custom decorator built only from the trait hooks.
+    struct Inverter {
+        child: Box<dyn BehaviorNode>,
+    }
+
+    impl Decorator for Inverter {
+        fn child(&self) -> &dyn BehaviorNode {
+            self.child.as_ref()
+        }
+
+        fn child_mut(&mut self) -> &mut dyn BehaviorNode {
+            self.child.as_mut()
+        }
+
+        fn decorate(&mut self, status: NodeStatus) -> NodeStatus {
+            match status {
+                NodeStatus::Success => NodeStatus::Failure,
+                NodeStatus::Failure => NodeStatus::Success,
+                NodeStatus::Running => NodeStatus::Running,
+            }
+        }
+    }
+
+    #[test]
+    fn test_custom_decorator_wraps_leaf() {
+        let mut world = World::new();
+        let entity = world.spawn_empty().id();
+
+        let (leaf, ticks) = probe(NodeStatus::Success);
+        let mut node: Box<dyn BehaviorNode> = Box::new(Inverter { child: leaf });
+
+        assert_eq!(node.tick(entity, &mut world), NodeStatus::Failure);
+        assert_eq!(ticks.load(Ordering::Relaxed), 1);
+        assert_eq!(node.children().len(), 1);
+        assert_eq!(node.name(), "Inverter");
+
+        let mut running = Inverter {
+            child: constant(NodeStatus::Running),
+        };
+        assert_eq!(running.tick(entity, &mut world), NodeStatus::Running);
+    }
+
+    #[test]
+    fn test_shared_cooldown_blocks_nodes_with_same_key() {
+        let mut world = World::new();
+        world.init_resource::<Time>();
+        let entity = world.spawn(Blackboard::default()).id();
+
+        let (roll, roll_ticks) = probe(NodeStatus::Success);
+        let (sidestep, sidestep_ticks) = probe(NodeStatus::Success);
+        let (block, block_ticks) = probe(NodeStatus::Success);
+        let mut roll = SharedCooldown::new(roll, "dodge", 2.0);
+        let mut sidestep = SharedCooldown::new(sidestep, "dodge", 2.0);
+        let mut block = SharedCooldown::new(block, "block", 2.0);
+
+        assert_eq!(roll.tick(entity, &mut world), NodeStatus::Success);
+        world
+            .resource_mut::<Time>()
+            .advance_by(std::time::Duration::from_secs(1));
+
+        // Same key is still cooling down, a different key is not
+        assert_eq!(sidestep.tick(entity, &mut world), NodeStatus::Failure);
+        assert_eq!(sidestep_ticks.load(Ordering::Relaxed), 0);
+        assert_eq!(block.tick(entity, &mut world), NodeStatus::Success);
+        assert_eq!(block_ticks.load(Ordering::Relaxed), 1);
+
+        world
+            .resource_mut::<Time>()
+            .advance_by(std::time::Duration::from_millis(1500));
+        assert_eq!(sidestep.tick(entity, &mut world), NodeStatus::Success);
+        assert_eq!(sidestep_ticks.load(Ordering::Relaxed), 1);
+        // The sidestep restarted the shared timer
+        assert_eq!(roll.tick(entity, &mut world), NodeStatus::Failure);
+        assert_eq!(roll_ticks.load(Ordering::Relaxed), 1);
+    }
+
+    /// Reports `Running` and counts how often it is reset.
+    struct Channel {
+        resets: Arc<AtomicU32>,
+    }
+
+    impl BehaviorNode for Channel {
+        fn tick(&mut self, _entity: Entity, _world: &mut World) -> NodeStatus {
+            NodeStatus::Running
+        }
+
+        fn reset(&mut self) {
+            self.resets.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn test_monitor_aborts_action_when_condition_fails() {
+        let mut world = World::new();
+        let entity = world.spawn_empty().id();
+
+        let resets = Arc::new(AtomicU32::new(0));
+        let action = Channel {
+            resets: resets.clone(),
+        };
+        let condition = Scripted(vec![
+            NodeStatus::Success,
+            NodeStatus::Success,
+            NodeStatus::Failure,
+        ]);
+        let mut node = monitor(Box::new(condition), Box::new(action));
+
+        assert_eq!(node.tick(entity, &mut world), NodeStatus::Running);
+        assert_eq!(node.tick(entity, &mut world), NodeStatus::Running);
+        assert_eq!(resets.load(Ordering::Relaxed), 0);
+
+        // The condition flips while the action is still running
+        assert_eq!(node.tick(entity, &mut world), NodeStatus::Failure);
+        assert_eq!(resets.load(Ordering::Relaxed), 1);
+        assert_eq!(node.name(), "Parallel");
+    }
+
+    #[test]
+    fn test_trace_buffer_records_node_paths_per_entity() {
+        let mut world = World::new();
+        world.insert_resource(BtTraceBuffer::new(3));
+
+        let guard = world
+            .spawn(BehaviorTree {
+                root: Box::new(Sequence::new(vec![
+                    Box::new(Selector::new(vec![
+                        constant(NodeStatus::Failure),
+                        constant(NodeStatus::Success),
+                    ])),
+                    constant(NodeStatus::Running),
+                ])),
+            })
+            .id();
+        let scout = world
+            .spawn(BehaviorTree {
+                root: Box::new(Selector::new(vec![constant(NodeStatus::Failure)])),
+            })
+            .id();
+
+        tick_behavior_trees(&mut world);
+
+        let trace = world.resource::<BtTraceBuffer>();
+        let guard_trace: Vec<(&str, NodeStatus)> = trace
+            .for_entity(guard)
+            .map(|entry| (entry.node_path.as_str(), entry.status))
+            .collect();
+        assert_eq!(
+            guard_trace,
+            vec![
+                ("Sequence", NodeStatus::Running),
+                ("Sequence/Selector[0]", NodeStatus::Success),
+            ]
+        );
+        let scout_trace: Vec<&BtTraceEntry> = trace.for_entity(scout).collect();
+        assert_eq!(scout_trace.len(), 1);
+        assert_eq!(scout_trace[0].node_path, "Selector");
+        assert_eq!(scout_trace[0].status, NodeStatus::Failure);
+
+        // A second tick pushes the oldest entries out
+        tick_behavior_trees(&mut world);
+        assert_eq!(world.resource::<BtTraceBuffer>().entries().count(), 3);
+
+        world.resource_mut::<BtTraceBuffer>().enabled = false;
+        world.resource_mut::<BtTraceBuffer>().clear();
+        tick_behavior_trees(&mut world);
+        assert_eq!(world.resource::<BtTraceBuffer>().entries().count(), 0);
+    }
+}
@@ -0,0 +1,216 @@
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+use crate::targeting::{Target, Threat};
+
+/// Keys the targeting system writes perception under.
+pub mod keys {
+    pub const TARGET: &str = "target";
+    pub const LAST_KNOWN_POSITION: &str = "last_known_position";
+    pub const THREAT: &str = "threat";
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BlackboardValue {
+    Entity(Entity),
+    Vec3(Vec3),
+    Float(f32),
+    Bool(bool),
+}
+
+impl From<Entity> for BlackboardValue {
+    fn from(value: Entity) -> Self {
+        Self::Entity(value)
+    }
+}
+
+impl From<Vec3> for BlackboardValue {
+    fn from(value: Vec3) -> Self {
+        Self::Vec3(value)
+    }
+}
+
+impl From<f32> for BlackboardValue {
+    fn from(value: f32) -> Self {
+        Self::Float(value)
+    }
+}
+
+impl From<bool> for BlackboardValue {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+/// Per-entity memory shared by its behavior tree and utility AI, so both
+/// read what was sensed once instead of querying perception separately.
+///
+/// Targeting fills the `keys` entries every frame; games can store their
+/// own values alongside them.
+#[derive(Component, Debug, Clone, Default, PartialEq)]
+pub struct Blackboard {
+    values: HashMap<String, BlackboardValue>,
+}
+
+impl Blackboard {
+    pub fn set(&mut self, key: impl Into<String>, value: impl Into<BlackboardValue>) {
+        self.values.insert(key.into(), value.into());
+    }
+
+    /// Set `key` to `value`, or clear it when `value` is `None`.
+    pub fn set_or_remove<V: Into<BlackboardValue>>(&mut self, key: &str, value: Option<V>) {
+        match value {
+            Some(value) => self.set(key, value),
+            None => self.remove(key),
+        }
+    }
+
+    pub fn remove(&mut self, key: &str) {
+        self.values.remove(key);
+    }
+
+    pub fn get(&self, key: &str) -> Option<BlackboardValue> {
+        self.values.get(key).copied()
+    }
+
+    pub fn get_entity(&self, key: &str) -> Option<Entity> {
+        match self.get(key)? {
+            BlackboardValue::Entity(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn get_vec3(&self, key: &str) -> Option<Vec3> {
+        match self.get(key)? {
+            BlackboardValue::Vec3(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn get_float(&self, key: &str) -> Option<f32> {
+        match self.get(key)? {
+            BlackboardValue::Float(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn get_bool(&self, key: &str) -> Option<bool> {
+        match self.get(key)? {
+            BlackboardValue::Bool(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn target(&self) -> Option<Entity> {
+        self.get_entity(keys::TARGET)
+    }
+
+    pub fn last_known_position(&self) -> Option<Vec3> {
+        self.get_vec3(keys::LAST_KNOWN_POSITION)
+    }
+
+    pub fn threat(&self) -> Option<f32> {
+        self.get_float(keys::THREAT)
+    }
+
+    /// Record what targeting currently perceives.
+    pub fn set_perception(
+        &mut self,
+        target: Option<Entity>,
+        last_known_position: Option<Vec3>,
+        threat: Option<f32>,
+    ) {
+        self.set_or_remove(keys::TARGET, target);
+        self.set_or_remove(keys::LAST_KNOWN_POSITION, last_known_position);
+        self.set_or_remove(keys::THREAT, threat);
+    }
+}
+
+/// The entity's current target, from its `Blackboard` when it has one and
+/// its `Target` otherwise.
+pub fn perceived_target(entity: Entity, world: &World) -> Option<Entity> {
+    match world.get::<Blackboard>(entity) {
+        Some(blackboard) => blackboard.target(),
+        None => world.get::<Target>(entity)?.entity,
+    }
+}
+
+/// Where the entity last saw its target, read like `perceived_target`.
+pub fn perceived_position(entity: Entity, world: &World) -> Option<Vec3> {
+    match world.get::<Blackboard>(entity) {
+        Some(blackboard) => blackboard.last_known_position(),
+        None => world.get::<Target>(entity)?.last_known_position,
+    }
+}
+
+/// How dangerous the entity's current target is, read like `perceived_target`.
+pub fn perceived_threat(entity: Entity, world: &World) -> Option<f32> {
+    match world.get::<Blackboard>(entity) {
+        Some(blackboard) => blackboard.threat(),
+        None => {
+            let target = world.get::<Target>(entity)?.entity?;
+            Some(world.get::<Threat>(target)?.0)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::targeting::write_perception;
+    use crate::utility_ai::scorers::{DistanceScorer, ThreatScorer};
+    use crate::utility_ai::Scorer;
+
+    #[test]
+    fn test_typed_accessors() {
+        let mut blackboard = Blackboard::default();
+        blackboard.set("alerted", true);
+        blackboard.set("morale", 0.25);
+
+        assert_eq!(blackboard.get_bool("alerted"), Some(true));
+        assert_eq!(blackboard.get_float("morale"), Some(0.25));
+        // Wrong type or missing key
+        assert_eq!(blackboard.get_entity("morale"), None);
+        assert_eq!(blackboard.target(), None);
+
+        blackboard.set_or_remove::<f32>("morale", None);
+        assert_eq!(blackboard.get("morale"), None);
+    }
+
+    #[test]
+    fn test_targeting_writes_blackboard_read_by_scorer() {
+        let mut app = App::new();
+        app.add_systems(Update, write_perception);
+
+        let target = app
+            .world
+            .spawn((
+                GlobalTransform::from_translation(Vec3::new(4.0, 0.0, 0.0)),
+                Threat(0.8),
+            ))
+            .id();
+        let hunter = app
+            .world
+            .spawn((
+                GlobalTransform::default(),
+                Target {
+                    entity: Some(target),
+                    last_known_position: Some(Vec3::new(4.0, 0.0, 0.0)),
+                    ..default()
+                },
+                Blackboard::default(),
+            ))
+            .id();
+
+        app.update();
+        let blackboard = app.world.get::<Blackboard>(hunter).unwrap();
+        assert_eq!(blackboard.target(), Some(target));
+        assert_eq!(blackboard.threat(), Some(0.8));
+
+        // Scorers read the blackboard, not the targeting component
+        app.world.entity_mut(hunter).remove::<Target>();
+        assert_eq!(ThreatScorer.score(hunter, &app.world), 0.8);
+        let distance = DistanceScorer { max_range: 8.0 }.score(hunter, &app.world);
+        assert!((distance - 0.5).abs() < 1e-6);
+    }
+}
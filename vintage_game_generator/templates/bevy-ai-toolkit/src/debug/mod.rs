@@ -0,0 +1,230 @@
+//! Live view of what every AI entity is currently doing.
+//!
+//! `AiDebugPlugin` collects one `AiDebugRow` per entity with a state machine,
+//! behavior tree or utility AI each frame. With the `debug_overlay` feature it
+//! also draws them in an egui window.
+
+use bevy::prelude::*;
+use std::fmt::Debug;
+
+use crate::behavior_tree::BehaviorTree;
+use crate::state_machine::StateMachine;
+use crate::utility_ai::UtilityAi;
+
+pub struct AiDebugPlugin;
+
+impl Plugin for AiDebugPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AiDebugStates>()
+            .init_resource::<AiDebugRows>()
+            .add_systems(Last, collect_ai_debug_rows);
+
+        #[cfg(feature = "debug_overlay")]
+        app.add_systems(Update, draw_ai_debug_window);
+    }
+}
+
+type StateReader = fn(&World, Entity) -> Option<String>;
+
+/// State machine types the collector knows how to read.
+#[derive(Resource, Default)]
+pub struct AiDebugStates {
+    readers: Vec<StateReader>,
+}
+
+impl AiDebugStates {
+    pub fn register<S: Component + Clone + Debug>(&mut self) {
+        self.readers.push(|world, entity| {
+            world
+                .get::<StateMachine<S>>(entity)
+                .map(|machine| format!("{:?}", machine.current_state))
+        });
+    }
+}
+
+pub trait AiDebugAppExt {
+    /// Show `StateMachine<S>` states in the debug rows.
+    fn register_debug_state<S: Component + Clone + Debug>(&mut self) -> &mut Self;
+}
+
+impl AiDebugAppExt for App {
+    fn register_debug_state<S: Component + Clone + Debug>(&mut self) -> &mut Self {
+        self.world
+            .get_resource_or_insert_with(AiDebugStates::default)
+            .register::<S>();
+        self
+    }
+}
+
+/// What one AI entity is doing this frame.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AiDebugRow {
+    pub entity: Entity,
+    pub name: Option<String>,
+    pub state: Option<String>,
+    /// Behavior tree node that's running, usually a leaf
+    pub running_node: Option<String>,
+    /// Highest-scoring utility consideration and its weighted score
+    pub top_action: Option<(String, f32)>,
+}
+
+/// Rows gathered by `collect_ai_debug_rows`, sorted by entity.
+#[derive(Resource, Default)]
+pub struct AiDebugRows(pub Vec<AiDebugRow>);
+
+/// One row per entity that has any registered AI component.
+pub fn collect_ai_rows(world: &World, states: &AiDebugStates) -> Vec<AiDebugRow> {
+    let mut rows: Vec<AiDebugRow> = world
+        .iter_entities()
+        .filter_map(|entity_ref| {
+            let entity = entity_ref.id();
+            let state = states
+                .readers
+                .iter()
+                .find_map(|reader| reader(world, entity));
+            let tree = world.get::<BehaviorTree>(entity);
+            let utility = world.get::<UtilityAi>(entity);
+            if state.is_none() && tree.is_none() && utility.is_none() {
+                return None;
+            }
+
+            Some(AiDebugRow {
+                entity,
+                name: world.get::<Name>(entity).map(|name| name.to_string()),
+                state,
+                running_node: tree.and_then(|tree| {
+                    tree.running_path()
+                        .last()
+                        .map(|node| node.name().to_string())
+                }),
+                top_action: utility.and_then(|ai| {
+                    ai.score_all(entity, world)
+                        .into_iter()
+                        .next()
+                        .map(|score| (score.action.unwrap_or(score.name), score.weighted))
+                }),
+            })
+        })
+        .collect();
+
+    rows.sort_by_key(|row| row.entity);
+    rows
+}
+
+pub fn collect_ai_debug_rows(world: &mut World) {
+    let rows = match world.get_resource::<AiDebugStates>() {
+        Some(states) => collect_ai_rows(world, states),
+        None => return,
+    };
+    world.insert_resource(AiDebugRows(rows));
+}
+
+#[cfg(feature = "debug_overlay")]
+pub fn draw_ai_debug_window(mut contexts: bevy_egui::EguiContexts, rows: Res<AiDebugRows>) {
+    use bevy_egui::egui;
+
+    egui::Window::new("AI Decisions").show(contexts.ctx_mut(), |ui| {
+        egui::Grid::new("ai_debug_grid")
+            .striped(true)
+            .show(ui, |ui| {
+                for heading in ["Entity", "State", "Running node", "Top action"] {
+                    ui.strong(heading);
+                }
+                ui.end_row();
+
+                for row in &rows.0 {
+                    ui.label(
+                        row.name
+                            .clone()
+                            .unwrap_or_else(|| format!("{:?}", row.entity)),
+                    );
+                    ui.label(row.state.as_deref().unwrap_or("-"));
+                    ui.label(row.running_node.as_deref().unwrap_or("-"));
+                    ui.label(
+                        row.top_action
+                            .as_ref()
+                            .map_or("-".to_string(), |(action, score)| {
+                                format!("{action} ({score:.2})")
+                            }),
+                    );
+                    ui.end_row();
+                }
+            });
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::behavior_tree::{BehaviorNode, NodeStatus, Selector};
+    use crate::utility_ai::scorers::Constant;
+    use crate::utility_ai::{Action, Consideration};
+
+    #[derive(Component, Clone, Debug)]
+    enum Mode {
+        Patrol,
+    }
+
+    struct Wait;
+
+    impl BehaviorNode for Wait {
+        fn tick(&mut self, _entity: Entity, _world: &mut World) -> NodeStatus {
+            NodeStatus::Running
+        }
+    }
+
+    struct Flee;
+
+    impl Action for Flee {
+        fn execute(&self, _entity: Entity, _commands: &mut Commands) {}
+
+        fn name(&self) -> Option<&str> {
+            Some("flee")
+        }
+    }
+
+    #[test]
+    fn test_collects_one_row_per_ai_entity() {
+        let mut world = World::new();
+        let mut states = AiDebugStates::default();
+        states.register::<Mode>();
+
+        let guard = world
+            .spawn((StateMachine::new(Mode::Patrol), Name::new("Guard")))
+            .id();
+
+        let mut root = Selector::new(vec![Box::new(Wait)]);
+        let mut scratch = World::new();
+        let scratch_entity = scratch.spawn_empty().id();
+        root.tick(scratch_entity, &mut scratch);
+        let wanderer = world
+            .spawn(BehaviorTree {
+                root: Box::new(root),
+            })
+            .id();
+
+        let coward = world
+            .spawn(UtilityAi {
+                considerations: vec![Consideration::named(
+                    "low_health",
+                    Box::new(Constant(0.8)),
+                    Box::new(Flee),
+                )],
+            })
+            .id();
+        world.spawn(Name::new("Rock"));
+
+        let rows = collect_ai_rows(&world, &states);
+        assert_eq!(rows.len(), 3);
+
+        assert_eq!(rows[0].entity, guard);
+        assert_eq!(rows[0].name.as_deref(), Some("Guard"));
+        assert_eq!(rows[0].state.as_deref(), Some("Patrol"));
+
+        assert_eq!(rows[1].entity, wanderer);
+        assert_eq!(rows[1].running_node.as_deref(), Some("Wait"));
+
+        assert_eq!(rows[2].entity, coward);
+        assert_eq!(rows[2].top_action, Some(("flee".to_string(), 0.8)));
+    }
+}
@@ -2,12 +2,26 @@ pub mod state_machine;
 pub mod behavior_tree;
 pub mod utility_ai;
 pub mod targeting;
+pub mod navigation;
+pub mod squad;
+pub mod planner;
+pub mod debug;
+pub mod rng;
+pub mod blackboard;
+pub mod update_rate;
 
 pub mod prelude {
     pub use crate::state_machine::*;
     pub use crate::behavior_tree::*;
     pub use crate::utility_ai::*;
     pub use crate::targeting::*;
+    pub use crate::navigation::*;
+    pub use crate::squad::*;
+    pub use crate::planner::*;
+    pub use crate::debug::*;
+    pub use crate::rng::*;
+    pub use crate::blackboard::*;
+    pub use crate::update_rate::*;
 }
 
 use bevy::prelude::*;
@@ -21,6 +35,10 @@ impl Plugin for AiToolkitPlugin {
             behavior_tree::BehaviorTreePlugin,
             utility_ai::UtilityAiPlugin,
             targeting::TargetingPlugin,
+            navigation::NavigationPlugin::default(),
+            squad::SquadPlugin,
+            planner::PlannerPlugin,
+            update_rate::AiUpdateRatePlugin,
         ));
     }
 }
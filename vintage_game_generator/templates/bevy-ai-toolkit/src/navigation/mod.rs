@@ -0,0 +1,367 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::behavior_tree::{BehaviorNode, NodeStatus};
+use crate::blackboard::perceived_position;
+use crate::rng::AiRng;
+use crate::targeting::TargetingSpace;
+use crate::utility_ai::Action;
+
+/// Routes `NavRequest`s into `MoveTo` goals. With `straight_line_mover`
+/// disabled, a navmesh or other pathfinder can consume the goals instead.
+pub struct NavigationPlugin {
+    pub straight_line_mover: bool,
+}
+
+impl Default for NavigationPlugin {
+    fn default() -> Self {
+        Self {
+            straight_line_mover: true,
+        }
+    }
+}
+
+impl Plugin for NavigationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<NavRequest>()
+            .add_systems(Update, apply_nav_requests);
+
+        if self.straight_line_mover {
+            app.add_systems(Update, move_straight_line.after(apply_nav_requests));
+        }
+    }
+}
+
+/// Movement goal for an entity; removed once the destination is reached.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct MoveTo {
+    pub destination: Vec3,
+}
+
+/// Units per second used by the straight-line mover.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct MoveSpeed(pub f32);
+
+impl Default for MoveSpeed {
+    fn default() -> Self {
+        Self(5.0)
+    }
+}
+
+/// Emitted by AI actions and behavior leaves that want an entity to move.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct NavRequest {
+    pub entity: Entity,
+    pub destination: Vec3,
+}
+
+const ARRIVAL_DISTANCE: f32 = 0.1;
+
+pub fn apply_nav_requests(mut commands: Commands, mut requests: EventReader<NavRequest>) {
+    for request in requests.read() {
+        if let Some(mut entity) = commands.get_entity(request.entity) {
+            entity.insert(MoveTo {
+                destination: request.destination,
+            });
+        }
+    }
+}
+
+pub fn move_straight_line(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Transform, &MoveTo, Option<&MoveSpeed>)>,
+) {
+    for (entity, mut transform, move_to, speed) in query.iter_mut() {
+        let speed = speed.copied().unwrap_or_default().0;
+        let offset = move_to.destination - transform.translation;
+        let step = speed * time.delta_seconds();
+
+        if offset.length() <= step.max(ARRIVAL_DISTANCE) {
+            transform.translation = move_to.destination;
+            commands.entity(entity).remove::<MoveTo>();
+        } else {
+            transform.translation += offset.normalize() * step;
+        }
+    }
+}
+
+/// Sends a `NavRequest` toward the entity's last known target position.
+fn request_chase(entity: Entity, world: &mut World) -> bool {
+    let Some(destination) = perceived_position(entity, world) else {
+        return false;
+    };
+
+    world.send_event(NavRequest {
+        entity,
+        destination,
+    });
+    true
+}
+
+/// Behavior leaf that requests movement toward the last known target position.
+pub struct ChaseTarget;
+
+impl BehaviorNode for ChaseTarget {
+    fn tick(&mut self, entity: Entity, world: &mut World) -> NodeStatus {
+        if request_chase(entity, world) {
+            NodeStatus::Success
+        } else {
+            NodeStatus::Failure
+        }
+    }
+}
+
+/// Utility action that requests movement toward the last known target position.
+pub struct ChaseTargetAction;
+
+impl Action for ChaseTargetAction {
+    fn execute(&self, entity: Entity, commands: &mut Commands) {
+        commands.add(move |world: &mut World| {
+            request_chase(entity, world);
+        });
+    }
+
+    fn name(&self) -> Option<&str> {
+        Some("chase_target")
+    }
+}
+
+/// What an entity does with itself when it has nothing to chase. Opt-in:
+/// `Wander` and `WanderAction` fail for entities without it.
+#[derive(Component, Debug, Clone, PartialEq)]
+pub enum IdleBehavior {
+    /// Stroll to random points within `radius` of `home`
+    Wander { home: Vec3, radius: f32 },
+    /// Walk the waypoints in order, looping back to the first
+    Patrol { waypoints: Vec<Vec3>, next: usize },
+}
+
+impl IdleBehavior {
+    pub fn wander(home: Vec3, radius: f32) -> Self {
+        IdleBehavior::Wander { home, radius }
+    }
+
+    pub fn patrol(waypoints: Vec<Vec3>) -> Self {
+        IdleBehavior::Patrol { waypoints, next: 0 }
+    }
+}
+
+/// Sends a `NavRequest` for the entity's next idle destination. An entity
+/// still walking to its last one is left alone.
+fn request_idle_move(entity: Entity, world: &mut World) -> bool {
+    if world.get::<MoveTo>(entity).is_some() {
+        return true;
+    }
+    let Some(idle) = world.get::<IdleBehavior>(entity).cloned() else {
+        return false;
+    };
+
+    let destination = match idle {
+        IdleBehavior::Wander { home, radius } => {
+            let space = world.get_resource::<TargetingSpace>().copied();
+            let mut rng = world.get_resource_or_insert_with(AiRng::default);
+            let angle = rng.0.gen_range(0.0..std::f32::consts::TAU);
+            // sqrt keeps points spread evenly over the disc
+            let distance = radius * rng.0.gen::<f32>().sqrt();
+            let (x, y) = (angle.cos() * distance, angle.sin() * distance);
+            match space.unwrap_or_default() {
+                TargetingSpace::Space2D => home + Vec3::new(x, y, 0.0),
+                TargetingSpace::Space3D => home + Vec3::new(x, 0.0, y),
+            }
+        }
+        IdleBehavior::Patrol { waypoints, next } => {
+            let Some(&waypoint) = waypoints.get(next % waypoints.len().max(1)) else {
+                return false;
+            };
+            if let Some(IdleBehavior::Patrol { next, .. }) =
+                world.get_mut::<IdleBehavior>(entity).as_deref_mut()
+            {
+                *next = (*next + 1) % waypoints.len();
+            }
+            waypoint
+        }
+    };
+
+    world.send_event(NavRequest {
+        entity,
+        destination,
+    });
+    true
+}
+
+/// Behavior leaf that keeps the entity moving per its `IdleBehavior`;
+/// fails if it has none. Put it last in a selector as the idle fallback.
+pub struct Wander;
+
+impl BehaviorNode for Wander {
+    fn tick(&mut self, entity: Entity, world: &mut World) -> NodeStatus {
+        if request_idle_move(entity, world) {
+            NodeStatus::Success
+        } else {
+            NodeStatus::Failure
+        }
+    }
+}
+
+/// Utility action that keeps the entity moving per its `IdleBehavior`.
+pub struct WanderAction;
+
+impl Action for WanderAction {
+    fn execute(&self, entity: Entity, commands: &mut Commands) {
+        commands.add(move |world: &mut World| {
+            request_idle_move(entity, world);
+        });
+    }
+
+    fn name(&self) -> Option<&str> {
+        Some("wander")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::targeting::Target;
+
+    #[test]
+    fn test_chase_emits_move_to_last_known_position() {
+        let mut app = App::new();
+        app.add_plugins(NavigationPlugin {
+            straight_line_mover: false,
+        });
+
+        let destination = Vec3::new(4.0, 0.0, -2.0);
+        let chaser = app
+            .world
+            .spawn(Target {
+                last_known_position: Some(destination),
+                ..default()
+            })
+            .id();
+
+        assert_eq!(
+            ChaseTarget.tick(chaser, &mut app.world),
+            NodeStatus::Success
+        );
+        app.update();
+
+        assert_eq!(
+            app.world.get::<MoveTo>(chaser),
+            Some(&MoveTo { destination })
+        );
+    }
+
+    /// The enemies `bevy_main_rs` generates rely on `AiToolkitPlugin` alone
+    /// to find the player and close in.
+    #[test]
+    fn test_generated_enemies_chase_the_player() {
+        use crate::behavior_tree::BehaviorTree;
+        use crate::targeting::{Targetable, Vision};
+        use crate::utility_ai::scorers::DistanceScorer;
+        use crate::utility_ai::{Consideration, UtilityAi, UtilityRunner};
+        use std::time::Duration;
+
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .add_plugins((bevy::transform::TransformPlugin, crate::AiToolkitPlugin));
+
+        let player = app.world.spawn((SpatialBundle::default(), Targetable)).id();
+        let start = Vec3::new(5.0, 0.0, 5.0);
+        let spawn_enemy = |world: &mut World| {
+            world
+                .spawn((
+                    SpatialBundle::from_transform(Transform::from_translation(start)),
+                    Vision {
+                        range: 10.0,
+                        field_of_view: 360.0,
+                    },
+                    Target::default(),
+                ))
+                .id()
+        };
+        let tree_enemy = spawn_enemy(&mut app.world);
+        app.world.entity_mut(tree_enemy).insert(BehaviorTree {
+            root: Box::new(ChaseTarget),
+        });
+        let utility_enemy = spawn_enemy(&mut app.world);
+        app.world.entity_mut(utility_enemy).insert((
+            UtilityAi {
+                considerations: vec![Consideration::named(
+                    "chase",
+                    Box::new(DistanceScorer { max_range: 10.0 }),
+                    Box::new(ChaseTargetAction),
+                )],
+            },
+            UtilityRunner::default(),
+        ));
+
+        for _ in 0..10 {
+            app.world
+                .resource_mut::<Time>()
+                .advance_by(Duration::from_millis(100));
+            app.update();
+        }
+
+        for enemy in [tree_enemy, utility_enemy] {
+            assert_eq!(app.world.get::<Target>(enemy).unwrap().entity, Some(player));
+            let position = app.world.get::<Transform>(enemy).unwrap().translation;
+            assert!(position.length() < start.length() - 1.0);
+        }
+    }
+
+    #[test]
+    fn test_chase_fails_without_known_position() {
+        let mut app = App::new();
+        app.add_plugins(NavigationPlugin::default());
+
+        let chaser = app.world.spawn(Target::default()).id();
+        assert_eq!(
+            ChaseTarget.tick(chaser, &mut app.world),
+            NodeStatus::Failure
+        );
+    }
+
+    #[test]
+    fn test_idle_entity_with_wander_moves() {
+        let mut app = App::new();
+        app.insert_resource(AiRng::seeded(7))
+            .add_plugins(NavigationPlugin {
+                straight_line_mover: false,
+            });
+
+        let home = Vec3::new(10.0, 0.0, 10.0);
+        let wanderer = app
+            .world
+            .spawn((Target::default(), IdleBehavior::wander(home, 3.0)))
+            .id();
+        let sentry = app.world.spawn(Target::default()).id();
+
+        assert_eq!(Wander.tick(wanderer, &mut app.world), NodeStatus::Success);
+        // Without an idle behavior the entity stays put
+        assert_eq!(Wander.tick(sentry, &mut app.world), NodeStatus::Failure);
+        app.update();
+
+        let goal = app.world.get::<MoveTo>(wanderer).unwrap().destination;
+        assert!(goal.distance(home) <= 3.0);
+        assert_eq!(goal.y, home.y);
+        assert!(app.world.get::<MoveTo>(sentry).is_none());
+
+        let waypoints = vec![Vec3::X, Vec3::Z];
+        let guard = app
+            .world
+            .spawn(IdleBehavior::patrol(waypoints.clone()))
+            .id();
+        for expected in [Vec3::X, Vec3::Z, Vec3::X] {
+            WanderAction.execute_world(guard, &mut app.world);
+            app.update();
+            assert_eq!(
+                app.world.get::<MoveTo>(guard),
+                Some(&MoveTo {
+                    destination: expected
+                })
+            );
+            app.world.entity_mut(guard).remove::<MoveTo>();
+        }
+    }
+}
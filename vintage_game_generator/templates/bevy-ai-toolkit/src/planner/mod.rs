@@ -0,0 +1,255 @@
+use bevy::prelude::*;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap, HashMap};
+use std::sync::Arc;
+
+use crate::utility_ai::Action;
+
+pub struct PlannerPlugin;
+
+impl Plugin for PlannerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, update_goap_agents);
+    }
+}
+
+/// Named boolean facts about the world. Missing facts count as `false`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct WorldState {
+    facts: BTreeMap<String, bool>,
+}
+
+impl WorldState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, fact: impl Into<String>, value: bool) -> Self {
+        self.set(fact, value);
+        self
+    }
+
+    pub fn set(&mut self, fact: impl Into<String>, value: bool) {
+        self.facts.insert(fact.into(), value);
+    }
+
+    pub fn get(&self, fact: &str) -> bool {
+        self.facts.get(fact).copied().unwrap_or(false)
+    }
+
+    /// True if every fact in `conditions` has the same value here.
+    pub fn satisfies(&self, conditions: &WorldState) -> bool {
+        self.unsatisfied(conditions) == 0
+    }
+
+    /// A copy of this state with `effects` applied on top.
+    pub fn apply(&self, effects: &WorldState) -> WorldState {
+        let mut next = self.clone();
+        for (fact, value) in &effects.facts {
+            next.facts.insert(fact.clone(), *value);
+        }
+        next
+    }
+
+    fn unsatisfied(&self, conditions: &WorldState) -> usize {
+        conditions
+            .facts
+            .iter()
+            .filter(|(fact, value)| self.get(fact) != **value)
+            .count()
+    }
+}
+
+/// A plannable step: usable when `preconditions` hold, and assumed to make
+/// `effects` true. Running it delegates to a utility AI `Action`.
+#[derive(Clone)]
+pub struct GoapAction {
+    pub name: String,
+    pub preconditions: WorldState,
+    pub effects: WorldState,
+    pub cost: u32,
+    pub action: Arc<dyn Action>,
+}
+
+impl GoapAction {
+    pub fn new(name: impl Into<String>, cost: u32, action: impl Action) -> Self {
+        Self {
+            name: name.into(),
+            preconditions: WorldState::new(),
+            effects: WorldState::new(),
+            cost,
+            action: Arc::new(action),
+        }
+    }
+
+    pub fn with_precondition(mut self, fact: impl Into<String>, value: bool) -> Self {
+        self.preconditions.set(fact, value);
+        self
+    }
+
+    pub fn with_effect(mut self, fact: impl Into<String>, value: bool) -> Self {
+        self.effects.set(fact, value);
+        self
+    }
+
+    pub fn execute(&self, entity: Entity, commands: &mut Commands) {
+        self.action.execute(entity, commands);
+    }
+}
+
+impl std::fmt::Debug for GoapAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GoapAction")
+            .field("name", &self.name)
+            .field("preconditions", &self.preconditions)
+            .field("effects", &self.effects)
+            .field("cost", &self.cost)
+            .finish()
+    }
+}
+
+struct PlanNode {
+    state: WorldState,
+    cost: u32,
+    parent: Option<(usize, usize)>,
+}
+
+/// Cheapest sequence of actions that turns `current` into a state satisfying
+/// `goal`, found with A*. Empty if the goal already holds or can't be reached.
+pub fn plan(current: &WorldState, goal: &WorldState, actions: &[GoapAction]) -> Vec<GoapAction> {
+    let mut nodes = vec![PlanNode {
+        state: current.clone(),
+        cost: 0,
+        parent: None,
+    }];
+    let mut best_cost = HashMap::from([(current.clone(), 0)]);
+    let mut open = BinaryHeap::new();
+    open.push(Reverse((current.unsatisfied(goal), 0usize)));
+
+    while let Some(Reverse((_, index))) = open.pop() {
+        let state = nodes[index].state.clone();
+        let cost = nodes[index].cost;
+
+        if state.satisfies(goal) {
+            let mut steps = Vec::new();
+            let mut cursor = index;
+            while let Some((parent, action_index)) = nodes[cursor].parent {
+                steps.push(actions[action_index].clone());
+                cursor = parent;
+            }
+            steps.reverse();
+            return steps;
+        }
+
+        // Skip stale heap entries for states already reached more cheaply
+        if best_cost.get(&state).is_some_and(|best| *best < cost) {
+            continue;
+        }
+
+        for (action_index, action) in actions.iter().enumerate() {
+            if !state.satisfies(&action.preconditions) {
+                continue;
+            }
+
+            let next = state.apply(&action.effects);
+            let next_cost = cost + action.cost;
+            if best_cost.get(&next).is_some_and(|best| *best <= next_cost) {
+                continue;
+            }
+
+            best_cost.insert(next.clone(), next_cost);
+            let estimate = next_cost as usize + next.unsatisfied(goal);
+            nodes.push(PlanNode {
+                state: next,
+                cost: next_cost,
+                parent: Some((index, action_index)),
+            });
+            open.push(Reverse((estimate, nodes.len() - 1)));
+        }
+    }
+
+    Vec::new()
+}
+
+/// Goal-driven agent. Game code keeps `state` up to date; the planner
+/// re-plans each frame and runs the first step of the current plan.
+#[derive(Component)]
+pub struct GoapAgent {
+    pub state: WorldState,
+    pub goal: WorldState,
+    pub actions: Vec<GoapAction>,
+    pub plan: Vec<GoapAction>,
+}
+
+impl GoapAgent {
+    pub fn new(goal: WorldState, actions: Vec<GoapAction>) -> Self {
+        Self {
+            state: WorldState::new(),
+            goal,
+            actions,
+            plan: Vec::new(),
+        }
+    }
+}
+
+pub fn update_goap_agents(mut commands: Commands, mut query: Query<(Entity, &mut GoapAgent)>) {
+    for (entity, mut agent) in query.iter_mut() {
+        agent.plan = plan(&agent.state, &agent.goal, &agent.actions);
+        if let Some(step) = agent.plan.first() {
+            step.execute(entity, &mut commands);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Component)]
+    struct Executed(&'static str);
+
+    struct Mark(&'static str);
+
+    impl Action for Mark {
+        fn execute(&self, entity: Entity, commands: &mut Commands) {
+            commands.entity(entity).insert(Executed(self.0));
+        }
+    }
+
+    fn combat_actions() -> Vec<GoapAction> {
+        vec![
+            GoapAction::new("attack", 1, Mark("attack"))
+                .with_precondition("has_weapon", true)
+                .with_effect("target_dead", true),
+            GoapAction::new("get_weapon", 1, Mark("get_weapon")).with_effect("has_weapon", true),
+            // Reaches the goal directly, but costs more than arming up first
+            GoapAction::new("punch", 5, Mark("punch")).with_effect("target_dead", true),
+        ]
+    }
+
+    #[test]
+    fn test_plans_two_step_sequence() {
+        let goal = WorldState::new().with("target_dead", true);
+        let steps = plan(&WorldState::new(), &goal, &combat_actions());
+
+        let names: Vec<&str> = steps.iter().map(|step| step.name.as_str()).collect();
+        assert_eq!(names, vec!["get_weapon", "attack"]);
+
+        let armed = WorldState::new().with("has_weapon", true);
+        assert_eq!(plan(&armed, &goal, &combat_actions()).len(), 1);
+        assert!(plan(&goal, &goal, &combat_actions()).is_empty());
+    }
+
+    #[test]
+    fn test_agent_executes_first_step() {
+        let mut app = App::new();
+        app.add_systems(Update, update_goap_agents);
+
+        let goal = WorldState::new().with("target_dead", true);
+        let agent = app.world.spawn(GoapAgent::new(goal, combat_actions())).id();
+
+        app.update();
+        assert_eq!(app.world.get::<Executed>(agent).unwrap().0, "get_weapon");
+        assert_eq!(app.world.get::<GoapAgent>(agent).unwrap().plan.len(), 2);
+    }
+}
@@ -0,0 +1,151 @@
+use bevy::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+use crate::targeting::Target;
+
+pub struct SquadPlugin;
+
+impl Plugin for SquadPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SquadBlackboard>()
+            .add_systems(Update, update_squad_knowledge);
+    }
+}
+
+/// Marks an entity as a member of the squad with the given id.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Squad(pub u32);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SquadRole {
+    Attacker,
+    Flanker,
+    Support,
+}
+
+/// Everything one squad knows and has agreed on.
+#[derive(Debug, Clone, Default)]
+pub struct SquadKnowledge {
+    pub known_targets: HashSet<Entity>,
+    pub roles: HashMap<Entity, SquadRole>,
+    /// Members currently holding an attack slot on each target.
+    pub attackers: HashMap<Entity, Vec<Entity>>,
+}
+
+/// Shared knowledge for every squad, readable by scorers and behavior nodes.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct SquadBlackboard {
+    pub squads: HashMap<Squad, SquadKnowledge>,
+}
+
+impl SquadBlackboard {
+    pub fn squad(&self, squad: Squad) -> Option<&SquadKnowledge> {
+        self.squads.get(&squad)
+    }
+
+    pub fn assign_role(&mut self, squad: Squad, member: Entity, role: SquadRole) {
+        self.squads
+            .entry(squad)
+            .or_default()
+            .roles
+            .insert(member, role);
+    }
+
+    pub fn attackers_of(&self, squad: Squad, target: Entity) -> &[Entity] {
+        self.squads
+            .get(&squad)
+            .and_then(|knowledge| knowledge.attackers.get(&target))
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+    }
+
+    /// Whether another squad member already holds a slot on `target`.
+    pub fn is_claimed_by_other(&self, squad: Squad, member: Entity, target: Entity) -> bool {
+        self.attackers_of(squad, target)
+            .iter()
+            .any(|attacker| *attacker != member)
+    }
+
+    /// Claims one of `max_attackers` slots on `target`. Returns `true` if the
+    /// member holds a slot afterwards.
+    pub fn claim_target(
+        &mut self,
+        squad: Squad,
+        member: Entity,
+        target: Entity,
+        max_attackers: usize,
+    ) -> bool {
+        let attackers = self
+            .squads
+            .entry(squad)
+            .or_default()
+            .attackers
+            .entry(target)
+            .or_default();
+
+        if attackers.contains(&member) {
+            return true;
+        }
+        if attackers.len() >= max_attackers {
+            return false;
+        }
+        attackers.push(member);
+        true
+    }
+
+    pub fn release_target(&mut self, squad: Squad, member: Entity, target: Entity) {
+        if let Some(knowledge) = self.squads.get_mut(&squad) {
+            if let Some(attackers) = knowledge.attackers.get_mut(&target) {
+                attackers.retain(|attacker| *attacker != member);
+                if attackers.is_empty() {
+                    knowledge.attackers.remove(&target);
+                }
+            }
+        }
+    }
+}
+
+/// Rebuilds each squad's known targets from its members' current targets.
+pub fn update_squad_knowledge(
+    mut blackboard: ResMut<SquadBlackboard>,
+    members: Query<(&Squad, &Target)>,
+) {
+    for knowledge in blackboard.squads.values_mut() {
+        knowledge.known_targets.clear();
+    }
+
+    for (squad, target) in members.iter() {
+        if let Some(target_entity) = target.entity {
+            blackboard
+                .squads
+                .entry(*squad)
+                .or_default()
+                .known_targets
+                .insert(target_entity);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_single_attacker_slot_is_exclusive() {
+        let mut world = World::new();
+        let first = world.spawn_empty().id();
+        let second = world.spawn_empty().id();
+        let target = world.spawn_empty().id();
+
+        let squad = Squad(1);
+        let mut blackboard = SquadBlackboard::default();
+
+        assert!(blackboard.claim_target(squad, first, target, 1));
+        assert!(!blackboard.claim_target(squad, second, target, 1));
+        assert!(blackboard.is_claimed_by_other(squad, second, target));
+        assert_eq!(blackboard.attackers_of(squad, target), &[first]);
+
+        blackboard.release_target(squad, first, target);
+        assert!(blackboard.claim_target(squad, second, target, 1));
+    }
+}
@@ -1,6 +1,10 @@
 use bevy::prelude::*;
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
 use std::marker::PhantomData;
 
+use crate::behavior_tree::BehaviorTree;
+
 pub struct StateMachinePlugin;
 
 impl Plugin for StateMachinePlugin {
@@ -12,6 +16,10 @@ impl Plugin for StateMachinePlugin {
 #[derive(Component)]
 pub struct StateMachine<S: Component + Clone> {
     pub current_state: S,
+    /// Actions run every frame while the machine is in the paired state.
+    actions: Vec<(S, Box<dyn StateAction<S>>)>,
+    /// `Vec<EventTransition<S, E>>` for each event type `E`.
+    event_transitions: HashMap<TypeId, Box<dyn Any + Send + Sync>>,
     _phantom: PhantomData<S>,
 }
 
@@ -19,6 +27,8 @@ impl<S: Component + Clone> StateMachine<S> {
     pub fn new(initial_state: S) -> Self {
         Self {
             current_state: initial_state,
+            actions: Vec::new(),
+            event_transitions: HashMap::new(),
             _phantom: PhantomData,
         }
     }
@@ -26,8 +36,328 @@ impl<S: Component + Clone> StateMachine<S> {
     pub fn transition_to(&mut self, next_state: S) {
         self.current_state = next_state;
     }
+
+    /// Run `action` every frame while in `state`.
+    pub fn with_action(mut self, state: S, action: impl StateAction<S>) -> Self {
+        self.add_action(state, action);
+        self
+    }
+
+    pub fn add_action(&mut self, state: S, action: impl StateAction<S>) {
+        self.actions.push((state, Box::new(action)));
+    }
+
+    /// Transition when an `E` event matching `transition` arrives.
+    pub fn with_event_transition<E: Event>(mut self, transition: EventTransition<S, E>) -> Self {
+        self.react_to_event(transition);
+        self
+    }
+
+    /// Transition when an `E` event matching `transition` arrives, instead
+    /// of polling for the change every frame. Requires
+    /// `add_event_transitions::<S, E>()`.
+    pub fn react_to_event<E: Event>(&mut self, transition: EventTransition<S, E>) {
+        self.event_transitions
+            .entry(TypeId::of::<E>())
+            .or_insert_with(|| Box::new(Vec::<EventTransition<S, E>>::new()))
+            .downcast_mut::<Vec<EventTransition<S, E>>>()
+            .expect("event transitions are keyed by their event type")
+            .push(transition);
+    }
+
+    /// State the first matching transition for `event` leads to, if any.
+    fn event_target<E: Event>(&self, entity: Entity, event: &E) -> Option<S>
+    where
+        S: PartialEq,
+    {
+        self.event_transitions
+            .get(&TypeId::of::<E>())?
+            .downcast_ref::<Vec<EventTransition<S, E>>>()?
+            .iter()
+            .find(|transition| {
+                transition
+                    .from
+                    .as_ref()
+                    .is_none_or(|from| *from == self.current_state)
+                    && (transition.predicate)(entity, event)
+            })
+            .map(|transition| transition.to.clone())
+    }
+}
+
+/// Decides whether an event should move the given machine.
+pub type EventPredicate<E> = Box<dyn Fn(Entity, &E) -> bool + Send + Sync>;
+
+/// Moves a machine to `to` when an event of type `E` satisfies `predicate`.
+///
+/// The predicate gets the machine's entity, so it can ignore events aimed at
+/// someone else.
+pub struct EventTransition<S, E> {
+    /// Only fire from this state; any state when `None`.
+    pub from: Option<S>,
+    pub to: S,
+    pub predicate: EventPredicate<E>,
+}
+
+impl<S, E: Event> EventTransition<S, E> {
+    pub fn new(to: S, predicate: impl Fn(Entity, &E) -> bool + Send + Sync + 'static) -> Self {
+        Self {
+            from: None,
+            to,
+            predicate: Box::new(predicate),
+        }
+    }
+
+    pub fn from_state(mut self, state: S) -> Self {
+        self.from = Some(state);
+        self
+    }
 }
 
 pub trait StateAction<S>: Send + Sync + 'static {
     fn execute(&self, entity: Entity, commands: &mut Commands, state: &S);
 }
+
+/// Runs the actions registered for each machine's current state.
+pub fn run_state_actions<S: Component + Clone + PartialEq>(
+    mut commands: Commands,
+    query: Query<(Entity, &StateMachine<S>)>,
+) {
+    for (entity, machine) in query.iter() {
+        for (state, action) in &machine.actions {
+            if *state == machine.current_state {
+                action.execute(entity, &mut commands, state);
+            }
+        }
+    }
+}
+
+/// Applies the transitions each machine registered for `E` events. Added
+/// for each state and event type by `add_event_transitions::<S, E>()`.
+pub fn apply_event_transitions<S: Component + Clone + PartialEq, E: Event>(
+    mut events: EventReader<E>,
+    mut query: Query<(Entity, &mut StateMachine<S>)>,
+) {
+    for event in events.read() {
+        for (entity, mut machine) in query.iter_mut() {
+            if let Some(next_state) = machine.event_target(entity, event) {
+                machine.transition_to(next_state);
+            }
+        }
+    }
+}
+
+pub trait StateMachineAppExt {
+    /// Drive `StateMachine<S>` components: run state actions and reset
+    /// state-scoped trees.
+    fn add_state_machine<S: Component + Clone + PartialEq>(&mut self) -> &mut Self;
+
+    /// Let `StateMachine<S>` components react to `E` events.
+    fn add_event_transitions<S: Component + Clone + PartialEq, E: Event>(&mut self) -> &mut Self;
+}
+
+impl StateMachineAppExt for App {
+    fn add_state_machine<S: Component + Clone + PartialEq>(&mut self) -> &mut Self {
+        self.add_systems(
+            Update,
+            (run_state_actions::<S>, reset_state_scoped_trees::<S>),
+        )
+    }
+
+    fn add_event_transitions<S: Component + Clone + PartialEq, E: Event>(&mut self) -> &mut Self {
+        self.add_event::<E>()
+            .add_systems(Update, apply_event_transitions::<S, E>)
+    }
+}
+
+/// Ties an entity's `BehaviorTree` to one state of its `StateMachine<S>`.
+/// The tree is reset whenever the machine leaves that state, so nodes left
+/// `Running` don't carry over into the next visit.
+#[derive(Component)]
+pub struct StateScopedTree<S: Component + Clone + PartialEq> {
+    pub state: S,
+    was_active: bool,
+}
+
+impl<S: Component + Clone + PartialEq> StateScopedTree<S> {
+    pub fn new(state: S) -> Self {
+        Self {
+            state,
+            was_active: false,
+        }
+    }
+}
+
+/// Resets state-scoped trees on exit. Added for each state type by
+/// `add_state_machine::<S>()`.
+pub fn reset_state_scoped_trees<S: Component + Clone + PartialEq>(
+    mut query: Query<(&StateMachine<S>, &mut StateScopedTree<S>, &mut BehaviorTree)>,
+) {
+    for (machine, mut scoped, mut tree) in query.iter_mut() {
+        let active = machine.current_state == scoped.state;
+        if scoped.was_active && !active {
+            tree.root.reset();
+        }
+        scoped.was_active = active;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::behavior_tree::{BehaviorNode, NodeStatus};
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+
+    #[derive(Component, Clone, PartialEq)]
+    enum Mode {
+        Patrol,
+        Chase,
+    }
+
+    /// Counts ticks since the last reset.
+    struct Progress(Arc<AtomicU32>);
+
+    impl BehaviorNode for Progress {
+        fn tick(&mut self, _entity: Entity, _world: &mut World) -> NodeStatus {
+            self.0.fetch_add(1, Ordering::Relaxed);
+            NodeStatus::Running
+        }
+
+        fn reset(&mut self) {
+            self.0.store(0, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn test_leaving_scoped_state_resets_tree() {
+        let mut app = App::new();
+        app.add_systems(Update, reset_state_scoped_trees::<Mode>);
+
+        let steps = Arc::new(AtomicU32::new(0));
+        let mut root = Progress(steps.clone());
+        let mut scratch = World::new();
+        let scratch_entity = scratch.spawn_empty().id();
+        root.tick(scratch_entity, &mut scratch);
+        root.tick(scratch_entity, &mut scratch);
+
+        let entity = app
+            .world
+            .spawn((
+                StateMachine::new(Mode::Patrol),
+                StateScopedTree::new(Mode::Patrol),
+                BehaviorTree {
+                    root: Box::new(root),
+                },
+            ))
+            .id();
+
+        app.update();
+        assert_eq!(steps.load(Ordering::Relaxed), 2);
+
+        app.world
+            .get_mut::<StateMachine<Mode>>(entity)
+            .unwrap()
+            .transition_to(Mode::Chase);
+        app.update();
+        assert_eq!(steps.load(Ordering::Relaxed), 0);
+    }
+
+    #[derive(Component, Debug, PartialEq)]
+    struct Ran(&'static str);
+
+    struct Mark(&'static str);
+
+    impl StateAction<Mode> for Mark {
+        fn execute(&self, entity: Entity, commands: &mut Commands, _state: &Mode) {
+            commands.entity(entity).insert(Ran(self.0));
+        }
+    }
+
+    #[test]
+    fn test_only_current_state_action_runs() {
+        let mut app = App::new();
+        app.add_state_machine::<Mode>();
+
+        let entity = app
+            .world
+            .spawn(
+                StateMachine::new(Mode::Patrol)
+                    .with_action(Mode::Patrol, Mark("patrol"))
+                    .with_action(Mode::Chase, Mark("chase")),
+            )
+            .id();
+
+        app.update();
+        assert_eq!(app.world.get::<Ran>(entity), Some(&Ran("patrol")));
+
+        app.world
+            .get_mut::<StateMachine<Mode>>(entity)
+            .unwrap()
+            .transition_to(Mode::Chase);
+        app.update();
+        assert_eq!(app.world.get::<Ran>(entity), Some(&Ran("chase")));
+    }
+
+    #[derive(Component, Clone, Debug, PartialEq)]
+    enum Morale {
+        Idle,
+        Fleeing,
+    }
+
+    #[derive(Event)]
+    struct DamageEvent {
+        target: Entity,
+        amount: f32,
+    }
+
+    #[test]
+    fn test_damage_event_makes_target_flee() {
+        let mut app = App::new();
+        app.add_event_transitions::<Morale, DamageEvent>();
+
+        let flee_when_hit = || {
+            EventTransition::new(Morale::Fleeing, |entity, event: &DamageEvent| {
+                event.target == entity && event.amount > 0.0
+            })
+            .from_state(Morale::Idle)
+        };
+        let villager = app
+            .world
+            .spawn(StateMachine::new(Morale::Idle).with_event_transition(flee_when_hit()))
+            .id();
+        let bystander = app
+            .world
+            .spawn(StateMachine::new(Morale::Idle).with_event_transition(flee_when_hit()))
+            .id();
+
+        app.update();
+        assert_eq!(
+            app.world
+                .get::<StateMachine<Morale>>(villager)
+                .unwrap()
+                .current_state,
+            Morale::Idle
+        );
+
+        app.world.send_event(DamageEvent {
+            target: villager,
+            amount: 5.0,
+        });
+        app.update();
+        assert_eq!(
+            app.world
+                .get::<StateMachine<Morale>>(villager)
+                .unwrap()
+                .current_state,
+            Morale::Fleeing
+        );
+        assert_eq!(
+            app.world
+                .get::<StateMachine<Morale>>(bystander)
+                .unwrap()
+                .current_state,
+            Morale::Idle
+        );
+    }
+}
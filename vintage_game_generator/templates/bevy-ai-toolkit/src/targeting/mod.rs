@@ -1,16 +1,275 @@
+use crate::behavior_tree::{BehaviorNode, NodeStatus};
+use crate::blackboard::{perceived_target, Blackboard};
+use crate::rng::AiRng;
+use crate::update_rate::{should_update, AiUpdateRate};
 use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
+use rand::Rng;
 
 pub struct TargetingPlugin;
 
 impl Plugin for TargetingPlugin {
-    fn build(&self, _app: &mut App) {
-        // Add targeting systems here
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TargetingSpace>()
+            .init_resource::<AiRng>()
+            .add_event::<PerceptionEvent>()
+            .add_event::<TargetAcquired>()
+            .add_event::<AllyAlerted>()
+            // Targets are picked first so perception, and `alert_allies`
+            // reading this frame's `TargetAcquired`, see them
+            .add_systems(
+                Update,
+                (
+                    (update_targets, update_multi_targets),
+                    (
+                        (perceive_stimuli, alert_allies, write_perception).chain(),
+                        predict_target_positions,
+                    ),
+                )
+                    .chain(),
+            )
+            .add_systems(
+                PreUpdate,
+                rebuild_spatial_index.run_if(resource_exists::<SpatialIndex>),
+            );
+    }
+}
+
+/// Which axes count when measuring distance between entities.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TargetingSpace {
+    /// XY plane only; z is treated as draw-order layering
+    Space2D,
+    #[default]
+    Space3D,
+}
+
+impl TargetingSpace {
+    pub fn project(&self, position: Vec3) -> Vec3 {
+        match self {
+            TargetingSpace::Space2D => position.truncate().extend(0.0),
+            TargetingSpace::Space3D => position,
+        }
+    }
+
+    pub fn distance(&self, a: Vec3, b: Vec3) -> f32 {
+        self.project(a).distance(self.project(b))
+    }
+}
+
+/// Uniform grid of `Targetable` positions, rebuilt every frame.
+///
+/// Insert this resource to let `update_targets` only look at cells within
+/// vision range instead of every target. Without it targeting checks all
+/// targets, which is fine for small scenes.
+#[derive(Resource, Debug, Clone)]
+pub struct SpatialIndex {
+    cell_size: f32,
+    cells: HashMap<IVec3, Vec<(Entity, Vec3)>>,
+}
+
+impl SpatialIndex {
+    /// Smallest allowed cell size; zero, negative or NaN sizes are raised to
+    /// this so cell lookups stay finite.
+    pub const MIN_CELL_SIZE: f32 = 0.01;
+
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size: cell_size.max(Self::MIN_CELL_SIZE),
+            cells: HashMap::default(),
+        }
+    }
+
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.values_mut().for_each(Vec::clear);
+    }
+
+    pub fn insert(&mut self, entity: Entity, position: Vec3) {
+        let cell = self.cell(position);
+        self.cells.entry(cell).or_default().push((entity, position));
+    }
+
+    /// Entries in every cell overlapping the cube of `radius` around `center`.
+    /// Callers still need to check the exact distance.
+    pub fn nearby(
+        &self,
+        center: Vec3,
+        radius: f32,
+    ) -> Box<dyn Iterator<Item = (Entity, Vec3)> + '_> {
+        let min = self.cell(center - Vec3::splat(radius));
+        let max = self.cell(center + Vec3::splat(radius));
+        let span = max.as_dvec3() - min.as_dvec3() + 1.0;
+
+        // Walking more cells than are occupied is slower than scanning them all
+        if span.x * span.y * span.z > self.cells.len() as f64 {
+            return Box::new(
+                self.cells
+                    .iter()
+                    .filter(move |(cell, _)| cell.cmpge(min).all() && cell.cmple(max).all())
+                    .flat_map(|(_, entries)| entries.iter().copied()),
+            );
+        }
+
+        Box::new(
+            (min.x..=max.x)
+                .flat_map(move |x| {
+                    (min.y..=max.y)
+                        .flat_map(move |y| (min.z..=max.z).map(move |z| IVec3::new(x, y, z)))
+                })
+                .filter_map(|cell| self.cells.get(&cell))
+                .flatten()
+                .copied(),
+        )
+    }
+
+    fn cell(&self, position: Vec3) -> IVec3 {
+        (position / self.cell_size).floor().as_ivec3()
+    }
+}
+
+impl Default for SpatialIndex {
+    fn default() -> Self {
+        Self::new(10.0)
+    }
+}
+
+/// Refills the `SpatialIndex` from current `Targetable` positions, projected
+/// into the active `TargetingSpace`.
+pub fn rebuild_spatial_index(
+    space: Option<Res<TargetingSpace>>,
+    mut index: ResMut<SpatialIndex>,
+    targets_query: Query<(Entity, &GlobalTransform), With<Targetable>>,
+) {
+    let space = space.map(|space| *space).unwrap_or_default();
+
+    index.clear();
+    for (entity, transform) in targets_query.iter() {
+        index.insert(entity, space.project(transform.translation()));
     }
 }
 
 #[derive(Component, Default)]
 pub struct Target {
     pub entity: Option<Entity>,
+    /// Where the target was last seen; kept after the target is lost.
+    pub last_known_position: Option<Vec3>,
+    /// Where to aim so a projectile meets the target, set for `LeadTarget`
+    /// shooters.
+    pub predicted_position: Option<Vec3>,
+}
+
+/// Linear velocity of a moving entity, used to lead shots at it.
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq)]
+pub struct Velocity(pub Vec3);
+
+/// Marks a shooter whose projectiles travel at `projectile_speed`, so its
+/// `Target::predicted_position` is kept up to date.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct LeadTarget {
+    pub projectile_speed: f32,
+}
+
+/// Misses a `LeadTarget` shooter's aim point by up to this distance on each
+/// axis, drawn from `AiRng`.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct AimSpread(pub f32);
+
+/// Point where a projectile fired from `shooter` at `projectile_speed` meets
+/// a target at `target` moving with `target_velocity`. Falls back to the
+/// target's current position when no intercept exists, e.g. a target
+/// outrunning the projectile.
+pub fn aim_point(
+    shooter: Vec3,
+    target: Vec3,
+    target_velocity: Vec3,
+    projectile_speed: f32,
+) -> Vec3 {
+    // Solve |offset + velocity * t| = speed * t for the earliest t > 0
+    let offset = target - shooter;
+    let a = target_velocity.length_squared() - projectile_speed * projectile_speed;
+    let half_b = offset.dot(target_velocity);
+    let c = offset.length_squared();
+
+    let time = if a.abs() < f32::EPSILON {
+        // Target and projectile are equally fast
+        (half_b < 0.0).then(|| -c / (2.0 * half_b))
+    } else {
+        let discriminant = half_b * half_b - a * c;
+        (discriminant >= 0.0)
+            .then(|| {
+                let root = discriminant.sqrt();
+                [(-half_b - root) / a, (-half_b + root) / a]
+                    .into_iter()
+                    .filter(|t| *t > 0.0)
+                    .reduce(f32::min)
+            })
+            .flatten()
+    };
+
+    match time {
+        Some(time) => target + target_velocity * time,
+        None => target,
+    }
+}
+
+/// Updates `Target::predicted_position` for shooters that lead their shots.
+pub fn predict_target_positions(
+    space: Option<Res<TargetingSpace>>,
+    mut rng: ResMut<AiRng>,
+    mut shooters: Query<(
+        &GlobalTransform,
+        &LeadTarget,
+        &mut Target,
+        Option<&AimSpread>,
+    )>,
+    targets: Query<(&GlobalTransform, Option<&Velocity>)>,
+) {
+    let space = space.map(|space| *space).unwrap_or_default();
+
+    for (transform, lead, mut target, spread) in shooters.iter_mut() {
+        target.predicted_position = target
+            .entity
+            .and_then(|entity| targets.get(entity).ok())
+            .map(|(target_transform, velocity)| {
+                let aim = aim_point(
+                    transform.translation(),
+                    target_transform.translation(),
+                    velocity.map_or(Vec3::ZERO, |velocity| velocity.0),
+                    lead.projectile_speed,
+                );
+                match spread {
+                    Some(spread) => {
+                        let offset = Vec3::new(
+                            rng.0.gen_range(-1.0..=1.0),
+                            rng.0.gen_range(-1.0..=1.0),
+                            rng.0.gen_range(-1.0..=1.0),
+                        );
+                        aim + space.project(offset) * spread.0
+                    }
+                    None => aim,
+                }
+            });
+    }
+}
+
+/// Ranked list of the closest targets, nearest first.
+#[derive(Component, Default)]
+pub struct MultiTarget {
+    pub entities: Vec<Entity>,
+    pub max: usize,
+}
+
+impl MultiTarget {
+    pub fn new(max: usize) -> Self {
+        Self {
+            entities: Vec::with_capacity(max),
+            max,
+        }
+    }
 }
 
 #[derive(Component)]
@@ -19,29 +278,877 @@ pub struct Vision {
     pub field_of_view: f32,
 }
 
+/// Radius within which an entity notices stimuli other than sight.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Awareness {
+    pub radius: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StimulusKind {
+    Sound,
+    Damage,
+    Sight,
+}
+
+/// A cue at `position` caused by `source`, e.g. a footstep or an incoming shot.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct PerceptionEvent {
+    pub source: Entity,
+    pub position: Vec3,
+    pub kind: StimulusKind,
+}
+
+/// Sent by `update_targets` when `spotter` locks onto a new target.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct TargetAcquired {
+    pub spotter: Entity,
+    pub target: Entity,
+}
+
+/// Sent by `alert_allies` for each ally told where `spotter`'s target was
+/// seen. Register it with `add_event_transitions` to send alerted allies
+/// into an investigate or chase state.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct AllyAlerted {
+    pub ally: Entity,
+    pub spotter: Entity,
+    pub position: Vec3,
+}
+
+/// Entities sharing a faction id are allies.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Faction(pub u32);
+
+/// Radius within which an entity alerts allies when it acquires a target.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct CallForHelp {
+    pub radius: f32,
+}
+
+/// Keeps the current target unless a candidate is clearly better, and waits
+/// before picking a new one after the current target is lost.
+#[derive(Component)]
+pub struct StickyTarget {
+    pub switch_margin: f32,
+    pub reacquire_delay: f32,
+    pub lost_for: Option<f32>,
+}
+
+impl StickyTarget {
+    pub fn new(switch_margin: f32, reacquire_delay: f32) -> Self {
+        Self {
+            switch_margin,
+            reacquire_delay,
+            lost_for: None,
+        }
+    }
+}
+
+/// Entities this viewer must never target, e.g. a quest NPC or a downed
+/// ally, even though they are `Targetable`.
+#[derive(Component, Debug, Clone, Default, PartialEq, Eq)]
+pub struct IgnoreTargets(pub HashSet<Entity>);
+
+/// Keeps a `Targetable` entity out of every viewer's targeting.
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct GloballyUntargetable;
+
+/// Everything a viewer may pick as its target.
+type TargetCandidates<'w, 's> = Query<
+    'w,
+    's,
+    (Entity, &'static GlobalTransform),
+    (With<Targetable>, Without<GloballyUntargetable>),
+>;
+
+/// Entities picking a single target in `update_targets`.
+type TargetSeekers<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static GlobalTransform,
+        &'static Vision,
+        &'static mut Target,
+        Option<&'static mut StickyTarget>,
+        Option<&'static IgnoreTargets>,
+        Option<&'static AiUpdateRate>,
+    ),
+>;
+
 pub fn update_targets(
-    mut query: Query<(Entity, &GlobalTransform, &Vision, &mut Target)>,
-    targets_query: Query<(Entity, &GlobalTransform), With<Targetable>>,
+    time: Res<Time>,
+    space: Option<Res<TargetingSpace>>,
+    index: Option<Res<SpatialIndex>>,
+    // Optional so apps that don't add `TargetingPlugin` can still run targeting
+    mut acquired: Option<ResMut<Events<TargetAcquired>>>,
+    mut query: TargetSeekers,
+    targets_query: TargetCandidates,
 ) {
-    for (entity, transform, vision, mut target) in query.iter_mut() {
+    let space = space.map(|space| *space).unwrap_or_default();
+    let mut candidates = Vec::new();
+    let mut updated = Vec::new();
+
+    for (entity, transform, vision, mut target, sticky, ignore, rate) in query.iter_mut() {
+        if !should_update(rate) {
+            continue;
+        }
+        updated.push((entity, target.entity));
         let mut closest_target = None;
         let mut closest_distance = vision.range;
+        let mut current_distance = None;
+
+        candidates.clear();
+        match index.as_deref() {
+            // Skip entries for targets despawned since the index was rebuilt
+            Some(index) => candidates.extend(
+                index
+                    .nearby(space.project(transform.translation()), vision.range)
+                    .filter(|(target_entity, _)| targets_query.contains(*target_entity)),
+            ),
+            None => candidates.extend(targets_query.iter().map(
+                |(target_entity, target_transform)| (target_entity, target_transform.translation()),
+            )),
+        }
 
-        for (target_entity, target_transform) in targets_query.iter() {
-            if entity == target_entity {
+        for &(target_entity, target_position) in &candidates {
+            if entity == target_entity
+                || ignore.is_some_and(|ignore| ignore.0.contains(&target_entity))
+            {
                 continue;
             }
 
-            let distance = transform.translation().distance(target_transform.translation());
+            let distance = space.distance(transform.translation(), target_position);
+            if Some(target_entity) == target.entity && distance <= vision.range {
+                current_distance = Some(distance);
+            }
             if distance < closest_distance {
                 closest_distance = distance;
                 closest_target = Some(target_entity);
             }
         }
 
+        let Some(mut sticky) = sticky else {
+            target.entity = closest_target;
+            continue;
+        };
+
+        if let Some(current_distance) = current_distance {
+            if closest_distance + sticky.switch_margin < current_distance {
+                target.entity = closest_target;
+            }
+            continue;
+        }
+
+        if target.entity.take().is_some() {
+            sticky.lost_for = Some(0.0);
+        }
+
+        if let Some(lost_for) = sticky.lost_for.as_mut() {
+            // A rated entity has been away for its whole interval
+            *lost_for += rate.map_or(time.delta_seconds(), |rate| rate.accumulator);
+            if *lost_for < sticky.reacquire_delay {
+                continue;
+            }
+            sticky.lost_for = None;
+        }
+
         target.entity = closest_target;
     }
+
+    // Remember where each target was seen, so it outlives losing sight of
+    // it, and announce targets that are new to their viewer
+    for (entity, previous) in updated {
+        let Ok((_, _, _, mut target, ..)) = query.get_mut(entity) else {
+            continue;
+        };
+        let Some(target_entity) = target.entity else {
+            continue;
+        };
+        if let Ok((_, target_transform)) = targets_query.get(target_entity) {
+            target.last_known_position = Some(target_transform.translation());
+        }
+        if previous != Some(target_entity) {
+            if let Some(acquired) = acquired.as_mut() {
+                acquired.send(TargetAcquired {
+                    spotter: entity,
+                    target: target_entity,
+                });
+            }
+        }
+    }
+}
+
+/// Points `Target::last_known_position` at the source of any stimulus that
+/// lands within an entity's awareness radius, regardless of line of sight.
+pub fn perceive_stimuli(
+    space: Option<Res<TargetingSpace>>,
+    mut events: EventReader<PerceptionEvent>,
+    mut perceivers: Query<(Entity, &GlobalTransform, &Awareness, &mut Target)>,
+    sources: Query<&GlobalTransform>,
+) {
+    let space = space.map(|space| *space).unwrap_or_default();
+
+    for event in events.read() {
+        // Prefer where the source actually is, e.g. the shooter rather than the impact
+        let source_position = sources
+            .get(event.source)
+            .map(|transform| transform.translation())
+            .unwrap_or(event.position);
+
+        for (entity, transform, awareness, mut target) in perceivers.iter_mut() {
+            if entity == event.source {
+                continue;
+            }
+            if space.distance(transform.translation(), event.position) <= awareness.radius {
+                target.last_known_position = Some(source_position);
+            }
+        }
+    }
+}
+
+/// Shares a spotter's `Target::last_known_position` with allies of the same
+/// `Faction` within its `CallForHelp` radius. Allies already locked onto a
+/// target of their own are left alone.
+pub fn alert_allies(
+    space: Option<Res<TargetingSpace>>,
+    mut acquired: EventReader<TargetAcquired>,
+    mut alerted: EventWriter<AllyAlerted>,
+    spotters: Query<(&GlobalTransform, &Faction, &CallForHelp)>,
+    mut allies: Query<(Entity, &GlobalTransform, &Faction, &mut Target)>,
+) {
+    let space = space.map(|space| *space).unwrap_or_default();
+
+    for event in acquired.read() {
+        let Ok((spotter_transform, spotter_faction, call)) = spotters.get(event.spotter) else {
+            continue;
+        };
+        let Some(position) = allies
+            .get(event.spotter)
+            .ok()
+            .and_then(|(_, _, _, target)| target.last_known_position)
+        else {
+            continue;
+        };
+
+        for (ally, transform, faction, mut target) in allies.iter_mut() {
+            if ally == event.spotter || faction != spotter_faction || target.entity.is_some() {
+                continue;
+            }
+            if space.distance(spotter_transform.translation(), transform.translation())
+                <= call.radius
+            {
+                target.last_known_position = Some(position);
+                alerted.send(AllyAlerted {
+                    ally,
+                    spotter: event.spotter,
+                    position,
+                });
+            }
+        }
+    }
+}
+
+/// Copies each entity's `Target`, and the threat of what it's aimed at,
+/// into its `Blackboard` for behavior trees and utility AI to read.
+pub fn write_perception(
+    mut perceivers: Query<(&Target, &mut Blackboard)>,
+    threats: Query<&Threat>,
+) {
+    for (target, mut blackboard) in perceivers.iter_mut() {
+        let threat = target
+            .entity
+            .and_then(|entity| threats.get(entity).ok())
+            .map(|threat| threat.0);
+        blackboard.set_perception(target.entity, target.last_known_position, threat);
+    }
+}
+
+/// Entities picking several targets in `update_multi_targets`.
+type MultiTargetSeekers<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static GlobalTransform,
+        &'static Vision,
+        &'static mut MultiTarget,
+        Option<&'static IgnoreTargets>,
+        Option<&'static AiUpdateRate>,
+    ),
+>;
+
+pub fn update_multi_targets(
+    space: Option<Res<TargetingSpace>>,
+    mut query: MultiTargetSeekers,
+    targets_query: TargetCandidates,
+) {
+    let space = space.map(|space| *space).unwrap_or_default();
+
+    for (entity, transform, vision, mut multi_target, ignore, rate) in query.iter_mut() {
+        if !should_update(rate) {
+            continue;
+        }
+        let mut candidates: Vec<(Entity, f32)> = targets_query
+            .iter()
+            .filter(|(target_entity, _)| {
+                *target_entity != entity
+                    && !ignore.is_some_and(|ignore| ignore.0.contains(target_entity))
+            })
+            .map(|(target_entity, target_transform)| {
+                (
+                    target_entity,
+                    space.distance(transform.translation(), target_transform.translation()),
+                )
+            })
+            .filter(|(_, distance)| *distance < vision.range)
+            .collect();
+
+        candidates.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+        let max = multi_target.max;
+        multi_target.entities.clear();
+        multi_target
+            .entities
+            .extend(candidates.into_iter().take(max).map(|(entity, _)| entity));
+    }
 }
 
 #[derive(Component)]
 pub struct Targetable;
+
+/// Condition leaf: `Success` while the entity's perceived target is an
+/// entity that still exists, otherwise `Failure`.
+pub struct HasTarget;
+
+impl BehaviorNode for HasTarget {
+    fn tick(&mut self, entity: Entity, world: &mut World) -> NodeStatus {
+        let has_target = perceived_target(entity, world)
+            .is_some_and(|target| world.get_entity(target).is_some());
+        if has_target {
+            NodeStatus::Success
+        } else {
+            NodeStatus::Failure
+        }
+    }
+}
+
+/// Condition leaf: `Success` when the entity's current target is within
+/// `range`, measured in the active `TargetingSpace`, otherwise `Failure`.
+pub struct TargetInRange {
+    pub range: f32,
+}
+
+impl BehaviorNode for TargetInRange {
+    fn tick(&mut self, entity: Entity, world: &mut World) -> NodeStatus {
+        let space = world
+            .get_resource::<TargetingSpace>()
+            .copied()
+            .unwrap_or_default();
+        let distance = perceived_target(entity, world)
+            .and_then(|target| world.get::<GlobalTransform>(target))
+            .zip(world.get::<GlobalTransform>(entity))
+            .map(|(target, own)| space.distance(own.translation(), target.translation()));
+
+        match distance {
+            Some(distance) if distance <= self.range => NodeStatus::Success,
+            _ => NodeStatus::Failure,
+        }
+    }
+}
+
+/// How dangerous a target is, normalized to `0.0..=1.0`. Set by the game.
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq)]
+pub struct Threat(pub f32);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spawn_targetable(app: &mut App, position: Vec3) -> Entity {
+        app.world
+            .spawn((GlobalTransform::from_translation(position), Targetable))
+            .id()
+    }
+
+    #[test]
+    fn test_plugin_acquires_targets() {
+        let mut app = App::new();
+        app.init_resource::<Time>().add_plugins(TargetingPlugin);
+
+        let player = spawn_targetable(&mut app, Vec3::new(5.0, 0.0, 0.0));
+        let seeker = app
+            .world
+            .spawn((
+                GlobalTransform::default(),
+                Vision {
+                    range: 10.0,
+                    field_of_view: 360.0,
+                },
+                Target::default(),
+                MultiTarget::new(2),
+            ))
+            .id();
+
+        app.update();
+        let target = app.world.get::<Target>(seeker).unwrap();
+        assert_eq!(target.entity, Some(player));
+        assert_eq!(target.last_known_position, Some(Vec3::new(5.0, 0.0, 0.0)));
+        assert_eq!(
+            app.world.get::<MultiTarget>(seeker).unwrap().entities,
+            vec![player]
+        );
+    }
+
+    #[test]
+    fn test_sticky_target_resists_marginal_switch() {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .add_systems(Update, update_targets);
+
+        let first = spawn_targetable(&mut app, Vec3::new(5.0, 0.0, 0.0));
+        let seeker = app
+            .world
+            .spawn((
+                GlobalTransform::default(),
+                Vision {
+                    range: 10.0,
+                    field_of_view: 360.0,
+                },
+                Target::default(),
+                StickyTarget::new(1.0, 0.5),
+            ))
+            .id();
+
+        app.update();
+        assert_eq!(app.world.get::<Target>(seeker).unwrap().entity, Some(first));
+
+        // Slightly closer, but not by more than the switch margin
+        spawn_targetable(&mut app, Vec3::new(-4.5, 0.0, 0.0));
+        app.update();
+        assert_eq!(app.world.get::<Target>(seeker).unwrap().entity, Some(first));
+
+        // Clearly closer candidates still take over
+        let close = spawn_targetable(&mut app, Vec3::new(0.0, 2.0, 0.0));
+        app.update();
+        assert_eq!(app.world.get::<Target>(seeker).unwrap().entity, Some(close));
+    }
+
+    #[test]
+    fn test_ignored_candidates_are_skipped() {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .add_systems(Update, update_targets);
+
+        let quest_npc = spawn_targetable(&mut app, Vec3::new(1.0, 0.0, 0.0));
+        let downed_ally = spawn_targetable(&mut app, Vec3::new(2.0, 0.0, 0.0));
+        app.world
+            .entity_mut(downed_ally)
+            .insert(GloballyUntargetable);
+        let enemy = spawn_targetable(&mut app, Vec3::new(5.0, 0.0, 0.0));
+        let seeker = app
+            .world
+            .spawn((
+                GlobalTransform::default(),
+                Vision {
+                    range: 10.0,
+                    field_of_view: 360.0,
+                },
+                Target::default(),
+                IgnoreTargets(HashSet::from_iter([quest_npc])),
+            ))
+            .id();
+
+        app.update();
+        assert_eq!(app.world.get::<Target>(seeker).unwrap().entity, Some(enemy));
+    }
+
+    #[test]
+    fn test_2d_space_ignores_depth() {
+        let space = TargetingSpace::Space2D;
+        let origin = Vec3::ZERO;
+        let near_layer = Vec3::new(3.0, 4.0, 0.0);
+        let far_layer = Vec3::new(3.0, 4.0, 100.0);
+
+        assert_eq!(space.distance(origin, near_layer), 5.0);
+        assert_eq!(
+            space.distance(origin, near_layer),
+            space.distance(origin, far_layer)
+        );
+        assert!(TargetingSpace::Space3D.distance(origin, far_layer) > 5.0);
+
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .insert_resource(TargetingSpace::Space2D)
+            .add_systems(Update, update_targets);
+
+        let layered = spawn_targetable(&mut app, far_layer);
+        let seeker = app
+            .world
+            .spawn((
+                GlobalTransform::default(),
+                Vision {
+                    range: 10.0,
+                    field_of_view: 360.0,
+                },
+                Target::default(),
+            ))
+            .id();
+
+        app.update();
+        assert_eq!(
+            app.world.get::<Target>(seeker).unwrap().entity,
+            Some(layered)
+        );
+    }
+
+    #[test]
+    fn test_multi_target_selects_nearest() {
+        let mut app = App::new();
+        app.add_systems(Update, update_multi_targets);
+
+        let far = spawn_targetable(&mut app, Vec3::new(8.0, 0.0, 0.0));
+        let nearest = spawn_targetable(&mut app, Vec3::new(1.0, 0.0, 0.0));
+        let middle = spawn_targetable(&mut app, Vec3::new(0.0, 3.0, 0.0));
+        let caster = app
+            .world
+            .spawn((
+                GlobalTransform::default(),
+                Vision {
+                    range: 10.0,
+                    field_of_view: 360.0,
+                },
+                MultiTarget::new(2),
+            ))
+            .id();
+
+        app.update();
+        let targets = &app.world.get::<MultiTarget>(caster).unwrap().entities;
+        assert_eq!(targets, &vec![nearest, middle]);
+        assert!(!targets.contains(&far));
+    }
+
+    #[test]
+    fn test_damage_stimulus_sets_last_known_position_without_sight() {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .add_event::<PerceptionEvent>()
+            .add_systems(Update, (update_targets, perceive_stimuli).chain());
+
+        let shooter = app
+            .world
+            .spawn(GlobalTransform::from_translation(Vec3::new(
+                -20.0, 0.0, 0.0,
+            )))
+            .id();
+        let victim = app
+            .world
+            .spawn((
+                GlobalTransform::default(),
+                Vision {
+                    range: 5.0,
+                    field_of_view: 90.0,
+                },
+                Awareness { radius: 2.0 },
+                Target::default(),
+            ))
+            .id();
+
+        app.world.send_event(PerceptionEvent {
+            source: shooter,
+            position: Vec3::ZERO,
+            kind: StimulusKind::Damage,
+        });
+        app.update();
+
+        let target = app.world.get::<Target>(victim).unwrap();
+        assert_eq!(target.entity, None);
+        assert_eq!(target.last_known_position, Some(Vec3::new(-20.0, 0.0, 0.0)));
+
+        // Stimuli outside the awareness radius go unnoticed
+        app.world
+            .get_mut::<Target>(victim)
+            .unwrap()
+            .last_known_position = None;
+        app.world.send_event(PerceptionEvent {
+            source: shooter,
+            position: Vec3::new(-20.0, 0.0, 0.0),
+            kind: StimulusKind::Sound,
+        });
+        app.update();
+        assert_eq!(
+            app.world.get::<Target>(victim).unwrap().last_known_position,
+            None
+        );
+    }
+
+    #[test]
+    fn test_acquiring_target_alerts_allies_in_radius() {
+        use crate::state_machine::{EventTransition, StateMachine, StateMachineAppExt};
+
+        #[derive(Component, Clone, Debug, PartialEq)]
+        enum Alertness {
+            Patrol,
+            Investigate,
+        }
+
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .add_plugins(TargetingPlugin)
+            .add_event_transitions::<Alertness, AllyAlerted>();
+
+        let player = spawn_targetable(&mut app, Vec3::new(5.0, 0.0, 0.0));
+        app.world.spawn((
+            GlobalTransform::default(),
+            Vision {
+                range: 10.0,
+                field_of_view: 360.0,
+            },
+            Target::default(),
+            Faction(1),
+            CallForHelp { radius: 15.0 },
+        ));
+        let mut spawn_guard = |position: Vec3, faction: u32| {
+            let investigate =
+                EventTransition::new(Alertness::Investigate, |entity, alert: &AllyAlerted| {
+                    alert.ally == entity
+                });
+            app.world
+                .spawn((
+                    GlobalTransform::from_translation(position),
+                    Target::default(),
+                    Faction(faction),
+                    StateMachine::new(Alertness::Patrol).with_event_transition(investigate),
+                ))
+                .id()
+        };
+        let nearby = spawn_guard(Vec3::new(-12.0, 0.0, 0.0), 1);
+        let distant = spawn_guard(Vec3::new(-40.0, 0.0, 0.0), 1);
+        let rival = spawn_guard(Vec3::new(-3.0, 0.0, 0.0), 2);
+
+        app.update();
+        app.update();
+
+        let player_position = app
+            .world
+            .get::<GlobalTransform>(player)
+            .unwrap()
+            .translation();
+        let alertness = |app: &App, guard: Entity| {
+            app.world
+                .get::<StateMachine<Alertness>>(guard)
+                .unwrap()
+                .current_state
+                .clone()
+        };
+        assert_eq!(
+            app.world.get::<Target>(nearby).unwrap().last_known_position,
+            Some(player_position)
+        );
+        assert_eq!(alertness(&app, nearby), Alertness::Investigate);
+        for guard in [distant, rival] {
+            assert_eq!(
+                app.world.get::<Target>(guard).unwrap().last_known_position,
+                None
+            );
+            assert_eq!(alertness(&app, guard), Alertness::Patrol);
+        }
+    }
+
+    #[test]
+    fn test_spatial_index_matches_brute_force() {
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(7);
+        let targets: Vec<Vec3> = (0..2000)
+            .map(|_| {
+                Vec3::new(
+                    rng.gen_range(-200.0..200.0),
+                    rng.gen_range(-200.0..200.0),
+                    0.0,
+                )
+            })
+            .collect();
+        let seekers: Vec<Vec3> = (0..100)
+            .map(|_| {
+                Vec3::new(
+                    rng.gen_range(-200.0..200.0),
+                    rng.gen_range(-200.0..200.0),
+                    0.0,
+                )
+            })
+            .collect();
+
+        let nearest = |index: Option<SpatialIndex>| {
+            let mut app = App::new();
+            app.init_resource::<Time>();
+            match index {
+                Some(index) => app
+                    .insert_resource(index)
+                    .add_systems(Update, (rebuild_spatial_index, update_targets).chain()),
+                None => app.add_systems(Update, update_targets),
+            };
+
+            let target_entities: Vec<Entity> = targets
+                .iter()
+                .map(|position| spawn_targetable(&mut app, *position))
+                .collect();
+            let seeker_entities: Vec<Entity> = seekers
+                .iter()
+                .map(|position| {
+                    app.world
+                        .spawn((
+                            GlobalTransform::from_translation(*position),
+                            Vision {
+                                range: 15.0,
+                                field_of_view: 360.0,
+                            },
+                            Target::default(),
+                        ))
+                        .id()
+                })
+                .collect();
+
+            app.update();
+            seeker_entities
+                .iter()
+                .map(|seeker| {
+                    app.world
+                        .get::<Target>(*seeker)
+                        .unwrap()
+                        .entity
+                        .map(|target| {
+                            target_entities
+                                .iter()
+                                .position(|entity| *entity == target)
+                                .unwrap()
+                        })
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let brute_force = nearest(None);
+        assert!(brute_force.iter().any(Option::is_some));
+        assert_eq!(nearest(Some(SpatialIndex::new(5.0))), brute_force);
+    }
+
+    #[test]
+    fn test_spatial_index_rejects_degenerate_cell_size() {
+        for cell_size in [0.0, -4.0, f32::NAN] {
+            let mut index = SpatialIndex::new(cell_size);
+            assert!(index.cell_size() > 0.0);
+
+            let entity = Entity::from_raw(1);
+            index.insert(entity, Vec3::new(3.0, 4.0, 0.0));
+            let found: Vec<Entity> = index
+                .nearby(Vec3::ZERO, 5.0)
+                .map(|(entity, _)| entity)
+                .collect();
+            assert_eq!(found, vec![entity]);
+        }
+    }
+
+    #[test]
+    fn test_lead_point_is_ahead_of_crossing_target() {
+        let mut app = App::new();
+        app.init_resource::<AiRng>()
+            .add_systems(Update, predict_target_positions);
+
+        let crossing = app
+            .world
+            .spawn((
+                GlobalTransform::from_translation(Vec3::new(10.0, 0.0, 0.0)),
+                Velocity(Vec3::new(0.0, 5.0, 0.0)),
+            ))
+            .id();
+        let shooter = app
+            .world
+            .spawn((
+                GlobalTransform::default(),
+                LeadTarget {
+                    projectile_speed: 20.0,
+                },
+                Target {
+                    entity: Some(crossing),
+                    ..default()
+                },
+            ))
+            .id();
+
+        app.update();
+        let lead = app
+            .world
+            .get::<Target>(shooter)
+            .unwrap()
+            .predicted_position
+            .unwrap();
+        assert!(lead.y > 0.0);
+        assert!((lead.x - 10.0).abs() < 1e-4);
+
+        // The projectile covers the distance in the time the target takes to get there
+        let time = lead.length() / 20.0;
+        assert!((lead.y - 5.0 * time).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_aim_point_falls_back_when_target_outruns_projectile() {
+        let target = Vec3::new(10.0, 0.0, 0.0);
+        let fleeing = Vec3::new(30.0, 0.0, 0.0);
+        assert_eq!(aim_point(Vec3::ZERO, target, fleeing, 20.0), target);
+    }
+
+    #[test]
+    fn test_has_target_condition() {
+        let mut world = World::new();
+        let target = world.spawn_empty().id();
+        let hunter = world
+            .spawn(Target {
+                entity: Some(target),
+                ..default()
+            })
+            .id();
+        let idle = world.spawn(Target::default()).id();
+        let untracked = world.spawn_empty().id();
+
+        assert_eq!(HasTarget.tick(hunter, &mut world), NodeStatus::Success);
+        assert_eq!(HasTarget.tick(idle, &mut world), NodeStatus::Failure);
+        assert_eq!(HasTarget.tick(untracked, &mut world), NodeStatus::Failure);
+
+        world.despawn(target);
+        assert_eq!(HasTarget.tick(hunter, &mut world), NodeStatus::Failure);
+    }
+
+    #[test]
+    fn test_target_in_range_condition() {
+        let mut world = World::new();
+        let spawn_hunter = |world: &mut World, target_position: Vec3| {
+            let target = world
+                .spawn(GlobalTransform::from_translation(target_position))
+                .id();
+            world
+                .spawn((
+                    GlobalTransform::default(),
+                    Target {
+                        entity: Some(target),
+                        ..default()
+                    },
+                ))
+                .id()
+        };
+        let near = spawn_hunter(&mut world, Vec3::new(1.5, 0.0, 0.0));
+        let far = spawn_hunter(&mut world, Vec3::new(0.0, 0.0, 8.0));
+        let idle = world
+            .spawn((GlobalTransform::default(), Target::default()))
+            .id();
+
+        let mut in_range = TargetInRange { range: 2.0 };
+        assert_eq!(in_range.tick(near, &mut world), NodeStatus::Success);
+        assert_eq!(in_range.tick(far, &mut world), NodeStatus::Failure);
+        assert_eq!(in_range.tick(idle, &mut world), NodeStatus::Failure);
+
+        // In 2D the far target's z offset is only layering
+        world.insert_resource(TargetingSpace::Space2D);
+        assert_eq!(in_range.tick(far, &mut world), NodeStatus::Success);
+    }
+}
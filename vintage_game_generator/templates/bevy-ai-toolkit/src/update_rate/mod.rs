@@ -0,0 +1,114 @@
+use bevy::prelude::*;
+
+pub struct AiUpdateRatePlugin;
+
+impl Plugin for AiUpdateRatePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PreUpdate, accumulate_update_rates)
+            .add_systems(PostUpdate, consume_update_rates);
+    }
+}
+
+/// Makes an entity's AI think every `interval` seconds instead of every
+/// frame, e.g. for distant or off-screen enemies.
+///
+/// Behavior trees, utility AI and targeting skip the entity until
+/// `accumulator` reaches `interval`. Entities without it update every frame.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct AiUpdateRate {
+    pub interval: f32,
+    /// Seconds since the entity last updated
+    pub accumulator: f32,
+}
+
+impl AiUpdateRate {
+    pub fn new(interval: f32) -> Self {
+        Self {
+            interval,
+            accumulator: 0.0,
+        }
+    }
+
+    /// Whether the entity updates this frame.
+    pub fn is_due(&self) -> bool {
+        self.accumulator >= self.interval
+    }
+}
+
+/// Whether AI systems should update an entity with this (optional) rate.
+pub fn should_update(rate: Option<&AiUpdateRate>) -> bool {
+    rate.is_none_or(AiUpdateRate::is_due)
+}
+
+/// Adds the frame time to every accumulator before the AI systems run.
+pub fn accumulate_update_rates(time: Res<Time>, mut rates: Query<&mut AiUpdateRate>) {
+    for mut rate in rates.iter_mut() {
+        rate.accumulator += time.delta_seconds();
+    }
+}
+
+/// Starts the next interval for every entity that updated this frame,
+/// keeping the overshoot so updates don't drift.
+pub fn consume_update_rates(mut rates: Query<&mut AiUpdateRate>) {
+    for mut rate in rates.iter_mut() {
+        if !rate.is_due() {
+            continue;
+        }
+        rate.accumulator = if rate.interval > 0.0 {
+            rate.accumulator % rate.interval
+        } else {
+            0.0
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::behavior_tree::{tick_behavior_trees, BehaviorNode, BehaviorTree, NodeStatus};
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+
+    struct Counting(Arc<AtomicU32>);
+
+    impl BehaviorNode for Counting {
+        fn tick(&mut self, _entity: Entity, _world: &mut World) -> NodeStatus {
+            self.0.fetch_add(1, Ordering::Relaxed);
+            NodeStatus::Running
+        }
+    }
+
+    fn counting_tree() -> (BehaviorTree, Arc<AtomicU32>) {
+        let ticks = Arc::new(AtomicU32::new(0));
+        let tree = BehaviorTree {
+            root: Box::new(Counting(ticks.clone())),
+        };
+        (tree, ticks)
+    }
+
+    #[test]
+    fn test_rated_entity_ticks_when_accumulator_crosses_interval() {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .add_plugins(AiUpdateRatePlugin)
+            .add_systems(Update, tick_behavior_trees);
+
+        let (tree, distant_ticks) = counting_tree();
+        app.world.spawn((tree, AiUpdateRate::new(0.5)));
+        let (tree, nearby_ticks) = counting_tree();
+        app.world.spawn(tree);
+
+        let mut distant = Vec::new();
+        for _ in 0..4 {
+            app.world
+                .resource_mut::<Time>()
+                .advance_by(std::time::Duration::from_millis(250));
+            app.update();
+            distant.push(distant_ticks.load(Ordering::Relaxed));
+        }
+
+        // 0.25s, 0.5s (due), 0.25s, 0.5s (due)
+        assert_eq!(distant, vec![0, 1, 1, 2]);
+        assert_eq!(nearby_ticks.load(Ordering::Relaxed), 4);
+    }
+}
@@ -0,0 +1,188 @@
+//! Data-driven utility AI configurations.
+//!
+//! Designers describe considerations in RON, referring to scorers and actions
+//! by the keys they were registered under:
+//!
+//! ```ron
+//! (
+//!     considerations: [
+//!         (name: "flee", scorer: "low_health", action: "flee", weight: 2.0, curve: Power(2.0)),
+//!         (name: "idle", scorer: "always", action: "wait"),
+//!     ],
+//! )
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{Action, Consideration, ResponseCurve, Scorer, UtilityAi};
+
+type ScorerFactory = Box<dyn Fn() -> Box<dyn Scorer> + Send + Sync>;
+type ActionFactory = Box<dyn Fn() -> Box<dyn Action> + Send + Sync>;
+
+/// Scorers and actions that `UtilityAiDef`s can refer to by key.
+#[derive(Resource, Default)]
+pub struct UtilityRegistry {
+    scorers: HashMap<String, ScorerFactory>,
+    actions: HashMap<String, ActionFactory>,
+}
+
+impl UtilityRegistry {
+    pub fn register_scorer(
+        &mut self,
+        key: impl Into<String>,
+        factory: impl Fn() -> Box<dyn Scorer> + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.scorers.insert(key.into(), Box::new(factory));
+        self
+    }
+
+    pub fn register_action(
+        &mut self,
+        key: impl Into<String>,
+        factory: impl Fn() -> Box<dyn Action> + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.actions.insert(key.into(), Box::new(factory));
+        self
+    }
+}
+
+fn default_weight() -> f32 {
+    1.0
+}
+
+/// One consideration, referring to registered scorers and actions by key.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConsiderationDef {
+    pub name: String,
+    pub scorer: String,
+    pub action: String,
+    #[serde(default = "default_weight")]
+    pub weight: f32,
+    #[serde(default)]
+    pub curve: ResponseCurve,
+}
+
+/// Serializable description of a `UtilityAi`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UtilityAiDef {
+    pub considerations: Vec<ConsiderationDef>,
+}
+
+/// A definition referred to a key missing from the registry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UtilityDefError {
+    UnknownScorer(String),
+    UnknownAction(String),
+}
+
+impl fmt::Display for UtilityDefError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UtilityDefError::UnknownScorer(key) => write!(f, "unknown scorer `{key}`"),
+            UtilityDefError::UnknownAction(key) => write!(f, "unknown action `{key}`"),
+        }
+    }
+}
+
+impl std::error::Error for UtilityDefError {}
+
+impl UtilityAiDef {
+    pub fn from_ron(source: &str) -> Result<Self, ron::error::SpannedError> {
+        ron::from_str(source)
+    }
+
+    pub fn to_ron(&self) -> Result<String, ron::Error> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+    }
+
+    /// Instantiate the configured scorers and actions.
+    pub fn build(&self, registry: &UtilityRegistry) -> Result<UtilityAi, UtilityDefError> {
+        let considerations = self
+            .considerations
+            .iter()
+            .map(|def| {
+                let scorer = registry
+                    .scorers
+                    .get(&def.scorer)
+                    .ok_or_else(|| UtilityDefError::UnknownScorer(def.scorer.clone()))?;
+                let action = registry
+                    .actions
+                    .get(&def.action)
+                    .ok_or_else(|| UtilityDefError::UnknownAction(def.action.clone()))?;
+                Ok(Consideration::named(def.name.clone(), scorer(), action())
+                    .with_weight(def.weight)
+                    .with_curve(def.curve))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(UtilityAi { considerations })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utility_ai::scorers::Constant;
+
+    struct Wait;
+
+    impl Action for Wait {
+        fn execute(&self, _entity: Entity, _commands: &mut Commands) {}
+    }
+
+    fn registry() -> UtilityRegistry {
+        let mut registry = UtilityRegistry::default();
+        registry
+            .register_scorer("low_health", || Box::new(Constant(0.5)))
+            .register_scorer("always", || Box::new(Constant(0.3)))
+            .register_action("wait", || Box::new(Wait));
+        registry
+    }
+
+    #[test]
+    fn test_ron_config_round_trips_and_builds() {
+        let def = UtilityAiDef::from_ron(
+            r#"(
+                considerations: [
+                    (name: "flee", scorer: "low_health", action: "wait", weight: 2.0, curve: Power(2.0)),
+                    (name: "idle", scorer: "always", action: "wait"),
+                ],
+            )"#,
+        )
+        .unwrap();
+        assert_eq!(UtilityAiDef::from_ron(&def.to_ron().unwrap()).unwrap(), def);
+
+        let ai = def.build(&registry()).unwrap();
+        let mut world = World::new();
+        let entity = world.spawn_empty().id();
+        let scores = ai.score_all(entity, &world);
+
+        assert_eq!(scores[0].name, "flee");
+        assert_eq!(scores[0].curved, 0.25);
+        assert_eq!(scores[0].weighted, 0.5);
+        // Weight and curve default to 1.0 and linear
+        assert_eq!(scores[1].name, "idle");
+        assert_eq!(scores[1].weighted, 0.3);
+    }
+
+    #[test]
+    fn test_build_reports_unknown_keys() {
+        let def = UtilityAiDef {
+            considerations: vec![ConsiderationDef {
+                name: "attack".to_string(),
+                scorer: "always".to_string(),
+                action: "strike".to_string(),
+                weight: 1.0,
+                curve: ResponseCurve::Linear,
+            }],
+        };
+        assert_eq!(
+            def.build(&registry()).err(),
+            Some(UtilityDefError::UnknownAction("strike".to_string()))
+        );
+    }
+}
@@ -1,24 +1,340 @@
+use crate::rng::AiRng;
+use crate::update_rate::{should_update, AiUpdateRate};
+use bevy::ecs::system::CommandQueue;
 use bevy::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+pub mod config;
+pub mod scorers;
 
 pub struct UtilityAiPlugin;
 
 impl Plugin for UtilityAiPlugin {
-    fn build(&self, _app: &mut App) {
-        // Add utility AI systems here
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CombatDifficulty>()
+            .init_resource::<AiRng>()
+            .add_systems(Update, run_utility_ai);
+    }
+}
+
+/// How sharply enemy utility AI plays.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CombatDifficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl CombatDifficulty {
+    /// Chance per decision of deliberately picking a suboptimal action.
+    pub fn mistake_chance(&self) -> f32 {
+        match self {
+            CombatDifficulty::Easy => 0.35,
+            CombatDifficulty::Normal => 0.1,
+            CombatDifficulty::Hard => 0.0,
+        }
+    }
+
+    /// Whether enemies may react out of turn, e.g. counters and interrupts.
+    pub fn reactions_enabled(&self) -> bool {
+        matches!(self, CombatDifficulty::Hard)
     }
 }
 
 pub trait Scorer: Send + Sync + 'static {
     fn score(&self, entity: Entity, world: &World) -> f32;
+
+    fn name(&self) -> &str {
+        std::any::type_name::<Self>()
+    }
+}
+
+impl Scorer for Box<dyn Scorer> {
+    fn score(&self, entity: Entity, world: &World) -> f32 {
+        (**self).score(entity, world)
+    }
+
+    fn name(&self) -> &str {
+        (**self).name()
+    }
 }
 
 pub trait Action: Send + Sync + 'static {
     fn execute(&self, entity: Entity, commands: &mut Commands);
+
+    /// Run the action directly against a `World`, e.g. in headless tests.
+    /// By default this applies the commands `execute` queues.
+    fn execute_world(&self, entity: Entity, world: &mut World) {
+        let mut queue = CommandQueue::default();
+        let mut commands = Commands::new(&mut queue, world);
+        self.execute(entity, &mut commands);
+        queue.apply(world);
+    }
+
+    fn name(&self) -> Option<&str> {
+        None
+    }
+
+    /// Whether a better-scoring action may cut this one off while it runs.
+    /// Non-interruptible actions, e.g. a channeled cast, are only preempted
+    /// by a score beating theirs by `UtilityRunner::preempt_margin`.
+    fn interruptible(&self) -> bool {
+        true
+    }
+
+    /// Whether the action is still in progress for `entity`. Instant actions
+    /// finish as soon as they execute.
+    fn is_running(&self, _entity: Entity, _world: &World) -> bool {
+        false
+    }
+
+    /// Called instead of running the action again on frames the runner keeps
+    /// it while it `is_running`. Most actions have nothing to do here.
+    fn tick(&self, _entity: Entity, _world: &mut World) {}
+}
+
+/// Anything an `Action` can be run against.
+pub trait ActionExecutor {
+    fn run(&mut self, action: &dyn Action, entity: Entity);
+}
+
+impl ActionExecutor for Commands<'_, '_> {
+    fn run(&mut self, action: &dyn Action, entity: Entity) {
+        action.execute(entity, self);
+    }
+}
+
+impl ActionExecutor for World {
+    fn run(&mut self, action: &dyn Action, entity: Entity) {
+        action.execute_world(entity, self);
+    }
+}
+
+/// Short chain of actions, e.g. move then attack, that doesn't need a
+/// behavior tree. Selecting it runs the first step; while the runner keeps it,
+/// each `tick` is passed on to the step still running, or runs the next step
+/// once that one stops `is_running`.
+/// It reports `is_running` until the last step has run (and finished), so it
+/// can't be cheaply preempted mid-sequence, then lets the utility system
+/// choose freely again.
+///
+/// Progress is kept per entity in `SequenceProgress`, so one sequence can be
+/// shared by many entities.
+pub struct ActionSequence {
+    steps: Vec<Arc<dyn Action>>,
+    /// Key of this sequence in `SequenceProgress`
+    id: u64,
+    name: Option<String>,
+}
+
+/// How far an entity has got through each `ActionSequence` it's running:
+/// the index of the step each runs next, by sequence id. Sequences that
+/// haven't started or have run their last step have no entry.
+#[derive(Component, Debug, Default)]
+pub struct SequenceProgress(HashMap<u64, usize>);
+
+static NEXT_SEQUENCE_ID: AtomicU64 = AtomicU64::new(0);
+
+impl ActionSequence {
+    pub fn new(steps: Vec<Box<dyn Action>>) -> Self {
+        Self {
+            steps: steps.into_iter().map(Arc::from).collect(),
+            id: NEXT_SEQUENCE_ID.fetch_add(1, Ordering::Relaxed),
+            name: None,
+        }
+    }
+
+    pub fn named(name: impl Into<String>, steps: Vec<Box<dyn Action>>) -> Self {
+        Self {
+            name: Some(name.into()),
+            ..Self::new(steps)
+        }
+    }
+
+    pub fn then(mut self, step: impl Action) -> Self {
+        self.steps.push(Arc::new(step));
+        self
+    }
+
+    /// Index of the step `entity` runs next, if some steps have run and the
+    /// rest are still to come.
+    pub fn next_step(&self, entity: Entity, world: &World) -> Option<usize> {
+        world
+            .get::<SequenceProgress>(entity)
+            .and_then(|progress| progress.0.get(&self.id).copied())
+    }
+}
+
+/// Runs the step `entity` is due for in the sequence `id` and moves it on to
+/// the one after, clearing its progress after the last step.
+fn advance_sequence(steps: &[Arc<dyn Action>], id: u64, entity: Entity, world: &mut World) {
+    let Some(mut entity_mut) = world.get_entity_mut(entity) else {
+        return;
+    };
+    if !entity_mut.contains::<SequenceProgress>() {
+        entity_mut.insert(SequenceProgress::default());
+    }
+    let mut progress = entity_mut.get_mut::<SequenceProgress>().unwrap();
+    let index = progress.0.get(&id).copied().unwrap_or(0);
+    if index + 1 < steps.len() {
+        progress.0.insert(id, index + 1);
+    } else {
+        progress.0.remove(&id);
+    }
+    if let Some(step) = steps.get(index) {
+        step.execute_world(entity, world);
+    }
+}
+
+/// A fresh selection starts the sequence over from its first step.
+fn restart_sequence(id: u64, entity: Entity, world: &mut World) {
+    if let Some(mut progress) = world.get_mut::<SequenceProgress>(entity) {
+        progress.0.remove(&id);
+    }
+}
+
+impl Action for ActionSequence {
+    fn execute(&self, entity: Entity, commands: &mut Commands) {
+        let steps = self.steps.clone();
+        let id = self.id;
+        commands.add(move |world: &mut World| {
+            restart_sequence(id, entity, world);
+            advance_sequence(&steps, id, entity, world);
+        });
+    }
+
+    fn execute_world(&self, entity: Entity, world: &mut World) {
+        restart_sequence(self.id, entity, world);
+        advance_sequence(&self.steps, self.id, entity, world);
+    }
+
+    fn tick(&self, entity: Entity, world: &mut World) {
+        let next = self.next_step(entity, world);
+        // The step that ran last; the final one once they all have
+        let Some(current) = next.unwrap_or(self.steps.len()).checked_sub(1) else {
+            return;
+        };
+        let step = &self.steps[current];
+        if step.is_running(entity, world) {
+            step.tick(entity, world);
+        } else if next.is_some() {
+            advance_sequence(&self.steps, self.id, entity, world);
+        }
+    }
+
+    fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    fn interruptible(&self) -> bool {
+        false
+    }
+
+    fn is_running(&self, entity: Entity, world: &World) -> bool {
+        self.next_step(entity, world).is_some()
+            || self
+                .steps
+                .last()
+                .is_some_and(|step| step.is_running(entity, world))
+    }
+}
+
+/// Maps a raw scorer output in `0.0..=1.0` onto a utility value.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum ResponseCurve {
+    #[default]
+    Linear,
+    Power(f32),
+    Inverse,
+    Logistic {
+        steepness: f32,
+        midpoint: f32,
+    },
+}
+
+impl ResponseCurve {
+    pub fn evaluate(&self, x: f32) -> f32 {
+        let x = x.clamp(0.0, 1.0);
+        match *self {
+            ResponseCurve::Linear => x,
+            ResponseCurve::Power(exponent) => x.powf(exponent),
+            ResponseCurve::Inverse => 1.0 - x,
+            ResponseCurve::Logistic {
+                steepness,
+                midpoint,
+            } => 1.0 / (1.0 + (-steepness * (x - midpoint)).exp()),
+        }
+    }
+
+    /// `n` evenly spaced `(input, output)` points from 0.0 to 1.0 inclusive,
+    /// for plotting the curve's shape.
+    pub fn sample(&self, n: usize) -> Vec<(f32, f32)> {
+        let step = match n {
+            0 => return Vec::new(),
+            1 => 0.0,
+            _ => 1.0 / (n - 1) as f32,
+        };
+        (0..n)
+            .map(|i| {
+                let x = i as f32 * step;
+                (x, self.evaluate(x))
+            })
+            .collect()
+    }
 }
 
 pub struct Consideration {
+    pub name: String,
     pub scorer: Box<dyn Scorer>,
     pub action: Box<dyn Action>,
+    pub curve: ResponseCurve,
+    pub weight: f32,
+}
+
+impl Consideration {
+    pub fn new(scorer: Box<dyn Scorer>, action: Box<dyn Action>) -> Self {
+        let name = scorer.name().to_string();
+        Self::named(name, scorer, action)
+    }
+
+    pub fn named(
+        name: impl Into<String>,
+        scorer: Box<dyn Scorer>,
+        action: Box<dyn Action>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            scorer,
+            action,
+            curve: ResponseCurve::Linear,
+            weight: 1.0,
+        }
+    }
+
+    pub fn with_curve(mut self, curve: ResponseCurve) -> Self {
+        self.curve = curve;
+        self
+    }
+
+    pub fn with_weight(mut self, weight: f32) -> Self {
+        self.weight = weight;
+        self
+    }
+}
+
+/// Breakdown of how a single consideration scored, for debugging and tuning.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConsiderationScore {
+    pub name: String,
+    pub action: Option<String>,
+    pub raw: f32,
+    pub curved: f32,
+    pub weighted: f32,
 }
 
 #[derive(Component)]
@@ -28,17 +344,746 @@ pub struct UtilityAi {
 
 impl UtilityAi {
     pub fn select_best(&self, entity: Entity, world: &World) -> Option<&Box<dyn Action>> {
+        self.best_index(entity, world)
+            .map(|index| &self.considerations[index].action)
+    }
+
+    fn best_index(&self, entity: Entity, world: &World) -> Option<usize> {
         let mut best_score = -1.0;
-        let mut best_action = None;
+        let mut best_index = None;
 
-        for consideration in &self.considerations {
-            let score = consideration.scorer.score(entity, world);
+        for (index, consideration) in self.considerations.iter().enumerate() {
+            let score = consideration
+                .curve
+                .evaluate(consideration.scorer.score(entity, world))
+                * consideration.weight;
             if score > best_score {
                 best_score = score;
-                best_action = Some(&consideration.action);
+                best_index = Some(index);
             }
         }
 
-        best_action
+        best_index
+    }
+
+    /// Like `select_best`, but may pick a worse action depending on difficulty.
+    pub fn select_with_difficulty(
+        &self,
+        entity: Entity,
+        world: &World,
+        difficulty: CombatDifficulty,
+        rng: &mut impl Rng,
+    ) -> Option<&dyn Action> {
+        let best = self.best_index(entity, world)?;
+        let index = self.with_mistakes(best, difficulty, rng);
+        Some(self.considerations[index].action.as_ref())
+    }
+
+    /// `best`, or on a mistake roll for `difficulty` any other consideration.
+    fn with_mistakes(
+        &self,
+        best: usize,
+        difficulty: CombatDifficulty,
+        rng: &mut impl Rng,
+    ) -> usize {
+        if self.considerations.len() < 2 || rng.gen::<f32>() >= difficulty.mistake_chance() {
+            return best;
+        }
+
+        let others: Vec<usize> = (0..self.considerations.len())
+            .filter(|index| *index != best)
+            .collect();
+        others[rng.gen_range(0..others.len())]
+    }
+
+    /// Picks an action at random, with odds proportional to each
+    /// consideration's weighted score. Falls back to `select_best` when
+    /// nothing scores above zero.
+    pub fn select_weighted(
+        &self,
+        entity: Entity,
+        world: &World,
+        rng: &mut impl Rng,
+    ) -> Option<&dyn Action> {
+        let scores: Vec<f32> = self
+            .considerations
+            .iter()
+            .map(|consideration| {
+                let score = consideration
+                    .curve
+                    .evaluate(consideration.scorer.score(entity, world))
+                    * consideration.weight;
+                score.max(0.0)
+            })
+            .collect();
+        let total: f32 = scores.iter().sum();
+        if total <= 0.0 {
+            return self.select_best(entity, world).map(Box::as_ref);
+        }
+
+        let mut roll = rng.gen::<f32>() * total;
+        for (consideration, score) in self.considerations.iter().zip(&scores) {
+            if roll < *score {
+                return Some(consideration.action.as_ref());
+            }
+            roll -= score;
+        }
+        // Rounding can leave the roll just past the last bucket
+        self.considerations
+            .iter()
+            .zip(&scores)
+            .rev()
+            .find(|(_, score)| **score > 0.0)
+            .map(|(consideration, _)| consideration.action.as_ref())
+    }
+
+    /// Index of the consideration to run next, given the one `current`ly
+    /// running. A running non-interruptible action is kept unless the best
+    /// score beats its own by more than `preempt_margin`.
+    pub fn choose(
+        &self,
+        entity: Entity,
+        world: &World,
+        current: Option<usize>,
+        preempt_margin: f32,
+    ) -> Option<usize> {
+        let scores: Vec<f32> = self
+            .considerations
+            .iter()
+            .map(|consideration| {
+                consideration
+                    .curve
+                    .evaluate(consideration.scorer.score(entity, world))
+                    * consideration.weight
+            })
+            .collect();
+        let best = (0..scores.len()).reduce(|best, index| {
+            if scores[index] > scores[best] {
+                index
+            } else {
+                best
+            }
+        })?;
+
+        let Some(current) = current.filter(|index| *index < scores.len()) else {
+            return Some(best);
+        };
+        let action = &self.considerations[current].action;
+        if action.is_running(entity, world)
+            && !action.interruptible()
+            && scores[best] <= scores[current] + preempt_margin
+        {
+            return Some(current);
+        }
+        Some(best)
+    }
+
+    /// Scores every consideration, sorted from highest to lowest weighted score.
+    pub fn score_all(&self, entity: Entity, world: &World) -> Vec<ConsiderationScore> {
+        let mut scores: Vec<ConsiderationScore> = self
+            .considerations
+            .iter()
+            .map(|consideration| {
+                let raw = consideration.scorer.score(entity, world);
+                let curved = consideration.curve.evaluate(raw);
+                ConsiderationScore {
+                    name: consideration.name.clone(),
+                    action: consideration.action.name().map(str::to_string),
+                    raw,
+                    curved,
+                    weighted: curved * consideration.weight,
+                }
+            })
+            .collect();
+
+        scores.sort_by(|a, b| b.weighted.total_cmp(&a.weighted));
+        scores
+    }
+}
+
+/// Score a running non-interruptible action must be beaten by to preempt it.
+pub const DEFAULT_PREEMPT_MARGIN: f32 = 0.1;
+
+/// Lets `run_utility_ai` pick and run the entity's best action every frame.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct UtilityRunner {
+    /// Consideration whose action ran last
+    pub current: Option<usize>,
+    pub preempt_margin: f32,
+}
+
+impl Default for UtilityRunner {
+    fn default() -> Self {
+        Self {
+            current: None,
+            preempt_margin: DEFAULT_PREEMPT_MARGIN,
+        }
+    }
+}
+
+/// Runs the chosen action for each entity with a `UtilityRunner`. An action
+/// that is still running isn't restarted. Entities whose `AiUpdateRate`
+/// isn't due are skipped.
+///
+/// With a `CombatDifficulty` resource, fresh choices may be deliberately
+/// suboptimal, drawing from `AiRng`, and on `Hard` any better score cuts off
+/// a running action regardless of `preempt_margin`. Without one the best
+/// action is always chosen.
+pub fn run_utility_ai(world: &mut World) {
+    let difficulty = world.get_resource::<CombatDifficulty>().copied();
+    let reactions = difficulty.is_some_and(|difficulty| difficulty.reactions_enabled());
+    let entities: Vec<Entity> = world
+        .query_filtered::<Entity, (With<UtilityAi>, With<UtilityRunner>)>()
+        .iter(world)
+        .collect();
+
+    for entity in entities {
+        if !should_update(world.get::<AiUpdateRate>(entity)) {
+            continue;
+        }
+        // Take the AI out so its action can borrow the world mutably. An
+        // earlier entity's action may have despawned this one.
+        let Some(ai) = world
+            .get_entity_mut(entity)
+            .and_then(|mut entity| entity.take::<UtilityAi>())
+        else {
+            continue;
+        };
+        let Some(runner) = world.get::<UtilityRunner>(entity).copied() else {
+            world.entity_mut(entity).insert(ai);
+            continue;
+        };
+        let margin = if reactions {
+            0.0
+        } else {
+            runner.preempt_margin
+        };
+
+        if let Some(mut chosen) = ai.choose(entity, world, runner.current, margin) {
+            let continuing = runner.current == Some(chosen)
+                && ai.considerations[chosen].action.is_running(entity, world);
+            if continuing {
+                ai.considerations[chosen].action.tick(entity, world);
+            } else {
+                if let Some(difficulty) = difficulty {
+                    let mut rng = world.get_resource_or_insert_with(AiRng::default);
+                    chosen = ai.with_mistakes(chosen, difficulty, &mut rng.0);
+                }
+                world.run(ai.considerations[chosen].action.as_ref(), entity);
+            }
+            if let Some(mut runner) = world.get_mut::<UtilityRunner>(entity) {
+                runner.current = Some(chosen);
+            }
+        }
+
+        // The action may have despawned the entity itself
+        if let Some(mut entity) = world.get_entity_mut(entity) {
+            entity.insert(ai);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FixedScorer(f32);
+
+    impl Scorer for FixedScorer {
+        fn score(&self, _entity: Entity, _world: &World) -> f32 {
+            self.0
+        }
+    }
+
+    struct NoopAction;
+
+    impl Action for NoopAction {
+        fn execute(&self, _entity: Entity, _commands: &mut Commands) {}
+    }
+
+    #[test]
+    fn test_sample_curve_shape() {
+        let linear = ResponseCurve::Linear.sample(5);
+        assert_eq!(linear.len(), 5);
+        assert_eq!(linear.first(), Some(&(0.0, 0.0)));
+        assert_eq!(linear.last(), Some(&(1.0, 1.0)));
+        assert!(linear.iter().all(|(x, y)| x == y));
+
+        let quadratic = ResponseCurve::Power(2.0).sample(5);
+        let expected = [0.0, 0.0625, 0.25, 0.5625, 1.0];
+        for ((x, y), expected) in quadratic.into_iter().zip(expected) {
+            assert!((y - x * x).abs() < 1e-6);
+            assert!((y - expected).abs() < 1e-6);
+        }
+
+        assert!(ResponseCurve::Inverse.sample(0).is_empty());
+        assert_eq!(ResponseCurve::Inverse.sample(1), vec![(0.0, 1.0)]);
+    }
+
+    #[test]
+    fn test_score_all() {
+        let mut world = World::new();
+        let entity = world.spawn_empty().id();
+
+        let ai = UtilityAi {
+            considerations: vec![
+                Consideration::new(Box::new(FixedScorer(0.5)), Box::new(NoopAction))
+                    .with_curve(ResponseCurve::Power(2.0))
+                    .with_weight(2.0),
+                Consideration::new(Box::new(FixedScorer(0.8)), Box::new(NoopAction))
+                    .with_curve(ResponseCurve::Inverse),
+            ],
+        };
+
+        let scores = ai.score_all(entity, &world);
+        assert_eq!(scores.len(), 2);
+
+        assert_eq!(scores[0].raw, 0.5);
+        assert_eq!(scores[0].curved, 0.25);
+        assert_eq!(scores[0].weighted, 0.5);
+
+        assert_eq!(scores[1].raw, 0.8);
+        assert!((scores[1].curved - 0.2).abs() < 1e-6);
+        assert!((scores[1].weighted - 0.2).abs() < 1e-6);
+    }
+
+    struct NamedAction;
+
+    impl Action for NamedAction {
+        fn execute(&self, _entity: Entity, _commands: &mut Commands) {}
+
+        fn name(&self) -> Option<&str> {
+            Some("flee")
+        }
+    }
+
+    #[test]
+    fn test_names_propagate_to_score_report() {
+        let mut world = World::new();
+        let entity = world.spawn_empty().id();
+
+        let ai = UtilityAi {
+            considerations: vec![
+                Consideration::named(
+                    "low_health",
+                    Box::new(FixedScorer(0.9)),
+                    Box::new(NamedAction),
+                ),
+                Consideration::named("idle", Box::new(FixedScorer(0.1)), Box::new(NoopAction)),
+            ],
+        };
+
+        let scores = ai.score_all(entity, &world);
+        assert_eq!(scores[0].name, "low_health");
+        assert_eq!(scores[0].action.as_deref(), Some("flee"));
+        assert_eq!(scores[1].name, "idle");
+        assert_eq!(scores[1].action, None);
+    }
+
+    #[derive(Component, Debug, PartialEq)]
+    struct Fleeing;
+
+    #[derive(Component, Debug, PartialEq)]
+    struct Stamina(u32);
+
+    struct FleeAction;
+
+    impl Action for FleeAction {
+        fn execute(&self, entity: Entity, commands: &mut Commands) {
+            commands.entity(entity).insert(Fleeing);
+        }
+    }
+
+    struct RestAction;
+
+    impl Action for RestAction {
+        fn execute(&self, _entity: Entity, _commands: &mut Commands) {}
+
+        fn execute_world(&self, entity: Entity, world: &mut World) {
+            if let Some(mut stamina) = world.get_mut::<Stamina>(entity) {
+                stamina.0 += 5;
+            }
+        }
+    }
+
+    #[test]
+    fn test_actions_run_against_bare_world() {
+        let mut world = World::new();
+        let entity = world.spawn(Stamina(10)).id();
+
+        // Commands-based actions go through the default adapter
+        world.run(&FleeAction, entity);
+        assert_eq!(world.get::<Fleeing>(entity), Some(&Fleeing));
+
+        world.run(&RestAction, entity);
+        assert_eq!(world.get::<Stamina>(entity), Some(&Stamina(15)));
+    }
+
+    #[test]
+    fn test_difficulty_controls_deviation() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let mut world = World::new();
+        let entity = world.spawn_empty().id();
+
+        let ai = UtilityAi {
+            considerations: vec![
+                Consideration::new(Box::new(FixedScorer(0.9)), Box::new(NamedAction)),
+                Consideration::new(Box::new(FixedScorer(0.2)), Box::new(NoopAction)),
+            ],
+        };
+        let deviations = |difficulty: CombatDifficulty| {
+            let mut rng = StdRng::seed_from_u64(7);
+            (0..20)
+                .filter(|_| {
+                    let action = ai
+                        .select_with_difficulty(entity, &world, difficulty, &mut rng)
+                        .unwrap();
+                    action.name() != Some("flee")
+                })
+                .count()
+        };
+
+        assert!(deviations(CombatDifficulty::Easy) > 0);
+        assert_eq!(deviations(CombatDifficulty::Hard), 0);
+    }
+
+    #[derive(Component, Default)]
+    struct Chosen(Vec<&'static str>);
+
+    struct Record(&'static str);
+
+    impl Action for Record {
+        fn execute(&self, entity: Entity, commands: &mut Commands) {
+            let label = self.0;
+            commands.add(move |world: &mut World| {
+                world.get_mut::<Chosen>(entity).unwrap().0.push(label);
+            });
+        }
+    }
+
+    #[test]
+    fn test_runner_plays_to_difficulty() {
+        let suboptimal_runs = |difficulty: CombatDifficulty| {
+            let mut app = App::new();
+            app.insert_resource(difficulty)
+                .insert_resource(AiRng::seeded(7))
+                .add_systems(Update, run_utility_ai);
+            let enemy = app
+                .world
+                .spawn((
+                    UtilityAi {
+                        considerations: vec![
+                            Consideration::new(
+                                Box::new(FixedScorer(0.9)),
+                                Box::new(Record("best")),
+                            ),
+                            Consideration::new(
+                                Box::new(FixedScorer(0.2)),
+                                Box::new(Record("worse")),
+                            ),
+                        ],
+                    },
+                    UtilityRunner::default(),
+                    Chosen::default(),
+                ))
+                .id();
+
+            for _ in 0..20 {
+                app.update();
+            }
+            let chosen = &app.world.get::<Chosen>(enemy).unwrap().0;
+            assert_eq!(chosen.len(), 20);
+            chosen.iter().filter(|label| **label != "best").count()
+        };
+
+        assert!(suboptimal_runs(CombatDifficulty::Easy) > 0);
+        assert_eq!(suboptimal_runs(CombatDifficulty::Hard), 0);
+    }
+
+    struct Despawn;
+
+    impl Action for Despawn {
+        fn execute(&self, entity: Entity, commands: &mut Commands) {
+            commands.entity(entity).despawn();
+        }
+    }
+
+    #[test]
+    fn test_runner_survives_action_despawning_entity() {
+        let mut app = App::new();
+        app.add_systems(Update, run_utility_ai);
+        let enemy = app
+            .world
+            .spawn((
+                UtilityAi {
+                    considerations: vec![Consideration::new(
+                        Box::new(FixedScorer(1.0)),
+                        Box::new(Despawn),
+                    )],
+                },
+                UtilityRunner::default(),
+            ))
+            .id();
+
+        app.update();
+        assert!(app.world.get_entity(enemy).is_none());
+        app.update();
+    }
+
+    #[derive(Resource)]
+    struct Scores {
+        channel: f32,
+        strike: f32,
+    }
+
+    struct ScoreOf(fn(&Scores) -> f32);
+
+    impl Scorer for ScoreOf {
+        fn score(&self, _entity: Entity, world: &World) -> f32 {
+            (self.0)(world.resource::<Scores>())
+        }
+    }
+
+    #[derive(Component)]
+    struct Channeling;
+
+    #[derive(Component, Debug, PartialEq)]
+    struct Strikes(u32);
+
+    /// Keeps channeling once started and can't be cut off cheaply.
+    struct ChannelAction;
+
+    impl Action for ChannelAction {
+        fn execute(&self, entity: Entity, commands: &mut Commands) {
+            commands.entity(entity).insert(Channeling);
+        }
+
+        fn interruptible(&self) -> bool {
+            false
+        }
+
+        fn is_running(&self, entity: Entity, world: &World) -> bool {
+            world.get::<Channeling>(entity).is_some()
+        }
+    }
+
+    struct StrikeAction;
+
+    impl Action for StrikeAction {
+        fn execute(&self, entity: Entity, commands: &mut Commands) {
+            commands.add(move |world: &mut World| {
+                world.get_mut::<Strikes>(entity).unwrap().0 += 1;
+            });
+        }
+    }
+
+    #[test]
+    fn test_running_channel_resists_marginal_preemption() {
+        let mut app = App::new();
+        app.insert_resource(Scores {
+            channel: 0.6,
+            strike: 0.5,
+        })
+        .add_systems(Update, run_utility_ai);
+
+        let caster = app
+            .world
+            .spawn((
+                UtilityAi {
+                    considerations: vec![
+                        Consideration::new(
+                            Box::new(ScoreOf(|scores| scores.channel)),
+                            Box::new(ChannelAction),
+                        ),
+                        Consideration::new(
+                            Box::new(ScoreOf(|scores| scores.strike)),
+                            Box::new(StrikeAction),
+                        ),
+                    ],
+                },
+                UtilityRunner::default(),
+                Strikes(0),
+            ))
+            .id();
+
+        app.update();
+        assert!(app.world.get::<Channeling>(caster).is_some());
+
+        // Strike now scores higher, but not by more than the margin
+        app.world.resource_mut::<Scores>().strike = 0.65;
+        app.update();
+        assert_eq!(app.world.get::<Strikes>(caster), Some(&Strikes(0)));
+        assert_eq!(
+            app.world.get::<UtilityRunner>(caster).unwrap().current,
+            Some(0)
+        );
+
+        // A clear win preempts the channel
+        app.world.resource_mut::<Scores>().strike = 0.8;
+        app.update();
+        assert_eq!(app.world.get::<Strikes>(caster), Some(&Strikes(1)));
+        assert_eq!(
+            app.world.get::<UtilityRunner>(caster).unwrap().current,
+            Some(1)
+        );
+    }
+
+    #[derive(Component, Debug, Default, PartialEq)]
+    struct Steps(Vec<&'static str>);
+
+    struct Step(&'static str);
+
+    impl Action for Step {
+        fn execute(&self, entity: Entity, commands: &mut Commands) {
+            let label = self.0;
+            commands.add(move |world: &mut World| {
+                world.get_mut::<Steps>(entity).unwrap().0.push(label);
+            });
+        }
+    }
+
+    #[test]
+    fn test_action_sequence_runs_steps_in_order() {
+        let mut app = App::new();
+        app.add_systems(Update, run_utility_ai);
+        let ambusher = app
+            .world
+            .spawn((
+                UtilityAi {
+                    considerations: vec![Consideration::new(
+                        Box::new(FixedScorer(1.0)),
+                        Box::new(
+                            ActionSequence::named("ambush", Vec::new())
+                                .then(Step("move"))
+                                .then(Step("aim"))
+                                .then(Step("attack")),
+                        ),
+                    )],
+                },
+                UtilityRunner::default(),
+                Steps::default(),
+            ))
+            .id();
+        let is_running = |world: &World| {
+            world.get::<UtilityAi>(ambusher).unwrap().considerations[0]
+                .action
+                .is_running(ambusher, world)
+        };
+
+        app.update();
+        assert!(is_running(&app.world));
+        app.update();
+        assert!(is_running(&app.world));
+        app.update();
+        assert!(!is_running(&app.world));
+        assert_eq!(
+            app.world.get::<Steps>(ambusher),
+            Some(&Steps(vec!["move", "aim", "attack"]))
+        );
+
+        // Done, so the next selection starts over
+        app.update();
+        assert_eq!(
+            app.world.get::<Steps>(ambusher),
+            Some(&Steps(vec!["move", "aim", "attack", "move"]))
+        );
+    }
+
+    /// Takes `frames` ticks to finish, then records its label.
+    struct Windup(&'static str, u32);
+
+    #[derive(Component)]
+    struct Winding(&'static str, u32);
+
+    impl Action for Windup {
+        fn execute(&self, entity: Entity, commands: &mut Commands) {
+            commands.entity(entity).insert(Winding(self.0, self.1));
+        }
+
+        fn is_running(&self, entity: Entity, world: &World) -> bool {
+            world.get::<Winding>(entity).is_some()
+        }
+
+        fn tick(&self, entity: Entity, world: &mut World) {
+            let mut winding = world.get_mut::<Winding>(entity).unwrap();
+            winding.1 -= 1;
+            if winding.1 == 0 {
+                let label = winding.0;
+                world.entity_mut(entity).remove::<Winding>();
+                world.get_mut::<Steps>(entity).unwrap().0.push(label);
+            }
+        }
+    }
+
+    #[test]
+    fn test_action_sequence_ticks_steps_that_take_several_frames() {
+        let mut app = App::new();
+        app.add_systems(Update, run_utility_ai);
+        let archer = app
+            .world
+            .spawn((
+                UtilityAi {
+                    considerations: vec![Consideration::new(
+                        Box::new(FixedScorer(1.0)),
+                        Box::new(
+                            ActionSequence::new(Vec::new())
+                                .then(Windup("aim", 2))
+                                .then(Windup("fire", 2)),
+                        ),
+                    )],
+                },
+                UtilityRunner::default(),
+                Steps::default(),
+            ))
+            .id();
+        let is_running = |world: &World| {
+            world.get::<UtilityAi>(archer).unwrap().considerations[0]
+                .action
+                .is_running(archer, world)
+        };
+
+        // Start aiming, wind it up over two frames, then start firing
+        for _ in 0..4 {
+            app.update();
+        }
+        assert_eq!(app.world.get::<Steps>(archer), Some(&Steps(vec!["aim"])));
+        assert!(is_running(&app.world));
+
+        // The last step is still ticked until it finishes
+        app.update();
+        assert!(is_running(&app.world));
+        app.update();
+        assert!(!is_running(&app.world));
+        assert_eq!(
+            app.world.get::<Steps>(archer),
+            Some(&Steps(vec!["aim", "fire"]))
+        );
+    }
+
+    #[test]
+    fn test_action_sequence_progress_is_per_entity() {
+        let sequence = ActionSequence::new(Vec::new())
+            .then(Step("move"))
+            .then(Step("aim"))
+            .then(Step("attack"));
+        let mut world = World::new();
+        let first = world.spawn(Steps::default()).id();
+        let second = world.spawn(Steps::default()).id();
+
+        world.run(&sequence, first);
+        sequence.tick(first, &mut world);
+        world.run(&sequence, second);
+        assert_eq!(sequence.next_step(first, &world), Some(2));
+        assert_eq!(sequence.next_step(second, &world), Some(1));
+
+        sequence.tick(first, &mut world);
+        assert!(!sequence.is_running(first, &world));
+        assert!(sequence.is_running(second, &world));
+        assert_eq!(
+            world.get::<Steps>(first),
+            Some(&Steps(vec!["move", "aim", "attack"]))
+        );
+        assert_eq!(world.get::<Steps>(second), Some(&Steps(vec!["move"])));
     }
 }
@@ -0,0 +1,353 @@
+//! Built-in scorers and combinators for building scorers out of others.
+//!
+//! Child scores are expected to be normalized to `0.0..=1.0`, so `And` and
+//! `Or` behave like fuzzy-logic conjunction and disjunction. `DistanceScorer`
+//! and `FacingScorer` score an entity's current target, as do `ThreatScorer`
+//! and `KillPotentialScorer`, which `choose_target` uses to pick who to attack.
+//! Targets are read from the entity's `Blackboard` when it has one.
+
+use bevy::prelude::*;
+
+use super::Scorer;
+use crate::blackboard::{perceived_position, perceived_target, perceived_threat, Blackboard};
+use crate::targeting::{Target, TargetingSpace, Threat};
+
+/// The lower of two scores.
+pub struct And<A, B>(pub A, pub B);
+
+impl<A: Scorer, B: Scorer> Scorer for And<A, B> {
+    fn score(&self, entity: Entity, world: &World) -> f32 {
+        self.0.score(entity, world).min(self.1.score(entity, world))
+    }
+}
+
+/// The higher of two scores.
+pub struct Or<A, B>(pub A, pub B);
+
+impl<A: Scorer, B: Scorer> Scorer for Or<A, B> {
+    fn score(&self, entity: Entity, world: &World) -> f32 {
+        self.0.score(entity, world).max(self.1.score(entity, world))
+    }
+}
+
+/// One minus the child score.
+pub struct Not<A>(pub A);
+
+impl<A: Scorer> Scorer for Not<A> {
+    fn score(&self, entity: Entity, world: &World) -> f32 {
+        1.0 - self.0.score(entity, world)
+    }
+}
+
+/// The child score clamped to `lo..=hi`.
+pub struct Clamp<A>(pub A, pub f32, pub f32);
+
+impl<A: Scorer> Scorer for Clamp<A> {
+    fn score(&self, entity: Entity, world: &World) -> f32 {
+        self.0.score(entity, world).clamp(self.1, self.2)
+    }
+}
+
+/// Always the same score.
+pub struct Constant(pub f32);
+
+impl Scorer for Constant {
+    fn score(&self, _entity: Entity, _world: &World) -> f32 {
+        self.0
+    }
+}
+
+/// Positions of an entity and its current target, projected into the active
+/// `TargetingSpace`. Falls back to the last known position of a lost target.
+fn target_positions(entity: Entity, world: &World) -> Option<(&GlobalTransform, Vec3, Vec3)> {
+    let space = world
+        .get_resource::<TargetingSpace>()
+        .copied()
+        .unwrap_or_default();
+    let transform = world.get::<GlobalTransform>(entity)?;
+    let target_position = perceived_target(entity, world)
+        .and_then(|target| world.get::<GlobalTransform>(target))
+        .map(GlobalTransform::translation)
+        .or_else(|| perceived_position(entity, world))?;
+
+    Some((
+        transform,
+        space.project(transform.translation()),
+        space.project(target_position),
+    ))
+}
+
+/// 1.0 when the target is on top of the entity, falling linearly to 0.0 at
+/// `max_range`. Scores 0.0 without a target.
+pub struct DistanceScorer {
+    pub max_range: f32,
+}
+
+impl Scorer for DistanceScorer {
+    fn score(&self, entity: Entity, world: &World) -> f32 {
+        let Some((_, position, target_position)) = target_positions(entity, world) else {
+            return 0.0;
+        };
+        if self.max_range <= 0.0 {
+            return 0.0;
+        }
+        (1.0 - position.distance(target_position) / self.max_range).clamp(0.0, 1.0)
+    }
+}
+
+/// 1.0 when the target is straight ahead, 0.5 when it is square to the side
+/// and 0.0 when it is directly behind. Scores 0.0 without a target.
+pub struct FacingScorer {
+    /// Local forward axis; `Vec3::NEG_Z` for 3D, usually `Vec3::X` for 2D.
+    pub forward: Vec3,
+}
+
+impl Default for FacingScorer {
+    fn default() -> Self {
+        Self {
+            forward: Vec3::NEG_Z,
+        }
+    }
+}
+
+impl Scorer for FacingScorer {
+    fn score(&self, entity: Entity, world: &World) -> f32 {
+        let Some((transform, position, target_position)) = target_positions(entity, world) else {
+            return 0.0;
+        };
+        let (_, rotation, _) = transform.to_scale_rotation_translation();
+        let space = world
+            .get_resource::<TargetingSpace>()
+            .copied()
+            .unwrap_or_default();
+        let forward = space.project(rotation * self.forward).normalize_or_zero();
+        let to_target = (target_position - position).normalize_or_zero();
+        if forward == Vec3::ZERO || to_target == Vec3::ZERO {
+            return 0.0;
+        }
+        (forward.dot(to_target) + 1.0) / 2.0
+    }
+}
+
+/// The current target's `Threat`. Scores 0.0 without a target.
+pub struct ThreatScorer;
+
+impl Scorer for ThreatScorer {
+    fn score(&self, entity: Entity, world: &World) -> f32 {
+        perceived_threat(entity, world).map_or(0.0, |threat| threat.clamp(0.0, 1.0))
+    }
+}
+
+/// Damage an attacker expects to deal to a target this turn.
+pub type DamageEstimate = Box<dyn Fn(Entity, Entity, &World) -> f32 + Send + Sync>;
+
+/// A target's remaining health, if it has any.
+pub type HealthLookup = Box<dyn Fn(Entity, &World) -> Option<f32> + Send + Sync>;
+
+/// Scores the current target by whether the entity can finish it this turn.
+/// Built with `kill_potential_scorer`.
+pub struct KillPotentialScorer {
+    pub expected_damage: DamageEstimate,
+    pub remaining_health: HealthLookup,
+}
+
+impl Scorer for KillPotentialScorer {
+    fn score(&self, entity: Entity, world: &World) -> f32 {
+        let Some(target) = perceived_target(entity, world) else {
+            return 0.0;
+        };
+        let Some(health) = (self.remaining_health)(target, world).filter(|health| *health > 0.0)
+        else {
+            return 0.0;
+        };
+
+        let damage = (self.expected_damage)(entity, target, world).max(0.0);
+        if damage >= health {
+            1.0
+        } else {
+            // Wounding counts for something, but never as much as a kill
+            0.5 * damage / health
+        }
+    }
+}
+
+/// 1.0 when the entity's expected damage meets or exceeds its target's
+/// remaining health; otherwise up to 0.5 by the share of health it would
+/// take. Combine with threat, e.g. `And(ThreatScorer, kill_potential_scorer(..))`,
+/// so enemies focus dangerous targets they can actually finish.
+pub fn kill_potential_scorer(
+    expected_damage: impl Fn(Entity, Entity, &World) -> f32 + Send + Sync + 'static,
+    remaining_health: impl Fn(Entity, &World) -> Option<f32> + Send + Sync + 'static,
+) -> KillPotentialScorer {
+    KillPotentialScorer {
+        expected_damage: Box::new(expected_damage),
+        remaining_health: Box::new(remaining_health),
+    }
+}
+
+/// Points the entity's `Target`, and its `Blackboard` if it has one, at
+/// `target`.
+fn aim_at(entity: Entity, target: Entity, world: &mut World) -> Option<()> {
+    world.get_mut::<Target>(entity)?.entity = Some(target);
+    let position = world
+        .get::<GlobalTransform>(target)
+        .map(GlobalTransform::translation);
+    let threat = world.get::<Threat>(target).map(|threat| threat.0);
+    if let Some(mut blackboard) = world.get_mut::<Blackboard>(entity) {
+        blackboard.set_perception(Some(target), position, threat);
+    }
+    Some(())
+}
+
+/// Points the entity's `Target` at whichever of `candidates` the scorer rates
+/// highest, ties going to the earlier candidate, and returns it. Returns
+/// `None` without a `Target` component or candidates.
+pub fn choose_target(
+    entity: Entity,
+    world: &mut World,
+    candidates: &[Entity],
+    scorer: &dyn Scorer,
+) -> Option<Entity> {
+    world.get::<Target>(entity)?;
+
+    let mut best: Option<(Entity, f32)> = None;
+    for &candidate in candidates {
+        aim_at(entity, candidate, world)?;
+        let score = scorer.score(entity, world);
+        match best {
+            Some((_, best_score)) if score <= best_score => {}
+            _ => best = Some((candidate, score)),
+        }
+    }
+
+    let (chosen, _) = best?;
+    aim_at(entity, chosen, world)?;
+    Some(chosen)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn score(scorer: impl Scorer) -> f32 {
+        let mut world = World::new();
+        let entity = world.spawn_empty().id();
+        scorer.score(entity, &world)
+    }
+
+    #[test]
+    fn test_constant() {
+        assert_eq!(score(Constant(0.4)), 0.4);
+    }
+
+    #[test]
+    fn test_and_takes_min() {
+        assert_eq!(score(And(Constant(0.3), Constant(0.8))), 0.3);
+    }
+
+    #[test]
+    fn test_or_takes_max() {
+        assert_eq!(score(Or(Constant(0.3), Constant(0.8))), 0.8);
+    }
+
+    #[test]
+    fn test_not_inverts() {
+        assert!((score(Not(Constant(0.3))) - 0.7).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_clamp_bounds_child() {
+        assert_eq!(score(Clamp(Constant(0.9), 0.2, 0.6)), 0.6);
+        assert_eq!(score(Clamp(Constant(0.1), 0.2, 0.6)), 0.2);
+        assert_eq!(score(Clamp(Constant(0.4), 0.2, 0.6)), 0.4);
+    }
+
+    #[test]
+    fn test_combinators_nest() {
+        // Low health and not already fleeing
+        let scorer = And(Constant(0.9), Not(Or(Constant(0.2), Constant(0.5))));
+        assert_eq!(score(scorer), 0.5);
+    }
+
+    fn spawn_with_target(world: &mut World, target_position: Vec3) -> Entity {
+        let target = world
+            .spawn(GlobalTransform::from_translation(target_position))
+            .id();
+        world
+            .spawn((
+                GlobalTransform::default(),
+                Target {
+                    entity: Some(target),
+                    ..default()
+                },
+            ))
+            .id()
+    }
+
+    #[test]
+    fn test_distance_scorer_falls_off_to_max_range() {
+        let scorer = DistanceScorer { max_range: 10.0 };
+        let mut world = World::new();
+
+        let close = spawn_with_target(&mut world, Vec3::ZERO);
+        assert!((scorer.score(close, &world) - 1.0).abs() < 1e-6);
+
+        let halfway = spawn_with_target(&mut world, Vec3::new(5.0, 0.0, 0.0));
+        assert!((scorer.score(halfway, &world) - 0.5).abs() < 1e-6);
+
+        let at_range = spawn_with_target(&mut world, Vec3::new(10.0, 0.0, 0.0));
+        assert!(scorer.score(at_range, &world).abs() < 1e-6);
+
+        let untargeted = world.spawn(GlobalTransform::default()).id();
+        assert_eq!(scorer.score(untargeted, &world), 0.0);
+    }
+
+    #[test]
+    fn test_facing_scorer_prefers_targets_ahead() {
+        let scorer = FacingScorer::default();
+        let mut world = World::new();
+
+        let ahead = spawn_with_target(&mut world, Vec3::new(0.0, 0.0, -5.0));
+        let side = spawn_with_target(&mut world, Vec3::new(5.0, 0.0, 0.0));
+        let behind = spawn_with_target(&mut world, Vec3::new(0.0, 0.0, 5.0));
+
+        assert!((scorer.score(ahead, &world) - 1.0).abs() < 1e-6);
+        assert!((scorer.score(side, &world) - 0.5).abs() < 1e-6);
+        assert!(scorer.score(behind, &world).abs() < 1e-6);
+    }
+
+    #[derive(Component)]
+    struct Power(f32);
+
+    #[derive(Component)]
+    struct Hp(f32);
+
+    #[test]
+    fn test_equal_threat_prefers_killable_target() {
+        let mut world = World::new();
+        let attacker = world.spawn((Power(30.0), Target::default())).id();
+        let sturdy = world.spawn((Hp(60.0), Threat(0.7))).id();
+        let wounded = world.spawn((Hp(25.0), Threat(0.7))).id();
+
+        let scorer = And(
+            ThreatScorer,
+            kill_potential_scorer(
+                |attacker, _, world| world.get::<Power>(attacker).map_or(0.0, |power| power.0),
+                |target, world| world.get::<Hp>(target).map(|hp| hp.0),
+            ),
+        );
+
+        // Threat alone can't separate them, so the first candidate wins
+        assert_eq!(
+            choose_target(attacker, &mut world, &[sturdy, wounded], &ThreatScorer),
+            Some(sturdy)
+        );
+
+        assert_eq!(
+            choose_target(attacker, &mut world, &[sturdy, wounded], &scorer),
+            Some(wounded)
+        );
+        assert_eq!(world.get::<Target>(attacker).unwrap().entity, Some(wounded));
+        assert_eq!(scorer.score(attacker, &world), 0.7);
+    }
+}